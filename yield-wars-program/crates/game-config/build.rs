@@ -0,0 +1,24 @@
+//! Pins the game authority at build time
+//!
+//! The authority is read from the `GAME_AUTHORITY` environment variable so no
+//! admin key is baked into the sources. Builds fail without it rather than fall
+//! back to a key someone else may hold.
+
+use std::{env, fs, path::Path};
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=GAME_AUTHORITY");
+
+    let authority = env::var("GAME_AUTHORITY").unwrap_or_default();
+    let authority = authority.trim();
+    if authority.is_empty() {
+        panic!("GAME_AUTHORITY must be set to the base58 public key of the game authority");
+    }
+    if !authority.chars().all(|c| c.is_ascii_alphanumeric() && !"0OIl".contains(c)) {
+        panic!("GAME_AUTHORITY `{}` is not a base58 public key", authority);
+    }
+
+    // Checked again by `pubkey!` when the crate compiles
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("game_authority.rs");
+    fs::write(out, format!("pubkey!(\"{}\")\n", authority)).unwrap();
+}
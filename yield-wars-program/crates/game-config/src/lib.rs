@@ -0,0 +1,21 @@
+//! Game-wide settings shared by the YieldWars systems
+//!
+//! Economy settings such as hardware durability, facility incident odds, the
+//! GPU catalog and staking parameters are managed by a single game authority.
+//! Systems check for its signature before changing those settings, so players
+//! can never choose the parameters of their own hardware.
+//!
+//! The authority is a build-time setting: `GAME_AUTHORITY` must hold its base58
+//! public key when the programs are built. Localnet builds generate a throwaway
+//! keypair in `tests/fixtures/game-authority.json` (see `yarn build:localnet`),
+//! which is never committed.
+
+use bolt_lang::*;
+
+/// Admin key allowed to manage the game's economy settings
+pub const GAME_AUTHORITY: Pubkey = include!(concat!(env!("OUT_DIR"), "/game_authority.rs"));
+
+/// Whether `account` is the game authority and signed the transaction
+pub fn is_game_authority(account: Option<&AccountInfo>) -> bool {
+    account.is_some_and(|account| account.is_signer && *account.key == GAME_AUTHORITY)
+}
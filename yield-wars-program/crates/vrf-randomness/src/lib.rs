@@ -0,0 +1,505 @@
+//! Verified access to randomness produced by the `vrf_client` program
+//!
+//! The `vrf_client` program stores oracle randomness in a `UserRandomness`
+//! PDA per user. Systems that need randomness receive that PDA, together with
+//! the user and the `vrf_client` program, as extra accounts. This crate checks
+//! that the PDA is genuine, belongs to the signing user, is unused and fresh,
+//! and marks it used through CPI so the same value can never be consumed twice.
+//!
+//! Bets that are settled later request their own randomness instead: the request
+//! is issued through CPI when the bet is placed, and the VRF callback delivers it
+//! once to a `BetRandomness` PDA of that bet, so the outcome can't be known or
+//! re-rolled before the bet is committed. Other outcomes settled later, such as
+//! production incidents and risky upgrades, use the same flow through a
+//! `CommittedRandomness` PDA keyed by the subject, the requester and a request
+//! number.
+//!
+//! Accounts are parsed by hand rather than through the `vrf_client` crate so
+//! the Bolt systems do not have to share its Anchor version.
+
+use bolt_lang::*;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::program::invoke;
+
+/// Program ID of the `vrf_client` program
+pub const VRF_CLIENT_PROGRAM_ID: Pubkey = pubkey!("2LwC4FAQgQfbJvNo5xAVVLbXap1SpnixhyeMejud58Pq");
+
+/// Seed of the `UserRandomness` PDA (`[RANDOMNESS_SEED, user]`)
+pub const RANDOMNESS_SEED: &[u8] = b"randomness";
+
+/// Seed of the `BetRandomness` PDA (`[BET_RANDOMNESS_SEED, bet, requester, bet_id]`)
+pub const BET_RANDOMNESS_SEED: &[u8] = b"bet-randomness";
+
+/// Seed of the `CommittedRandomness` PDA (`[COMMITTED_RANDOMNESS_SEED, subject, requester, request_id]`)
+pub const COMMITTED_RANDOMNESS_SEED: &[u8] = b"committed-randomness";
+
+/// Anchor account discriminator of `UserRandomness`
+const USER_RANDOMNESS_DISCRIMINATOR: [u8; 8] = [61, 180, 221, 26, 148, 37, 75, 198];
+
+/// Anchor instruction discriminator of `mark_randomness_used`
+const MARK_RANDOMNESS_USED_DISCRIMINATOR: [u8; 8] = [238, 159, 82, 45, 160, 158, 137, 194];
+
+/// Size of a serialized `UserRandomness` account (discriminator + 32 + 32 + 8 + 1)
+const USER_RANDOMNESS_LEN: usize = 8 + 32 + 32 + 8 + 1;
+
+/// Anchor account discriminator of `BetRandomness`
+const BET_RANDOMNESS_DISCRIMINATOR: [u8; 8] = [208, 140, 36, 123, 16, 36, 3, 64];
+
+/// Anchor instruction discriminator of `request_bet_randomness`
+const REQUEST_BET_RANDOMNESS_DISCRIMINATOR: [u8; 8] = [64, 225, 249, 243, 59, 177, 74, 230];
+
+/// Size of a serialized `BetRandomness` account (discriminator + 32 + 8 + 32 + 8 + 1)
+const BET_RANDOMNESS_LEN: usize = 8 + 32 + 8 + 32 + 8 + 1;
+
+/// Anchor account discriminator of `CommittedRandomness`
+const COMMITTED_RANDOMNESS_DISCRIMINATOR: [u8; 8] = [14, 187, 111, 209, 224, 7, 128, 87];
+
+/// Anchor instruction discriminator of `request_committed_randomness`
+const REQUEST_COMMITTED_RANDOMNESS_DISCRIMINATOR: [u8; 8] = [125, 246, 145, 123, 201, 150, 68, 115];
+
+/// Size of a serialized `CommittedRandomness` account (discriminator + 32 + 32 + 8 + 32 + 8 + 1)
+const COMMITTED_RANDOMNESS_LEN: usize = 8 + 32 + 32 + 8 + 32 + 8 + 1;
+
+/// Derive a uniformly distributed u64 from randomness bytes
+fn random_u64(randomness: &[u8; 32]) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&randomness[0..8]);
+    u64::from_le_bytes(bytes)
+}
+
+/// Randomness read from a verified `UserRandomness` account
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UserRandomness {
+    /// User the randomness was generated for
+    pub user: Pubkey,
+    /// Raw randomness bytes from the VRF oracle
+    pub randomness: [u8; 32],
+    /// Time the randomness was delivered (Unix timestamp)
+    pub timestamp: i64,
+    /// Whether the randomness has already been consumed
+    pub is_used: bool,
+}
+
+impl UserRandomness {
+    /// Derive a uniformly distributed u64 from the randomness bytes
+    pub fn random_u64(&self) -> u64 {
+        random_u64(&self.randomness)
+    }
+}
+
+/// Randomness requested for a single bet, read from its `BetRandomness` PDA
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BetRandomness {
+    /// Bet account the randomness was requested for
+    pub bet: Pubkey,
+    /// Number of the bet on that account
+    pub bet_id: u64,
+    /// Raw randomness bytes from the VRF oracle, zero until delivered
+    pub randomness: [u8; 32],
+    /// Slot the randomness was requested in
+    pub requested_slot: u64,
+    /// Whether the VRF callback has delivered the randomness
+    pub is_fulfilled: bool,
+}
+
+impl BetRandomness {
+    /// Derive a uniformly distributed u64 from the randomness bytes
+    pub fn random_u64(&self) -> u64 {
+        random_u64(&self.randomness)
+    }
+}
+
+/// Randomness committed to a subject, read from its `CommittedRandomness` PDA
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CommittedRandomness {
+    /// Account the randomness was committed to
+    pub subject: Pubkey,
+    /// Signer that requested the randomness
+    pub requester: Pubkey,
+    /// Number of the request for that subject and requester
+    pub request_id: u64,
+    /// Raw randomness bytes from the VRF oracle, zero until delivered
+    pub randomness: [u8; 32],
+    /// Slot the randomness was requested in
+    pub requested_slot: u64,
+    /// Whether the VRF callback has delivered the randomness
+    pub is_fulfilled: bool,
+}
+
+impl CommittedRandomness {
+    /// Derive a uniformly distributed u64 from the randomness bytes
+    pub fn random_u64(&self) -> u64 {
+        random_u64(&self.randomness)
+    }
+}
+
+/// Load and verify a `UserRandomness` account without consuming it
+///
+/// Checks that the account is owned by `vrf_client`, sits at the canonical PDA
+/// of `user`, was generated for `user`, has not been used and was delivered
+/// at or after `min_timestamp`. `user` must have signed the transaction.
+pub fn load_user_randomness(
+    randomness_account: &AccountInfo,
+    user: &AccountInfo,
+    min_timestamp: i64,
+) -> Result<UserRandomness> {
+    if !user.is_signer {
+        return Err(RandomnessError::UserNotSigner.into());
+    }
+
+    if *randomness_account.owner != VRF_CLIENT_PROGRAM_ID {
+        msg!("Randomness account owner {} is not the VRF client", randomness_account.owner);
+        return Err(RandomnessError::InvalidRandomnessAccount.into());
+    }
+
+    let (expected_pda, _) = Pubkey::find_program_address(
+        &[RANDOMNESS_SEED, user.key.as_ref()],
+        &VRF_CLIENT_PROGRAM_ID,
+    );
+    if *randomness_account.key != expected_pda {
+        msg!("Randomness account {} is not the PDA of user {}", randomness_account.key, user.key);
+        return Err(RandomnessError::InvalidRandomnessAccount.into());
+    }
+
+    let data = randomness_account.try_borrow_data()?;
+    if data.len() < USER_RANDOMNESS_LEN || data[0..8] != USER_RANDOMNESS_DISCRIMINATOR {
+        return Err(RandomnessError::InvalidRandomnessAccount.into());
+    }
+
+    let mut user_bytes = [0u8; 32];
+    user_bytes.copy_from_slice(&data[8..40]);
+    let mut randomness = [0u8; 32];
+    randomness.copy_from_slice(&data[40..72]);
+    let mut timestamp_bytes = [0u8; 8];
+    timestamp_bytes.copy_from_slice(&data[72..80]);
+
+    let user_randomness = UserRandomness {
+        user: Pubkey::new_from_array(user_bytes),
+        randomness,
+        timestamp: i64::from_le_bytes(timestamp_bytes),
+        is_used: data[80] != 0,
+    };
+
+    if user_randomness.user != *user.key {
+        return Err(RandomnessError::InvalidRandomnessUser.into());
+    }
+
+    if user_randomness.is_used {
+        return Err(RandomnessError::RandomnessAlreadyUsed.into());
+    }
+
+    if user_randomness.timestamp < min_timestamp {
+        msg!("Randomness from {} is older than required {}", user_randomness.timestamp, min_timestamp);
+        return Err(RandomnessError::StaleRandomness.into());
+    }
+
+    Ok(user_randomness)
+}
+
+/// Mark a `UserRandomness` account as used through CPI to `vrf_client`
+pub fn mark_randomness_used<'info>(
+    vrf_program: &AccountInfo<'info>,
+    randomness_account: &AccountInfo<'info>,
+    user: &AccountInfo<'info>,
+) -> Result<()> {
+    if *vrf_program.key != VRF_CLIENT_PROGRAM_ID {
+        return Err(RandomnessError::InvalidVrfProgram.into());
+    }
+
+    let instruction = Instruction {
+        program_id: VRF_CLIENT_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*user.key, true),            // user (signer)
+            AccountMeta::new(*randomness_account.key, false),      // user_randomness_account
+        ],
+        data: MARK_RANDOMNESS_USED_DISCRIMINATOR.to_vec(),
+    };
+
+    invoke(
+        &instruction,
+        &[vrf_program.clone(), user.clone(), randomness_account.clone()],
+    )?;
+
+    Ok(())
+}
+
+/// Verify a `UserRandomness` account and consume it in the same transaction
+///
+/// Expects `accounts` to hold, in order: the `UserRandomness` PDA, the user
+/// it belongs to (signer) and the `vrf_client` program.
+pub fn consume_user_randomness<'info>(
+    accounts: &[AccountInfo<'info>],
+    min_timestamp: i64,
+) -> Result<UserRandomness> {
+    if accounts.len() < 3 {
+        msg!("Expected randomness, user and VRF program accounts, got {}", accounts.len());
+        return Err(RandomnessError::MissingRandomnessAccounts.into());
+    }
+
+    let randomness_account = &accounts[0];
+    let user = &accounts[1];
+    let vrf_program = &accounts[2];
+
+    let user_randomness = load_user_randomness(randomness_account, user, min_timestamp)?;
+    mark_randomness_used(vrf_program, randomness_account, user)?;
+
+    Ok(user_randomness)
+}
+
+/// Request randomness for a bet through CPI to `vrf_client`
+///
+/// Expects `accounts` to hold, in order: the requester (signer, pays for the PDA),
+/// the `BetRandomness` PDA of `bet`, the requester and `bet_id`, the `vrf_client`
+/// program identity, the oracle queue, the system program, the slot hashes sysvar,
+/// the VRF program and the `vrf_client` program. The PDA is created by the request,
+/// so a bet can only ever be settled with randomness requested in the transaction
+/// that placed it. The requester is part of the PDA seeds, so nobody else can
+/// create it ahead of the bet.
+pub fn request_bet_randomness<'info>(
+    bet: &AccountInfo<'info>,
+    accounts: &[AccountInfo<'info>],
+    bet_id: u64,
+    client_seed: u8,
+) -> Result<()> {
+    if accounts.len() < 8 {
+        msg!("Expected 8 randomness request accounts, got {}", accounts.len());
+        return Err(RandomnessError::MissingRandomnessAccounts.into());
+    }
+
+    let payer = &accounts[0];
+    let bet_randomness = &accounts[1];
+    let vrf_program = &accounts[7];
+
+    if !payer.is_signer {
+        return Err(RandomnessError::UserNotSigner.into());
+    }
+
+    if *vrf_program.key != VRF_CLIENT_PROGRAM_ID {
+        return Err(RandomnessError::InvalidVrfProgram.into());
+    }
+
+    let mut data = REQUEST_BET_RANDOMNESS_DISCRIMINATOR.to_vec();
+    data.push(client_seed);
+    data.extend_from_slice(&bet_id.to_le_bytes());
+
+    let instruction = Instruction {
+        program_id: VRF_CLIENT_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*payer.key, true),                    // payer (signer)
+            AccountMeta::new_readonly(*bet.key, false),            // bet
+            AccountMeta::new(*bet_randomness.key, false),          // bet_randomness
+            AccountMeta::new_readonly(*accounts[2].key, false),    // program_identity
+            AccountMeta::new(*accounts[3].key, false),             // oracle_queue
+            AccountMeta::new_readonly(*accounts[4].key, false),    // system_program
+            AccountMeta::new_readonly(*accounts[5].key, false),    // slot_hashes
+            AccountMeta::new_readonly(*accounts[6].key, false),    // vrf_program
+        ],
+        data,
+    };
+
+    let mut infos = vec![bet.clone()];
+    infos.extend_from_slice(&accounts[..8]);
+    invoke(&instruction, &infos)?;
+
+    Ok(())
+}
+
+/// Load the `BetRandomness` PDA of a bet, whether or not it has been delivered
+///
+/// Checks that the account is owned by `vrf_client`, sits at the canonical PDA
+/// of `bet`, `requester` and `bet_id` and was requested for that bet.
+pub fn load_bet_randomness(
+    bet_randomness_account: &AccountInfo,
+    bet: &Pubkey,
+    requester: &Pubkey,
+    bet_id: u64,
+) -> Result<BetRandomness> {
+    if *bet_randomness_account.owner != VRF_CLIENT_PROGRAM_ID {
+        msg!("Bet randomness account owner {} is not the VRF client", bet_randomness_account.owner);
+        return Err(RandomnessError::InvalidRandomnessAccount.into());
+    }
+
+    let (expected_pda, _) = Pubkey::find_program_address(
+        &[BET_RANDOMNESS_SEED, bet.as_ref(), requester.as_ref(), &bet_id.to_le_bytes()],
+        &VRF_CLIENT_PROGRAM_ID,
+    );
+    if *bet_randomness_account.key != expected_pda {
+        msg!("Randomness account {} is not the PDA of bet {} of {}", bet_randomness_account.key, bet_id, bet);
+        return Err(RandomnessError::InvalidRandomnessAccount.into());
+    }
+
+    let data = bet_randomness_account.try_borrow_data()?;
+    if data.len() < BET_RANDOMNESS_LEN || data[0..8] != BET_RANDOMNESS_DISCRIMINATOR {
+        return Err(RandomnessError::InvalidRandomnessAccount.into());
+    }
+
+    let mut bet_bytes = [0u8; 32];
+    bet_bytes.copy_from_slice(&data[8..40]);
+    let mut bet_id_bytes = [0u8; 8];
+    bet_id_bytes.copy_from_slice(&data[40..48]);
+    let mut randomness = [0u8; 32];
+    randomness.copy_from_slice(&data[48..80]);
+    let mut slot_bytes = [0u8; 8];
+    slot_bytes.copy_from_slice(&data[80..88]);
+
+    let bet_randomness = BetRandomness {
+        bet: Pubkey::new_from_array(bet_bytes),
+        bet_id: u64::from_le_bytes(bet_id_bytes),
+        randomness,
+        requested_slot: u64::from_le_bytes(slot_bytes),
+        is_fulfilled: data[88] != 0,
+    };
+
+    if bet_randomness.bet != *bet || bet_randomness.bet_id != bet_id {
+        return Err(RandomnessError::InvalidRandomnessAccount.into());
+    }
+
+    Ok(bet_randomness)
+}
+
+/// Request randomness committed to a subject through CPI to `vrf_client`
+///
+/// Expects `accounts` to hold, in order: the requester (signer, pays for the PDA),
+/// the `CommittedRandomness` PDA of `subject`, the requester and `request_id`, the
+/// `vrf_client` program identity, the oracle queue, the system program, the slot
+/// hashes sysvar, the VRF program and the `vrf_client` program. The requester is
+/// part of the PDA seeds, so nobody else can create the PDA ahead of the request.
+pub fn request_committed_randomness<'info>(
+    subject: &AccountInfo<'info>,
+    accounts: &[AccountInfo<'info>],
+    request_id: u64,
+    client_seed: u8,
+) -> Result<()> {
+    if accounts.len() < 8 {
+        msg!("Expected 8 randomness request accounts, got {}", accounts.len());
+        return Err(RandomnessError::MissingRandomnessAccounts.into());
+    }
+
+    let requester = &accounts[0];
+    let committed_randomness = &accounts[1];
+    let vrf_program = &accounts[7];
+
+    if !requester.is_signer {
+        return Err(RandomnessError::UserNotSigner.into());
+    }
+
+    if *vrf_program.key != VRF_CLIENT_PROGRAM_ID {
+        return Err(RandomnessError::InvalidVrfProgram.into());
+    }
+
+    let mut data = REQUEST_COMMITTED_RANDOMNESS_DISCRIMINATOR.to_vec();
+    data.push(client_seed);
+    data.extend_from_slice(&request_id.to_le_bytes());
+
+    let instruction = Instruction {
+        program_id: VRF_CLIENT_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*requester.key, true),                // payer (signer)
+            AccountMeta::new_readonly(*subject.key, false),        // subject
+            AccountMeta::new(*committed_randomness.key, false),    // committed_randomness
+            AccountMeta::new_readonly(*accounts[2].key, false),    // program_identity
+            AccountMeta::new(*accounts[3].key, false),             // oracle_queue
+            AccountMeta::new_readonly(*accounts[4].key, false),    // system_program
+            AccountMeta::new_readonly(*accounts[5].key, false),    // slot_hashes
+            AccountMeta::new_readonly(*accounts[6].key, false),    // vrf_program
+        ],
+        data,
+    };
+
+    let mut infos = vec![subject.clone()];
+    infos.extend_from_slice(&accounts[..8]);
+    invoke(&instruction, &infos)?;
+
+    Ok(())
+}
+
+/// Load the `CommittedRandomness` PDA of a request, whether or not it has been delivered
+///
+/// Checks that the account is owned by `vrf_client`, sits at the canonical PDA of
+/// `subject`, `requester` and `request_id` and was requested for them.
+pub fn load_committed_randomness(
+    committed_randomness_account: &AccountInfo,
+    subject: &Pubkey,
+    requester: &Pubkey,
+    request_id: u64,
+) -> Result<CommittedRandomness> {
+    if *committed_randomness_account.owner != VRF_CLIENT_PROGRAM_ID {
+        msg!("Committed randomness account owner {} is not the VRF client", committed_randomness_account.owner);
+        return Err(RandomnessError::InvalidRandomnessAccount.into());
+    }
+
+    let (expected_pda, _) = Pubkey::find_program_address(
+        &[COMMITTED_RANDOMNESS_SEED, subject.as_ref(), requester.as_ref(), &request_id.to_le_bytes()],
+        &VRF_CLIENT_PROGRAM_ID,
+    );
+    if *committed_randomness_account.key != expected_pda {
+        msg!("Randomness account {} is not the PDA of request {} for {}", committed_randomness_account.key, request_id, subject);
+        return Err(RandomnessError::InvalidRandomnessAccount.into());
+    }
+
+    let data = committed_randomness_account.try_borrow_data()?;
+    if data.len() < COMMITTED_RANDOMNESS_LEN || data[0..8] != COMMITTED_RANDOMNESS_DISCRIMINATOR {
+        return Err(RandomnessError::InvalidRandomnessAccount.into());
+    }
+
+    let mut subject_bytes = [0u8; 32];
+    subject_bytes.copy_from_slice(&data[8..40]);
+    let mut requester_bytes = [0u8; 32];
+    requester_bytes.copy_from_slice(&data[40..72]);
+    let mut request_id_bytes = [0u8; 8];
+    request_id_bytes.copy_from_slice(&data[72..80]);
+    let mut randomness = [0u8; 32];
+    randomness.copy_from_slice(&data[80..112]);
+    let mut slot_bytes = [0u8; 8];
+    slot_bytes.copy_from_slice(&data[112..120]);
+
+    let committed_randomness = CommittedRandomness {
+        subject: Pubkey::new_from_array(subject_bytes),
+        requester: Pubkey::new_from_array(requester_bytes),
+        request_id: u64::from_le_bytes(request_id_bytes),
+        randomness,
+        requested_slot: u64::from_le_bytes(slot_bytes),
+        is_fulfilled: data[120] != 0,
+    };
+
+    if committed_randomness.subject != *subject
+        || committed_randomness.requester != *requester
+        || committed_randomness.request_id != request_id
+    {
+        return Err(RandomnessError::InvalidRandomnessAccount.into());
+    }
+
+    Ok(committed_randomness)
+}
+
+/// Errors that can occur when verifying VRF randomness
+#[error_code]
+pub enum RandomnessError {
+    /// Randomness, user or VRF program account was not provided
+    #[msg("Randomness accounts were not provided")]
+    MissingRandomnessAccounts,
+
+    /// Account is not a UserRandomness PDA of the VRF client
+    #[msg("Invalid randomness account")]
+    InvalidRandomnessAccount,
+
+    /// Randomness was generated for a different user
+    #[msg("Randomness belongs to a different user")]
+    InvalidRandomnessUser,
+
+    /// User owning the randomness did not sign the transaction
+    #[msg("Randomness user must sign the transaction")]
+    UserNotSigner,
+
+    /// Randomness has already been consumed
+    #[msg("Randomness has already been used")]
+    RandomnessAlreadyUsed,
+
+    /// Randomness is older than allowed
+    #[msg("Randomness is stale, request new randomness")]
+    StaleRandomness,
+
+    /// VRF client program account does not match
+    #[msg("Invalid VRF client program")]
+    InvalidVrfProgram,
+}
@@ -0,0 +1,1428 @@
+//! Fixed-point yield math shared by the YieldWars systems
+//!
+//! Every multiplier in the game is expressed in basis points (10000 = 100%).
+//! The helpers in this crate carry all intermediate values in `u128` at 1e-12
+//! precision, so chained multipliers lose at most a few trillionths before the
+//! final token amount is produced. Every rounding step rounds down so a
+//! producer can never be paid more than it earned.
+//!
+//! The crate is plain Rust with no Solana dependencies so the formulas can be
+//! unit tested off-chain and reused by `resource_production`, `staking` and
+//! `upgrade` alike. [`collect`] is the whole payout of a collection, shared by
+//! the ResourceProduction system and the read-only previews.
+
+/// Basis point denominator (10000 = 100%)
+pub const BPS_DENOMINATOR: u32 = 10_000;
+
+/// Number of seconds in an hour, used to convert per-hour rates
+pub const SECONDS_PER_HOUR: u64 = 3_600;
+
+/// Fixed-point scale for composed multipliers (1_000_000_000_000 = 1.0x)
+pub const MULTIPLIER_SCALE: u128 = 1_000_000_000_000;
+
+/// Multiply an amount by a basis point value, rounding down
+///
+/// Returns `None` if the result does not fit in a `u64`.
+pub fn mul_bps(amount: u64, bps: u32) -> Option<u64> {
+    let result = (amount as u128)
+        .checked_mul(bps as u128)?
+        .checked_div(BPS_DENOMINATOR as u128)?;
+
+    u64::try_from(result).ok()
+}
+
+/// Compose a list of basis point multipliers into a single fixed-point factor
+///
+/// The returned value is scaled by [`MULTIPLIER_SCALE`]. An empty list yields
+/// exactly `MULTIPLIER_SCALE` (1.0x). The first three multipliers compose
+/// without any rounding; every further multiplier rounds the factor down at
+/// 1e-12 precision, so the result undershoots the exact product by less than
+/// one unit of the scale per extra multiplier.
+pub fn compose_bps(multipliers_bps: &[u32]) -> Option<u128> {
+    let mut factor = MULTIPLIER_SCALE;
+
+    for multiplier in multipliers_bps {
+        factor = factor
+            .checked_mul(*multiplier as u128)?
+            .checked_div(BPS_DENOMINATOR as u128)?;
+    }
+
+    Some(factor)
+}
+
+/// Linear level curve: level 1 is 100%, each further level adds `step_bps`
+///
+/// Level 0 is treated as level 1 so freshly initialized entities are never
+/// penalized.
+pub fn level_multiplier_bps(level: u8, step_bps: u32) -> Option<u32> {
+    let extra_levels = level.saturating_sub(1) as u32;
+
+    extra_levels
+        .checked_mul(step_bps)?
+        .checked_add(BPS_DENOMINATOR)
+}
+
+/// Accrue a per-hour rate over `elapsed_seconds` with a fixed-point factor
+///
+/// `factor` is scaled by [`MULTIPLIER_SCALE`], as returned by [`compose_bps`].
+pub fn accrue_scaled(rate_per_hour: u64, factor: u128, elapsed_seconds: u64) -> Option<u64> {
+    let result = (rate_per_hour as u128)
+        .checked_mul(elapsed_seconds as u128)?
+        .checked_mul(factor)?
+        .checked_div(MULTIPLIER_SCALE.checked_mul(SECONDS_PER_HOUR as u128)?)?;
+
+    u64::try_from(result).ok()
+}
+
+/// Accrue a per-hour rate over `elapsed_seconds`, applying every multiplier
+///
+/// This is the building block for production yield and staking rewards:
+/// `rate_per_hour * elapsed_seconds / 3600 * m1 / 10000 * m2 / 10000 ...`
+/// rounded down once at the end.
+pub fn accrue(rate_per_hour: u64, multipliers_bps: &[u32], elapsed_seconds: u64) -> Option<u64> {
+    accrue_scaled(rate_per_hour, compose_bps(multipliers_bps)?, elapsed_seconds)
+}
+
+/// Efficiency factor from hardware durability (10000 = undamaged)
+///
+/// Hardware at or above `degradation_threshold` runs at full efficiency.
+/// Below the threshold efficiency falls linearly to zero at zero durability.
+pub fn durability_factor_bps(durability: u32, degradation_threshold: u32) -> u32 {
+    if durability >= degradation_threshold {
+        return BPS_DENOMINATOR;
+    }
+
+    ((durability as u64 * BPS_DENOMINATOR as u64) / degradation_threshold as u64) as u32
+}
+
+/// Effective efficiency multiplier of worn, possibly overclocked hardware
+///
+/// `rated_efficiency_bps × overclock_bps × durability factor`, rounded down.
+pub fn worn_efficiency_bps(
+    rated_efficiency_bps: u32,
+    overclock_bps: u32,
+    durability: u32,
+    degradation_threshold: u32,
+) -> Option<u32> {
+    let factor = compose_bps(&[
+        rated_efficiency_bps,
+        overclock_bps,
+        durability_factor_bps(durability, degradation_threshold),
+    ])?;
+
+    u32::try_from(factor.checked_mul(BPS_DENOMINATOR as u128)? / MULTIPLIER_SCALE).ok()
+}
+
+/// Outcome of running hardware for a period of time
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WearOutcome {
+    /// Durability left after the period
+    pub remaining_durability: u32,
+    /// Seconds the hardware actually produced before breaking down
+    pub producing_seconds: u64,
+}
+
+impl WearOutcome {
+    /// Whether the hardware broke down during the period
+    pub fn is_broken(&self) -> bool {
+        self.remaining_durability == 0
+    }
+}
+
+/// Wear hardware down for `elapsed_seconds` of production
+///
+/// Wear accrues at `wear_per_hour × overclock_bps`. If the hardware runs out
+/// of durability part way through, production stops at the moment it breaks.
+pub fn apply_wear(
+    durability: u32,
+    wear_per_hour: u32,
+    overclock_bps: u32,
+    elapsed_seconds: u64,
+) -> Option<WearOutcome> {
+    let wear = accrue(wear_per_hour as u64, &[overclock_bps], elapsed_seconds)?;
+
+    if wear < durability as u64 {
+        return Some(WearOutcome {
+            remaining_durability: durability - wear as u32,
+            producing_seconds: elapsed_seconds,
+        });
+    }
+
+    let producing_seconds = (elapsed_seconds as u128)
+        .checked_mul(durability as u128)?
+        .checked_div(wear as u128)
+        .unwrap_or(0);
+
+    Some(WearOutcome {
+        remaining_durability: 0,
+        producing_seconds: producing_seconds as u64,
+    })
+}
+
+/// Wear parameters of hardware whose durability is tracked
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HardwareWear {
+    /// Durability left at the start of the period
+    pub current_durability: u32,
+    /// Durability points lost per hour at stock clocks
+    pub wear_per_hour: u32,
+    /// Overclock multiplier (10000 = stock clocks)
+    pub overclock_bps: u32,
+    /// Efficiency of the undamaged hardware at stock clocks
+    pub rated_efficiency_bps: u32,
+    /// Durability below which efficiency starts to degrade
+    pub degradation_threshold: u32,
+}
+
+/// Hardware wear over a collection period and the efficiency to pay out
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WornPeriod {
+    /// Durability left and seconds actually produced
+    pub outcome: WearOutcome,
+    /// Efficiency of the hardware at the end of the period
+    pub end_efficiency_bps: u32,
+    /// Efficiency to apply to the whole period
+    pub average_efficiency_bps: u32,
+}
+
+/// Wear hardware down over a collection period
+///
+/// Efficiency degrades over the period, so the yield is paid out at the
+/// average of `start_efficiency_bps` and the efficiency at the end.
+pub fn wear_period(
+    hardware: &HardwareWear,
+    start_efficiency_bps: u32,
+    elapsed_seconds: u64,
+) -> Option<WornPeriod> {
+    let outcome = apply_wear(
+        hardware.current_durability,
+        hardware.wear_per_hour,
+        hardware.overclock_bps,
+        elapsed_seconds,
+    )?;
+
+    let end_efficiency_bps = worn_efficiency_bps(
+        hardware.rated_efficiency_bps,
+        hardware.overclock_bps,
+        outcome.remaining_durability,
+        hardware.degradation_threshold,
+    )?;
+
+    let average_efficiency_bps = ((start_efficiency_bps as u64 + end_efficiency_bps as u64) / 2) as u32;
+
+    Some(WornPeriod {
+        outcome,
+        end_efficiency_bps,
+        average_efficiency_bps,
+    })
+}
+
+/// Per-hour rates of a producing entity
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProductionRates {
+    /// USDC produced per hour
+    pub usdc_per_hour: u64,
+    /// AiFi produced per hour
+    pub aifi_per_hour: u64,
+    /// BTC mined per hour
+    pub btc_per_hour: u64,
+    /// ETH mined per hour
+    pub eth_per_hour: u64,
+    /// SOL earned per hour
+    pub sol_per_hour: u64,
+    /// Operating cost per hour in USDC
+    pub operating_cost_per_hour: u64,
+}
+
+/// Multipliers applied on top of the base production rates
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct YieldModifiers<'a> {
+    /// Level curve multiplier (10000 = 100%), see [`level_multiplier_bps`]
+    pub level_bps: u32,
+    /// Efficiency multiplier from the Production component (10000 = 100%)
+    pub efficiency_bps: u32,
+    /// Bonus from housing the producer in a data center (10000 = no bonus)
+    pub housing_bonus_bps: u32,
+    /// Permanent bonus of the owner's prestige (10000 = no bonus), see [`prestige_multiplier_bps`]
+    pub prestige_bps: u32,
+    /// Bonus of the owner's locked AiFi (10000 = no bonus), see [`vote_escrow_multiplier_bps`]
+    pub vote_escrow_bps: u32,
+    /// Temporary event multipliers (10000 = no effect)
+    pub event_multipliers_bps: &'a [u32],
+    /// Discount applied to operating costs (10000 = free, 0 = full price)
+    pub operating_cost_discount_bps: u32,
+}
+
+impl YieldModifiers<'static> {
+    /// Modifiers that leave the base rates unchanged
+    pub const fn neutral() -> Self {
+        YieldModifiers {
+            level_bps: BPS_DENOMINATOR,
+            efficiency_bps: BPS_DENOMINATOR,
+            housing_bonus_bps: BPS_DENOMINATOR,
+            prestige_bps: BPS_DENOMINATOR,
+            vote_escrow_bps: BPS_DENOMINATOR,
+            event_multipliers_bps: &[],
+            operating_cost_discount_bps: 0,
+        }
+    }
+}
+
+impl Default for YieldModifiers<'static> {
+    fn default() -> Self {
+        Self::neutral()
+    }
+}
+
+/// Result of a production yield calculation
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct YieldBreakdown {
+    /// Gross USDC earned
+    pub usdc_earned: u64,
+    /// Gross AiFi earned
+    pub aifi_earned: u64,
+    /// Gross BTC mined
+    pub btc_earned: u64,
+    /// Gross ETH mined
+    pub eth_earned: u64,
+    /// Gross SOL earned
+    pub sol_earned: u64,
+    /// Operating cost owed in USDC, after discounts
+    pub operating_cost: u64,
+}
+
+impl YieldBreakdown {
+    /// USDC earned minus operating costs (may be negative)
+    pub fn net_usdc(&self) -> i128 {
+        self.usdc_earned as i128 - self.operating_cost as i128
+    }
+}
+
+/// Compute the yield of a producer over `elapsed_seconds`
+///
+/// `base rate × level curve × efficiency × housing bonus × prestige × vote escrow × events`
+/// for each produced currency, and `operating cost × (1 − discount)` for the
+/// cost side. The factor is rounded down at 1e-12 precision for every
+/// multiplier past the third, as in [`compose_bps`]; each amount is then
+/// rounded down independently.
+pub fn production_yield(
+    rates: &ProductionRates,
+    modifiers: &YieldModifiers,
+    elapsed_seconds: u64,
+) -> Option<YieldBreakdown> {
+    let mut factor = compose_bps(&[
+        modifiers.level_bps,
+        modifiers.efficiency_bps,
+        modifiers.housing_bonus_bps,
+        modifiers.prestige_bps,
+        modifiers.vote_escrow_bps,
+    ])?;
+
+    for multiplier in modifiers.event_multipliers_bps {
+        factor = factor
+            .checked_mul(*multiplier as u128)?
+            .checked_div(BPS_DENOMINATOR as u128)?;
+    }
+
+    let cost_bps = BPS_DENOMINATOR.saturating_sub(modifiers.operating_cost_discount_bps);
+
+    Some(YieldBreakdown {
+        usdc_earned: accrue_scaled(rates.usdc_per_hour, factor, elapsed_seconds)?,
+        aifi_earned: accrue_scaled(rates.aifi_per_hour, factor, elapsed_seconds)?,
+        btc_earned: accrue_scaled(rates.btc_per_hour, factor, elapsed_seconds)?,
+        eth_earned: accrue_scaled(rates.eth_per_hour, factor, elapsed_seconds)?,
+        sol_earned: accrue_scaled(rates.sol_per_hour, factor, elapsed_seconds)?,
+        operating_cost: accrue(rates.operating_cost_per_hour, &[cost_bps], elapsed_seconds)?,
+    })
+}
+
+/// Minimum staking duration in seconds before any reward accrues
+pub const MIN_REWARD_DURATION: u64 = SECONDS_PER_HOUR;
+
+/// Fixed-point scale of prices quoted in USDC (1_000_000 = 1 USDC)
+pub const PRICE_SCALE: u128 = 1_000_000;
+
+/// USDC value of `amount` of a currency priced at `price`, rounded down
+pub fn usdc_value(amount: u64, price: u64) -> Option<u64> {
+    let value = (amount as u128)
+        .checked_mul(price as u128)?
+        .checked_div(PRICE_SCALE)?;
+
+    u64::try_from(value).ok()
+}
+
+/// Hourly USDC value of a producer's output, with AiFi valued at `aifi_price`
+pub fn production_value_per_hour(usdc_per_hour: u64, aifi_per_hour: u64, aifi_price: u64) -> Option<u64> {
+    usdc_per_hour.checked_add(usdc_value(aifi_per_hour, aifi_price)?)
+}
+
+/// Staking rewards settled between two checkpoints of a staking period
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StakingSettlement {
+    /// USDC earned after the lock period, free to collect
+    pub unlocked: u64,
+    /// USDC earned during the lock period, lost in part on early unstaking
+    pub locked: u64,
+}
+
+/// Cumulative staking reward after `staked_for` seconds of staking
+///
+/// Nothing is earned before [`MIN_REWARD_DURATION`]; after that the whole
+/// period counts, including the first hour.
+fn cumulative_staking_reward(value_per_hour: u64, reward_multipliers_bps: &[u32], staked_for: u64) -> Option<u64> {
+    if staked_for < MIN_REWARD_DURATION {
+        return Some(0);
+    }
+
+    accrue(value_per_hour, reward_multipliers_bps, staked_for)
+}
+
+/// Settle the staking rewards earned since the last checkpoint
+///
+/// Rewards are paid in USDC and accrue per second at `value_per_hour` times
+/// every reward multiplier, such as the reward rate and the lock tier bonus,
+/// once the entity has been staked for [`MIN_REWARD_DURATION`]. A 500 bps rate
+/// pays 5% of the production value.
+/// `settled_until` and `staked_for` are seconds since staking started. Rewards
+/// earned before `min_staking_period` are locked, the rest are unlocked.
+/// Settling in several steps never pays more than settling once.
+pub fn settle_staking_rewards(
+    value_per_hour: u64,
+    reward_multipliers_bps: &[u32],
+    settled_until: u64,
+    staked_for: u64,
+    min_staking_period: u64,
+) -> Option<StakingSettlement> {
+    let settled_until = settled_until.min(staked_for);
+    let reward_at = |seconds: u64| cumulative_staking_reward(value_per_hour, reward_multipliers_bps, seconds);
+
+    let total = reward_at(staked_for)?.checked_sub(reward_at(settled_until)?)?;
+    let locked = reward_at(staked_for.min(min_staking_period))?
+        .checked_sub(reward_at(settled_until.min(min_staking_period))?)?;
+
+    Some(StakingSettlement {
+        unlocked: total.checked_sub(locked)?,
+        locked,
+    })
+}
+
+/// Part of the locked rewards lost by unstaking before the lock period ends
+///
+/// Penalties above 100% cannot take more than the locked rewards.
+pub fn early_unstake_penalty(locked_rewards: u64, penalty_bps: u32) -> Option<u64> {
+    Some(mul_bps(locked_rewards, penalty_bps)?.min(locked_rewards))
+}
+
+/// Fixed-point scale of the staking pool reward-per-share accumulator
+pub const ACC_REWARD_SCALE: u128 = 1_000_000_000_000;
+
+/// Rewards a staking pool emits over `elapsed_seconds`, rounded down
+///
+/// A pool that is not set up (`epoch_duration` of 0) emits nothing.
+pub fn pool_emission(reward_per_epoch: u64, epoch_duration: u32, elapsed_seconds: u64) -> Option<u64> {
+    if epoch_duration == 0 {
+        return Some(0);
+    }
+
+    let emission = (reward_per_epoch as u128)
+        .checked_mul(elapsed_seconds as u128)?
+        .checked_div(epoch_duration as u128)?;
+
+    u64::try_from(emission).ok()
+}
+
+/// Growth of the reward-per-share accumulator when `emission` is shared by `total_weight`
+///
+/// Nothing accumulates while the pool is empty.
+pub fn reward_per_share(emission: u64, total_weight: u64) -> Option<u128> {
+    if total_weight == 0 {
+        return Some(0);
+    }
+
+    (emission as u128)
+        .checked_mul(ACC_REWARD_SCALE)?
+        .checked_div(total_weight as u128)
+}
+
+/// Rewards `weight` has earned since the accumulator was zero, rounded down
+///
+/// A staker is owed the difference between this value now and when they last
+/// harvested, which is stored as their reward debt. Rounding down keeps the
+/// sum paid to all stakers within the emitted rewards.
+pub fn pool_reward_debt(weight: u64, acc_reward_per_share: u128) -> Option<u64> {
+    let debt = (weight as u128)
+        .checked_mul(acc_reward_per_share)?
+        .checked_div(ACC_REWARD_SCALE)?;
+
+    u64::try_from(debt).ok()
+}
+
+/// Vote-escrow weight of `amount` locked for `lock_duration` seconds
+///
+/// `amount × lock_duration / max_lock_duration`, rounded down, so only a lock of the
+/// maximum duration weighs its full amount. Longer locks are capped at the maximum.
+pub fn vote_escrow_weight(amount: u64, lock_duration: u64, max_lock_duration: u64) -> Option<u64> {
+    if max_lock_duration == 0 {
+        return Some(0);
+    }
+
+    let weight = (amount as u128)
+        .checked_mul(lock_duration.min(max_lock_duration) as u128)?
+        .checked_div(max_lock_duration as u128)?;
+
+    u64::try_from(weight).ok()
+}
+
+/// Production multiplier granted by a vote-escrow `weight`, capped at `max_bps`
+///
+/// Every `weight_per_bps` of weight adds one basis point to the neutral multiplier.
+pub fn vote_escrow_multiplier_bps(weight: u64, weight_per_bps: u64, max_bps: u32) -> u32 {
+    if weight_per_bps == 0 {
+        return BPS_DENOMINATOR;
+    }
+
+    let multiplier = (BPS_DENOMINATOR as u64).saturating_add(weight / weight_per_bps);
+
+    multiplier.min(max_bps.max(BPS_DENOMINATOR) as u64) as u32
+}
+
+/// Random incident that can strike a producer when resources are collected
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Incident {
+    /// Nothing happened
+    None = 0,
+    /// Power outage, yield for the period is reduced
+    PowerOutage = 1,
+    /// GPU burnout, hardware loses durability
+    GpuBurnout = 2,
+    /// Crypto-mining bonus, yield for the period is increased
+    MiningBonus = 3,
+}
+
+/// Chance of each incident per collection, in basis points (10000 = certain)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IncidentOdds {
+    /// Chance of a power outage
+    pub power_outage_bps: u32,
+    /// Chance of a GPU burnout
+    pub gpu_burnout_bps: u32,
+    /// Chance of a mining bonus
+    pub mining_bonus_bps: u32,
+}
+
+/// Effective incident odds of a facility
+///
+/// Energy contracts cover part of the power supply and reduce outages by their
+/// coverage; cooling reduces burnouts by its rating, while overclocking raises
+/// them. Coverage and cooling are in basis points, capped at 100%.
+pub fn incident_odds(
+    base: &IncidentOdds,
+    cooling_rating_bps: u32,
+    energy_contract_coverage_bps: u32,
+    overclock_bps: u32,
+) -> Option<IncidentOdds> {
+    let uncovered_bps = BPS_DENOMINATOR.saturating_sub(energy_contract_coverage_bps);
+    let uncooled_bps = BPS_DENOMINATOR.saturating_sub(cooling_rating_bps);
+
+    let power_outage = mul_bps(base.power_outage_bps as u64, uncovered_bps)?;
+    let gpu_burnout = accrue(base.gpu_burnout_bps as u64, &[uncooled_bps, overclock_bps], SECONDS_PER_HOUR)?;
+
+    Some(IncidentOdds {
+        power_outage_bps: power_outage.min(BPS_DENOMINATOR as u64) as u32,
+        gpu_burnout_bps: gpu_burnout.min(BPS_DENOMINATOR as u64) as u32,
+        mining_bonus_bps: base.mining_bonus_bps.min(BPS_DENOMINATOR),
+    })
+}
+
+/// Pick the incident selected by a uniformly random `roll`
+///
+/// The roll is reduced to a basis point value and compared against the
+/// cumulative odds in the order outage, burnout, bonus. If the odds add up to
+/// more than 100% the later incidents are crowded out.
+pub fn roll_incident(roll: u64, odds: &IncidentOdds) -> Incident {
+    let value = roll % BPS_DENOMINATOR as u64;
+
+    let outage_end = odds.power_outage_bps as u64;
+    let burnout_end = outage_end + odds.gpu_burnout_bps as u64;
+    let bonus_end = burnout_end + odds.mining_bonus_bps as u64;
+
+    if value < outage_end {
+        Incident::PowerOutage
+    } else if value < burnout_end {
+        Incident::GpuBurnout
+    } else if value < bonus_end {
+        Incident::MiningBonus
+    } else {
+        Incident::None
+    }
+}
+
+/// Outcome of a risky upgrade attempt
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum UpgradeOutcome {
+    /// Upgrade succeeded, the entity reaches the next level
+    Success = 0,
+    /// Upgrade failed, the payment is lost
+    Failure = 1,
+    /// Upgrade failed badly, the entity also drops a level
+    CriticalFailure = 2,
+}
+
+/// Pick the outcome of a risky upgrade from a uniformly random `roll`
+///
+/// The roll is reduced to a basis point value. The lowest `success_bps` values
+/// succeed and the highest `critical_failure_bps` values are critical failures;
+/// when the two overlap, success wins.
+pub fn roll_upgrade(roll: u64, success_bps: u32, critical_failure_bps: u32) -> UpgradeOutcome {
+    let value = roll % BPS_DENOMINATOR as u64;
+    let critical_start = BPS_DENOMINATOR.saturating_sub(critical_failure_bps) as u64;
+
+    if value < success_bps as u64 {
+        UpgradeOutcome::Success
+    } else if value >= critical_start {
+        UpgradeOutcome::CriticalFailure
+    } else {
+        UpgradeOutcome::Failure
+    }
+}
+
+/// Yield multiplier of a collection period that overlaps a construction window
+///
+/// Seconds of `[period_start, period_end)` inside `[window_start, window_end)`
+/// produce at `window_bps`, the others at 100%. The result is the time-weighted
+/// average, rounded down.
+pub fn construction_multiplier_bps(
+    period_start: i64,
+    period_end: i64,
+    window_start: i64,
+    window_end: i64,
+    window_bps: u32,
+) -> u32 {
+    let period = period_end.saturating_sub(period_start);
+    let overlap = period_end
+        .min(window_end)
+        .saturating_sub(period_start.max(window_start));
+
+    if period <= 0 || overlap <= 0 {
+        return BPS_DENOMINATOR;
+    }
+
+    let overlap = overlap as u128;
+    let period = period as u128;
+    let weighted = overlap * window_bps as u128 + (period - overlap) * BPS_DENOMINATOR as u128;
+
+    (weighted / period) as u32
+}
+
+/// AiFi cost of skipping `seconds` of construction, rounded up
+pub fn speed_up_cost(seconds: u64, aifi_per_hour: u64) -> Option<u64> {
+    let cost = (seconds as u128)
+        .checked_mul(aifi_per_hour as u128)?
+        .div_ceil(SECONDS_PER_HOUR as u128);
+
+    u64::try_from(cost).ok()
+}
+
+/// Prestige points earned by resetting with `net_worth`
+///
+/// Nothing is earned below `threshold`. Above it every `point_value` of net worth
+/// is worth one point, rounded down.
+pub fn prestige_points(net_worth: u64, threshold: u64, point_value: u64) -> u64 {
+    if net_worth < threshold || point_value == 0 {
+        return 0;
+    }
+
+    net_worth / point_value
+}
+
+/// Production multiplier granted by `points` prestige points, capped at `max_bps`
+pub fn prestige_multiplier_bps(points: u64, bps_per_point: u32, max_bps: u32) -> u32 {
+    let bonus = (points as u128).saturating_mul(bps_per_point as u128);
+    let multiplier = (BPS_DENOMINATOR as u128).saturating_add(bonus);
+
+    multiplier.min(max_bps.max(BPS_DENOMINATOR) as u128) as u32
+}
+
+/// Share of a collection period's yield owed to the host of a hosted GPU
+///
+/// Seconds of `[period_start, period_end)` inside the hosting term
+/// `[term_start, term_end)` pay `share_bps` to the host, the others nothing. The
+/// result is the time-weighted average, rounded down.
+pub fn hosting_share_bps(
+    period_start: i64,
+    period_end: i64,
+    term_start: i64,
+    term_end: i64,
+    share_bps: u32,
+) -> u32 {
+    let period = period_end.saturating_sub(period_start);
+    let overlap = period_end
+        .min(term_end)
+        .saturating_sub(period_start.max(term_start));
+
+    if period <= 0 || overlap <= 0 {
+        return 0;
+    }
+
+    let share_bps = share_bps.min(BPS_DENOMINATOR) as u128;
+    (overlap as u128 * share_bps / period as u128) as u32
+}
+
+/// Refund for returning hardware worth `value` to the game
+///
+/// `value × salvage_rate_bps × durability / max_durability`, rounded down. Hardware
+/// without a maximum durability is treated as undamaged.
+pub fn salvage_value(value: u64, salvage_rate_bps: u32, durability: u32, max_durability: u32) -> Option<u64> {
+    let refund = mul_bps(value, salvage_rate_bps)?;
+    if max_durability == 0 {
+        return Some(refund);
+    }
+
+    let durability = durability.min(max_durability);
+    Some((refund as u128 * durability as u128 / max_durability as u128) as u64)
+}
+
+/// Incident roll of a collection and the effect of each incident at the facility
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IncidentRoll {
+    /// Uniformly random value the incident is picked with, see [`roll_incident`]
+    pub roll: u64,
+    /// Odds of the facility before cooling, energy contracts and overclocking
+    pub base_odds: IncidentOdds,
+    /// Cooling rating of the facility (10000 = 100%)
+    pub cooling_rating_bps: u32,
+    /// Share of power covered by energy contracts (10000 = 100%)
+    pub energy_contract_coverage_bps: u32,
+    /// Yield multiplier of a power outage
+    pub outage_multiplier_bps: u32,
+    /// Yield multiplier of a mining bonus
+    pub mining_bonus_multiplier_bps: u32,
+    /// Durability points a burnout costs tracked hardware
+    pub burnout_damage: u32,
+}
+
+/// Upgrade construction window of a producer
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConstructionWindow {
+    /// Start of the construction (Unix timestamp)
+    pub start_time: i64,
+    /// End of the construction (Unix timestamp)
+    pub end_time: i64,
+    /// Yield multiplier while under construction (10000 = unaffected)
+    pub yield_bps: u32,
+}
+
+/// Term of an active hosting agreement
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HostingTerm {
+    /// Start of the term (Unix timestamp)
+    pub start_time: i64,
+    /// End of the term (Unix timestamp)
+    pub end_time: i64,
+    /// Host's share of the yield earned during the term
+    pub share_bps: u32,
+}
+
+/// Everything the payout of a collection depends on
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Collection {
+    /// Time of the previous collection (Unix timestamp)
+    pub period_start: i64,
+    /// Time the collection ends at (Unix timestamp)
+    pub period_end: i64,
+    /// Base rates of the producer
+    pub rates: ProductionRates,
+    /// Efficiency multiplier of the producer at the start of the period
+    pub efficiency_bps: u32,
+    /// Wear parameters, if the hardware's durability is tracked
+    pub hardware: Option<HardwareWear>,
+    /// Prestige bonus of the owner (10000 = no bonus)
+    pub prestige_bps: u32,
+    /// Vote-escrow bonus of the owner (10000 = no bonus)
+    pub vote_escrow_bps: u32,
+    /// Latest upgrade construction of the producer
+    pub construction: ConstructionWindow,
+    /// Incident roll, if the facility rolls one for this collection
+    pub incident: Option<IncidentRoll>,
+    /// Active hosting agreement of the producer
+    pub hosting: Option<HostingTerm>,
+}
+
+/// State of tracked hardware after a collection
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WornHardware {
+    /// Durability left, after burnout damage
+    pub remaining_durability: u32,
+    /// Efficiency multiplier of the hardware from now on
+    pub efficiency_bps: u32,
+}
+
+/// Payout and side effects of a collection
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CollectionOutcome {
+    /// Yield the owner keeps after the host's share, and the operating cost they pay
+    pub owner: YieldBreakdown,
+    /// Yield paid to the host, who never pays operating costs
+    pub host: YieldBreakdown,
+    /// Time-weighted share of the yield paid to the host
+    pub host_share_bps: u32,
+    /// Seconds of production counted (shorter than the period if the hardware breaks)
+    pub producing_seconds: u64,
+    /// State of tracked hardware after wear and burnout damage
+    pub hardware: Option<WornHardware>,
+    /// Incident picked by the roll
+    pub incident: Incident,
+    /// Yield multiplier applied by the incident (10000 = no effect)
+    pub incident_multiplier_bps: u32,
+    /// Durability points lost to the incident
+    pub incident_damage: u32,
+}
+
+/// Compute the payout of a collection over `[period_start, period_end)`
+///
+/// Tracked hardware wears down, stops producing the moment it breaks and is paid
+/// at the average efficiency of the period. The incident picked by the roll
+/// scales the yield or damages tracked hardware, seconds under construction
+/// produce at the construction yield, and the host's share of every currency is
+/// split off the owner's yield. An empty period pays nothing.
+pub fn collect(collection: &Collection) -> Option<CollectionOutcome> {
+    let elapsed_seconds = collection.period_end.saturating_sub(collection.period_start).max(0) as u64;
+
+    let mut producing_seconds = elapsed_seconds;
+    let mut efficiency_bps = collection.efficiency_bps;
+    let mut worn_period = None;
+
+    if let Some(hardware) = &collection.hardware {
+        let period = wear_period(hardware, efficiency_bps, elapsed_seconds)?;
+        efficiency_bps = period.average_efficiency_bps;
+        producing_seconds = period.outcome.producing_seconds;
+        worn_period = Some(period);
+    }
+
+    let mut incident = Incident::None;
+    let mut incident_multiplier_bps = BPS_DENOMINATOR;
+    let mut incident_damage = 0;
+
+    if let Some(roll) = &collection.incident {
+        let overclock_bps = collection.hardware.map_or(BPS_DENOMINATOR, |hardware| hardware.overclock_bps);
+        let odds = incident_odds(
+            &roll.base_odds,
+            roll.cooling_rating_bps,
+            roll.energy_contract_coverage_bps,
+            overclock_bps,
+        )?;
+
+        incident = roll_incident(roll.roll, &odds);
+
+        match incident {
+            Incident::PowerOutage => incident_multiplier_bps = roll.outage_multiplier_bps,
+            Incident::MiningBonus => incident_multiplier_bps = roll.mining_bonus_multiplier_bps,
+            // Burnouts only damage hardware whose durability is tracked
+            Incident::GpuBurnout if worn_period.is_some() => incident_damage = roll.burnout_damage,
+            _ => {},
+        }
+    }
+
+    let construction_bps = construction_multiplier_bps(
+        collection.period_start,
+        collection.period_end,
+        collection.construction.start_time,
+        collection.construction.end_time,
+        collection.construction.yield_bps,
+    );
+    let event_multipliers = [incident_multiplier_bps, construction_bps];
+    let modifiers = YieldModifiers {
+        efficiency_bps,
+        prestige_bps: collection.prestige_bps,
+        vote_escrow_bps: collection.vote_escrow_bps,
+        event_multipliers_bps: &event_multipliers,
+        ..YieldModifiers::neutral()
+    };
+
+    let mut owner = production_yield(&collection.rates, &modifiers, producing_seconds)?;
+
+    let host_share_bps = collection.hosting.map_or(0, |term| {
+        hosting_share_bps(
+            collection.period_start,
+            collection.period_end,
+            term.start_time,
+            term.end_time,
+            term.share_bps,
+        )
+    });
+    let host = YieldBreakdown {
+        usdc_earned: mul_bps(owner.usdc_earned, host_share_bps)?,
+        aifi_earned: mul_bps(owner.aifi_earned, host_share_bps)?,
+        btc_earned: mul_bps(owner.btc_earned, host_share_bps)?,
+        eth_earned: mul_bps(owner.eth_earned, host_share_bps)?,
+        sol_earned: mul_bps(owner.sol_earned, host_share_bps)?,
+        operating_cost: 0,
+    };
+
+    owner.usdc_earned -= host.usdc_earned;
+    owner.aifi_earned -= host.aifi_earned;
+    owner.btc_earned -= host.btc_earned;
+    owner.eth_earned -= host.eth_earned;
+    owner.sol_earned -= host.sol_earned;
+
+    let hardware = match (collection.hardware, worn_period) {
+        (Some(hardware), Some(period)) => {
+            let remaining_durability = period.outcome.remaining_durability.saturating_sub(incident_damage);
+            let efficiency_bps = if incident_damage > 0 {
+                worn_efficiency_bps(
+                    hardware.rated_efficiency_bps,
+                    hardware.overclock_bps,
+                    remaining_durability,
+                    hardware.degradation_threshold,
+                )?
+            } else {
+                period.end_efficiency_bps
+            };
+
+            Some(WornHardware { remaining_durability, efficiency_bps })
+        },
+        _ => None,
+    };
+
+    Some(CollectionOutcome {
+        owner,
+        host,
+        host_share_bps,
+        producing_seconds,
+        hardware,
+        incident,
+        incident_multiplier_bps,
+        incident_damage,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE_HOUR: u64 = SECONDS_PER_HOUR;
+
+    fn rates(usdc: u64, aifi: u64, cost: u64) -> ProductionRates {
+        ProductionRates {
+            usdc_per_hour: usdc,
+            aifi_per_hour: aifi,
+            operating_cost_per_hour: cost,
+            ..ProductionRates::default()
+        }
+    }
+
+    #[test]
+    fn mul_bps_rounds_down() {
+        assert_eq!(mul_bps(10_000, 15_000), Some(15_000));
+        assert_eq!(mul_bps(3, 5_000), Some(1));
+        assert_eq!(mul_bps(1, 9_999), Some(0));
+        assert_eq!(mul_bps(u64::MAX, BPS_DENOMINATOR), Some(u64::MAX));
+        assert_eq!(mul_bps(u64::MAX, BPS_DENOMINATOR + 1), None);
+    }
+
+    #[test]
+    fn compose_is_exact_for_three_multipliers() {
+        assert_eq!(compose_bps(&[]), Some(MULTIPLIER_SCALE));
+        assert_eq!(compose_bps(&[15_000, 12_345, 9_999]), Some(1_851_564_825_000));
+    }
+
+    #[test]
+    fn compose_rounds_down_past_three_multipliers() {
+        // 1.851564825 × 0.0003 = 0.0005554694475, truncated at 1e-12
+        assert_eq!(
+            compose_bps(&[15_000, 12_345, 9_999, 3]),
+            Some(555_469_447)
+        );
+        assert_eq!(
+            compose_bps(&[10_000, 10_000, 10_000, 10_000, 10_000, 10_000, 10_000]),
+            Some(MULTIPLIER_SCALE)
+        );
+    }
+
+    #[test]
+    fn level_curve_is_linear_from_level_one() {
+        assert_eq!(level_multiplier_bps(0, 500), Some(10_000));
+        assert_eq!(level_multiplier_bps(1, 500), Some(10_000));
+        assert_eq!(level_multiplier_bps(2, 500), Some(10_500));
+        assert_eq!(level_multiplier_bps(10, 2_500), Some(32_500));
+        assert_eq!(level_multiplier_bps(u8::MAX, u32::MAX), None);
+    }
+
+    #[test]
+    fn fractional_efficiency_is_not_truncated() {
+        // 150% used to act as 100% because of integer division
+        let breakdown = production_yield(
+            &rates(1_000_000, 2_000_000, 0),
+            &YieldModifiers { efficiency_bps: 15_000, ..YieldModifiers::neutral() },
+            ONE_HOUR,
+        )
+        .unwrap();
+        assert_eq!(breakdown.usdc_earned, 1_500_000);
+        assert_eq!(breakdown.aifi_earned, 3_000_000);
+
+        // Anything under 100% used to produce nothing
+        let breakdown = production_yield(
+            &rates(1_000_000, 0, 0),
+            &YieldModifiers { efficiency_bps: 9_999, ..YieldModifiers::neutral() },
+            ONE_HOUR,
+        )
+        .unwrap();
+        assert_eq!(breakdown.usdc_earned, 999_900);
+    }
+
+    #[test]
+    fn partial_hours_accrue_per_second() {
+        assert_eq!(accrue(3_600, &[], 1), Some(1));
+        assert_eq!(accrue(3_600, &[], 1_799), Some(1_799));
+        // 1 token per hour for 59 minutes rounds down to zero
+        assert_eq!(accrue(1, &[], ONE_HOUR - 1), Some(0));
+        assert_eq!(accrue(1, &[], ONE_HOUR), Some(1));
+        // 5 USDC/h for 1 second is 1388.88.. micro-USDC
+        assert_eq!(accrue(5_000_000, &[], 1), Some(1_388));
+    }
+
+    #[test]
+    fn multipliers_are_rounded_once() {
+        // Rounding after each step would lose a unit at every multiplication
+        // 7 * 1.5 = 10.5, * 1.5 = 15.75, * 1.5 = 23.625
+        assert_eq!(accrue(7, &[15_000, 15_000, 15_000], ONE_HOUR), Some(23));
+        let stepwise = mul_bps(mul_bps(mul_bps(7, 15_000).unwrap(), 15_000).unwrap(), 15_000);
+        assert_eq!(stepwise, Some(22));
+    }
+
+    #[test]
+    fn full_formula_applies_every_modifier() {
+        let events = [11_000, 5_000];
+        let modifiers = YieldModifiers {
+            level_bps: level_multiplier_bps(3, 1_000).unwrap(),
+            efficiency_bps: 9_000,
+            housing_bonus_bps: 11_000,
+            prestige_bps: 12_500,
+            vote_escrow_bps: 10_800,
+            event_multipliers_bps: &events,
+            operating_cost_discount_bps: 2_500,
+        };
+        let breakdown =
+            production_yield(&rates(10_000_000, 1_000_000, 4_000_000), &modifiers, 2 * ONE_HOUR)
+                .unwrap();
+
+        // 10 USDC * 2h * 1.2 * 0.9 * 1.1 * 1.25 * 1.08 * 1.1 * 0.5 = 17.6418 USDC
+        assert_eq!(breakdown.usdc_earned, 17_641_800);
+        assert_eq!(breakdown.aifi_earned, 1_764_180);
+        // 4 USDC * 2h * 0.75 = 6 USDC
+        assert_eq!(breakdown.operating_cost, 6_000_000);
+        assert_eq!(breakdown.net_usdc(), 11_641_800);
+    }
+
+    #[test]
+    fn mined_currencies_share_the_yield_factor() {
+        let miner = ProductionRates {
+            btc_per_hour: 50,
+            eth_per_hour: 1_000,
+            sol_per_hour: 2_000_000,
+            operating_cost_per_hour: 1_000_000,
+            ..ProductionRates::default()
+        };
+        let modifiers = YieldModifiers { efficiency_bps: 12_000, ..YieldModifiers::neutral() };
+        let breakdown = production_yield(&miner, &modifiers, 90 * 60).unwrap();
+
+        // 1.5h at 120%: 50 sats/h -> 90, 1000 -> 1800, 2 SOL -> 3.6 SOL
+        assert_eq!(breakdown.btc_earned, 90);
+        assert_eq!(breakdown.eth_earned, 1_800);
+        assert_eq!(breakdown.sol_earned, 3_600_000);
+        assert_eq!(breakdown.usdc_earned, 0);
+        assert_eq!(breakdown.operating_cost, 1_500_000);
+    }
+
+    #[test]
+    fn discount_is_capped_at_free() {
+        let breakdown = production_yield(
+            &rates(0, 0, 1_000_000),
+            &YieldModifiers { operating_cost_discount_bps: 20_000, ..YieldModifiers::neutral() },
+            ONE_HOUR,
+        )
+        .unwrap();
+        assert_eq!(breakdown.operating_cost, 0);
+    }
+
+    #[test]
+    fn costs_can_exceed_earnings() {
+        let breakdown =
+            production_yield(&rates(1_000, 0, 3_000), &YieldModifiers::neutral(), ONE_HOUR)
+                .unwrap();
+        assert_eq!(breakdown.net_usdc(), -2_000);
+    }
+
+    #[test]
+    fn zero_elapsed_time_yields_nothing() {
+        let breakdown =
+            production_yield(&rates(5_000_000, 5_000_000, 1_000_000), &YieldModifiers::neutral(), 0)
+                .unwrap();
+        assert_eq!(breakdown, YieldBreakdown::default());
+    }
+
+    #[test]
+    fn overflow_is_reported() {
+        assert_eq!(accrue(u64::MAX, &[u32::MAX], u64::MAX), None);
+        assert_eq!(accrue(u64::MAX, &[], ONE_HOUR * 2), None);
+        assert_eq!(accrue(u64::MAX, &[], ONE_HOUR), Some(u64::MAX));
+    }
+
+    #[test]
+    fn durability_factor_degrades_below_threshold() {
+        assert_eq!(durability_factor_bps(10_000, 5_000), 10_000);
+        assert_eq!(durability_factor_bps(5_000, 5_000), 10_000);
+        assert_eq!(durability_factor_bps(2_500, 5_000), 5_000);
+        assert_eq!(durability_factor_bps(1, 3_000), 3);
+        assert_eq!(durability_factor_bps(0, 5_000), 0);
+        assert_eq!(durability_factor_bps(0, 0), 10_000);
+    }
+
+    #[test]
+    fn worn_efficiency_combines_overclock_and_wear() {
+        assert_eq!(worn_efficiency_bps(10_000, 10_000, 10_000, 5_000), Some(10_000));
+        assert_eq!(worn_efficiency_bps(12_000, 12_500, 10_000, 5_000), Some(15_000));
+        assert_eq!(worn_efficiency_bps(12_000, 12_500, 2_500, 5_000), Some(7_500));
+        assert_eq!(worn_efficiency_bps(12_000, 12_500, 0, 5_000), Some(0));
+    }
+
+    #[test]
+    fn wear_accrues_with_overclock() {
+        let outcome = apply_wear(10_000, 100, 10_000, 10 * ONE_HOUR).unwrap();
+        assert_eq!(outcome, WearOutcome { remaining_durability: 9_000, producing_seconds: 10 * ONE_HOUR });
+
+        let outcome = apply_wear(10_000, 100, 15_000, 10 * ONE_HOUR).unwrap();
+        assert_eq!(outcome.remaining_durability, 8_500);
+        assert!(!outcome.is_broken());
+    }
+
+    #[test]
+    fn hardware_stops_producing_when_it_breaks() {
+        // 500 points left at 100 per hour lasts exactly 5 of the 8 hours
+        let outcome = apply_wear(500, 100, 10_000, 8 * ONE_HOUR).unwrap();
+        assert!(outcome.is_broken());
+        assert_eq!(outcome.producing_seconds, 5 * ONE_HOUR);
+
+        // Exactly running out also counts as broken
+        let outcome = apply_wear(500, 100, 10_000, 5 * ONE_HOUR).unwrap();
+        assert!(outcome.is_broken());
+        assert_eq!(outcome.producing_seconds, 5 * ONE_HOUR);
+
+        // Already broken hardware produces nothing
+        let outcome = apply_wear(0, 100, 10_000, ONE_HOUR).unwrap();
+        assert_eq!(outcome.producing_seconds, 0);
+    }
+
+    #[test]
+    fn hardware_without_wear_never_breaks() {
+        let outcome = apply_wear(10_000, 0, 20_000, 1_000 * ONE_HOUR).unwrap();
+        assert_eq!(outcome.remaining_durability, 10_000);
+        assert_eq!(outcome.producing_seconds, 1_000 * ONE_HOUR);
+    }
+
+    #[test]
+    fn cooling_and_energy_contracts_reduce_odds() {
+        let base = IncidentOdds { power_outage_bps: 1_000, gpu_burnout_bps: 800, mining_bonus_bps: 300 };
+
+        assert_eq!(incident_odds(&base, 0, 0, BPS_DENOMINATOR), Some(base));
+
+        let odds = incident_odds(&base, 7_500, 5_000, BPS_DENOMINATOR).unwrap();
+        assert_eq!(odds, IncidentOdds { power_outage_bps: 500, gpu_burnout_bps: 200, mining_bonus_bps: 300 });
+
+        // Full coverage and perfect cooling remove the risk but not the bonus
+        let odds = incident_odds(&base, 10_000, 12_000, BPS_DENOMINATOR).unwrap();
+        assert_eq!(odds, IncidentOdds { power_outage_bps: 0, gpu_burnout_bps: 0, mining_bonus_bps: 300 });
+    }
+
+    #[test]
+    fn overclocking_raises_burnout_odds() {
+        let base = IncidentOdds { power_outage_bps: 0, gpu_burnout_bps: 800, mining_bonus_bps: 0 };
+        let odds = incident_odds(&base, 5_000, 0, 15_000).unwrap();
+        assert_eq!(odds.gpu_burnout_bps, 600);
+
+        let base = IncidentOdds { gpu_burnout_bps: 9_000, ..base };
+        let odds = incident_odds(&base, 0, 0, 20_000).unwrap();
+        assert_eq!(odds.gpu_burnout_bps, BPS_DENOMINATOR);
+    }
+
+    #[test]
+    fn roll_selects_incident_by_cumulative_odds() {
+        let odds = IncidentOdds { power_outage_bps: 100, gpu_burnout_bps: 200, mining_bonus_bps: 300 };
+
+        assert_eq!(roll_incident(0, &odds), Incident::PowerOutage);
+        assert_eq!(roll_incident(99, &odds), Incident::PowerOutage);
+        assert_eq!(roll_incident(100, &odds), Incident::GpuBurnout);
+        assert_eq!(roll_incident(299, &odds), Incident::GpuBurnout);
+        assert_eq!(roll_incident(300, &odds), Incident::MiningBonus);
+        assert_eq!(roll_incident(599, &odds), Incident::MiningBonus);
+        assert_eq!(roll_incident(600, &odds), Incident::None);
+        // Rolls wrap around the basis point range
+        assert_eq!(roll_incident(10_050, &odds), Incident::PowerOutage);
+        assert_eq!(roll_incident(u64::MAX, &IncidentOdds::default()), Incident::None);
+    }
+
+    #[test]
+    fn wear_period_pays_average_efficiency() {
+        let hardware = HardwareWear {
+            current_durability: 6_000,
+            wear_per_hour: 1_000,
+            overclock_bps: BPS_DENOMINATOR,
+            rated_efficiency_bps: BPS_DENOMINATOR,
+            degradation_threshold: 5_000,
+        };
+
+        // 6000 -> 2000 durability ends at 40% efficiency, averaged with 100%
+        let period = wear_period(&hardware, BPS_DENOMINATOR, 4 * ONE_HOUR).unwrap();
+        assert_eq!(period.outcome.remaining_durability, 2_000);
+        assert_eq!(period.end_efficiency_bps, 4_000);
+        assert_eq!(period.average_efficiency_bps, 7_000);
+    }
+
+    #[test]
+    fn upgrade_rolls_split_success_failure_and_critical() {
+        // 60% success, 15% critical failure, 25% plain failure
+        assert_eq!(roll_upgrade(0, 6_000, 1_500), UpgradeOutcome::Success);
+        assert_eq!(roll_upgrade(5_999, 6_000, 1_500), UpgradeOutcome::Success);
+        assert_eq!(roll_upgrade(6_000, 6_000, 1_500), UpgradeOutcome::Failure);
+        assert_eq!(roll_upgrade(8_499, 6_000, 1_500), UpgradeOutcome::Failure);
+        assert_eq!(roll_upgrade(8_500, 6_000, 1_500), UpgradeOutcome::CriticalFailure);
+        assert_eq!(roll_upgrade(19_999, 6_000, 1_500), UpgradeOutcome::CriticalFailure);
+        // Overlapping odds favour success
+        assert_eq!(roll_upgrade(9_000, 9_500, 1_000), UpgradeOutcome::Success);
+        assert_eq!(roll_upgrade(u64::MAX, 0, 0), UpgradeOutcome::Failure);
+    }
+
+    #[test]
+    fn construction_slows_only_overlapping_seconds() {
+        // Collecting 4h, of which the last 2h were under construction at 25%
+        assert_eq!(construction_multiplier_bps(0, 4 * 3_600, 2 * 3_600, 10 * 3_600, 2_500), 6_250);
+        // Paused for the whole period
+        assert_eq!(construction_multiplier_bps(100, 200, 0, 1_000, 0), 0);
+        // Window entirely in the past or future
+        assert_eq!(construction_multiplier_bps(1_000, 2_000, 0, 1_000, 0), BPS_DENOMINATOR);
+        assert_eq!(construction_multiplier_bps(1_000, 2_000, 2_000, 3_000, 0), BPS_DENOMINATOR);
+    }
+
+    #[test]
+    fn hosts_share_only_hosted_seconds() {
+        // Collecting 4h, of which the first 3h were hosted at 30%
+        assert_eq!(hosting_share_bps(0, 4 * 3_600, -3_600, 3 * 3_600, 3_000), 2_250);
+        // Hosted for the whole period
+        assert_eq!(hosting_share_bps(100, 200, 0, 1_000, 3_000), 3_000);
+        // Term entirely in the past or future
+        assert_eq!(hosting_share_bps(1_000, 2_000, 0, 1_000, 3_000), 0);
+        assert_eq!(hosting_share_bps(1_000, 2_000, 2_000, 3_000, 3_000), 0);
+        // The host never receives more than the yield
+        assert_eq!(hosting_share_bps(0, 100, 0, 100, 20_000), BPS_DENOMINATOR);
+    }
+
+    #[test]
+    fn speed_ups_round_up() {
+        // 10 AiFi per hour skipped, 1 second still costs something
+        assert_eq!(speed_up_cost(1, 10_000_000), Some(2_778));
+        assert_eq!(speed_up_cost(1_800, 10_000_000), Some(5_000_000));
+        assert_eq!(speed_up_cost(0, 10_000_000), Some(0));
+    }
+
+    #[test]
+    fn prestige_needs_the_threshold() {
+        assert_eq!(prestige_points(999_999, 1_000_000, 100_000), 0);
+        assert_eq!(prestige_points(1_000_000, 1_000_000, 100_000), 10);
+        assert_eq!(prestige_points(1_250_000, 1_000_000, 100_000), 12);
+        assert_eq!(prestige_points(1_250_000, 1_000_000, 0), 0);
+
+        // 1% per point, capped at 3x
+        assert_eq!(prestige_multiplier_bps(0, 100, 30_000), BPS_DENOMINATOR);
+        assert_eq!(prestige_multiplier_bps(12, 100, 30_000), 11_200);
+        assert_eq!(prestige_multiplier_bps(u64::MAX, 100, 30_000), 30_000);
+    }
+
+    #[test]
+    fn salvage_scales_with_durability() {
+        // Half the value back at full durability, a quarter when half worn
+        assert_eq!(salvage_value(1_000_000, 5_000, 100, 100), Some(500_000));
+        assert_eq!(salvage_value(1_000_000, 5_000, 50, 100), Some(250_000));
+        assert_eq!(salvage_value(1_000_000, 5_000, 0, 100), Some(0));
+        // Durability above the maximum or without tracking counts as undamaged
+        assert_eq!(salvage_value(1_000_000, 5_000, 150, 100), Some(500_000));
+        assert_eq!(salvage_value(1_000_000, 5_000, 0, 0), Some(500_000));
+    }
+
+    #[test]
+    fn production_is_valued_in_usdc() {
+        // 2 USDC and 3 AiFi per hour with AiFi at 0.5 USDC
+        assert_eq!(production_value_per_hour(2_000_000, 3_000_000, 500_000), Some(3_500_000));
+        assert_eq!(production_value_per_hour(2_000_000, 3_000_000, 0), Some(2_000_000));
+        assert_eq!(usdc_value(1, 999_999), Some(0));
+        assert_eq!(production_value_per_hour(u64::MAX, 1_000_000, 1_000_000), None);
+    }
+
+    #[test]
+    fn staking_rewards_need_a_full_hour() {
+        assert_eq!(
+            settle_staking_rewards(1_000_000, &[10_000], 0, MIN_REWARD_DURATION - 1, 0),
+            Some(StakingSettlement::default())
+        );
+
+        // The first hour is paid once it is complete
+        let settlement = settle_staking_rewards(3_000_000, &[500], 0, 2 * ONE_HOUR, 0).unwrap();
+        assert_eq!(settlement, StakingSettlement { unlocked: 300_000, locked: 0 });
+        let settlement = settle_staking_rewards(3_000_000, &[500], ONE_HOUR / 2, 2 * ONE_HOUR, 0).unwrap();
+        assert_eq!(settlement.unlocked, 300_000);
+    }
+
+    #[test]
+    fn checkpoints_do_not_change_the_total() {
+        let once = settle_staking_rewards(1_000_001, &[777], 0, 10 * ONE_HOUR, 0).unwrap();
+
+        let mut settled = 0;
+        let mut total = 0;
+        for staked_for in [ONE_HOUR + 7, 3 * ONE_HOUR + 1, 5 * ONE_HOUR, 10 * ONE_HOUR] {
+            total += settle_staking_rewards(1_000_001, &[777], settled, staked_for, 0).unwrap().unlocked;
+            settled = staked_for;
+        }
+
+        assert_eq!(total, once.unlocked);
+    }
+
+    #[test]
+    fn lock_period_rewards_are_locked() {
+        // One hour inside a two hour lock, one hour after it
+        let settlement = settle_staking_rewards(1_000_000, &[10_000], ONE_HOUR, 3 * ONE_HOUR, 2 * ONE_HOUR).unwrap();
+        assert_eq!(settlement, StakingSettlement { unlocked: 1_000_000, locked: 1_000_000 });
+
+        let settlement = settle_staking_rewards(1_000_000, &[10_000], 0, ONE_HOUR, 2 * ONE_HOUR).unwrap();
+        assert_eq!(settlement, StakingSettlement { unlocked: 0, locked: 1_000_000 });
+    }
+
+    #[test]
+    fn lock_tier_multiplies_rewards() {
+        // 5% of the production value with a 2x tier bonus
+        let settlement = settle_staking_rewards(1_000_000, &[500, 20_000], 0, ONE_HOUR, 0).unwrap();
+        assert_eq!(settlement.unlocked, 100_000);
+    }
+
+    #[test]
+    fn early_unstake_is_penalized() {
+        assert_eq!(early_unstake_penalty(1_000_000, 2_500), Some(250_000));
+
+        // Penalties above 100% cannot take more than the reward
+        assert_eq!(early_unstake_penalty(1_000_000, 20_000), Some(1_000_000));
+    }
+
+    #[test]
+    fn pool_shares_a_bounded_emission() {
+        // 24 USDC per day, half a day elapsed
+        let emission = pool_emission(24_000_000, 86_400, 43_200).unwrap();
+        assert_eq!(emission, 12_000_000);
+        assert_eq!(pool_emission(24_000_000, 0, 43_200), Some(0));
+
+        // Stakers of weight 1 and 2 split the emission 1:2 and never exceed it
+        let acc = reward_per_share(emission, 3).unwrap();
+        let first = pool_reward_debt(1, acc).unwrap();
+        let second = pool_reward_debt(2, acc).unwrap();
+        assert_eq!((first, second), (4_000_000, 8_000_000));
+
+        let acc = reward_per_share(10, 3).unwrap();
+        assert!(pool_reward_debt(1, acc).unwrap() + pool_reward_debt(2, acc).unwrap() <= 10);
+
+        assert_eq!(reward_per_share(emission, 0), Some(0));
+    }
+
+    #[test]
+    fn longer_locks_weigh_more() {
+        let year = 365 * 86_400;
+
+        // 1,000 AiFi for a quarter, half and all of the maximum lock
+        assert_eq!(vote_escrow_weight(1_000_000_000, year / 4, year), Some(250_000_000));
+        assert_eq!(vote_escrow_weight(1_000_000_000, year / 2, year), Some(500_000_000));
+        assert_eq!(vote_escrow_weight(1_000_000_000, 2 * year, year), Some(1_000_000_000));
+        assert_eq!(vote_escrow_weight(1_000_000_000, year, 0), Some(0));
+
+        // One basis point per 10 AiFi of weight, capped at 1.25x
+        assert_eq!(vote_escrow_multiplier_bps(0, 10_000_000, 12_500), BPS_DENOMINATOR);
+        assert_eq!(vote_escrow_multiplier_bps(500_000_000, 10_000_000, 12_500), 10_050);
+        assert_eq!(vote_escrow_multiplier_bps(u64::MAX, 10_000_000, 12_500), 12_500);
+        assert_eq!(vote_escrow_multiplier_bps(u64::MAX, 0, 12_500), BPS_DENOMINATOR);
+    }
+
+    fn collection(rates: ProductionRates) -> Collection {
+        Collection {
+            period_start: 0,
+            period_end: ONE_HOUR as i64,
+            rates,
+            efficiency_bps: BPS_DENOMINATOR,
+            prestige_bps: BPS_DENOMINATOR,
+            vote_escrow_bps: BPS_DENOMINATOR,
+            ..Collection::default()
+        }
+    }
+
+    #[test]
+    fn collect_splits_the_host_share() {
+        let hosted = Collection {
+            hosting: Some(HostingTerm { start_time: 0, end_time: 1_800, share_bps: 2_000 }),
+            ..collection(rates(10_000_000, 1_000_000, 1_000_000))
+        };
+        let outcome = collect(&hosted).unwrap();
+
+        // Half of the hour hosted at 20% is a 10% share
+        assert_eq!(outcome.host_share_bps, 1_000);
+        assert_eq!(outcome.host.usdc_earned, 1_000_000);
+        assert_eq!(outcome.host.aifi_earned, 100_000);
+        assert_eq!(outcome.owner.usdc_earned, 9_000_000);
+        assert_eq!(outcome.owner.aifi_earned, 900_000);
+        // The owner alone pays the operating costs
+        assert_eq!(outcome.owner.operating_cost, 1_000_000);
+        assert_eq!(outcome.host.operating_cost, 0);
+    }
+
+    #[test]
+    fn collect_applies_the_rolled_incident() {
+        let roll = IncidentRoll {
+            roll: 150,
+            base_odds: IncidentOdds { power_outage_bps: 100, gpu_burnout_bps: 100, mining_bonus_bps: 100 },
+            outage_multiplier_bps: 5_000,
+            mining_bonus_multiplier_bps: 15_000,
+            burnout_damage: 500,
+            ..IncidentRoll::default()
+        };
+        let hardware = HardwareWear {
+            current_durability: 10_000,
+            wear_per_hour: 100,
+            overclock_bps: BPS_DENOMINATOR,
+            rated_efficiency_bps: BPS_DENOMINATOR,
+            degradation_threshold: 9_500,
+        };
+
+        // 150 falls into the burnout range [100, 200)
+        let burnout = collect(&Collection {
+            hardware: Some(hardware),
+            incident: Some(roll),
+            ..collection(rates(1_000_000, 0, 0))
+        })
+        .unwrap();
+        assert_eq!(burnout.incident, Incident::GpuBurnout);
+        assert_eq!(burnout.incident_damage, 500);
+        assert_eq!(
+            burnout.hardware,
+            Some(WornHardware { remaining_durability: 9_400, efficiency_bps: 9_894 })
+        );
+        assert_eq!(burnout.owner.usdc_earned, 1_000_000);
+
+        // Untracked hardware shrugs the burnout off, the outage halves the yield
+        let untracked = collect(&Collection { incident: Some(roll), ..collection(rates(1_000_000, 0, 0)) }).unwrap();
+        assert_eq!(untracked.incident_damage, 0);
+        assert_eq!(untracked.hardware, None);
+
+        let outage = collect(&Collection {
+            incident: Some(IncidentRoll { roll: 50, ..roll }),
+            ..collection(rates(1_000_000, 0, 0))
+        })
+        .unwrap();
+        assert_eq!(outage.incident, Incident::PowerOutage);
+        assert_eq!(outage.owner.usdc_earned, 500_000);
+    }
+}
@@ -0,0 +1,71 @@
+#![allow(unexpected_cfgs)]
+
+use bolt_lang::*;
+
+declare_id!("Fh7sZatMSsNHTAKAMHgTKgMEMT7DytQ8eg4xNGPYehVR");
+
+/// Durability component that tracks hardware wear and maintenance
+///
+/// This component is attached to hardware entities such as GPUs. It includes:
+/// - Current and maximum durability of the hardware
+/// - Wear rate while producing, which overclocking speeds up
+/// - Repair costs in USDC and AiFi per durability point
+///
+/// Low durability lowers the Production efficiency multiplier, and hardware
+/// that reaches zero durability breaks and stops producing until repaired.
+/// A max durability of 0 means wear is not tracked for the entity.
+#[component]
+#[derive(Default)]
+pub struct Durability {
+    /// Current durability points
+    pub current_durability: u32,
+
+    /// Maximum durability points (restored by a full repair)
+    pub max_durability: u32,
+
+    /// Durability points lost per hour of production at stock clocks
+    pub wear_per_hour: u32,
+
+    /// Durability below which production efficiency starts to degrade
+    pub degradation_threshold: u32,
+
+    /// Overclock multiplier applied to both output and wear (10000 = stock clocks)
+    pub overclock_multiplier: u32,
+
+    /// Efficiency multiplier of the undamaged hardware at stock clocks (10000 = 100%)
+    pub rated_efficiency: u32,
+
+    /// USDC cost to restore one durability point
+    pub repair_usdc_per_point: u64,
+
+    /// AiFi cost to restore one durability point
+    pub repair_aifi_per_point: u64,
+
+    /// Timestamp up to which wear has been applied (Unix timestamp)
+    pub last_wear_time: i64,
+
+    /// Whether the hardware has broken down
+    pub is_broken: bool,
+
+    /// Number of repairs performed on this hardware
+    pub repair_count: u32,
+
+    /// Type of the hardware entity (uses same enum as Ownership component)
+    pub durability_type: u8,
+}
+
+/// Errors that can occur when interacting with the Durability component
+#[error_code]
+pub enum DurabilityError {
+    /// Hardware has broken down and must be repaired
+    #[msg("Hardware is broken and must be repaired")]
+    HardwareBroken,
+
+    /// Hardware is already at maximum durability
+    #[msg("Hardware is already at maximum durability")]
+    AlreadyAtMaxDurability,
+
+    /// Arithmetic overflow in durability calculations
+    #[msg("Arithmetic overflow in durability calculation")]
+    ArithmeticOverflow,
+}
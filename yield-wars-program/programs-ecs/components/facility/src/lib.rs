@@ -0,0 +1,57 @@
+#![allow(unexpected_cfgs)]
+
+use bolt_lang::*;
+
+declare_id!("Bzh299w9KzXWyNhy9S1yEw7ZQoinjNs9NHpjZwkNhTxW");
+
+/// Facility component that describes where a producer is operated
+///
+/// This component is attached to producing entities such as GPUs. It includes:
+/// - Cooling rating of the data center housing the hardware
+/// - Share of the power supply covered by energy contracts
+/// - Base odds and effects of random incidents
+///
+/// When incidents are enabled the ResourceProduction system rolls a VRF-based
+/// incident on every collection. Cooling lowers the odds of a GPU burnout and
+/// energy contracts lower the odds of a power outage.
+#[component]
+#[derive(Default)]
+pub struct Facility {
+    /// Cooling rating of the data center (10000 = burnouts fully prevented)
+    pub cooling_rating: u32,
+
+    /// Share of the power supply covered by energy contracts (10000 = outages fully prevented)
+    pub energy_contract_coverage: u32,
+
+    /// Base chance of a power outage per collection (10000 = 100%)
+    pub power_outage_odds: u32,
+
+    /// Base chance of a GPU burnout per collection (10000 = 100%)
+    pub gpu_burnout_odds: u32,
+
+    /// Chance of a crypto-mining bonus per collection (10000 = 100%)
+    pub mining_bonus_odds: u32,
+
+    /// Yield multiplier for a collection hit by a power outage (10000 = no loss)
+    pub outage_yield_multiplier: u32,
+
+    /// Durability points lost to a GPU burnout
+    pub burnout_durability_damage: u32,
+
+    /// Yield multiplier for a collection with a mining bonus (10000 = no bonus)
+    pub mining_bonus_multiplier: u32,
+
+    /// Whether incidents are rolled for this producer
+    pub incidents_enabled: bool,
+
+    /// Type of the facility entity (uses same enum as Ownership component)
+    pub facility_type: u8,
+}
+
+/// Errors that can occur when interacting with the Facility component
+#[error_code]
+pub enum FacilityError {
+    /// Incident odds add up to more than 100%
+    #[msg("Incident odds exceed 100%")]
+    InvalidIncidentOdds,
+}
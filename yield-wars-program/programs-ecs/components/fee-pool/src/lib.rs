@@ -0,0 +1,55 @@
+#![allow(unexpected_cfgs)]
+
+use bolt_lang::*;
+
+declare_id!("6Ea8tv5YACtkUCBEH6dnphwDnADLHBXhnjjCrYWca4kt");
+
+/// FeePool component that shares protocol fees between vote-escrow positions
+///
+/// This component is attached to a game entity such as the GPU catalog. It includes:
+/// - Total weight and AiFi of the open positions
+/// - Accumulated fees per unit of weight since the pool was set up
+/// - Fees deposited and fees waiting for positions to share them
+///
+/// The AiFiLock system deposits fees from the treasury and splits them between the
+/// positions in proportion to their weight. Each position's weight and fee debt are
+/// kept in its VoteEscrow.
+#[component]
+#[derive(Default)]
+pub struct FeePool {
+    /// Admin allowed to deposit fees
+    pub authority: Pubkey,
+
+    /// Total weight of the open positions
+    pub total_weight: u64,
+
+    /// AiFi locked in the open positions
+    pub total_locked_aifi: u64,
+
+    /// Number of open positions
+    pub position_count: u32,
+
+    /// Fees earned per unit of weight, scaled by the accumulator scale
+    pub acc_fee_per_weight: u128,
+
+    /// USDC of fees deposited since the pool was set up
+    pub total_fees_deposited: u64,
+
+    /// USDC of fees deposited while no position was open
+    pub undistributed_fees: u64,
+}
+
+impl FeePool {
+    /// Whether an admin has deposited into this pool
+    pub fn is_registered(&self) -> bool {
+        self.authority != Pubkey::default()
+    }
+}
+
+/// Errors that can occur when interacting with the FeePool component
+#[error_code]
+pub enum FeePoolError {
+    /// Signer is not the pool admin
+    #[msg("Only the admin can deposit fees")]
+    Unauthorized,
+}
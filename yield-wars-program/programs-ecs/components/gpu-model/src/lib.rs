@@ -0,0 +1,87 @@
+#![allow(unexpected_cfgs)]
+
+use bolt_lang::*;
+
+declare_id!("A6aD7nW7qSKddtWqCS8QxtoGSkH27J3tJRfqR7uzDLT2");
+
+/// GpuModel component that describes one entry of the GPU model catalog
+///
+/// The catalog is managed by an admin and holds one entity per GPU model, such as a
+/// consumer card, an ASIC rig mining BTC or a validator node earning SOL. Each model includes:
+/// - Base production rates of every currency it outputs and operating cost
+/// - Staking parameters
+/// - Higher-tier model obtained by fusing two GPUs of this model, and its AiFi fee
+/// - Value of a GPU and the share refunded when it is salvaged
+///
+/// GPUs are spawned from the catalog, which copies these stats onto the new GPU,
+/// so players can no longer choose the rates of their own hardware. The upgrade
+/// curve of a model lives in an UpgradeTable component on the same entity.
+#[component]
+#[derive(Default)]
+pub struct GpuModel {
+    /// Admin allowed to update this model
+    pub authority: Pubkey,
+
+    /// Identifier of the model (0 = unregistered)
+    pub model_id: u8,
+
+    /// Whether new GPUs of this model can be spawned
+    pub is_available: bool,
+
+    /// Base USDC tokens produced per hour
+    pub usdc_per_hour: u64,
+
+    /// Base AiFi tokens produced per hour
+    pub aifi_per_hour: u64,
+
+    /// Operating cost per hour in USDC
+    pub operating_cost: u64,
+
+    /// Efficiency multiplier of a new GPU (10000 = 100%)
+    pub efficiency_multiplier: u32,
+
+    /// Minimum staking period in seconds before penalty-free unstaking
+    pub min_staking_period: u32,
+
+    /// Staking reward rate (10000 = 100%)
+    pub reward_rate: u32,
+
+    /// Penalty rate for early unstaking (10000 = 100%)
+    pub unstaking_penalty: u32,
+
+    /// Model produced by fusing two GPUs of this model (0 = cannot be fused)
+    pub fusion_model_id: u8,
+
+    /// AiFi fee of fusing two GPUs of this model
+    pub fusion_aifi_fee: u64,
+
+    /// USDC value of a new GPU of this model, before upgrades
+    pub salvage_usdc_value: u64,
+
+    /// AiFi value of a new GPU of this model, before upgrades
+    pub salvage_aifi_value: u64,
+
+    /// Share of the GPU value and upgrade spend refunded on salvage (10000 = 100%)
+    pub salvage_rate: u32,
+
+    /// Base BTC mined per hour
+    pub btc_per_hour: u64,
+
+    /// Base ETH mined per hour
+    pub eth_per_hour: u64,
+
+    /// Base SOL earned per hour
+    pub sol_per_hour: u64,
+}
+
+/// Errors that can occur when interacting with the GpuModel component
+#[error_code]
+pub enum GpuModelError {
+    /// Model is not registered in the catalog
+    #[msg("GPU model is not registered")]
+    ModelNotRegistered,
+
+    /// Model can no longer be spawned
+    #[msg("GPU model is not available")]
+    ModelNotAvailable,
+}
@@ -0,0 +1,100 @@
+#![allow(unexpected_cfgs)]
+
+use bolt_lang::*;
+
+declare_id!("6c3kCYXj1TJ5PUZ7T8RydsdY34y2PQA1oDeE9nxNoQYK");
+
+/// HostingAgreement component that shares a GPU's yield with the player hosting it
+///
+/// This component is attached to GPU entities. It includes:
+/// - Wallets of the GPU owner and of the host
+/// - Share of the yield paid to the host and the length of the term
+/// - When the term started and ends
+/// - Yield paid to the host so far
+/// - Key the host accepts the agreement with
+///
+/// The Hosting system offers, accepts and ends agreements. While the term runs the
+/// ResourceProduction system pays the host's share of every collection to the host's
+/// Wallet. The GPU stays in its owner's Ownership for the whole agreement.
+#[component]
+#[derive(Default)]
+pub struct HostingAgreement {
+    /// Stage of the agreement (uses the HostingStatus values)
+    pub status: u8,
+
+    /// Wallet of the GPU owner, paying the operating costs and keeping the rest of the yield
+    pub owner_wallet: Pubkey,
+
+    /// Wallet of the host receiving its share of the yield
+    pub host_wallet: Pubkey,
+
+    /// Production component of the hosted GPU
+    pub production: Pubkey,
+
+    /// Share of the yield paid to the host (10000 = 100%)
+    pub host_share_bps: u32,
+
+    /// Length of the term in seconds
+    pub term: u32,
+
+    /// Time the host accepted the agreement (Unix timestamp)
+    pub start_time: i64,
+
+    /// Time the term ends (Unix timestamp)
+    pub end_time: i64,
+
+    /// USDC paid to the host under this agreement
+    pub host_usdc_earned: u64,
+
+    /// AiFi paid to the host under this agreement
+    pub host_aifi_earned: u64,
+
+    /// Key the host signs the acceptance with
+    pub host_authority: Pubkey,
+}
+
+/// Stages of a hosting agreement
+pub enum HostingStatus {
+    /// No agreement
+    None = 0,
+    /// Offered by the owner, waiting for the host
+    Offered = 1,
+    /// Accepted by the host, sharing yield during the term
+    Active = 2,
+}
+
+impl HostingStatus {
+    /// Convert status to u8
+    pub fn to_u8(&self) -> u8 {
+        match self {
+            HostingStatus::None => 0,
+            HostingStatus::Offered => 1,
+            HostingStatus::Active => 2,
+        }
+    }
+}
+
+impl HostingAgreement {
+    /// Whether the owner has offered the agreement and the host has yet to accept it
+    pub fn is_offered(&self) -> bool {
+        self.status == HostingStatus::Offered.to_u8()
+    }
+
+    /// Whether the host has accepted the agreement
+    pub fn is_active(&self) -> bool {
+        self.status == HostingStatus::Active.to_u8()
+    }
+
+    /// Clear the agreement so the owner can offer a new one
+    pub fn clear(&mut self) {
+        *self = HostingAgreement::default();
+    }
+}
+
+/// Errors that can occur when interacting with the HostingAgreement component
+#[error_code]
+pub enum HostingAgreementError {
+    /// Wallet does not match the agreement
+    #[msg("Wallet does not match the hosting agreement")]
+    WalletMismatch,
+}
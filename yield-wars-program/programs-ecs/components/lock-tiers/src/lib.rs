@@ -0,0 +1,94 @@
+#![allow(unexpected_cfgs)]
+
+use bolt_lang::*;
+
+declare_id!("Byr3VNmc2JmzAqoWfByAXwiXDsVSsUJZrwjrL4MCJCgp");
+
+/// Maximum number of lock tiers a table can describe
+pub const MAX_LOCK_TIERS: usize = 8;
+
+/// Number of stakeable types that can have an unbonding period
+pub const MAX_UNBONDING_TYPES: usize = 8;
+
+/// LockTiers component that describes the lock periods players can stake for
+///
+/// This component is attached to a game entity such as the GPU catalog. Entry `i`
+/// describes lock tier `i`:
+/// - Lock duration before the entity can leave without penalty
+/// - Reward multiplier applied to the staking reward rate
+/// - Share of the lock period's rewards lost by unstaking early
+///
+/// It also holds the unbonding period of each stakeable type, indexed by the type
+/// (uses same enum as Ownership component). Types without an entry unbond instantly.
+///
+/// The Staking system lets admins edit the table and copies the chosen tier onto the
+/// Stakeable when an entity is staked, so later edits do not change running locks.
+#[component]
+#[derive(Default)]
+pub struct LockTiers {
+    /// Admin allowed to edit this table
+    pub authority: Pubkey,
+
+    /// Lock duration in seconds of each tier
+    #[max_len(8)]
+    pub durations: Vec<u32>,
+
+    /// Reward multiplier of each tier (10000 = 100%)
+    #[max_len(8)]
+    pub reward_multipliers: Vec<u32>,
+
+    /// Early unstaking penalty of each tier (10000 = 100%)
+    #[max_len(8)]
+    pub penalties: Vec<u32>,
+
+    /// Unbonding period in seconds of each stakeable type
+    #[max_len(8)]
+    pub unbonding_periods: Vec<u32>,
+}
+
+/// One lock tier of a lock tier table
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LockTier {
+    /// Lock duration in seconds
+    pub duration: u32,
+    /// Reward multiplier (10000 = 100%)
+    pub reward_multiplier: u32,
+    /// Early unstaking penalty (10000 = 100%)
+    pub penalty: u32,
+}
+
+impl LockTiers {
+    /// Whether an admin has registered this table
+    pub fn is_registered(&self) -> bool {
+        self.authority != Pubkey::default()
+    }
+
+    /// Number of tiers described by the table
+    pub fn tier_count(&self) -> usize {
+        self.durations.len()
+    }
+
+    /// Lock tier at `index`
+    pub fn tier(&self, index: u8) -> Option<LockTier> {
+        let index = index as usize;
+
+        Some(LockTier {
+            duration: *self.durations.get(index)?,
+            reward_multiplier: *self.reward_multipliers.get(index)?,
+            penalty: *self.penalties.get(index)?,
+        })
+    }
+
+    /// Unbonding period in seconds of a stakeable type (0 = withdrawn on unstake)
+    pub fn unbonding_period(&self, stakeable_type: u8) -> u32 {
+        self.unbonding_periods.get(stakeable_type as usize).copied().unwrap_or(0)
+    }
+}
+
+/// Errors that can occur when interacting with the LockTiers component
+#[error_code]
+pub enum LockTiersError {
+    /// Tier is not described by the table
+    #[msg("Lock tier does not exist")]
+    InvalidTier,
+}
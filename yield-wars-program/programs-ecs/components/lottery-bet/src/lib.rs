@@ -0,0 +1,106 @@
+use bolt_lang::*;
+
+declare_id!("CUD2W1VtQ3kyMbJiii3rcLLDiN1UTTu7rgijFCYxEDYE");
+
+/// LotteryBet component that escrows a player's bet until VRF randomness settles it
+///
+/// This component is attached to player entities. It includes:
+/// - The lottery and wallet the pending bet belongs to
+/// - The AiFi held in escrow for the pending bet
+/// - When the bet was placed, used to refund it if the randomness never arrives
+/// - The outcome of the last settled bet
+/// - Who requested the randomness of the pending bet
+///
+/// The Lottery system places a bet by escrowing the AiFi here and requesting randomness
+/// for the bet in the same transaction. Once the VRF callback has delivered it, anyone
+/// can settle the bet. A player can only have one pending bet at a time.
+#[component]
+#[derive(Default)]
+pub struct LotteryBet {
+    /// Stage of the current bet (uses the BetStatus values)
+    pub status: u8,
+
+    /// LotteryPrize component the pending bet was placed in
+    pub lottery: Pubkey,
+
+    /// Wallet the escrow came from and any prize or refund is paid to
+    pub wallet: Pubkey,
+
+    /// AiFi held in escrow for the pending bet
+    pub bet_amount: u64,
+
+    /// Number of bets placed with this component, used to derive each bet's randomness PDA
+    pub bet_count: u64,
+
+    /// Slot the pending bet was placed in
+    pub placed_slot: u64,
+
+    /// Time the pending bet was placed (Unix timestamp)
+    pub placed_time: i64,
+
+    /// Outcome of the last settled or refunded bet (uses the BetOutcome values)
+    pub last_outcome: u8,
+
+    /// USDC won by the last settled bet
+    pub last_prize: u64,
+
+    /// Signer that requested the randomness of the pending bet, part of its PDA seeds
+    pub requester: Pubkey,
+}
+
+/// Stages of a bet
+pub enum BetStatus {
+    /// No bet is pending
+    None = 0,
+    /// Bet is escrowed and waiting for randomness
+    Pending = 1,
+}
+
+impl BetStatus {
+    /// Convert status to u8
+    pub fn to_u8(&self) -> u8 {
+        match self {
+            BetStatus::None => 0,
+            BetStatus::Pending => 1,
+        }
+    }
+}
+
+/// Outcomes of a finished bet
+pub enum BetOutcome {
+    /// No bet has finished yet
+    None = 0,
+    /// Bet won a prize from the pool
+    Won = 1,
+    /// Bet was forfeited to the pool
+    Lost = 2,
+    /// Randomness never arrived and the escrow was returned
+    Refunded = 3,
+}
+
+impl BetOutcome {
+    /// Convert outcome to u8
+    pub fn to_u8(&self) -> u8 {
+        match self {
+            BetOutcome::None => 0,
+            BetOutcome::Won => 1,
+            BetOutcome::Lost => 2,
+            BetOutcome::Refunded => 3,
+        }
+    }
+}
+
+impl LotteryBet {
+    /// Whether a bet is escrowed and waiting to be settled
+    pub fn is_pending(&self) -> bool {
+        self.status == BetStatus::Pending.to_u8()
+    }
+
+    /// Release the escrow, recording how the bet finished
+    pub fn finish(&mut self, outcome: BetOutcome, prize: u64) {
+        self.status = BetStatus::None.to_u8();
+        self.bet_amount = 0;
+        self.last_outcome = outcome.to_u8();
+        self.last_prize = prize;
+    }
+}
@@ -8,7 +8,12 @@ declare_id!("4xUdb6YrCjMeXNFJXXEpHxKVsYoHcRKYFn7Ehz5s8xN9");
 /// - Current prize pool amount
 /// - Minimum bet amount
 /// - Win probability
+/// - House edge kept from every bet
+/// - Game authority that set it up
 /// - Recent winners and prizes
+///
+/// Bets are added to the prize pool after the house edge, and prizes are paid out of
+/// the pool, so the lottery can never pay out more than it has taken in.
 #[component]
 #[derive(Default)]
 pub struct LotteryPrize {
@@ -18,7 +23,7 @@ pub struct LotteryPrize {
     /// Win probability as a percentage (10000 = 100%, 100 = 1%)
     pub win_probability: u32,
 
-    /// Maximum win multiplier (10000 = 10x, 5000 = 5x)
+    /// Maximum win multiplier, always above 1x (10000 = 10x, 5000 = 5x)
     pub max_win_multiplier: u32,
 
     /// Timestamp of last lottery update
@@ -37,9 +42,57 @@ pub struct LotteryPrize {
     #[max_len(10)]
     pub recent_winners: Vec<Pubkey>,
 
-    /// Prize amounts in AiFi tokens corresponding to recent winners
+    /// Prize amounts in USDC corresponding to recent winners
     #[max_len(10)]
     pub recent_prizes: Vec<u64>,
+
+    /// USDC in the prize pool, funded by bets, early unstaking penalties and top-ups
+    pub prize_pool: u64,
+
+    /// Share of every bet kept by the house instead of going to the pool (10000 = 100%)
+    pub house_edge_bps: u32,
+
+    /// USDC value of the bets kept by the house
+    pub house_earnings: u64,
+
+    /// Game authority that initialized the lottery
+    pub authority: Pubkey,
+}
+
+impl LotteryPrize {
+    /// Whether the game authority has initialized this lottery
+    pub fn is_registered(&self) -> bool {
+        self.authority != Pubkey::default()
+    }
+
+    /// Multiplier paid on a winning roll between 1 and win_probability (1000 = 1x)
+    ///
+    /// Winning rolls spread the prize between just above 1x and the maximum.
+    pub fn win_multiplier(&self, random_number: u64) -> u64 {
+        let spread = (self.max_win_multiplier as u64).saturating_sub(1000).max(1);
+        1000 + (random_number % spread) + 1
+    }
+
+    /// Share of the bets returned to players as prizes over time (10000 = 100%)
+    ///
+    /// This is the win probability times the average multiplier of the winning rolls.
+    /// Prizes are paid out of a pool that only keeps the bets after the house edge, so
+    /// without top-ups the return can't exceed that share.
+    pub fn rtp_bps(&self) -> u32 {
+        let wins = self.win_probability as u64;
+        let spread = (self.max_win_multiplier as u64).saturating_sub(1000).max(1);
+
+        // Sum of `roll % spread` over the winning rolls 1..=wins
+        let full_cycles = wins / spread;
+        let rest = wins % spread;
+        let spread_sum = full_cycles * (spread * (spread - 1) / 2) + rest * (rest + 1) / 2;
+
+        // Each winning roll pays 1001 + roll % spread per 1000 bet, out of 10000 rolls
+        let odds_rtp = (wins * 1001 + spread_sum) / 1000;
+        let pool_share = 10_000u64.saturating_sub(self.house_edge_bps as u64);
+
+        odds_rtp.min(pool_share) as u32
+    }
 }
 
 /// Errors that can occur when interacting with the LotteryPrize component
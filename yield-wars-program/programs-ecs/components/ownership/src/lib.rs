@@ -5,6 +5,15 @@ use borsh::{BorshDeserialize};
 
 declare_id!("4M5dU6my7BmVMoAUYmRa3ZnJRMMQzW7e4Yf32wiPh9wS");
 
+/// Maximum number of entities a single owner can track
+pub const MAX_OWNED_ENTITIES: usize = 32;
+
+/// Program ID of the Bolt World program, which owns the entity accounts
+pub const WORLD_PROGRAM_ID: Pubkey = pubkey!("WorLD15A7CrDwLcLy4fRqtaTb9fbd8o8iqiEMUDse2n");
+
+/// Anchor account discriminator of a World `Entity`
+const ENTITY_DISCRIMINATOR: [u8; 8] = [46, 157, 161, 161, 254, 46, 79, 24];
+
 /// Ownership component that tracks which entities own other entities
 ///
 /// This component is used to establish ownership relationships between entities in the YieldWars game.
@@ -29,6 +38,124 @@ pub struct Ownership {
     pub owner_entity: Option<Pubkey>,
 }
 
+impl Ownership {
+    /// Whether `entity` of type `entity_type` is in the owned entities list
+    pub fn owns(&self, entity: &Pubkey, entity_type: u8) -> bool {
+        self.owned_entities.iter()
+            .zip(self.owned_entity_types.iter())
+            .any(|(owned, owned_type)| owned == entity && *owned_type == entity_type)
+    }
+
+    /// Add `entity` to the owned entities list
+    pub fn add_entity(&mut self, entity: Pubkey, entity_type: u8) -> Result<()> {
+        if self.owned_entities.len() >= MAX_OWNED_ENTITIES {
+            return Err(OwnershipError::TooManyEntities.into());
+        }
+
+        self.owned_entities.push(entity);
+        self.owned_entity_types.push(entity_type);
+
+        Ok(())
+    }
+
+    /// Remove `entity` from the owned entities list by swapping in the last entry
+    pub fn remove_entity(&mut self, entity: &Pubkey) -> Result<()> {
+        let index = self.owned_entities.iter()
+            .position(|owned| owned == entity)
+            .ok_or(OwnershipError::NotOwned)?;
+
+        self.owned_entities.swap_remove(index);
+        if index < self.owned_entity_types.len() {
+            self.owned_entity_types.swap_remove(index);
+        }
+
+        Ok(())
+    }
+}
+
+/// Convert a u64 entity ID to the Pubkey stored in ownership lists
+///
+/// Uses the same layout as the AssignOwnership and Market systems: the ID in
+/// little-endian followed by an `ENTITYID` marker.
+pub fn entity_id_to_pubkey(entity_id: u64) -> Pubkey {
+    let mut bytes = [0u8; 32];
+    bytes[0..8].copy_from_slice(&entity_id.to_le_bytes());
+    bytes[8..16].copy_from_slice(b"ENTITYID");
+
+    Pubkey::new_from_array(bytes)
+}
+
+/// Read the ID of a World entity account
+pub fn entity_id(entity: &AccountInfo) -> Result<u64> {
+    if *entity.owner != WORLD_PROGRAM_ID {
+        return Err(OwnershipError::InvalidEntity.into());
+    }
+
+    let data = entity.try_borrow_data()?;
+    if data.len() < 16 || data[0..8] != ENTITY_DISCRIMINATOR {
+        return Err(OwnershipError::InvalidEntity.into());
+    }
+
+    let mut id_bytes = [0u8; 8];
+    id_bytes.copy_from_slice(&data[8..16]);
+
+    Ok(u64::from_le_bytes(id_bytes))
+}
+
+/// Whether `component` is the component of `entity` created by `component_program`
+pub fn is_component_of(component: &Pubkey, component_program: &Pubkey, entity: &Pubkey) -> bool {
+    let (expected, _) = Pubkey::find_program_address(&[b"", entity.as_ref()], component_program);
+    *component == expected
+}
+
+/// Verify that a player owns an asset and that the given components belong to them
+///
+/// `entities` holds the player entity followed by the asset entity. Player and
+/// asset components are given as (component, component program) pairs and must sit
+/// at the component PDAs of their entity. The asset is owned when it is listed in
+/// the player's Ownership with `asset_type`, or when it is the player entity itself.
+pub fn verify_player_asset(
+    player_ownership: &Ownership,
+    entities: &[AccountInfo],
+    player_components: &[(Pubkey, Pubkey)],
+    asset_components: &[(Pubkey, Pubkey)],
+    asset_type: u8,
+) -> Result<()> {
+    let (player_entity, asset_entity) = match entities {
+        [player_entity, asset_entity, ..] => (player_entity, asset_entity),
+        _ => return Err(OwnershipError::InvalidEntity.into()),
+    };
+
+    let player_id = entity_id(player_entity)?;
+    let asset_id = entity_id(asset_entity)?;
+
+    for (component, component_program) in player_components {
+        if !is_component_of(component, component_program, player_entity.key) {
+            msg!("Component {} does not belong to player entity {}", component, player_id);
+            return Err(OwnershipError::ComponentMismatch.into());
+        }
+    }
+
+    for (component, component_program) in asset_components {
+        if !is_component_of(component, component_program, asset_entity.key) {
+            msg!("Component {} does not belong to asset entity {}", component, asset_id);
+            return Err(OwnershipError::ComponentMismatch.into());
+        }
+    }
+
+    if player_ownership.owner_type != EntityType::Player.to_u8() {
+        return Err(OwnershipError::NotOwned.into());
+    }
+
+    let is_player_itself = asset_id == player_id && asset_type == EntityType::Player.to_u8();
+    if !is_player_itself && !player_ownership.owns(&entity_id_to_pubkey(asset_id), asset_type) {
+        msg!("Entity {} of type {} is not owned by player {}", asset_id, asset_type, player_id);
+        return Err(OwnershipError::NotOwned.into());
+    }
+
+    Ok(())
+}
+
 /// Entity type enum for the Ownership component
 pub enum EntityType {
     /// Player account (wallet)
@@ -73,4 +200,12 @@ pub enum OwnershipError {
     /// Entity is not owned by the current owner
     #[msg("Entity is not owned by this owner")]
     NotOwned,
+    
+    /// Account is not a World entity
+    #[msg("Invalid entity account")]
+    InvalidEntity,
+    
+    /// Component does not belong to the expected entity
+    #[msg("Component does not belong to this entity")]
+    ComponentMismatch,
 }
\ No newline at end of file
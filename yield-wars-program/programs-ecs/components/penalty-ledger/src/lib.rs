@@ -0,0 +1,142 @@
+#![allow(unexpected_cfgs)]
+
+use bolt_lang::*;
+
+declare_id!("6DLKGDigCncDQHjELkAzUDoQ9Q9D7G2xJLvgi9EbUBZh");
+
+/// Maximum number of recent redistributions kept by the ledger
+pub const MAX_RECENT_PENALTIES: usize = 10;
+
+/// PenaltyLedger component that routes early unstaking penalties and records where they went
+///
+/// This component is attached to a game entity such as the GPU catalog. It includes:
+/// - Destination of the penalties and the component receiving them
+/// - Total USDC sent to each destination
+/// - The most recent redistributions, oldest first
+///
+/// The Staking system moves each early unstaking penalty to the configured destination
+/// and records it here, so the economy dashboard can follow the penalties.
+#[component]
+#[derive(Default)]
+pub struct PenaltyLedger {
+    /// Admin allowed to route the penalties
+    pub authority: Pubkey,
+
+    /// Where penalties go (uses the PenaltyDestination values)
+    pub destination: u8,
+
+    /// Treasury Wallet, StakingPool or LotteryPrize component receiving the penalties
+    pub destination_account: Pubkey,
+
+    /// USDC of penalties that left the economy
+    pub total_burned: u64,
+
+    /// USDC of penalties sent to the treasury
+    pub total_to_treasury: u64,
+
+    /// USDC of penalties shared with the stakers of a staking pool
+    pub total_to_pool: u64,
+
+    /// USDC of penalties added to the lottery prize pool
+    pub total_to_lottery: u64,
+
+    /// Number of penalties recorded
+    pub redistribution_count: u64,
+
+    /// Stakeable components of the recently penalized entities
+    #[max_len(10)]
+    pub recent_stakeables: Vec<Pubkey>,
+
+    /// Destination of each recent penalty
+    #[max_len(10)]
+    pub recent_destinations: Vec<u8>,
+
+    /// USDC amount of each recent penalty
+    #[max_len(10)]
+    pub recent_amounts: Vec<u64>,
+
+    /// Time of each recent penalty (Unix timestamp)
+    #[max_len(10)]
+    pub recent_times: Vec<i64>,
+}
+
+/// Destinations an early unstaking penalty can be routed to
+pub enum PenaltyDestination {
+    /// Penalty leaves the economy
+    Burn = 0,
+    /// Treasury Wallet
+    Treasury = 1,
+    /// Reward budget of a StakingPool, shared by its remaining stakers
+    StakingPool = 2,
+    /// Prize pool of the lottery
+    Lottery = 3,
+}
+
+impl PenaltyDestination {
+    /// Convert destination to u8
+    pub fn to_u8(&self) -> u8 {
+        match self {
+            PenaltyDestination::Burn => 0,
+            PenaltyDestination::Treasury => 1,
+            PenaltyDestination::StakingPool => 2,
+            PenaltyDestination::Lottery => 3,
+        }
+    }
+
+    /// Destination of a u8 value
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(PenaltyDestination::Burn),
+            1 => Some(PenaltyDestination::Treasury),
+            2 => Some(PenaltyDestination::StakingPool),
+            3 => Some(PenaltyDestination::Lottery),
+            _ => None,
+        }
+    }
+}
+
+impl PenaltyLedger {
+    /// Whether an admin has registered this ledger
+    pub fn is_registered(&self) -> bool {
+        self.authority != Pubkey::default()
+    }
+
+    /// Record a penalty sent to `destination`
+    pub fn record(&mut self, stakeable: Pubkey, destination: u8, amount: u64, time: i64) -> Result<()> {
+        let total = match PenaltyDestination::from_u8(destination) {
+            Some(PenaltyDestination::Burn) => &mut self.total_burned,
+            Some(PenaltyDestination::Treasury) => &mut self.total_to_treasury,
+            Some(PenaltyDestination::StakingPool) => &mut self.total_to_pool,
+            Some(PenaltyDestination::Lottery) => &mut self.total_to_lottery,
+            None => return Err(PenaltyLedgerError::InvalidDestination.into()),
+        };
+        *total = total.checked_add(amount).ok_or(PenaltyLedgerError::ArithmeticOverflow)?;
+        self.redistribution_count = self.redistribution_count.saturating_add(1);
+
+        if self.recent_stakeables.len() >= MAX_RECENT_PENALTIES {
+            self.recent_stakeables.remove(0);
+            self.recent_destinations.remove(0);
+            self.recent_amounts.remove(0);
+            self.recent_times.remove(0);
+        }
+
+        self.recent_stakeables.push(stakeable);
+        self.recent_destinations.push(destination);
+        self.recent_amounts.push(amount);
+        self.recent_times.push(time);
+
+        Ok(())
+    }
+}
+
+/// Errors that can occur when interacting with the PenaltyLedger component
+#[error_code]
+pub enum PenaltyLedgerError {
+    /// Destination is not a PenaltyDestination
+    #[msg("Invalid penalty destination")]
+    InvalidDestination,
+
+    /// Arithmetic overflow in the totals
+    #[msg("Arithmetic overflow in calculation")]
+    ArithmeticOverflow,
+}
@@ -0,0 +1,87 @@
+#![allow(unexpected_cfgs)]
+
+use bolt_lang::*;
+
+declare_id!("3Ht7AJdVfHDBgETfEkaufyq4DqdEDXSRKCG1x626j5as");
+
+/// PlayerProfile component that tracks the long-term progression of a player
+///
+/// This component is attached to player entities next to their Wallet. It includes:
+/// - Prestige level and the prestige points earned by resetting
+/// - Permanent production multiplier granted by those points
+/// - Lifetime statistics that survive prestige resets
+/// - Production multiplier of the player's locked AiFi and when it ends
+///
+/// The Prestige system sets up the profile and resets the player in exchange for
+/// points. The ResourceProduction system applies the multiplier to every collection
+/// credited to the player's wallet and the Leaderboard system reports it. The AiFiLock
+/// system sets the vote-escrow multiplier, which applies until the lock expires.
+#[component]
+#[derive(Default)]
+pub struct PlayerProfile {
+    /// Number of prestige resets performed
+    pub prestige_level: u16,
+
+    /// Prestige points earned over all resets
+    pub prestige_points: u64,
+
+    /// Production multiplier granted by prestige (10000 = no bonus, 0 = profile not set up)
+    pub production_multiplier: u32,
+
+    /// USDC collected from production over the player's lifetime
+    pub lifetime_usdc_earned: u64,
+
+    /// AiFi collected from production over the player's lifetime
+    pub lifetime_aifi_earned: u64,
+
+    /// Net worth given up by prestige resets
+    pub lifetime_net_worth: u64,
+
+    /// Highest net worth reached before a reset
+    pub highest_net_worth: u64,
+
+    /// GPUs given up by prestige resets
+    pub gpus_reset: u32,
+
+    /// Timestamp of the last prestige reset (Unix timestamp)
+    pub last_prestige_time: i64,
+
+    /// Production multiplier granted by locked AiFi (10000 = no bonus)
+    pub vote_escrow_multiplier: u32,
+
+    /// Time the vote-escrow multiplier ends (Unix timestamp)
+    pub vote_escrow_end_time: i64,
+}
+
+impl PlayerProfile {
+    /// Whether the Prestige system has set up this profile
+    pub fn is_initialized(&self) -> bool {
+        self.production_multiplier != 0
+    }
+
+    /// Production multiplier to apply, neutral for a profile that is not set up
+    pub fn multiplier_bps(&self) -> u32 {
+        if self.is_initialized() {
+            self.production_multiplier
+        } else {
+            10000
+        }
+    }
+
+    /// Vote-escrow multiplier to apply at `current_time`, neutral once the lock expires
+    pub fn vote_escrow_bps(&self, current_time: i64) -> u32 {
+        if current_time < self.vote_escrow_end_time && self.vote_escrow_multiplier != 0 {
+            self.vote_escrow_multiplier
+        } else {
+            10000
+        }
+    }
+}
+
+/// Errors that can occur when interacting with the PlayerProfile component
+#[error_code]
+pub enum PlayerProfileError {
+    /// Profile does not belong to the player
+    #[msg("Player profile does not belong to the player")]
+    ProfileMismatch,
+}
@@ -53,6 +53,9 @@ pub struct Price {
     
     /// Demand factor affecting price (10000 = neutral)
     pub demand_factor: u32,
+
+    /// Game authority for prices the game values assets with, default otherwise
+    pub authority: Pubkey,
 }
 
 /// Errors that can occur when interacting with the Price component
@@ -7,8 +7,8 @@ declare_id!("Hx47WJJoq9uzSRkZ8o4nRF57W1zpuYwAAc6pWHfbGQAr");
 /// Production component that tracks resource generation rates
 ///
 /// This component is used to define the production capabilities of entities in the YieldWars game,
-/// such as GPUs that produce USDC and AiFi tokens. It includes:
-/// - Production rates per hour for USDC and AiFi
+/// such as GPUs that produce USDC and AiFi tokens or rigs that mine BTC, ETH or SOL. It includes:
+/// - Production rates per hour for USDC, AiFi and the mined cryptocurrencies
 /// - Last collection timestamp to calculate uncollected resources
 /// - Efficiency multiplier that can be affected by upgrades, data centers, or energy contracts
 #[component]
@@ -28,6 +28,7 @@ pub struct Production {
     /// - Placing GPUs in data centers
     /// - Purchasing energy contracts
     /// - Applying upgrades
+    /// It is reduced by hardware wear tracked in the Durability component
     pub efficiency_multiplier: u32,
     
     /// Type of production entity (uses same enum as Ownership component)
@@ -41,6 +42,57 @@ pub struct Production {
     
     /// Operating cost per hour in USDC
     pub operating_cost: u64,
+
+    /// Most recent incident rolled on collection (0 = None, 1 = PowerOutage, 2 = GpuBurnout, 3 = MiningBonus)
+    pub last_incident: u8,
+
+    /// Timestamp of the most recent incident (Unix timestamp)
+    pub last_incident_time: i64,
+
+    /// Yield multiplier applied by the most recent incident (10000 = no effect)
+    pub last_incident_multiplier: u32,
+
+    /// Durability points lost in the most recent incident
+    pub last_incident_damage: u32,
+
+    /// Total number of incidents this producer has suffered or enjoyed
+    pub incident_count: u32,
+
+    /// GPU model this producer was spawned from (0 = not spawned from the catalog)
+    pub model_id: u8,
+
+    /// Start of the latest upgrade construction (Unix timestamp)
+    pub construction_start: i64,
+
+    /// End of the latest upgrade construction (Unix timestamp)
+    pub construction_end: i64,
+
+    /// Yield multiplier while under construction (10000 = unaffected, 0 = paused)
+    pub construction_yield: u32,
+
+    /// Whether the producer was consumed, for example by fusing it into another GPU
+    pub is_retired: bool,
+
+    /// Number of incident rolls requested for this producer
+    pub incident_request_id: u64,
+
+    /// Signer that requested the pending incident roll
+    pub incident_requester: Pubkey,
+
+    /// Time the next collection is committed to end at while an incident roll is pending (Unix timestamp)
+    pub incident_roll_time: i64,
+
+    /// Whether an incident roll was requested and the next collection has to settle it
+    pub incident_roll_pending: bool,
+
+    /// BTC mined per hour
+    pub btc_per_hour: u64,
+
+    /// ETH mined per hour
+    pub eth_per_hour: u64,
+
+    /// SOL earned per hour
+    pub sol_per_hour: u64,
 }
 
 /// Errors that can occur when interacting with the Production component
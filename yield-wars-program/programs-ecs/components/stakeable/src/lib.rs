@@ -4,11 +4,19 @@ use bolt_lang::*;
 
 declare_id!("6ewq3Rkx3c2kLu9qq46fCNS9ZhBshzskCEAgX7WspkVQ");
 
+/// Hours of reward base one USDC of compounded rewards is spread over (30 days)
+///
+/// Every reinvested USDC earns the reward rate once every 30 days.
+pub const COMPOUND_PERIOD_HOURS: u64 = 720;
+
 /// Stakeable component that enables staking functionality for entities
 ///
 /// This component is attached to entities that can be staked, such as GPUs.
 /// Staking locks an entity for a period of time, during which it generates bonus rewards.
-/// Early unstaking may incur a penalty based on the configured rates.
+/// Early unstaking may incur a penalty based on the configured rates. Unstaked entities
+/// may have to unbond, earning and producing nothing, before they can be withdrawn.
+/// Settled rewards can be compounded into the entity's reward base or saved toward its
+/// next upgrade instead of being paid out.
 #[component]
 #[derive(Default)]
 pub struct Stakeable {
@@ -18,22 +26,22 @@ pub struct Stakeable {
     /// Unix timestamp when the entity was staked
     pub staking_start_time: i64,
     
-    /// Minimum staking period in seconds before rewards can be claimed without penalty
+    /// Lock period in seconds of the default tier, used when no lock tiers are set up
     pub min_staking_period: u32,
     
-    /// Reward rate (100 = 1%, 500 = 5%, etc.)
+    /// Reward rate as a share of the entity's production value (100 = 1%, 500 = 5%, etc.)
     pub reward_rate: u32,
     
-    /// Penalty rate for early unstaking (100 = 1%, 500 = 5%, etc.)
+    /// Penalty rate for early unstaking of the default tier (100 = 1%, 500 = 5%, etc.)
     pub unstaking_penalty: u32,
     
-    /// Accumulated USDC rewards (calculated at claim time)
+    /// Accumulated USDC rewards ready to collect
     pub accumulated_usdc_rewards: u64,
     
-    /// Accumulated AiFi rewards (calculated at claim time) 
+    /// Accumulated AiFi rewards ready to collect
     pub accumulated_aifi_rewards: u64,
     
-    /// Last time rewards were claimed (Unix timestamp)
+    /// Last time rewards were settled (Unix timestamp)
     pub last_claim_time: i64,
     
     /// Type of the stakeable entity (uses same enum as Ownership component)
@@ -42,11 +50,101 @@ pub struct Stakeable {
     /// Whether rewards can be claimed (might be locked during certain periods)
     pub can_claim_rewards: bool,
     
-    /// Base USDC per hour used for reward calculations
+    /// Base USDC per hour of the entity when it was set up (rewards use its Production)
     pub base_usdc_per_hour: u64,
     
-    /// Base AiFi per hour used for reward calculations
+    /// Base AiFi per hour of the entity when it was set up (rewards use its Production)
     pub base_aifi_per_hour: u64,
+    
+    /// USDC rewards settled during the current lock period, released once it ends
+    pub locked_usdc_rewards: u64,
+    
+    /// StakingPool the entity is staked in (default key = staked on its own)
+    pub pool: Pubkey,
+    
+    /// Stake weight of the entity in its pool
+    pub pool_weight: u64,
+    
+    /// Pool rewards already accounted for at the entity's weight
+    pub pool_reward_debt: u64,
+    
+    /// Lock tier chosen when the entity was staked
+    pub lock_tier: u8,
+    
+    /// Time the current lock period ends (Unix timestamp)
+    pub unlock_time: i64,
+    
+    /// Reward multiplier of the chosen lock tier (10000 = 100%)
+    pub lock_multiplier: u32,
+    
+    /// Early unstaking penalty of the chosen lock tier (10000 = 100%)
+    pub lock_penalty: u32,
+    
+    /// Whether the entity was unstaked and is waiting out its unbonding period
+    pub is_unbonding: bool,
+    
+    /// Time the unbonding period ends and the entity can be withdrawn (Unix timestamp)
+    pub unbonding_end_time: i64,
+    
+    /// What happens to settled rewards when compounded (uses the CompoundMode values)
+    pub compound_mode: u8,
+    
+    /// USDC value of the rewards reinvested into the reward base
+    pub compounded_rewards: u64,
+    
+    /// USDC of rewards saved toward the next upgrade
+    pub upgrade_savings_usdc: u64,
+    
+    /// AiFi of rewards saved toward the next upgrade
+    pub upgrade_savings_aifi: u64,
+}
+
+/// What compounding does with the settled rewards of an entity
+pub enum CompoundMode {
+    /// Rewards are paid out when collected
+    Off = 0,
+    /// Rewards raise the reward base of the entity
+    RewardBase = 1,
+    /// Rewards are saved toward the next upgrade of the entity
+    Upgrade = 2,
+}
+
+impl CompoundMode {
+    /// Convert mode to u8
+    pub fn to_u8(&self) -> u8 {
+        match self {
+            CompoundMode::Off => 0,
+            CompoundMode::RewardBase => 1,
+            CompoundMode::Upgrade => 2,
+        }
+    }
+    
+    /// Mode of a u8 value
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(CompoundMode::Off),
+            1 => Some(CompoundMode::RewardBase),
+            2 => Some(CompoundMode::Upgrade),
+            _ => None,
+        }
+    }
+}
+
+impl Stakeable {
+    /// Whether the entity is staked in a StakingPool
+    pub fn in_pool(&self) -> bool {
+        self.pool != Pubkey::default()
+    }
+
+    /// Length in seconds of the current lock period
+    pub fn lock_period(&self) -> u64 {
+        self.unlock_time.saturating_sub(self.staking_start_time).max(0) as u64
+    }
+
+    /// Hourly value rewards are earned on: the production value plus the compounded rewards
+    pub fn reward_base(&self, production_value_per_hour: u64) -> u64 {
+        production_value_per_hour.saturating_add(self.compounded_rewards / COMPOUND_PERIOD_HOURS)
+    }
 }
 
 /// Errors that can occur when interacting with the Stakeable component
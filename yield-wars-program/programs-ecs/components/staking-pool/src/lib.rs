@@ -0,0 +1,62 @@
+#![allow(unexpected_cfgs)]
+
+use bolt_lang::*;
+
+declare_id!("2aZRgugPmtW9wGhWRoQFtGdcZhG8aQcLxeu1YZoW5m11");
+
+/// StakingPool component that shares a fixed reward budget between its stakers
+///
+/// This component is attached to a game entity such as the GPU catalog. It includes:
+/// - Reward budget emitted over each epoch
+/// - Accumulated reward per unit of stake weight since the pool was set up
+/// - Total stake weight and number of stakers
+///
+/// The Staking system emits the budget as time passes and splits it between the
+/// stakers in proportion to their weight, so emissions stay bounded however much
+/// is staked. Each staker's weight and reward debt are kept in their Stakeable.
+/// Early unstaking penalties routed to the pool are shared on top of the budget.
+#[component]
+#[derive(Default)]
+pub struct StakingPool {
+    /// Admin allowed to configure the pool
+    pub authority: Pubkey,
+
+    /// USDC emitted to the stakers over each epoch
+    pub reward_per_epoch: u64,
+
+    /// Length of an epoch in seconds (0 = pool not set up)
+    pub epoch_duration: u32,
+
+    /// Rewards earned per unit of stake weight, scaled by the accumulator scale
+    pub acc_reward_per_share: u128,
+
+    /// Total stake weight of the stakers
+    pub total_weight: u64,
+
+    /// Number of stakers in the pool
+    pub staker_count: u32,
+
+    /// Last time the emissions were accounted for (Unix timestamp)
+    pub last_update_time: i64,
+
+    /// USDC emitted to the stakers since the pool was set up
+    pub total_emitted: u64,
+
+    /// USDC of early unstaking penalties waiting to be shared with the stakers
+    pub undistributed_rewards: u64,
+}
+
+impl StakingPool {
+    /// Whether an admin has set up this pool
+    pub fn is_configured(&self) -> bool {
+        self.epoch_duration != 0
+    }
+}
+
+/// Errors that can occur when interacting with the StakingPool component
+#[error_code]
+pub enum StakingPoolError {
+    /// Pool has not been set up
+    #[msg("Staking pool is not set up")]
+    NotConfigured,
+}
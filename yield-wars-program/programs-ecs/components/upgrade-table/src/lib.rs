@@ -0,0 +1,202 @@
+#![allow(unexpected_cfgs)]
+
+use bolt_lang::*;
+
+declare_id!("2u7VCavujdj8o8ohimDzZjRTjEz7K5pmsP32esFbZRHK");
+
+/// Maximum number of upgrades a table can describe
+pub const MAX_UPGRADE_LEVELS: usize = 10;
+
+/// UpgradeTable component that describes the upgrade curve of an entity model
+///
+/// One table exists per entity model, such as a GPU model of the catalog or players.
+/// Entry `i` describes the upgrade from level `i + 1` to level `i + 2`:
+/// - USDC and AiFi cost of the upgrade
+/// - Absolute production rate gains per hour
+/// - Cooldown since the previous upgrade
+/// - Player level required to perform it
+/// - Construction time before the upgrade takes effect
+/// - Success and critical failure chances once upgrades become risky
+///
+/// The Upgrade system looks up the next level of an entity from its table, so the
+/// maximum level is the number of entries plus one. The construction settings of the
+/// table apply to every level that takes time to build. Upgrades reaching the risk
+/// start level or above are rolled with VRF randomness.
+#[component]
+#[derive(Default)]
+pub struct UpgradeTable {
+    /// Admin allowed to edit this table
+    pub authority: Pubkey,
+
+    /// Type of the entity this table applies to (uses same enum as Ownership component)
+    pub entity_type: u8,
+
+    /// GPU model this table applies to (0 = entities not spawned from the catalog)
+    pub model_id: u8,
+
+    /// USDC cost of each upgrade
+    #[max_len(10)]
+    pub usdc_costs: Vec<u64>,
+
+    /// AiFi cost of each upgrade
+    #[max_len(10)]
+    pub aifi_costs: Vec<u64>,
+
+    /// USDC per hour gained by each upgrade
+    #[max_len(10)]
+    pub usdc_gains: Vec<u64>,
+
+    /// AiFi per hour gained by each upgrade
+    #[max_len(10)]
+    pub aifi_gains: Vec<u64>,
+
+    /// BTC per hour gained by each upgrade
+    #[max_len(10)]
+    pub btc_gains: Vec<u64>,
+
+    /// ETH per hour gained by each upgrade
+    #[max_len(10)]
+    pub eth_gains: Vec<u64>,
+
+    /// SOL per hour gained by each upgrade
+    #[max_len(10)]
+    pub sol_gains: Vec<u64>,
+
+    /// Cooldown in seconds since the previous upgrade before each upgrade
+    #[max_len(10)]
+    pub cooldowns: Vec<u32>,
+
+    /// Player level required for each upgrade (0 = no requirement)
+    #[max_len(10)]
+    pub required_player_levels: Vec<u8>,
+
+    /// Construction time in seconds of each upgrade (0 = instant)
+    #[max_len(10)]
+    pub build_durations: Vec<u32>,
+
+    /// Yield while an upgrade is under construction (10000 = unaffected, 0 = paused)
+    pub construction_yield: u32,
+
+    /// AiFi cost of skipping one hour of construction
+    pub speed_up_aifi_per_hour: u64,
+
+    /// Share of the cost refunded when an upgrade is cancelled (10000 = 100%)
+    pub cancel_refund_rate: u32,
+
+    /// Chance that each upgrade succeeds once it is risky (10000 = certain)
+    #[max_len(10)]
+    pub success_rates: Vec<u32>,
+
+    /// Chance that each failed upgrade also drops a level (10000 = certain)
+    #[max_len(10)]
+    pub critical_failure_rates: Vec<u32>,
+
+    /// First level whose upgrade is a gamble (0 = all upgrades succeed)
+    pub risk_start_level: u8,
+
+    /// AiFi cost of one protection item, which prevents a level drop (0 = not for sale)
+    pub protection_aifi_cost: u64,
+}
+
+/// One upgrade step of an upgrade table
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UpgradeLevel {
+    /// USDC cost of the upgrade
+    pub usdc_cost: u64,
+    /// AiFi cost of the upgrade
+    pub aifi_cost: u64,
+    /// USDC per hour gained
+    pub usdc_gain: u64,
+    /// AiFi per hour gained
+    pub aifi_gain: u64,
+    /// BTC per hour gained
+    pub btc_gain: u64,
+    /// ETH per hour gained
+    pub eth_gain: u64,
+    /// SOL per hour gained
+    pub sol_gain: u64,
+    /// Cooldown in seconds since the previous upgrade
+    pub cooldown: u32,
+    /// Player level required (0 = no requirement)
+    pub required_player_level: u8,
+    /// Construction time in seconds (0 = instant)
+    pub build_duration: u32,
+    /// Whether the upgrade is rolled with VRF randomness
+    pub is_risky: bool,
+    /// Chance that the upgrade succeeds when risky (10000 = certain)
+    pub success_rate: u32,
+    /// Chance of a critical failure that drops a level when risky (10000 = certain)
+    pub critical_failure_rate: u32,
+}
+
+impl UpgradeLevel {
+    /// Success and critical failure chances, upgrades that are not risky always succeed
+    pub fn odds(&self) -> (u32, u32) {
+        if self.is_risky {
+            (self.success_rate, self.critical_failure_rate)
+        } else {
+            (10000, 0)
+        }
+    }
+}
+
+impl UpgradeTable {
+    /// Whether an admin has registered this table
+    pub fn is_registered(&self) -> bool {
+        self.authority != Pubkey::default()
+    }
+
+    /// Number of upgrades described by the table
+    pub fn level_count(&self) -> usize {
+        self.usdc_costs.len()
+    }
+
+    /// Highest level reachable with this table
+    pub fn max_level(&self) -> u8 {
+        (self.level_count() + 1) as u8
+    }
+
+    /// Total USDC and AiFi spent on upgrades to reach `level`
+    pub fn invested_costs(&self, level: u8) -> (u64, u64) {
+        let upgrades = (level as usize).saturating_sub(1);
+        let usdc = self.usdc_costs.iter().take(upgrades).fold(0u64, |sum, cost| sum.saturating_add(*cost));
+        let aifi = self.aifi_costs.iter().take(upgrades).fold(0u64, |sum, cost| sum.saturating_add(*cost));
+
+        (usdc, aifi)
+    }
+
+    /// Upgrade that takes an entity from `current_level` to the next level
+    pub fn next_level(&self, current_level: u8) -> Option<UpgradeLevel> {
+        let index = (current_level as usize).checked_sub(1)?;
+        let is_risky = self.risk_start_level > 0
+            && current_level.checked_add(1)? >= self.risk_start_level;
+
+        Some(UpgradeLevel {
+            usdc_cost: *self.usdc_costs.get(index)?,
+            aifi_cost: *self.aifi_costs.get(index)?,
+            usdc_gain: *self.usdc_gains.get(index)?,
+            aifi_gain: *self.aifi_gains.get(index)?,
+            btc_gain: *self.btc_gains.get(index)?,
+            eth_gain: *self.eth_gains.get(index)?,
+            sol_gain: *self.sol_gains.get(index)?,
+            cooldown: *self.cooldowns.get(index)?,
+            required_player_level: *self.required_player_levels.get(index)?,
+            build_duration: *self.build_durations.get(index)?,
+            is_risky,
+            success_rate: *self.success_rates.get(index)?,
+            critical_failure_rate: *self.critical_failure_rates.get(index)?,
+        })
+    }
+}
+
+/// Errors that can occur when interacting with the UpgradeTable component
+#[error_code]
+pub enum UpgradeTableError {
+    /// Table has not been registered
+    #[msg("Upgrade table is not registered")]
+    TableNotRegistered,
+
+    /// Table does not describe the entity being upgraded
+    #[msg("Upgrade table does not match the entity")]
+    TableMismatch,
+}
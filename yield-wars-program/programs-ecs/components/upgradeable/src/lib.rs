@@ -4,11 +4,17 @@ use bolt_lang::*;
 
 declare_id!("dXEvE23Lv9XX5f6ssDbzbGNQmeomC1Mi4U16EoHA3pY");
 
+/// Maximum number of risky upgrade attempts kept in the history
+pub const MAX_ATTEMPT_HISTORY: usize = 10;
+
 /// Upgradeable component that defines upgrade capabilities for entities
 ///
 /// This component is attached to entities that can be upgraded, such as GPUs and Data Centers.
-/// It tracks the current level, maximum possible level, and stores required upgrade costs.
-/// The upgrade costs define the amount of resources needed for each level upgrade.
+/// It tracks the current level, maximum possible level, and the next upgrade of the entity.
+/// The next upgrade is looked up from the UpgradeTable of the entity model after every upgrade.
+/// Upgrades that take time to build are tracked here until they are finished or cancelled,
+/// together with the roll requested for them when they are risky, and the latest risky
+/// upgrade attempts are kept so players can audit their rolls.
 #[component]
 #[derive(Default)]
 pub struct Upgradeable {
@@ -33,14 +39,80 @@ pub struct Upgradeable {
     /// AiFi cost for the next upgrade
     pub next_upgrade_aifi_cost: u64,
     
-    /// Cooldown between upgrades in seconds
+    /// Cooldown in seconds since the last upgrade before the next upgrade
     pub upgrade_cooldown: u32,
     
-    /// Production boost percentage for USDC after next upgrade (10000 = 100%)
-    pub next_usdc_boost: u32,
+    /// USDC per hour gained by the next upgrade
+    pub next_usdc_gain: u64,
+    
+    /// AiFi per hour gained by the next upgrade
+    pub next_aifi_gain: u64,
+    
+    /// Player level required for the next upgrade (0 = no requirement)
+    pub next_required_player_level: u8,
+    
+    /// Construction time in seconds of the next upgrade (0 = instant)
+    pub next_build_duration: u32,
+    
+    /// Chance that the next upgrade succeeds (10000 = certain)
+    pub next_success_rate: u32,
+    
+    /// Chance that the next upgrade drops a level (10000 = certain)
+    pub next_critical_failure_rate: u32,
+    
+    /// Whether an upgrade is under construction
+    pub upgrade_in_progress: bool,
+    
+    /// Time the upgrade under construction was started (Unix timestamp)
+    pub upgrade_started_at: i64,
+    
+    /// Time the upgrade under construction can be finished (Unix timestamp)
+    pub upgrade_ready_at: i64,
+    
+    /// USDC paid for the upgrade under construction
+    pub upgrade_paid_usdc: u64,
+    
+    /// AiFi paid for the upgrade under construction
+    pub upgrade_paid_aifi: u64,
+    
+    /// Protection items that each prevent one level drop on a critical failure
+    pub protection_items: u16,
+    
+    /// Level each recent risky attempt tried to reach
+    #[max_len(10)]
+    pub attempt_levels: Vec<u8>,
+    
+    /// Outcome of each recent risky attempt
+    /// (0 = success, 1 = failure, 2 = critical failure, 3 = critical failure prevented by protection)
+    #[max_len(10)]
+    pub attempt_outcomes: Vec<u8>,
+    
+    /// Roll of each recent risky attempt in basis points (success below the success rate)
+    #[max_len(10)]
+    pub attempt_rolls: Vec<u32>,
+    
+    /// Success rate of each recent risky attempt (10000 = certain)
+    #[max_len(10)]
+    pub attempt_success_rates: Vec<u32>,
+    
+    /// Time of each recent risky attempt (Unix timestamp)
+    #[max_len(10)]
+    pub attempt_times: Vec<i64>,
+    
+    /// Part of the USDC paid for the upgrade under construction that came from savings
+    pub upgrade_paid_savings_usdc: u64,
+    
+    /// Part of the AiFi paid for the upgrade under construction that came from savings
+    pub upgrade_paid_savings_aifi: u64,
+    
+    /// Number of the last roll requested for a risky upgrade
+    pub upgrade_request_id: u64,
+    
+    /// Signer that requested the roll of the risky upgrade under construction
+    pub upgrade_requester: Pubkey,
     
-    /// Production boost percentage for AiFi after next upgrade (10000 = 100%)
-    pub next_aifi_boost: u32,
+    /// Whether the upgrade under construction is settled by a committed roll
+    pub upgrade_roll_pending: bool,
 }
 
 /// Errors that can occur when interacting with the Upgradeable component
@@ -0,0 +1,61 @@
+#![allow(unexpected_cfgs)]
+
+use bolt_lang::*;
+
+declare_id!("VdaoDogFAPNSk3dEcoM6kMCpTXjf9o4bG6osfS6gy4L");
+
+/// VoteEscrow component that holds a player's locked AiFi position
+///
+/// This component is attached to player entities next to their Wallet. It includes:
+/// - AiFi locked out of the wallet and when it unlocks
+/// - Weight of the position, larger for longer locks
+/// - Protocol fees already accounted for and claimed
+///
+/// The AiFiLock system creates and extends positions. Until it unlocks, the weight
+/// is the player's voting power and boosts their production. The position keeps its
+/// share of the protocol fees until the AiFi is withdrawn.
+#[component]
+#[derive(Default)]
+pub struct VoteEscrow {
+    /// AiFi locked in the position
+    pub locked_aifi: u64,
+
+    /// Time the position was created (Unix timestamp)
+    pub lock_start_time: i64,
+
+    /// Time the AiFi can be withdrawn (Unix timestamp)
+    pub unlock_time: i64,
+
+    /// Weight of the position in the FeePool
+    pub weight: u64,
+
+    /// Protocol fees already accounted for at the position's weight
+    pub fee_debt: u64,
+
+    /// USDC of protocol fees claimed by the position
+    pub fees_claimed: u64,
+}
+
+impl VoteEscrow {
+    /// Whether the position holds locked AiFi
+    pub fn is_locked(&self) -> bool {
+        self.locked_aifi != 0
+    }
+
+    /// Voting power of the position, zero once the lock has expired
+    pub fn voting_power(&self, current_time: i64) -> u64 {
+        if current_time < self.unlock_time {
+            self.weight
+        } else {
+            0
+        }
+    }
+}
+
+/// Errors that can occur when interacting with the VoteEscrow component
+#[error_code]
+pub enum VoteEscrowError {
+    /// Position holds no AiFi
+    #[msg("No AiFi is locked")]
+    NotLocked,
+}
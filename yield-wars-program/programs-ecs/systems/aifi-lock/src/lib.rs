@@ -0,0 +1,377 @@
+#![allow(unexpected_cfgs)]
+
+use bolt_lang::*;
+use vote_escrow::VoteEscrow;
+use wallet::Wallet;
+use player_profile::PlayerProfile;
+use fee_pool::FeePool;
+
+declare_id!("BqA5XGqgoCcYWx3WVMoy7UAhD9SwdAgMvy12eqdXXwse");
+
+/// Shortest AiFi lock in seconds (7 days)
+pub const MIN_LOCK_DURATION: u32 = 604_800;
+
+/// Longest AiFi lock in seconds, weighing the full amount (365 days)
+pub const MAX_LOCK_DURATION: u32 = 31_536_000;
+
+/// Vote-escrow weight worth one basis point of production bonus (10 AiFi)
+pub const WEIGHT_PER_BONUS_BPS: u64 = 10_000_000;
+
+/// Highest production multiplier locked AiFi can grant (12500 = 1.25x)
+pub const MAX_VOTE_ESCROW_MULTIPLIER: u32 = 12_500;
+
+/// AiFiLock system for locking AiFi into vote-escrow positions
+///
+/// This system allows a player to:
+/// - Lock AiFi from their wallet, or add to and extend an open lock
+/// - Withdraw the AiFi once the lock has expired
+/// - Claim their share of the protocol fees
+///
+/// It also lets the game authority deposit protocol fees from the treasury.
+///
+/// The player's VoteEscrow, Wallet and PlayerProfile are the first three components,
+/// followed by the FeePool and the treasury Wallet. Lock, Withdraw and ClaimFees expect
+/// the player entity as an extra account after the components, and the player profile
+/// must be set up before locking. Positions only use a FeePool registered by the game
+/// authority. DepositFees expects the game authority as a signer in the first extra
+/// account and the catalog entity in the second: the first deposit registers the pool,
+/// and the pool and the treasury must both belong to the catalog entity.
+///
+/// A lock weighs its AiFi in proportion to its duration, up to the full amount for the
+/// longest lock. Until the lock expires the weight is the player's voting power and
+/// raises their production multiplier. Deposited fees are shared between the positions
+/// in proportion to their weight until the AiFi is withdrawn. Locks start and expire on
+/// the Clock sysvar, so a lock can't be backdated or withdrawn early.
+#[system]
+pub mod aifi_lock {
+
+    /// Operation types supported by the AiFiLock system
+    pub enum OperationType {
+        /// Lock AiFi, or add to and extend an open lock
+        Lock = 0,
+        /// Withdraw the AiFi of an expired lock
+        Withdraw = 1,
+        /// Claim the protocol fees of a position
+        ClaimFees = 2,
+        /// Deposit protocol fees from the treasury
+        DepositFees = 3,
+    }
+
+    /// Arguments for the AiFiLock system
+    #[arguments]
+    pub struct Args {
+        /// Type of operation to perform
+        pub operation_type: u8,
+        /// AiFi to lock (for Lock) or USDC to deposit (for DepositFees)
+        pub amount: u64,
+        /// Lock duration in seconds from now (for Lock)
+        pub lock_duration: u32,
+    }
+
+    /// Main execution function for the AiFiLock system
+    pub fn execute(ctx: Context<Components>, args: Args) -> Result<Components> {
+        // Pay out the fees a position has earned and return the amount
+        fn claim_fees(position: &mut VoteEscrow, pool: &FeePool, wallet: &mut Wallet) -> Result<u64> {
+            let earned = yield_math::pool_reward_debt(position.weight, pool.acc_fee_per_weight)
+                .ok_or(AiFiLockError::ArithmeticOverflow)?;
+            let fees = earned.saturating_sub(position.fee_debt);
+
+            position.fee_debt = earned;
+            position.fees_claimed = position.fees_claimed.checked_add(fees)
+                .ok_or(AiFiLockError::ArithmeticOverflow)?;
+            wallet.usdc_balance = wallet.usdc_balance.checked_add(fees)
+                .ok_or(AiFiLockError::ArithmeticOverflow)?;
+
+            Ok(fees)
+        }
+
+        // Share the fees deposited while no position was open
+        fn distribute_fees(pool: &mut FeePool) -> Result<()> {
+            if pool.total_weight > 0 && pool.undistributed_fees > 0 {
+                let increase = yield_math::reward_per_share(pool.undistributed_fees, pool.total_weight)
+                    .ok_or(AiFiLockError::ArithmeticOverflow)?;
+
+                pool.acc_fee_per_weight = pool.acc_fee_per_weight.checked_add(increase)
+                    .ok_or(AiFiLockError::ArithmeticOverflow)?;
+                pool.undistributed_fees = 0;
+            }
+
+            Ok(())
+        }
+
+        // Positions are managed by the player holding them, in the game's fee pool
+        if args.operation_type <= 2 {
+            if ctx.accounts.fee_pool.authority != game_config::GAME_AUTHORITY {
+                return Err(AiFiLockError::FeePoolNotCanonical.into());
+            }
+
+            let player_entity = ctx.remaining_accounts.get(5)
+                .ok_or(AiFiLockError::PositionMismatch)?;
+
+            if !ownership::is_component_of(&ctx.accounts.vote_escrow.key(), &vote_escrow::ID, player_entity.key)
+                || !ownership::is_component_of(&ctx.accounts.wallet.key(), &wallet::ID, player_entity.key)
+                || !ownership::is_component_of(&ctx.accounts.player_profile.key(), &player_profile::ID, player_entity.key)
+            {
+                return Err(AiFiLockError::PositionMismatch.into());
+            }
+        }
+
+        // Locks run on the cluster's clock
+        let current_time = Clock::get()?.unix_timestamp;
+
+        match args.operation_type {
+            // Lock AiFi, or add to and extend an open lock
+            0 => {
+                if !ctx.accounts.player_profile.is_initialized() {
+                    return Err(AiFiLockError::ProfileNotSetUp.into());
+                }
+
+                if args.lock_duration < MIN_LOCK_DURATION || args.lock_duration > MAX_LOCK_DURATION {
+                    return Err(AiFiLockError::InvalidLockDuration.into());
+                }
+
+                let position = &mut ctx.accounts.vote_escrow;
+                let pool = &mut ctx.accounts.fee_pool;
+                let wallet = &mut ctx.accounts.wallet;
+
+                let unlock_time = current_time
+                    .checked_add(args.lock_duration as i64)
+                    .ok_or(AiFiLockError::ArithmeticOverflow)?;
+
+                if position.is_locked() {
+                    // Expired locks have to be withdrawn before locking again
+                    if current_time >= position.unlock_time {
+                        return Err(AiFiLockError::LockExpired.into());
+                    }
+
+                    if unlock_time < position.unlock_time {
+                        return Err(AiFiLockError::LockShortened.into());
+                    }
+
+                    // Fees earned at the old weight are paid before it changes
+                    claim_fees(position, pool, wallet)?;
+
+                    pool.total_weight = pool.total_weight.saturating_sub(position.weight);
+                } else {
+                    if args.amount == 0 {
+                        return Err(AiFiLockError::InvalidAmount.into());
+                    }
+
+                    position.lock_start_time = current_time;
+                    pool.position_count = pool.position_count.checked_add(1)
+                        .ok_or(AiFiLockError::ArithmeticOverflow)?;
+                }
+
+                if wallet.aifi_balance < args.amount {
+                    return Err(AiFiLockError::InsufficientFunds.into());
+                }
+
+                // Move the AiFi out of the wallet
+                wallet.aifi_balance = wallet.aifi_balance.checked_sub(args.amount)
+                    .ok_or(AiFiLockError::InsufficientFunds)?;
+                position.locked_aifi = position.locked_aifi.checked_add(args.amount)
+                    .ok_or(AiFiLockError::ArithmeticOverflow)?;
+                pool.total_locked_aifi = pool.total_locked_aifi.checked_add(args.amount)
+                    .ok_or(AiFiLockError::ArithmeticOverflow)?;
+
+                // Weigh the whole position over the remaining lock
+                position.unlock_time = unlock_time;
+                position.weight = yield_math::vote_escrow_weight(
+                    position.locked_aifi,
+                    args.lock_duration as u64,
+                    MAX_LOCK_DURATION as u64,
+                ).ok_or(AiFiLockError::ArithmeticOverflow)?;
+
+                pool.total_weight = pool.total_weight.checked_add(position.weight)
+                    .ok_or(AiFiLockError::ArithmeticOverflow)?;
+                position.fee_debt = yield_math::pool_reward_debt(position.weight, pool.acc_fee_per_weight)
+                    .ok_or(AiFiLockError::ArithmeticOverflow)?;
+                distribute_fees(pool)?;
+
+                // Boost production until the lock expires
+                let profile = &mut ctx.accounts.player_profile;
+                profile.vote_escrow_multiplier = yield_math::vote_escrow_multiplier_bps(
+                    position.weight,
+                    WEIGHT_PER_BONUS_BPS,
+                    MAX_VOTE_ESCROW_MULTIPLIER,
+                );
+                profile.vote_escrow_end_time = unlock_time;
+
+                msg!("Locked {} AiFi until {} with weight {}, production multiplier {}",
+                     position.locked_aifi, unlock_time, position.weight, profile.vote_escrow_multiplier);
+            },
+            // Withdraw the AiFi of an expired lock
+            1 => {
+                let position = &mut ctx.accounts.vote_escrow;
+                let pool = &mut ctx.accounts.fee_pool;
+                let wallet = &mut ctx.accounts.wallet;
+
+                if !position.is_locked() {
+                    return Err(AiFiLockError::NotLocked.into());
+                }
+
+                if current_time < position.unlock_time {
+                    msg!("AiFi is locked until {}", position.unlock_time);
+                    return Err(AiFiLockError::LockNotExpired.into());
+                }
+
+                let fees = claim_fees(position, pool, wallet)?;
+
+                // Return the AiFi to the wallet
+                wallet.aifi_balance = wallet.aifi_balance.checked_add(position.locked_aifi)
+                    .ok_or(AiFiLockError::ArithmeticOverflow)?;
+
+                pool.total_weight = pool.total_weight.saturating_sub(position.weight);
+                pool.total_locked_aifi = pool.total_locked_aifi.saturating_sub(position.locked_aifi);
+                pool.position_count = pool.position_count.saturating_sub(1);
+
+                msg!("Withdrew {} AiFi and {} USDC of fees", position.locked_aifi, fees);
+
+                position.locked_aifi = 0;
+                position.weight = 0;
+                position.fee_debt = 0;
+                position.lock_start_time = 0;
+                position.unlock_time = 0;
+
+                let profile = &mut ctx.accounts.player_profile;
+                profile.vote_escrow_multiplier = yield_math::BPS_DENOMINATOR;
+                profile.vote_escrow_end_time = 0;
+            },
+            // Claim the protocol fees of a position
+            2 => {
+                let position = &mut ctx.accounts.vote_escrow;
+
+                if !position.is_locked() {
+                    return Err(AiFiLockError::NotLocked.into());
+                }
+
+                let fees = claim_fees(position, &ctx.accounts.fee_pool, &mut ctx.accounts.wallet)?;
+                if fees == 0 {
+                    return Err(AiFiLockError::NoFeesAvailable.into());
+                }
+
+                msg!("Claimed {} USDC of protocol fees", fees);
+            },
+            // Deposit protocol fees from the treasury
+            3 => {
+                if !game_config::is_game_authority(ctx.remaining_accounts.get(5)) {
+                    return Err(AiFiLockError::Unauthorized.into());
+                }
+
+                // Fees come out of the game's treasury into the game's fee pool
+                let catalog_entity = ctx.remaining_accounts.get(6)
+                    .ok_or(AiFiLockError::TreasuryMismatch)?;
+
+                if !ownership::is_component_of(&ctx.accounts.fee_pool.key(), &fee_pool::ID, catalog_entity.key)
+                    || !ownership::is_component_of(&ctx.accounts.treasury.key(), &wallet::ID, catalog_entity.key)
+                {
+                    return Err(AiFiLockError::TreasuryMismatch.into());
+                }
+
+                let pool = &mut ctx.accounts.fee_pool;
+                if !pool.is_registered() {
+                    pool.authority = game_config::GAME_AUTHORITY;
+                } else if pool.authority != game_config::GAME_AUTHORITY {
+                    return Err(AiFiLockError::FeePoolNotCanonical.into());
+                }
+
+                if args.amount == 0 {
+                    return Err(AiFiLockError::InvalidAmount.into());
+                }
+
+                let treasury = &mut ctx.accounts.treasury;
+                if treasury.usdc_balance < args.amount {
+                    return Err(AiFiLockError::InsufficientFunds.into());
+                }
+
+                treasury.usdc_balance = treasury.usdc_balance.checked_sub(args.amount)
+                    .ok_or(AiFiLockError::InsufficientFunds)?;
+                pool.undistributed_fees = pool.undistributed_fees.checked_add(args.amount)
+                    .ok_or(AiFiLockError::ArithmeticOverflow)?;
+                pool.total_fees_deposited = pool.total_fees_deposited.checked_add(args.amount)
+                    .ok_or(AiFiLockError::ArithmeticOverflow)?;
+                distribute_fees(pool)?;
+
+                msg!("Deposited {} USDC of fees for {} positions with total weight {}",
+                     args.amount, pool.position_count, pool.total_weight);
+            },
+            _ => return Err(AiFiLockError::InvalidOperation.into()),
+        }
+
+        Ok(ctx.accounts)
+    }
+
+    /// Components required for the AiFiLock system
+    #[system_input]
+    pub struct Components {
+        pub vote_escrow: VoteEscrow,
+        pub wallet: Wallet,
+        pub player_profile: PlayerProfile,
+        pub fee_pool: FeePool,
+        pub treasury: Wallet,
+    }
+}
+
+/// Errors that can occur in the AiFiLock system
+#[error_code]
+pub enum AiFiLockError {
+    /// Position, wallet or profile does not belong to the player entity
+    #[msg("Vote-escrow position does not belong to the player")]
+    PositionMismatch,
+
+    /// Player profile has not been set up
+    #[msg("Player profile is not set up")]
+    ProfileNotSetUp,
+
+    /// Lock duration is outside the allowed range
+    #[msg("Invalid lock duration")]
+    InvalidLockDuration,
+
+    /// New unlock time is earlier than the current one
+    #[msg("Locks can only be extended")]
+    LockShortened,
+
+    /// Lock has expired and must be withdrawn first
+    #[msg("Lock has expired")]
+    LockExpired,
+
+    /// Lock has not expired yet
+    #[msg("Lock has not expired")]
+    LockNotExpired,
+
+    /// Position holds no AiFi
+    #[msg("No AiFi is locked")]
+    NotLocked,
+
+    /// Amount must be positive
+    #[msg("Invalid amount")]
+    InvalidAmount,
+
+    /// Wallet or treasury does not hold enough funds
+    #[msg("Insufficient funds")]
+    InsufficientFunds,
+
+    /// No fees to claim
+    #[msg("No fees available to claim")]
+    NoFeesAvailable,
+
+    /// Signer is not the game authority
+    #[msg("Only the admin can deposit fees")]
+    Unauthorized,
+
+    /// Arithmetic overflow during calculation
+    #[msg("Arithmetic overflow in calculation")]
+    ArithmeticOverflow,
+
+    /// Invalid operation type specified
+    #[msg("Invalid operation type specified")]
+    InvalidOperation,
+
+    /// FeePool was not registered by the game authority
+    #[msg("Fee pool is not the game's pool")]
+    FeePoolNotCanonical,
+
+    /// Fee pool or treasury does not belong to the catalog entity
+    #[msg("Treasury does not belong to the catalog")]
+    TreasuryMismatch,
+}
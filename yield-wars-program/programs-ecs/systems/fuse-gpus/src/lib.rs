@@ -0,0 +1,390 @@
+#![allow(unexpected_cfgs)]
+
+use bolt_lang::*;
+use gpu_model::GpuModel;
+use upgrade_table::UpgradeTable;
+use ownership::{EntityType, Ownership};
+use wallet::Wallet;
+use production::Production;
+use upgradeable::Upgradeable;
+use stakeable::Stakeable;
+use durability::Durability;
+
+declare_id!("Gh8hdYGYkqawYxbTAVDtpsAbtKzVefyUFcSqLUjpLc6o");
+
+/// FuseGpus system for merging two GPUs into a higher-tier GPU
+///
+/// This system allows a player to:
+/// - Fuse two owned GPUs of the same model and level into a GPU of the model's fusion tier
+///
+/// The first three components belong to the catalog: the model of the fused GPUs, the
+/// fusion model and its upgrade table. The player's Ownership and Wallet follow, then the
+/// Production, Stakeable and Durability of the first GPU, the Production and Stakeable of
+/// the second GPU and the components of the new GPU entity.
+///
+/// The player entity, both GPU entities, the new GPU entity, the catalog entity of the
+/// fused model and the catalog entity of the fusion model are passed as extra accounts
+/// after the components. Both models must have been registered by the game authority.
+///
+/// Both GPUs are removed from the player's Ownership and retired, the new GPU is spawned
+/// from the fusion model at level 1 with the hardware of the first GPU, in mint condition,
+/// and added to the Ownership, and the model's AiFi fusion fee is charged to the wallet.
+#[system]
+pub mod fuse_gpus {
+
+    /// Operation types supported by the FuseGpus system
+    pub enum OperationType {
+        /// Fuse two GPUs into a higher-tier GPU
+        Fuse = 0,
+    }
+
+    /// Arguments for the FuseGpus system
+    #[arguments]
+    pub struct Args {
+        /// Type of operation to perform
+        pub operation_type: u8,
+        /// Entity ID of the first GPU
+        pub first_entity_id: u64,
+        /// Entity ID of the second GPU
+        pub second_entity_id: u64,
+        /// Entity ID of the new GPU
+        pub fused_entity_id: u64,
+        /// Whether production of the new GPU should start right away
+        pub is_active: bool,
+        /// Current time in seconds (Unix timestamp)
+        pub current_time: i64,
+    }
+
+    /// Main execution function for the FuseGpus system
+    pub fn execute(ctx: Context<Components>, args: Args) -> Result<Components> {
+        // Stop a fused GPU from producing, upgrading or earning staking rewards
+        fn retire(production: &mut Production, stakeable: &mut Stakeable) {
+            production.is_active = false;
+            production.is_retired = true;
+            production.usdc_per_hour = 0;
+            production.aifi_per_hour = 0;
+            production.btc_per_hour = 0;
+            production.eth_per_hour = 0;
+            production.sol_per_hour = 0;
+            production.operating_cost = 0;
+
+            stakeable.can_claim_rewards = false;
+            stakeable.reward_rate = 0;
+            stakeable.base_usdc_per_hour = 0;
+            stakeable.base_aifi_per_hour = 0;
+        }
+
+        match args.operation_type {
+            // Fuse two GPUs into a higher-tier GPU
+            0 => {
+                let (player_entity, first_entity, second_entity, fused_entity, model_entity, fusion_entity) =
+                    match ctx.remaining_accounts.get(14..20) {
+                        Some([player, first, second, fused, model, fusion]) => (player, first, second, fused, model, fusion),
+                        _ => return Err(FuseGpusError::EntityMismatch.into()),
+                    };
+
+                // The same GPU cannot be fused with itself
+                if args.first_entity_id == args.second_entity_id
+                    || first_entity.key == second_entity.key
+                    || ctx.accounts.first_production.key() == ctx.accounts.second_production.key()
+                {
+                    return Err(FuseGpusError::SameGpu.into());
+                }
+
+                // The entity IDs must name the entities that are passed
+                if ownership::entity_id(first_entity)? != args.first_entity_id
+                    || ownership::entity_id(second_entity)? != args.second_entity_id
+                    || ownership::entity_id(fused_entity)? != args.fused_entity_id
+                {
+                    return Err(FuseGpusError::EntityMismatch.into());
+                }
+
+                // Both GPUs must belong to the player
+                let gpu_type = EntityType::GPU.to_u8();
+                let player_components = [
+                    (ctx.accounts.wallet.key(), wallet::ID),
+                    (ctx.accounts.owner_ownership.key(), ownership::ID),
+                ];
+
+                ownership::verify_player_asset(
+                    &ctx.accounts.owner_ownership,
+                    &[player_entity.clone(), first_entity.clone()],
+                    &player_components,
+                    &[
+                        (ctx.accounts.first_production.key(), production::ID),
+                        (ctx.accounts.first_stakeable.key(), stakeable::ID),
+                        (ctx.accounts.first_durability.key(), durability::ID),
+                    ],
+                    gpu_type,
+                )?;
+
+                ownership::verify_player_asset(
+                    &ctx.accounts.owner_ownership,
+                    &[player_entity.clone(), second_entity.clone()],
+                    &player_components,
+                    &[
+                        (ctx.accounts.second_production.key(), production::ID),
+                        (ctx.accounts.second_stakeable.key(), stakeable::ID),
+                    ],
+                    gpu_type,
+                )?;
+
+                // The new GPU is spawned on the components of its own entity
+                for (component, component_program) in [
+                    (ctx.accounts.fused_production.key(), production::ID),
+                    (ctx.accounts.fused_upgradeable.key(), upgradeable::ID),
+                    (ctx.accounts.fused_stakeable.key(), stakeable::ID),
+                    (ctx.accounts.fused_durability.key(), durability::ID),
+                ] {
+                    if !ownership::is_component_of(&component, &component_program, fused_entity.key) {
+                        return Err(FuseGpusError::EntityMismatch.into());
+                    }
+                }
+
+                let gpu_model = &ctx.accounts.gpu_model;
+                let fusion_model = &ctx.accounts.fusion_model;
+                let fusion_table = &ctx.accounts.fusion_table;
+
+                // Only the game's catalog can fuse GPUs
+                if gpu_model.authority != game_config::GAME_AUTHORITY
+                    || fusion_model.authority != game_config::GAME_AUTHORITY
+                    || fusion_table.authority != game_config::GAME_AUTHORITY
+                    || !ownership::is_component_of(&gpu_model.key(), &gpu_model::ID, model_entity.key)
+                    || !ownership::is_component_of(&fusion_model.key(), &gpu_model::ID, fusion_entity.key)
+                    || !ownership::is_component_of(&fusion_table.key(), &upgrade_table::ID, fusion_entity.key)
+                {
+                    return Err(FuseGpusError::CatalogMismatch.into());
+                }
+
+                if gpu_model.model_id == 0 || gpu_model.fusion_model_id == 0 {
+                    return Err(FuseGpusError::NotFusable.into());
+                }
+
+                if fusion_model.model_id != gpu_model.fusion_model_id {
+                    return Err(FuseGpusError::FusionModelMismatch.into());
+                }
+
+                // The upgrade curve must belong to the fusion model
+                if !fusion_table.is_registered()
+                    || fusion_table.entity_type != EntityType::GPU.to_u8()
+                    || fusion_table.model_id != fusion_model.model_id
+                {
+                    return Err(FuseGpusError::UpgradeTableMismatch.into());
+                }
+
+                let first_production = &ctx.accounts.first_production;
+                let second_production = &ctx.accounts.second_production;
+
+                // Both GPUs must be live GPUs of the model at the same level
+                for production in [first_production, second_production] {
+                    if production.producer_type != EntityType::GPU.to_u8()
+                        || production.model_id != gpu_model.model_id
+                        || production.is_retired
+                    {
+                        return Err(FuseGpusError::ModelMismatch.into());
+                    }
+                }
+
+                if first_production.level != second_production.level {
+                    return Err(FuseGpusError::LevelMismatch.into());
+                }
+
+                // Unbonding GPUs are still held by staking
+                let first_stakeable = &ctx.accounts.first_stakeable;
+                let second_stakeable = &ctx.accounts.second_stakeable;
+                if first_stakeable.is_staked || first_stakeable.is_unbonding
+                    || second_stakeable.is_staked || second_stakeable.is_unbonding
+                {
+                    return Err(FuseGpusError::GpuStaked.into());
+                }
+
+                // The new GPU entity must be blank
+                let fused_production = &ctx.accounts.fused_production;
+                if fused_production.model_id != 0
+                    || fused_production.producer_type == gpu_type
+                    || ctx.accounts.fused_durability.max_durability != 0
+                {
+                    return Err(FuseGpusError::AlreadySpawned.into());
+                }
+
+                // Listed GPUs can be fused, but not the player entity itself
+                let owner_ownership = &mut ctx.accounts.owner_ownership;
+                let first_key = ownership::entity_id_to_pubkey(args.first_entity_id);
+                let second_key = ownership::entity_id_to_pubkey(args.second_entity_id);
+
+                if !owner_ownership.owns(&first_key, gpu_type) || !owner_ownership.owns(&second_key, gpu_type) {
+                    return Err(FuseGpusError::NotTheOwner.into());
+                }
+
+                // Charge the fusion fee
+                let wallet = &mut ctx.accounts.wallet;
+                if wallet.aifi_balance < gpu_model.fusion_aifi_fee {
+                    return Err(FuseGpusError::InsufficientAifiFunds.into());
+                }
+
+                wallet.aifi_balance = wallet.aifi_balance.checked_sub(gpu_model.fusion_aifi_fee)
+                    .ok_or(FuseGpusError::ArithmeticOverflow)?;
+
+                // Swap the two GPUs for the new one in the player's Ownership
+                owner_ownership.remove_entity(&first_key)?;
+                owner_ownership.remove_entity(&second_key)?;
+                owner_ownership.add_entity(ownership::entity_id_to_pubkey(args.fused_entity_id), gpu_type)?;
+
+                retire(&mut ctx.accounts.first_production, &mut ctx.accounts.first_stakeable);
+                retire(&mut ctx.accounts.second_production, &mut ctx.accounts.second_stakeable);
+
+                // Spawn the new GPU from the fusion model
+                let fusion_model = &ctx.accounts.fusion_model;
+                let fusion_table = &ctx.accounts.fusion_table;
+                let production = &mut ctx.accounts.fused_production;
+                let upgradeable = &mut ctx.accounts.fused_upgradeable;
+                let stakeable = &mut ctx.accounts.fused_stakeable;
+
+                production.model_id = fusion_model.model_id;
+                production.usdc_per_hour = fusion_model.usdc_per_hour;
+                production.aifi_per_hour = fusion_model.aifi_per_hour;
+                production.btc_per_hour = fusion_model.btc_per_hour;
+                production.eth_per_hour = fusion_model.eth_per_hour;
+                production.sol_per_hour = fusion_model.sol_per_hour;
+                production.operating_cost = fusion_model.operating_cost;
+                production.efficiency_multiplier = fusion_model.efficiency_multiplier;
+                production.producer_type = gpu_type;
+                production.level = 1;
+                production.is_active = args.is_active;
+                production.last_collection_time = args.current_time;
+
+                let next_level = fusion_table.next_level(1).unwrap_or_default();
+
+                upgradeable.current_level = 1;
+                upgradeable.max_level = fusion_table.max_level();
+                upgradeable.last_upgrade_time = args.current_time;
+                upgradeable.can_upgrade = upgradeable.current_level < upgradeable.max_level;
+                upgradeable.upgradeable_type = gpu_type;
+                upgradeable.next_upgrade_usdc_cost = next_level.usdc_cost;
+                upgradeable.next_upgrade_aifi_cost = next_level.aifi_cost;
+                upgradeable.upgrade_cooldown = next_level.cooldown;
+                upgradeable.next_usdc_gain = next_level.usdc_gain;
+                upgradeable.next_aifi_gain = next_level.aifi_gain;
+                upgradeable.next_required_player_level = next_level.required_player_level;
+                upgradeable.next_build_duration = next_level.build_duration;
+                (upgradeable.next_success_rate, upgradeable.next_critical_failure_rate) = next_level.odds();
+
+                stakeable.is_staked = false;
+                stakeable.staking_start_time = 0;
+                stakeable.min_staking_period = fusion_model.min_staking_period;
+                stakeable.reward_rate = fusion_model.reward_rate;
+                stakeable.unstaking_penalty = fusion_model.unstaking_penalty;
+                stakeable.accumulated_usdc_rewards = 0;
+                stakeable.accumulated_aifi_rewards = 0;
+                stakeable.last_claim_time = args.current_time;
+                stakeable.stakeable_type = gpu_type;
+                stakeable.can_claim_rewards = true;
+                stakeable.base_usdc_per_hour = fusion_model.usdc_per_hour;
+                stakeable.base_aifi_per_hour = fusion_model.aifi_per_hour;
+
+                // The new GPU takes over the hardware of the first GPU, in mint condition
+                let source = &ctx.accounts.first_durability;
+                let durability = &mut ctx.accounts.fused_durability;
+
+                durability.current_durability = source.max_durability;
+                durability.max_durability = source.max_durability;
+                durability.wear_per_hour = source.wear_per_hour;
+                durability.degradation_threshold = source.degradation_threshold;
+                durability.overclock_multiplier = yield_math::BPS_DENOMINATOR;
+                durability.rated_efficiency = fusion_model.efficiency_multiplier;
+                durability.repair_usdc_per_point = source.repair_usdc_per_point;
+                durability.repair_aifi_per_point = source.repair_aifi_per_point;
+                durability.last_wear_time = args.current_time;
+                durability.is_broken = false;
+                durability.repair_count = 0;
+                durability.durability_type = gpu_type;
+
+                msg!("Fused GPUs {} and {} into GPU {} of model {}: {} USDC/hr, {} AiFi/hr",
+                     args.first_entity_id, args.second_entity_id, args.fused_entity_id,
+                     fusion_model.model_id, production.usdc_per_hour, production.aifi_per_hour);
+            },
+            _ => return Err(FuseGpusError::InvalidOperation.into()),
+        }
+
+        Ok(ctx.accounts)
+    }
+
+    /// Components required for the FuseGpus system
+    #[system_input]
+    pub struct Components {
+        pub gpu_model: GpuModel,
+        pub fusion_model: GpuModel,
+        pub fusion_table: UpgradeTable,
+        pub owner_ownership: Ownership,
+        pub wallet: Wallet,
+        pub first_production: Production,
+        pub first_stakeable: Stakeable,
+        pub first_durability: Durability,
+        pub second_production: Production,
+        pub second_stakeable: Stakeable,
+        pub fused_production: Production,
+        pub fused_upgradeable: Upgradeable,
+        pub fused_stakeable: Stakeable,
+        pub fused_durability: Durability,
+    }
+}
+
+/// Errors that can occur in the FuseGpus system
+#[error_code]
+pub enum FuseGpusError {
+    /// Both GPUs are the same entity
+    #[msg("A GPU cannot be fused with itself")]
+    SameGpu,
+
+    /// Model has no fusion tier
+    #[msg("GPU model cannot be fused")]
+    NotFusable,
+
+    /// Fusion model does not match the model's fusion tier
+    #[msg("Fusion model does not match the GPU model")]
+    FusionModelMismatch,
+
+    /// Upgrade table does not belong to the fusion model
+    #[msg("Upgrade table does not belong to the fusion model")]
+    UpgradeTableMismatch,
+
+    /// A GPU is not a live GPU of the model
+    #[msg("GPU is not of the fused model")]
+    ModelMismatch,
+
+    /// GPUs are at different levels
+    #[msg("GPUs must be at the same level")]
+    LevelMismatch,
+
+    /// A GPU is staked or unbonding
+    #[msg("Staked or unbonding GPUs cannot be fused")]
+    GpuStaked,
+
+    /// New GPU entity has already been spawned
+    #[msg("Entity is already a GPU")]
+    AlreadySpawned,
+
+    /// Player does not own both GPUs
+    #[msg("Player does not own both GPUs")]
+    NotTheOwner,
+
+    /// Insufficient AiFi for the fusion fee
+    #[msg("Insufficient AiFi funds for the fusion fee")]
+    InsufficientAifiFunds,
+
+    /// Arithmetic overflow during calculation
+    #[msg("Arithmetic overflow in calculation")]
+    ArithmeticOverflow,
+
+    /// Invalid operation type specified
+    #[msg("Invalid operation type specified")]
+    InvalidOperation,
+
+    /// Entity accounts are missing or do not match the entity IDs and components
+    #[msg("Entities do not match the fused GPUs")]
+    EntityMismatch,
+
+    /// Model, fusion model or upgrade table is not part of the game's catalog
+    #[msg("GPU model is not part of the game's catalog")]
+    CatalogMismatch,
+}
@@ -0,0 +1,198 @@
+#![allow(unexpected_cfgs)]
+
+use bolt_lang::*;
+use gpu_model::GpuModel;
+
+declare_id!("68Vio9etmJNDcY4bRjYfCdDi8cPSS7TnKskHejJykcGV");
+
+/// GpuCatalog system for managing the GPU model catalog
+///
+/// This system allows the catalog admin to:
+/// - Register a new GPU model
+/// - Update the stats of a registered model
+/// - Make a model available or unavailable for spawning
+///
+/// The catalog admin is the game authority. It must sign the transaction and is
+/// passed as the first extra account after the components.
+#[system]
+pub mod gpu_catalog {
+
+    /// Operation types supported by the GpuCatalog system
+    pub enum OperationType {
+        /// Register a new GPU model
+        RegisterModel = 0,
+        /// Update the stats of a registered model
+        UpdateModel = 1,
+        /// Make a model available or unavailable for spawning
+        SetAvailable = 2,
+    }
+
+    /// Arguments for the GpuCatalog system
+    #[arguments]
+    pub struct Args {
+        /// Type of operation to perform
+        pub operation_type: u8,
+        /// Identifier of the model (for RegisterModel, must not be 0)
+        pub model_id: u8,
+        /// Base USDC per hour production rate
+        pub usdc_per_hour: u64,
+        /// Base AiFi per hour production rate
+        pub aifi_per_hour: u64,
+        /// Base BTC per hour mining rate
+        pub btc_per_hour: u64,
+        /// Base ETH per hour mining rate
+        pub eth_per_hour: u64,
+        /// Base SOL per hour earning rate
+        pub sol_per_hour: u64,
+        /// Operating cost per hour in USDC
+        pub operating_cost: u64,
+        /// Efficiency multiplier of a new GPU (10000 = 100%)
+        pub efficiency_multiplier: u32,
+        /// Minimum staking period in seconds before penalty-free unstaking
+        pub min_staking_period: u32,
+        /// Staking reward rate (10000 = 100%)
+        pub reward_rate: u32,
+        /// Penalty rate for early unstaking (10000 = 100%)
+        pub unstaking_penalty: u32,
+        /// Whether new GPUs of this model can be spawned
+        pub is_available: bool,
+        /// Model produced by fusing two GPUs of this model (0 = cannot be fused)
+        pub fusion_model_id: u8,
+        /// AiFi fee of fusing two GPUs of this model
+        pub fusion_aifi_fee: u64,
+        /// USDC value of a new GPU of this model
+        pub salvage_usdc_value: u64,
+        /// AiFi value of a new GPU of this model
+        pub salvage_aifi_value: u64,
+        /// Share of the value refunded on salvage (10000 = 100%, 0 = cannot be salvaged)
+        pub salvage_rate: u32,
+    }
+
+    /// Main execution function for the GpuCatalog system
+    pub fn execute(ctx: Context<Components>, args: Args) -> Result<Components> {
+        // Copy the model stats from the arguments after validating them
+        fn set_stats(gpu_model: &mut GpuModel, model_id: u8, args: &Args) -> Result<()> {
+            // A model cannot fuse into itself
+            if args.efficiency_multiplier == 0
+                || args.unstaking_penalty > yield_math::BPS_DENOMINATOR
+                || args.salvage_rate > yield_math::BPS_DENOMINATOR
+                || args.fusion_model_id == model_id
+            {
+                return Err(GpuCatalogError::InvalidParameters.into());
+            }
+
+            gpu_model.is_available = args.is_available;
+            gpu_model.usdc_per_hour = args.usdc_per_hour;
+            gpu_model.aifi_per_hour = args.aifi_per_hour;
+            gpu_model.btc_per_hour = args.btc_per_hour;
+            gpu_model.eth_per_hour = args.eth_per_hour;
+            gpu_model.sol_per_hour = args.sol_per_hour;
+            gpu_model.operating_cost = args.operating_cost;
+            gpu_model.efficiency_multiplier = args.efficiency_multiplier;
+            gpu_model.min_staking_period = args.min_staking_period;
+            gpu_model.reward_rate = args.reward_rate;
+            gpu_model.unstaking_penalty = args.unstaking_penalty;
+            gpu_model.fusion_model_id = args.fusion_model_id;
+            gpu_model.fusion_aifi_fee = args.fusion_aifi_fee;
+            gpu_model.salvage_usdc_value = args.salvage_usdc_value;
+            gpu_model.salvage_aifi_value = args.salvage_aifi_value;
+            gpu_model.salvage_rate = args.salvage_rate;
+
+            Ok(())
+        }
+
+        // Only the admin that registered the model may change it
+        fn check_admin(gpu_model: &GpuModel, admin: &AccountInfo) -> Result<()> {
+            if gpu_model.model_id == 0 {
+                return Err(GpuCatalogError::ModelNotRegistered.into());
+            }
+
+            if gpu_model.authority != *admin.key {
+                return Err(GpuCatalogError::Unauthorized.into());
+            }
+
+            Ok(())
+        }
+
+        // The admin is passed after the single gpu_model component
+        let admin = ctx.remaining_accounts.get(1)
+            .ok_or(GpuCatalogError::Unauthorized)?;
+
+        if !game_config::is_game_authority(Some(admin)) {
+            return Err(GpuCatalogError::Unauthorized.into());
+        }
+
+        let gpu_model = &mut ctx.accounts.gpu_model;
+
+        match args.operation_type {
+            // Register a new GPU model
+            0 => {
+                if gpu_model.model_id != 0 {
+                    return Err(GpuCatalogError::ModelAlreadyRegistered.into());
+                }
+
+                if args.model_id == 0 {
+                    return Err(GpuCatalogError::InvalidParameters.into());
+                }
+
+                set_stats(gpu_model, args.model_id, &args)?;
+                gpu_model.authority = *admin.key;
+                gpu_model.model_id = args.model_id;
+
+                msg!("Registered GPU model {}: {} USDC/hr, {} AiFi/hr, cost {} USDC/hr",
+                     gpu_model.model_id, gpu_model.usdc_per_hour, gpu_model.aifi_per_hour,
+                     gpu_model.operating_cost);
+            },
+            // Update the stats of a registered model
+            1 => {
+                check_admin(gpu_model, admin)?;
+                let model_id = gpu_model.model_id;
+                set_stats(gpu_model, model_id, &args)?;
+
+                msg!("Updated GPU model {}: {} USDC/hr, {} AiFi/hr, cost {} USDC/hr",
+                     gpu_model.model_id, gpu_model.usdc_per_hour, gpu_model.aifi_per_hour,
+                     gpu_model.operating_cost);
+            },
+            // Make a model available or unavailable for spawning
+            2 => {
+                check_admin(gpu_model, admin)?;
+                gpu_model.is_available = args.is_available;
+
+                msg!("GPU model {} availability set to {}", gpu_model.model_id, args.is_available);
+            },
+            _ => return Err(GpuCatalogError::InvalidOperation.into()),
+        }
+
+        Ok(ctx.accounts)
+    }
+
+    /// Components required for the GpuCatalog system
+    #[system_input]
+    pub struct Components {
+        pub gpu_model: GpuModel,
+    }
+}
+
+/// Errors that can occur in the GpuCatalog system
+#[error_code]
+pub enum GpuCatalogError {
+    /// Signer is not the catalog admin
+    #[msg("Only the catalog admin can manage this model")]
+    Unauthorized,
+
+    /// Model has already been registered
+    #[msg("GPU model is already registered")]
+    ModelAlreadyRegistered,
+
+    /// Model has not been registered
+    #[msg("GPU model is not registered")]
+    ModelNotRegistered,
+
+    /// Invalid model parameters
+    #[msg("Invalid GPU model parameters")]
+    InvalidParameters,
+
+    /// Invalid operation type specified
+    #[msg("Invalid operation type specified")]
+    InvalidOperation,
+}
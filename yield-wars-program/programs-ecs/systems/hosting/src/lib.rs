@@ -0,0 +1,264 @@
+#![allow(unexpected_cfgs)]
+
+use bolt_lang::*;
+use hosting_agreement::{HostingAgreement, HostingStatus};
+use production::Production;
+use ownership::{EntityType, Ownership};
+use wallet::Wallet;
+
+declare_id!("Bb4oJXGYjH3JRb8uWeeSBABuRGEeRN3VB2odAxnP87ju");
+
+/// Largest share of the yield a host can receive (10000 = 100%)
+pub const MAX_HOST_SHARE: u32 = 10_000;
+
+/// Shortest hosting term in seconds (1 day)
+pub const MIN_HOSTING_TERM: u32 = 86_400;
+
+/// Longest hosting term in seconds (365 days)
+pub const MAX_HOSTING_TERM: u32 = 31_536_000;
+
+/// Hosting system for delegating GPUs to other players' data centers
+///
+/// This system allows:
+/// - A GPU owner to offer a hosting agreement to a host's wallet
+/// - The host to accept the offer, starting the term
+/// - The owner to withdraw an offer, or anyone to end an agreement whose term is over
+///
+/// The HostingAgreement and Production of the GPU are the first two components,
+/// followed by the owner's Ownership and Wallet and the host's Wallet. Offering and
+/// withdrawing an offer expect the owner's player entity and the GPU entity as extra
+/// accounts after the components. Offering also takes the key the host accepts with
+/// after them. Accepting expects the host's player entity and that key as a signer.
+///
+/// Only the yield is shared: the GPU stays in its owner's Ownership, and the owner
+/// keeps paying its operating costs. The host's share of every collection made during
+/// the term is paid by the ResourceProduction system. An agreement can only end once
+/// the GPU has been collected up to the end of its term, so the host is paid its whole
+/// share before the sharing stops. Terms start and end on the Clock sysvar, so neither
+/// side can backdate an agreement to end it early.
+#[system]
+pub mod hosting {
+
+    /// Operation types supported by the Hosting system
+    pub enum OperationType {
+        /// Offer a hosting agreement to a host
+        Offer = 0,
+        /// Accept an offered agreement as the host
+        Accept = 1,
+        /// Withdraw an offer or end an agreement after its term
+        End = 2,
+    }
+
+    /// Arguments for the Hosting system
+    #[arguments]
+    pub struct Args {
+        /// Type of operation to perform
+        pub operation_type: u8,
+        /// Share of the yield paid to the host (for Offer, 10000 = 100%)
+        pub host_share_bps: u32,
+        /// Length of the term in seconds (for Offer)
+        pub term: u32,
+    }
+
+    /// Main execution function for the Hosting system
+    pub fn execute(ctx: Context<Components>, args: Args) -> Result<Components> {
+        // The owner must own the GPU the agreement and production belong to
+        fn verify_owner(ctx: &Context<Components>) -> Result<()> {
+            ownership::verify_player_asset(
+                &ctx.accounts.owner_ownership,
+                ctx.remaining_accounts.get(5..).unwrap_or(&[]),
+                &[
+                    (ctx.accounts.owner_wallet.key(), wallet::ID),
+                    (ctx.accounts.owner_ownership.key(), ownership::ID),
+                ],
+                &[
+                    (ctx.accounts.hosting_agreement.key(), hosting_agreement::ID),
+                    (ctx.accounts.production.key(), production::ID),
+                ],
+                EntityType::GPU.to_u8(),
+            )
+        }
+
+        match args.operation_type {
+            // Offer a hosting agreement to a host
+            0 => {
+                verify_owner(&ctx)?;
+
+                let production = &ctx.accounts.production;
+                if production.producer_type != EntityType::GPU.to_u8()
+                    || production.is_retired
+                    || !production.is_active
+                {
+                    return Err(HostingError::NotHostable.into());
+                }
+
+                let host_authority = ctx.remaining_accounts.get(7)
+                    .ok_or(HostingError::HostMismatch)?;
+
+                if ctx.accounts.hosting_agreement.status != HostingStatus::None.to_u8() {
+                    return Err(HostingError::AgreementInProgress.into());
+                }
+
+                if args.host_share_bps == 0 || args.host_share_bps > MAX_HOST_SHARE {
+                    return Err(HostingError::InvalidShare.into());
+                }
+
+                if args.term < MIN_HOSTING_TERM || args.term > MAX_HOSTING_TERM {
+                    return Err(HostingError::InvalidTerm.into());
+                }
+
+                let owner_wallet = ctx.accounts.owner_wallet.key();
+                let host_wallet = ctx.accounts.host_wallet.key();
+                if owner_wallet == host_wallet {
+                    return Err(HostingError::SelfHosting.into());
+                }
+
+                let agreement = &mut ctx.accounts.hosting_agreement;
+                agreement.clear();
+                agreement.status = HostingStatus::Offered.to_u8();
+                agreement.owner_wallet = owner_wallet;
+                agreement.host_wallet = host_wallet;
+                agreement.production = production.key();
+                agreement.host_share_bps = args.host_share_bps;
+                agreement.term = args.term;
+                agreement.host_authority = *host_authority.key;
+
+                msg!("Offered hosting to wallet {} for {} bps of the yield over {} seconds",
+                     host_wallet, args.host_share_bps, args.term);
+            },
+            // Accept an offered agreement as the host
+            1 => {
+                let host_entity = ctx.remaining_accounts.get(5)
+                    .ok_or(HostingError::HostMismatch)?;
+                let host_authority = ctx.remaining_accounts.get(6)
+                    .ok_or(HostingError::HostMismatch)?;
+
+                let agreement = &mut ctx.accounts.hosting_agreement;
+                if !agreement.is_offered() {
+                    return Err(HostingError::NotOffered.into());
+                }
+
+                // Only the host named by the offer can accept it
+                if !host_authority.is_signer || *host_authority.key != agreement.host_authority {
+                    return Err(HostingError::HostMismatch.into());
+                }
+
+                let host_wallet = ctx.accounts.host_wallet.key();
+                if host_wallet != agreement.host_wallet
+                    || !ownership::is_component_of(&host_wallet, &wallet::ID, host_entity.key)
+                {
+                    return Err(HostingError::HostMismatch.into());
+                }
+
+                if agreement.production != ctx.accounts.production.key() {
+                    return Err(HostingError::HostMismatch.into());
+                }
+
+                // The term runs on the cluster's clock
+                let current_time = Clock::get()?.unix_timestamp;
+
+                agreement.status = HostingStatus::Active.to_u8();
+                agreement.start_time = current_time;
+                agreement.end_time = current_time
+                    .checked_add(agreement.term as i64)
+                    .ok_or(HostingError::ArithmeticOverflow)?;
+
+                msg!("Hosting accepted until {}", agreement.end_time);
+            },
+            // Withdraw an offer or end an agreement after its term
+            2 => {
+                let agreement = &ctx.accounts.hosting_agreement;
+
+                if agreement.is_offered() {
+                    verify_owner(&ctx)?;
+                } else if agreement.is_active() {
+                    if Clock::get()?.unix_timestamp < agreement.end_time {
+                        return Err(HostingError::TermNotFinished.into());
+                    }
+
+                    // The host's share of the whole term is paid by collecting up to its end
+                    if agreement.production != ctx.accounts.production.key() {
+                        return Err(HostingError::HostMismatch.into());
+                    }
+
+                    if ctx.accounts.production.last_collection_time < agreement.end_time {
+                        msg!("Collect up to {} before ending the agreement", agreement.end_time);
+                        return Err(HostingError::HostNotPaid.into());
+                    }
+                } else {
+                    return Err(HostingError::NoAgreement.into());
+                }
+
+                let agreement = &mut ctx.accounts.hosting_agreement;
+                msg!("Hosting ended after paying the host {} USDC and {} AiFi",
+                     agreement.host_usdc_earned, agreement.host_aifi_earned);
+                agreement.clear();
+            },
+            _ => return Err(HostingError::InvalidOperation.into()),
+        }
+
+        Ok(ctx.accounts)
+    }
+
+    /// Components required for the Hosting system
+    #[system_input]
+    pub struct Components {
+        pub hosting_agreement: HostingAgreement,
+        pub production: Production,
+        pub owner_ownership: Ownership,
+        pub owner_wallet: Wallet,
+        pub host_wallet: Wallet,
+    }
+}
+
+/// Errors that can occur in the Hosting system
+#[error_code]
+pub enum HostingError {
+    /// Producer is not an active GPU
+    #[msg("Only active GPUs can be hosted")]
+    NotHostable,
+
+    /// GPU already has an offered or active agreement
+    #[msg("GPU already has a hosting agreement")]
+    AgreementInProgress,
+
+    /// Host share is zero or above the maximum
+    #[msg("Invalid host share")]
+    InvalidShare,
+
+    /// Term is outside the allowed range
+    #[msg("Invalid hosting term")]
+    InvalidTerm,
+
+    /// Owner tried to host their own GPU
+    #[msg("The owner cannot host their own GPU")]
+    SelfHosting,
+
+    /// Agreement has not been offered
+    #[msg("No hosting agreement is offered")]
+    NotOffered,
+
+    /// Host wallet or GPU does not match the agreement
+    #[msg("Host does not match the hosting agreement")]
+    HostMismatch,
+
+    /// Term of the agreement is still running
+    #[msg("Hosting term has not finished")]
+    TermNotFinished,
+
+    /// GPU has no agreement to end
+    #[msg("GPU has no hosting agreement")]
+    NoAgreement,
+
+    /// Arithmetic overflow in calculation
+    #[msg("Arithmetic overflow in calculation")]
+    ArithmeticOverflow,
+
+    /// Invalid operation type specified
+    #[msg("Invalid operation type specified")]
+    InvalidOperation,
+
+    /// GPU has not been collected up to the end of the term
+    #[msg("The host's share of the term has not been collected")]
+    HostNotPaid,
+}
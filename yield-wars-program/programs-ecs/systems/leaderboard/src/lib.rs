@@ -3,6 +3,7 @@
 use bolt_lang::*;
 use wallet::Wallet;
 use price::Price;
+use player_profile::PlayerProfile;
 use solana_program::account_info::AccountInfo;
 use solana_program::pubkey::Pubkey;
 use solana_program::instruction::{AccountMeta, Instruction};
@@ -102,17 +103,22 @@ pub mod leaderboard {
         // Log the result
         msg!("Calculated player wealth: {} USDC", total_value / 1_000_000);
         
+        // Report the player's prestige alongside their wealth
+        let profile = &accounts.player_profile;
+        msg!("Prestige level {}: {} points, production multiplier {}",
+             profile.prestige_level, profile.prestige_points, profile.multiplier_bps());
+        
         // SOAR Leaderboard Integration
         match args.operation_type {
             // Initialize Player
             0 => {
                 msg!("Initializing player in SOAR");
-                if ctx.remaining_accounts.len() >= 8 {
-                    let soar_program = &ctx.remaining_accounts[4]; // SOAR program
-                    let payer = &ctx.remaining_accounts[5]; // Payer (signer)
-                    let user = &ctx.remaining_accounts[5]; // User (same as payer for our case)
-                    let player_account = &ctx.remaining_accounts[6]; // Player account
-                    let system_program = &ctx.remaining_accounts[7]; // System program
+                if ctx.remaining_accounts.len() >= 9 {
+                    let soar_program = &ctx.remaining_accounts[5]; // SOAR program
+                    let payer = &ctx.remaining_accounts[6]; // Payer (signer)
+                    let user = &ctx.remaining_accounts[6]; // User (same as payer for our case)
+                    let player_account = &ctx.remaining_accounts[7]; // Player account
+                    let system_program = &ctx.remaining_accounts[8]; // System program
                     
                     msg!("SOAR program ID: {}", soar_program.key);
                     msg!("Payer/User: {}", payer.key);
@@ -168,15 +174,15 @@ pub mod leaderboard {
             // Register Player with Leaderboard
             1 => {
                 msg!("Registering player with leaderboard in SOAR");
-                if ctx.remaining_accounts.len() >= 11 {
-                    let soar_program = &ctx.remaining_accounts[4]; // SOAR program
-                    let payer = &ctx.remaining_accounts[5]; // Payer (signer)
-                    let user = &ctx.remaining_accounts[5]; // User (same as payer in our case)
-                    let player_account = &ctx.remaining_accounts[6]; // Player account
-                    let game_account = &ctx.remaining_accounts[7]; // Game account
-                    let leaderboard = &ctx.remaining_accounts[8]; // Leaderboard account
-                    let new_list = &ctx.remaining_accounts[9]; // New PlayerScoresList account
-                    let system_program = &ctx.remaining_accounts[10]; // System program
+                if ctx.remaining_accounts.len() >= 12 {
+                    let soar_program = &ctx.remaining_accounts[5]; // SOAR program
+                    let payer = &ctx.remaining_accounts[6]; // Payer (signer)
+                    let user = &ctx.remaining_accounts[6]; // User (same as payer in our case)
+                    let player_account = &ctx.remaining_accounts[7]; // Player account
+                    let game_account = &ctx.remaining_accounts[8]; // Game account
+                    let leaderboard = &ctx.remaining_accounts[9]; // Leaderboard account
+                    let new_list = &ctx.remaining_accounts[10]; // New PlayerScoresList account
+                    let system_program = &ctx.remaining_accounts[11]; // System program
                     
                     msg!("SOAR program ID: {}", soar_program.key);
                     msg!("Payer/User: {}", payer.key);
@@ -233,7 +239,7 @@ pub mod leaderboard {
                         }
                     }
                 } else {
-                    msg!("Not enough remaining accounts for SOAR player registration (needed 11, got {})",
+                    msg!("Not enough remaining accounts for SOAR player registration (needed 12, got {})",
                         ctx.remaining_accounts.len());
                     return Err(LeaderboardError::SoarSubmissionFailed.into());
                 }
@@ -244,15 +250,15 @@ pub mod leaderboard {
                 msg!("Submitting score to SOAR leaderboard");
                 
                 // Check if we have the required remaining accounts
-                if ctx.remaining_accounts.len() >= 8 {
-                    let soar_program = &ctx.remaining_accounts[4]; // SOAR program
-                    let authority = &ctx.remaining_accounts[5]; // Authority (signer)
-                    let player_account = &ctx.remaining_accounts[6]; // Player account
-                    let game_account = &ctx.remaining_accounts[7]; // Game account
-                    let leaderboard = &ctx.remaining_accounts[8]; // Leaderboard account
-                    let top_entries = &ctx.remaining_accounts[9]; // Top entries account
-                    let system_program = &ctx.remaining_accounts[10]; // System program
-                    let player_scores = &ctx.remaining_accounts[11]; // Player scores account
+                if ctx.remaining_accounts.len() >= 13 {
+                    let soar_program = &ctx.remaining_accounts[5]; // SOAR program
+                    let authority = &ctx.remaining_accounts[6]; // Authority (signer)
+                    let player_account = &ctx.remaining_accounts[7]; // Player account
+                    let game_account = &ctx.remaining_accounts[8]; // Game account
+                    let leaderboard = &ctx.remaining_accounts[9]; // Leaderboard account
+                    let top_entries = &ctx.remaining_accounts[10]; // Top entries account
+                    let system_program = &ctx.remaining_accounts[11]; // System program
+                    let player_scores = &ctx.remaining_accounts[12]; // Player scores account
                     
                     // Debug logging for account addresses
                     msg!("ctx.remaining_accounts.len(): {}", ctx.remaining_accounts.len());
@@ -315,7 +321,7 @@ pub mod leaderboard {
                         }
                     }
                 } else {
-                    msg!("Not enough remaining accounts for SOAR submission (needed 8, got {})", 
+                    msg!("Not enough remaining accounts for SOAR submission (needed 13, got {})", 
                             ctx.remaining_accounts.len());
                     return Err(LeaderboardError::SoarSubmissionFailed.into());
                 }
@@ -341,6 +347,7 @@ pub mod leaderboard {
         //pub price_eth: Price,
         //pub price_sol: Price,
         pub price_aifi: Price,
+        pub player_profile: PlayerProfile,
     }
 }
 
@@ -1,7 +1,7 @@
-use crate::{LotterySystemError, AIFI_TO_USDC_RATIO};
+use crate::{LotterySystemError, AIFI_TO_USDC_RATIO, BET_REFUND_SLOTS, MAX_HOUSE_EDGE};
 use bolt_lang::*;
 use borsh::{BorshDeserialize, BorshSerialize};
-use ephemeral_vrf_sdk::rnd::random_u64;
+use lottery_bet::{BetOutcome, BetStatus, LotteryBet};
 use lottery_prize::LotteryPrize;
 use serde::{Deserialize, Serialize};
 use wallet::Wallet;
@@ -11,6 +11,7 @@ pub struct InitializeArgs {
     pub min_bet_amount: u64,
     pub win_probability: u32, // 0 to 10000 (100% = 10000)
     pub max_win_multiplier: u32,
+    pub house_edge_bps: u32, // 0 to MAX_HOUSE_EDGE (100% = 10000)
 }
 
 // Initialize lottery with starting parameters
@@ -19,6 +20,7 @@ pub fn initialize(
     min_bet_amount: u64,
     win_probability: u32,
     max_win_multiplier: u32,
+    house_edge_bps: u32,
     current_time: i64,
 ) -> Result<()> {
     // Validate inputs
@@ -26,14 +28,21 @@ pub fn initialize(
         return err!(LotterySystemError::InvalidWinProbability);
     }
 
-    if max_win_multiplier == 0 {
+    // Prizes pay between 1x and the maximum, so the maximum must be above 1x
+    if max_win_multiplier <= 1000 {
         return err!(LotterySystemError::InvalidMaxWinMultiplier);
     }
 
+    if house_edge_bps > MAX_HOUSE_EDGE {
+        return err!(LotterySystemError::InvalidHouseEdge);
+    }
+
     // Set initial values
+    lottery_prize.authority = game_config::GAME_AUTHORITY;
     lottery_prize.min_bet_amount = min_bet_amount;
     lottery_prize.win_probability = win_probability;
     lottery_prize.max_win_multiplier = max_win_multiplier;
+    lottery_prize.house_edge_bps = house_edge_bps;
     lottery_prize.is_active = true;
     lottery_prize.last_update_time = current_time;
     lottery_prize.total_bets = 0;
@@ -43,24 +52,40 @@ pub fn initialize(
     lottery_prize.recent_winners = Vec::new();
     lottery_prize.recent_prizes = Vec::new();
 
+    msg!(
+        "\tLottery returns {} bps of bets to players",
+        lottery_prize.rtp_bps()
+    );
+
     Ok(())
 }
 
 #[derive(Debug, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
 pub struct PlaceBetArgs {
     pub bet_amount: u64,
-    pub randomness: [u8; 32],
+    pub client_seed: u8, // Mixed into the VRF request
 }
 
-// Unified place_bet function that always uses VRF randomness
-pub fn place_bet(
+// Escrow a bet and request the randomness that will settle it
+#[allow(clippy::too_many_arguments)]
+pub fn place_bet<'info>(
     lottery_prize: &mut LotteryPrize,
     player_wallet: &mut Wallet,
-    bet_amount: u64,
+    lottery_bet: &mut LotteryBet,
+    bet_account: &AccountInfo<'info>,
+    lottery_key: Pubkey,
     player_pubkey: Pubkey,
+    bet_amount: u64,
+    client_seed: u8,
     current_time: i64,
-    randomness: [u8; 32],
+    current_slot: u64,
+    request_accounts: &[AccountInfo<'info>],
 ) -> Result<()> {
+    // Bets only go to the game's lottery
+    if lottery_prize.authority != game_config::GAME_AUTHORITY {
+        return err!(LotterySystemError::LotteryNotCanonical);
+    }
+
     // Check if lottery is active
     if !lottery_prize.is_active {
         return err!(LotterySystemError::LotteryNotActive);
@@ -76,12 +101,31 @@ pub fn place_bet(
         return err!(LotterySystemError::InsufficientFunds);
     }
 
-    // Deduct bet amount from player's wallet (AiFi balance)
+    // Only one bet can wait for randomness at a time
+    if lottery_bet.is_pending() {
+        return err!(LotterySystemError::BetPending);
+    }
+
+    // Move the bet amount from the player's wallet (AiFi balance) into escrow
     player_wallet.aifi_balance = player_wallet
         .aifi_balance
         .checked_sub(bet_amount)
         .ok_or(error!(LotterySystemError::InsufficientFunds))?;
 
+    lottery_bet.status = BetStatus::Pending.to_u8();
+    lottery_bet.lottery = lottery_key;
+    lottery_bet.wallet = player_pubkey;
+    lottery_bet.bet_amount = bet_amount;
+    lottery_bet.bet_count = lottery_bet
+        .bet_count
+        .checked_add(1)
+        .ok_or(error!(LotterySystemError::ArithmeticOverflow))?;
+    lottery_bet.placed_slot = current_slot;
+    lottery_bet.placed_time = current_time;
+    if let Some(requester) = request_accounts.first() {
+        lottery_bet.requester = *requester.key;
+    }
+
     // Update lottery stats
     lottery_prize.total_bets = lottery_prize
         .total_bets
@@ -89,13 +133,93 @@ pub fn place_bet(
         .unwrap_or(lottery_prize.total_bets);
     lottery_prize.last_update_time = current_time;
 
+    // The randomness is requested in the same transaction, so nobody can know it yet
+    vrf_randomness::request_bet_randomness(
+        bet_account,
+        request_accounts,
+        lottery_bet.bet_count,
+        client_seed,
+    )?;
+
+    msg!(
+        "\tEscrowed {} AiFi for bet {}, waiting for VRF randomness",
+        bet_amount,
+        lottery_bet.bet_count
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct SettleBetArgs {
+    pub bet_id: u64, // Number of the pending bet on the LotteryBet
+}
+
+// Settle a pending bet with the randomness delivered by the VRF callback
+#[allow(clippy::too_many_arguments)]
+pub fn settle_bet(
+    lottery_prize: &mut LotteryPrize,
+    player_wallet: &mut Wallet,
+    lottery_bet: &mut LotteryBet,
+    bet_key: Pubkey,
+    bet_id: u64,
+    lottery_key: Pubkey,
+    player_pubkey: Pubkey,
+    randomness_account: Option<&AccountInfo>,
+    current_time: i64,
+) -> Result<()> {
+    if !lottery_bet.is_pending() {
+        return err!(LotterySystemError::NoPendingBet);
+    }
+
+    if lottery_bet.bet_count != bet_id
+        || lottery_bet.lottery != lottery_key
+        || lottery_bet.wallet != player_pubkey
+    {
+        return err!(LotterySystemError::BetMismatch);
+    }
+
+    let randomness_account =
+        randomness_account.ok_or(error!(LotterySystemError::RandomnessNotDelivered))?;
+    let randomness = vrf_randomness::load_bet_randomness(
+        randomness_account,
+        &bet_key,
+        &lottery_bet.requester,
+        bet_id,
+    )?;
+    if !randomness.is_fulfilled {
+        return err!(LotterySystemError::RandomnessNotDelivered);
+    }
+
+    let bet_amount = lottery_bet.bet_amount;
+
+    // Add the bet to the prize pool, keeping the house edge
+    let bet_value = bet_amount
+        .checked_mul(AIFI_TO_USDC_RATIO)
+        .ok_or(error!(LotterySystemError::ArithmeticOverflow))?;
+    let house_cut = yield_math::mul_bps(bet_value, lottery_prize.house_edge_bps)
+        .ok_or(error!(LotterySystemError::ArithmeticOverflow))?;
+
+    lottery_prize.prize_pool = lottery_prize
+        .prize_pool
+        .checked_add(bet_value - house_cut)
+        .ok_or(error!(LotterySystemError::ArithmeticOverflow))?;
+    lottery_prize.house_earnings = lottery_prize
+        .house_earnings
+        .checked_add(house_cut)
+        .ok_or(error!(LotterySystemError::ArithmeticOverflow))?;
+
+    lottery_prize.last_update_time = current_time;
+
     // Get raw randomness value from VRF
-    let raw_random = random_u64(&randomness);
+    let raw_random = randomness.random_u64();
     let random_number = (raw_random % 10000) + 1;
 
     msg!("\tUsing VRF randomness: {}", random_number);
     msg!("\tRaw VRF value: {}", raw_random);
 
+    let mut usdc_won = 0;
+
     if random_number <= lottery_prize.win_probability as u64 {
         // Player won!
         lottery_prize.total_wins = lottery_prize
@@ -103,22 +227,20 @@ pub fn place_bet(
             .checked_add(1)
             .unwrap_or(lottery_prize.total_wins);
 
-        // Calculate multiplier seed from random_number
-        let multiplier_seed = random_number;
-
         // Calculate a multiplier between 1000 (1x) and max_win_multiplier
-        let multiplier =
-            1000_u64 + ((multiplier_seed % (lottery_prize.max_win_multiplier as u64 - 1000)) + 1);
+        let multiplier = lottery_prize.win_multiplier(random_number);
 
         // Calculate USDC prize using the bet amount * AIFI_TO_USDC_RATIO * multiplier
-        let usdc_prize = (bet_amount as u128)
-            .checked_mul(AIFI_TO_USDC_RATIO as u128) // Convert AiFi to USDC
-            .unwrap_or(0)
+        let usdc_prize = (bet_value as u128)
             .checked_mul(multiplier as u128) // Apply multiplier
             .unwrap_or(0)
             .checked_div(1000) // Convert from basis points
             .unwrap_or(0) as u64; // Final USDC prize amount
 
+        // Prizes are paid out of the pool and can't exceed it
+        let usdc_prize = usdc_prize.min(lottery_prize.prize_pool);
+        lottery_prize.prize_pool -= usdc_prize;
+
         // Add USDC prize to player's wallet (USDC balance)
         player_wallet.usdc_balance = player_wallet
             .usdc_balance
@@ -139,10 +261,78 @@ pub fn place_bet(
             usdc_prize,
             multiplier as f64 / 1000.0
         );
+        msg!("\t{} USDC left in the prize pool", lottery_prize.prize_pool);
+        usdc_won = usdc_prize;
     } else {
         msg!("\tPlayer lost the bet. Better luck next time!");
     }
 
+    let outcome = if usdc_won > 0 {
+        BetOutcome::Won
+    } else {
+        BetOutcome::Lost
+    };
+    lottery_bet.finish(outcome, usdc_won);
+
+    Ok(())
+}
+
+#[derive(Debug, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct RefundBetArgs {
+    pub bet_id: u64, // Number of the pending bet on the LotteryBet
+}
+
+// Return the escrow of a bet whose randomness never arrived
+pub fn refund_bet(
+    player_wallet: &mut Wallet,
+    lottery_bet: &mut LotteryBet,
+    bet_key: Pubkey,
+    bet_id: u64,
+    player_pubkey: Pubkey,
+    randomness_account: Option<&AccountInfo>,
+    current_slot: u64,
+) -> Result<()> {
+    if !lottery_bet.is_pending() {
+        return err!(LotterySystemError::NoPendingBet);
+    }
+
+    if lottery_bet.bet_count != bet_id || lottery_bet.wallet != player_pubkey {
+        return err!(LotterySystemError::BetMismatch);
+    }
+
+    let refund_slot = lottery_bet
+        .placed_slot
+        .checked_add(BET_REFUND_SLOTS)
+        .ok_or(error!(LotterySystemError::ArithmeticOverflow))?;
+    if current_slot < refund_slot {
+        return err!(LotterySystemError::BetNotExpired);
+    }
+
+    // A bet whose randomness arrived must be settled, even when it lost
+    let randomness_account =
+        randomness_account.ok_or(error!(LotterySystemError::RandomnessNotDelivered))?;
+    let randomness = vrf_randomness::load_bet_randomness(
+        randomness_account,
+        &bet_key,
+        &lottery_bet.requester,
+        bet_id,
+    )?;
+    if randomness.is_fulfilled {
+        return err!(LotterySystemError::RandomnessDelivered);
+    }
+
+    player_wallet.aifi_balance = player_wallet
+        .aifi_balance
+        .checked_add(lottery_bet.bet_amount)
+        .ok_or(error!(LotterySystemError::ArithmeticOverflow))?;
+
+    msg!(
+        "\tRefunded {} AiFi for bet {}",
+        lottery_bet.bet_amount,
+        lottery_bet.bet_count
+    );
+    lottery_bet.finish(BetOutcome::Refunded, 0);
+
     Ok(())
 }
 
@@ -151,6 +341,7 @@ pub struct UpdateParamsArgs {
     pub min_bet_amount: u64,
     pub win_probability: u32,
     pub max_win_multiplier: u32,
+    pub house_edge_bps: u32,
     pub is_active: bool,
 }
 
@@ -160,24 +351,79 @@ pub fn update_params(
     min_bet_amount: u64,
     win_probability: u32,
     max_win_multiplier: u32,
+    house_edge_bps: u32,
     is_active: bool,
     current_time: i64,
 ) -> Result<()> {
+    if lottery_prize.authority != game_config::GAME_AUTHORITY {
+        return err!(LotterySystemError::LotteryNotCanonical);
+    }
+
     // Validate inputs
     if win_probability == 0 || win_probability > 10000 {
         return err!(LotterySystemError::InvalidWinProbability);
     }
 
-    if max_win_multiplier == 0 {
+    // Prizes pay between 1x and the maximum, so the maximum must be above 1x
+    if max_win_multiplier <= 1000 {
         return err!(LotterySystemError::InvalidMaxWinMultiplier);
     }
 
+    if house_edge_bps > MAX_HOUSE_EDGE {
+        return err!(LotterySystemError::InvalidHouseEdge);
+    }
+
     // Update parameters
     lottery_prize.min_bet_amount = min_bet_amount;
     lottery_prize.win_probability = win_probability;
     lottery_prize.max_win_multiplier = max_win_multiplier;
+    lottery_prize.house_edge_bps = house_edge_bps;
     lottery_prize.is_active = is_active;
     lottery_prize.last_update_time = current_time;
 
+    msg!(
+        "\tLottery returns {} bps of bets to players",
+        lottery_prize.rtp_bps()
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct TopUpPoolArgs {
+    pub amount: u64, // USDC moved from the wallet into the prize pool
+}
+
+// Top up the prize pool from an admin's wallet
+pub fn top_up_pool(
+    lottery_prize: &mut LotteryPrize,
+    admin_wallet: &mut Wallet,
+    amount: u64,
+    current_time: i64,
+) -> Result<()> {
+    if lottery_prize.authority != game_config::GAME_AUTHORITY {
+        return err!(LotterySystemError::LotteryNotCanonical);
+    }
+
+    if amount == 0 {
+        return err!(LotterySystemError::InvalidTopUpAmount);
+    }
+
+    admin_wallet.usdc_balance = admin_wallet
+        .usdc_balance
+        .checked_sub(amount)
+        .ok_or(error!(LotterySystemError::InsufficientFunds))?;
+
+    lottery_prize.prize_pool = lottery_prize
+        .prize_pool
+        .checked_add(amount)
+        .ok_or(error!(LotterySystemError::ArithmeticOverflow))?;
+    lottery_prize.last_update_time = current_time;
+
+    msg!(
+        "\tTopped up the prize pool to {} USDC",
+        lottery_prize.prize_pool
+    );
+
     Ok(())
 }
@@ -1,5 +1,6 @@
 use bolt_lang::*;
 use borsh::{BorshDeserialize, BorshSerialize};
+use lottery_bet::LotteryBet;
 use lottery_prize::LotteryPrize;
 use wallet::Wallet;
 
@@ -8,6 +9,8 @@ mod instructions;
 declare_id!("A3Cr4W7xT1QFH23CxGqMe5uYZKzSLEwT8JsjdswSRMrx");
 
 pub const AIFI_TO_USDC_RATIO: u64 = 5; // 5 USDC per AiFi
+pub const MAX_HOUSE_EDGE: u32 = 5000; // 50% of every bet
+pub const BET_REFUND_SLOTS: u64 = 150; // Slots (about a minute) before an unsettled bet can be refunded
 
 #[system]
 pub mod lottery {
@@ -16,10 +19,26 @@ pub mod lottery {
         Initialize(instructions::InitializeArgs),
         PlaceBet(instructions::PlaceBetArgs),
         UpdateParams(instructions::UpdateParamsArgs),
+        TopUpPool(instructions::TopUpPoolArgs),
+        SettleBet(instructions::SettleBetArgs),
+        RefundBet(instructions::RefundBetArgs),
     }
 
     pub fn execute(ctx: Context<Components>, args: OperationType) -> Result<Components> {
-        let current_time = Clock::get()?.unix_timestamp;
+        let clock = Clock::get()?;
+        let current_time = clock.unix_timestamp;
+        let lottery_key = ctx.accounts.lottery_prize.key();
+        let player_pubkey = ctx.accounts.player_wallet.key();
+        let bet_key = ctx.accounts.lottery_bet.key();
+
+        // Setting up, tuning and funding the lottery is up to the game authority passed after the components
+        let is_admin_operation = matches!(
+            args,
+            OperationType::Initialize(_) | OperationType::UpdateParams(_) | OperationType::TopUpPool(_)
+        );
+        if is_admin_operation && !game_config::is_game_authority(ctx.remaining_accounts.get(3)) {
+            return err!(LotterySystemError::Unauthorized);
+        }
 
         match args {
             OperationType::Initialize(args) => {
@@ -28,18 +47,50 @@ pub mod lottery {
                     args.min_bet_amount,
                     args.win_probability,
                     args.max_win_multiplier,
+                    args.house_edge_bps,
                     current_time,
                 )?;
             }
             OperationType::PlaceBet(args) => {
-                let player_pubkey = ctx.accounts.player_wallet.key();
+                let bet_account = ctx.accounts.lottery_bet.to_account_info();
                 instructions::place_bet(
                     &mut ctx.accounts.lottery_prize,
                     &mut ctx.accounts.player_wallet,
+                    &mut ctx.accounts.lottery_bet,
+                    &bet_account,
+                    lottery_key,
+                    player_pubkey,
                     args.bet_amount,
+                    args.client_seed,
+                    current_time,
+                    clock.slot,
+                    // The accounts of the vrf_client randomness request follow the components
+                    ctx.remaining_accounts.get(3..).unwrap_or(&[]),
+                )?;
+            }
+            OperationType::SettleBet(args) => {
+                instructions::settle_bet(
+                    &mut ctx.accounts.lottery_prize,
+                    &mut ctx.accounts.player_wallet,
+                    &mut ctx.accounts.lottery_bet,
+                    bet_key,
+                    args.bet_id,
+                    lottery_key,
                     player_pubkey,
+                    // The BetRandomness PDA of the pending bet follows the components
+                    ctx.remaining_accounts.get(3),
                     current_time,
-                    args.randomness,
+                )?;
+            }
+            OperationType::RefundBet(args) => {
+                instructions::refund_bet(
+                    &mut ctx.accounts.player_wallet,
+                    &mut ctx.accounts.lottery_bet,
+                    bet_key,
+                    args.bet_id,
+                    player_pubkey,
+                    ctx.remaining_accounts.get(3),
+                    clock.slot,
                 )?;
             }
             OperationType::UpdateParams(args) => {
@@ -48,10 +99,19 @@ pub mod lottery {
                     args.min_bet_amount,
                     args.win_probability,
                     args.max_win_multiplier,
+                    args.house_edge_bps,
                     args.is_active,
                     current_time,
                 )?;
             }
+            OperationType::TopUpPool(args) => {
+                instructions::top_up_pool(
+                    &mut ctx.accounts.lottery_prize,
+                    &mut ctx.accounts.player_wallet,
+                    args.amount,
+                    current_time,
+                )?;
+            }
         }
 
         Ok(ctx.accounts)
@@ -61,6 +121,7 @@ pub mod lottery {
     pub struct Components {
         pub lottery_prize: LotteryPrize,
         pub player_wallet: Wallet,
+        pub lottery_bet: LotteryBet,
     }
 }
 
@@ -78,6 +139,28 @@ pub enum LotterySystemError {
     NoPrizeToClaim,
     #[msg("Invalid win probability (must be between 1 and 10000)")]
     InvalidWinProbability,
-    #[msg("Invalid max win multiplier (must be greater than 0)")]
+    #[msg("Invalid max win multiplier (must be greater than 1000)")]
     InvalidMaxWinMultiplier,
+    #[msg("Invalid house edge (must be at most 5000)")]
+    InvalidHouseEdge,
+    #[msg("Top-up amount must be greater than 0")]
+    InvalidTopUpAmount,
+    #[msg("Arithmetic overflow in calculation")]
+    ArithmeticOverflow,
+    #[msg("A bet is already waiting for randomness")]
+    BetPending,
+    #[msg("No bet is waiting to be settled")]
+    NoPendingBet,
+    #[msg("Bet does not belong to this lottery or wallet")]
+    BetMismatch,
+    #[msg("Randomness for the bet has not been delivered yet")]
+    RandomnessNotDelivered,
+    #[msg("Randomness for the bet was delivered, settle it instead")]
+    RandomnessDelivered,
+    #[msg("Bet can't be refunded yet")]
+    BetNotExpired,
+    #[msg("Only the game authority can manage the lottery")]
+    Unauthorized,
+    #[msg("Lottery was not initialized by the game authority")]
+    LotteryNotCanonical,
 }
@@ -0,0 +1,384 @@
+#![allow(unexpected_cfgs)]
+
+use bolt_lang::*;
+use durability::Durability;
+use facility::Facility;
+use wallet::Wallet;
+use production::Production;
+use ownership::Ownership;
+
+declare_id!("99rQBjiKsmyZmbFYC77yGtJ4f6xTS51w8JCxGxbPHvqc");
+
+pub const MAX_MINING_BONUS_MULTIPLIER: u32 = 30_000; // Mining bonuses at most triple the yield of a collection
+
+/// Maintenance system for hardware durability and repairs
+///
+/// This system allows entities to:
+/// - Initialize durability properties
+/// - Repair worn or broken hardware for USDC and AiFi
+/// - Change the overclock setting of hardware
+/// - Update durability parameters
+/// - Configure the facility (cooling, energy contracts and incident odds)
+///
+/// Wear and incidents is applied by the ResourceProduction system when resources are collected.
+///
+/// The last component is the Ownership of the player owning the hardware. Repairs and
+/// overclock changes expect the player entity and the hardware entity as extra accounts
+/// after the components, and the player must own the hardware. Durability parameters
+/// and facilities are managed by the game authority, which signs and is passed as the
+/// first extra account instead. Durability can only be initialized once, a repair is the only way
+/// to restore it.
+#[system]
+pub mod maintenance {
+
+    /// Operation types supported by the Maintenance system
+    pub enum OperationType {
+        /// Initialize durability properties
+        Initialize = 0,
+        /// Repair the hardware
+        Repair = 1,
+        /// Change the overclock setting
+        SetOverclock = 2,
+        /// Update durability parameters
+        UpdateParams = 3,
+        /// Configure the facility the hardware is operated in
+        ConfigureFacility = 4,
+    }
+
+    /// Arguments for the Maintenance system
+    #[arguments]
+    pub struct Args {
+        /// Type of operation to perform
+        pub operation_type: u8,
+        /// Entity type being maintained (0 = Player, 1 = GPU, etc.)
+        pub entity_type: u8,
+        /// Maximum durability points (for Initialize and UpdateParams)
+        pub max_durability: u32,
+        /// Durability points lost per hour of production at stock clocks
+        pub wear_per_hour: u32,
+        /// Durability below which efficiency starts to degrade
+        pub degradation_threshold: u32,
+        /// Overclock multiplier (10000 = stock clocks, for Initialize and SetOverclock)
+        pub overclock_multiplier: u32,
+        /// USDC cost per durability point restored
+        pub repair_usdc_per_point: u64,
+        /// AiFi cost per durability point restored
+        pub repair_aifi_per_point: u64,
+        /// Durability points to restore (for Repair, 0 = full repair)
+        pub repair_points: u32,
+        /// Current time in seconds (Unix timestamp)
+        pub current_time: i64,
+        /// Cooling rating of the data center (for ConfigureFacility, 10000 = 100%)
+        pub cooling_rating: u32,
+        /// Share of power covered by energy contracts (for ConfigureFacility, 10000 = 100%)
+        pub energy_contract_coverage: u32,
+        /// Base chance of a power outage per collection (10000 = 100%)
+        pub power_outage_odds: u32,
+        /// Base chance of a GPU burnout per collection (10000 = 100%)
+        pub gpu_burnout_odds: u32,
+        /// Chance of a mining bonus per collection (10000 = 100%)
+        pub mining_bonus_odds: u32,
+        /// Yield multiplier during a power outage (10000 = no loss)
+        pub outage_yield_multiplier: u32,
+        /// Durability points lost to a GPU burnout
+        pub burnout_durability_damage: u32,
+        /// Yield multiplier during a mining bonus (10000 = no bonus)
+        pub mining_bonus_multiplier: u32,
+        /// Whether incidents are rolled on collection
+        pub incidents_enabled: bool,
+    }
+
+    /// Main execution function for the Maintenance system
+    pub fn execute(ctx: Context<Components>, args: Args) -> Result<Components> {
+        // Repairs and clock changes are limited to the player owning the hardware
+        if matches!(args.operation_type, 1 | 2) {
+            ownership::verify_player_asset(
+                &ctx.accounts.owner_ownership,
+                ctx.remaining_accounts.get(5..).unwrap_or(&[]),
+                &[
+                    (ctx.accounts.wallet.key(), wallet::ID),
+                    (ctx.accounts.owner_ownership.key(), ownership::ID),
+                ],
+                &[
+                    (ctx.accounts.durability.key(), durability::ID),
+                    (ctx.accounts.production.key(), production::ID),
+                ],
+                ctx.accounts.durability.durability_type,
+            )?;
+        }
+
+        // Durability parameters and facilities are managed by the game authority
+        if matches!(args.operation_type, 0 | 3 | 4)
+            && !game_config::is_game_authority(ctx.remaining_accounts.get(5))
+        {
+            return Err(MaintenanceError::Unauthorized.into());
+        }
+
+        match args.operation_type {
+            // Initialize durability properties
+            0 => {
+                let durability = &mut ctx.accounts.durability;
+                let production = &mut ctx.accounts.production;
+
+                // Initializing again would restore full durability without paying for a repair
+                if durability.max_durability != 0 {
+                    return Err(MaintenanceError::AlreadyInitialized.into());
+                }
+
+                if args.max_durability == 0 || args.degradation_threshold > args.max_durability {
+                    return Err(MaintenanceError::InvalidParameters.into());
+                }
+
+                // Capture the undamaged efficiency of the hardware
+                durability.rated_efficiency = production.efficiency_multiplier;
+                durability.current_durability = args.max_durability;
+                durability.max_durability = args.max_durability;
+                durability.wear_per_hour = args.wear_per_hour;
+                durability.degradation_threshold = args.degradation_threshold;
+                durability.overclock_multiplier = if args.overclock_multiplier == 0 {
+                    yield_math::BPS_DENOMINATOR
+                } else {
+                    args.overclock_multiplier
+                };
+                durability.repair_usdc_per_point = args.repair_usdc_per_point;
+                durability.repair_aifi_per_point = args.repair_aifi_per_point;
+                durability.last_wear_time = args.current_time;
+                durability.is_broken = false;
+                durability.repair_count = 0;
+                durability.durability_type = args.entity_type;
+
+                production.efficiency_multiplier = yield_math::worn_efficiency_bps(
+                    durability.rated_efficiency,
+                    durability.overclock_multiplier,
+                    durability.current_durability,
+                    durability.degradation_threshold,
+                ).ok_or(MaintenanceError::ArithmeticOverflow)?;
+
+                msg!("Initialized durability: {} points, {} wear/hr, efficiency {}",
+                     durability.max_durability, durability.wear_per_hour, production.efficiency_multiplier);
+            },
+            // Repair the hardware
+            1 => {
+                let durability = &mut ctx.accounts.durability;
+                let wallet = &mut ctx.accounts.wallet;
+                let production = &mut ctx.accounts.production;
+
+                if durability.max_durability == 0 {
+                    return Err(MaintenanceError::DurabilityNotTracked.into());
+                }
+
+                let missing_points = durability.max_durability
+                    .saturating_sub(durability.current_durability);
+
+                if missing_points == 0 {
+                    return Err(MaintenanceError::AlreadyAtMaxDurability.into());
+                }
+
+                // Restore the requested points, capped at max durability
+                let repair_points = if args.repair_points == 0 {
+                    missing_points
+                } else {
+                    args.repair_points.min(missing_points)
+                };
+
+                let usdc_cost = durability.repair_usdc_per_point
+                    .checked_mul(repair_points as u64)
+                    .ok_or(MaintenanceError::ArithmeticOverflow)?;
+
+                let aifi_cost = durability.repair_aifi_per_point
+                    .checked_mul(repair_points as u64)
+                    .ok_or(MaintenanceError::ArithmeticOverflow)?;
+
+                // Check if wallet has enough funds
+                if wallet.usdc_balance < usdc_cost {
+                    return Err(MaintenanceError::InsufficientUsdcFunds.into());
+                }
+
+                if wallet.aifi_balance < aifi_cost {
+                    return Err(MaintenanceError::InsufficientAifiFunds.into());
+                }
+
+                // Deduct repair costs
+                wallet.usdc_balance = wallet.usdc_balance
+                    .checked_sub(usdc_cost)
+                    .ok_or(MaintenanceError::ArithmeticOverflow)?;
+
+                wallet.aifi_balance = wallet.aifi_balance
+                    .checked_sub(aifi_cost)
+                    .ok_or(MaintenanceError::ArithmeticOverflow)?;
+
+                durability.current_durability = durability.current_durability
+                    .checked_add(repair_points)
+                    .ok_or(MaintenanceError::ArithmeticOverflow)?;
+
+                durability.repair_count = durability.repair_count.saturating_add(1);
+
+                // Broken hardware produced nothing while it was down
+                if durability.is_broken {
+                    durability.is_broken = false;
+                    durability.last_wear_time = args.current_time;
+                    production.last_collection_time = args.current_time;
+                }
+
+                production.efficiency_multiplier = yield_math::worn_efficiency_bps(
+                    durability.rated_efficiency,
+                    durability.overclock_multiplier,
+                    durability.current_durability,
+                    durability.degradation_threshold,
+                ).ok_or(MaintenanceError::ArithmeticOverflow)?;
+
+                msg!("Repaired {} points for {} USDC and {} AiFi, durability now {}/{}",
+                     repair_points, usdc_cost, aifi_cost, durability.current_durability, durability.max_durability);
+            },
+            // Change the overclock setting
+            2 => {
+                let durability = &mut ctx.accounts.durability;
+                let production = &mut ctx.accounts.production;
+
+                if durability.max_durability == 0 {
+                    return Err(MaintenanceError::DurabilityNotTracked.into());
+                }
+
+                // Clocks can only be changed while stopped so the new setting is never applied retroactively
+                if production.is_active {
+                    return Err(MaintenanceError::ProductionActive.into());
+                }
+
+                if args.overclock_multiplier == 0 {
+                    return Err(MaintenanceError::InvalidParameters.into());
+                }
+
+                durability.overclock_multiplier = args.overclock_multiplier;
+
+                production.efficiency_multiplier = yield_math::worn_efficiency_bps(
+                    durability.rated_efficiency,
+                    durability.overclock_multiplier,
+                    durability.current_durability,
+                    durability.degradation_threshold,
+                ).ok_or(MaintenanceError::ArithmeticOverflow)?;
+
+                msg!("Overclock set to {}, efficiency now {}",
+                     durability.overclock_multiplier, production.efficiency_multiplier);
+            },
+            // Update durability parameters
+            3 => {
+                let durability = &mut ctx.accounts.durability;
+                let production = &mut ctx.accounts.production;
+
+                if args.max_durability == 0 || args.degradation_threshold > args.max_durability {
+                    return Err(MaintenanceError::InvalidParameters.into());
+                }
+
+                durability.max_durability = args.max_durability;
+                durability.current_durability = durability.current_durability.min(args.max_durability);
+                durability.wear_per_hour = args.wear_per_hour;
+                durability.degradation_threshold = args.degradation_threshold;
+                durability.repair_usdc_per_point = args.repair_usdc_per_point;
+                durability.repair_aifi_per_point = args.repair_aifi_per_point;
+
+                production.efficiency_multiplier = yield_math::worn_efficiency_bps(
+                    durability.rated_efficiency,
+                    durability.overclock_multiplier,
+                    durability.current_durability,
+                    durability.degradation_threshold,
+                ).ok_or(MaintenanceError::ArithmeticOverflow)?;
+
+                msg!("Durability parameters updated: max={}, wear={}/hr, threshold={}",
+                     args.max_durability, args.wear_per_hour, args.degradation_threshold);
+            },
+            // Configure the facility the hardware is operated in
+            4 => {
+                let facility = &mut ctx.accounts.facility;
+
+                let total_odds = args.power_outage_odds as u64
+                    + args.gpu_burnout_odds as u64
+                    + args.mining_bonus_odds as u64;
+
+                if total_odds > yield_math::BPS_DENOMINATOR as u64 {
+                    return Err(MaintenanceError::InvalidParameters.into());
+                }
+
+                // Cooling and coverage cap at 100%, outages can only lower yield and bonuses only raise it, up to a cap
+                if args.cooling_rating > yield_math::BPS_DENOMINATOR
+                    || args.energy_contract_coverage > yield_math::BPS_DENOMINATOR
+                    || args.outage_yield_multiplier > yield_math::BPS_DENOMINATOR
+                    || args.mining_bonus_multiplier < yield_math::BPS_DENOMINATOR
+                    || args.mining_bonus_multiplier > MAX_MINING_BONUS_MULTIPLIER
+                {
+                    return Err(MaintenanceError::InvalidParameters.into());
+                }
+
+                facility.cooling_rating = args.cooling_rating;
+                facility.energy_contract_coverage = args.energy_contract_coverage;
+                facility.power_outage_odds = args.power_outage_odds;
+                facility.gpu_burnout_odds = args.gpu_burnout_odds;
+                facility.mining_bonus_odds = args.mining_bonus_odds;
+                facility.outage_yield_multiplier = args.outage_yield_multiplier;
+                facility.burnout_durability_damage = args.burnout_durability_damage;
+                facility.mining_bonus_multiplier = args.mining_bonus_multiplier;
+                facility.incidents_enabled = args.incidents_enabled;
+                facility.facility_type = args.entity_type;
+
+                msg!("Facility configured: cooling={}, energy coverage={}, incidents enabled={}",
+                     args.cooling_rating, args.energy_contract_coverage, args.incidents_enabled);
+            },
+            _ => return Err(MaintenanceError::InvalidOperation.into()),
+        }
+
+        Ok(ctx.accounts)
+    }
+
+    /// Components required for the Maintenance system
+    #[system_input]
+    pub struct Components {
+        pub durability: Durability,
+        pub wallet: Wallet,
+        pub production: Production,
+        pub facility: Facility,
+        pub owner_ownership: Ownership,
+    }
+}
+
+/// Errors that can occur in the Maintenance system
+#[error_code]
+pub enum MaintenanceError {
+    /// Durability is not tracked for this entity
+    #[msg("Durability is not tracked for this entity")]
+    DurabilityNotTracked,
+
+    /// Hardware is already at maximum durability
+    #[msg("Hardware is already at maximum durability")]
+    AlreadyAtMaxDurability,
+
+    /// Insufficient USDC funds for repair
+    #[msg("Insufficient USDC funds for repair")]
+    InsufficientUsdcFunds,
+
+    /// Insufficient AiFi funds for repair
+    #[msg("Insufficient AiFi funds for repair")]
+    InsufficientAifiFunds,
+
+    /// Production must be stopped for this operation
+    #[msg("Production must be stopped for this operation")]
+    ProductionActive,
+
+    /// Invalid durability parameters
+    #[msg("Invalid durability parameters")]
+    InvalidParameters,
+
+    /// Invalid operation type specified
+    #[msg("Invalid operation type specified")]
+    InvalidOperation,
+
+    /// Arithmetic overflow during calculation
+    #[msg("Arithmetic overflow in calculation")]
+    ArithmeticOverflow,
+
+    /// Durability has already been initialized
+    #[msg("Durability is already initialized")]
+    AlreadyInitialized,
+
+    /// Signer is not the game authority
+    #[msg("Only the game authority can change durability settings")]
+    Unauthorized,
+}
@@ -0,0 +1,301 @@
+#![allow(unexpected_cfgs)]
+
+use bolt_lang::*;
+use player_profile::PlayerProfile;
+use wallet::Wallet;
+use ownership::{EntityType, Ownership};
+use price::Price;
+use production::Production;
+use stakeable::Stakeable;
+
+declare_id!("J48VmkUvj7gPgYmAAaiBgqAxAW1X7e8uW1pMkNZpLw49");
+
+/// Net worth in USDC units a player needs before resetting (1,000,000 USDC)
+pub const PRESTIGE_THRESHOLD: u64 = 1_000_000_000_000;
+
+/// Net worth in USDC units worth one prestige point (100,000 USDC)
+pub const PRESTIGE_POINT_VALUE: u64 = 100_000_000_000;
+
+/// Production bonus of each prestige point (100 = 1%)
+pub const MULTIPLIER_PER_POINT: u32 = 100;
+
+/// Highest production multiplier prestige can grant (30000 = 3x)
+pub const MAX_PRESTIGE_MULTIPLIER: u32 = 30000;
+
+// Currency types of the priced balances (uses same values as the Wallet component)
+const CURRENCY_BTC: u8 = 1;
+const CURRENCY_ETH: u8 = 2;
+const CURRENCY_SOL: u8 = 3;
+const CURRENCY_AIFI: u8 = 4;
+
+/// Prestige system for resetting late-game players in exchange for permanent bonuses
+///
+/// This system allows a player to:
+/// - Set up their player profile
+/// - Give up their GPUs once their net worth passes the prestige threshold
+/// - Reset once their net worth passes the prestige threshold
+///
+/// The player's PlayerProfile, Wallet and Ownership are the first three components,
+/// followed by the BTC, ETH, SOL and AiFi Price components used to value the wallet,
+/// which must be managed by the game authority, and the Production and Stakeable of the
+/// GPU given up by RetireGpu. Other operations ignore the GPU components. The player
+/// entity is passed as an extra account after the components, followed by the GPU
+/// entity for RetireGpu.
+///
+/// Before a reset every GPU is given up with RetireGpu, which removes it from the
+/// player's Ownership and retires its Production and Stakeable. The reset then empties
+/// the wallet. The net worth given up earns prestige points, which raise the production
+/// multiplier the ResourceProduction system applies to the player's collections.
+#[system]
+pub mod prestige {
+
+    /// Operation types supported by the Prestige system
+    pub enum OperationType {
+        /// Set up the player profile
+        Initialize = 0,
+        /// Reset the player for prestige points
+        Prestige = 1,
+        /// Give up a GPU ahead of a reset
+        RetireGpu = 2,
+    }
+
+    /// Arguments for the Prestige system
+    #[arguments]
+    pub struct Args {
+        /// Type of operation to perform
+        pub operation_type: u8,
+        /// Current time in seconds (Unix timestamp)
+        pub current_time: i64,
+    }
+
+    /// Main execution function for the Prestige system
+    pub fn execute(ctx: Context<Components>, args: Args) -> Result<Components> {
+        // USDC value of a balance at the game's price of its currency
+        fn value_of(balance: u64, price: &Price, currency: u8) -> Result<u64> {
+            if price.authority != game_config::GAME_AUTHORITY {
+                return Err(PrestigeError::PriceNotCanonical.into());
+            }
+
+            if price.price_type != currency {
+                return Err(PrestigeError::CurrencyPriceMismatch.into());
+            }
+
+            let value = (balance as u128)
+                .checked_mul(price.current_price as u128)
+                .ok_or(PrestigeError::ArithmeticOverflow)?
+                / 1_000_000;
+
+            u64::try_from(value).map_err(|_| PrestigeError::ArithmeticOverflow.into())
+        }
+
+        // Value the wallet in USDC and return it with the prestige points it is worth
+        fn prestige_worth(ctx: &Context<Components>) -> Result<(u64, u64)> {
+            let wallet = &ctx.accounts.wallet;
+            let priced_values = [
+                value_of(wallet.btc_balance, &ctx.accounts.price_btc, CURRENCY_BTC)?,
+                value_of(wallet.eth_balance, &ctx.accounts.price_eth, CURRENCY_ETH)?,
+                value_of(wallet.sol_balance, &ctx.accounts.price_sol, CURRENCY_SOL)?,
+                value_of(wallet.aifi_balance, &ctx.accounts.price_aifi, CURRENCY_AIFI)?,
+            ];
+
+            let mut net_worth = wallet.usdc_balance;
+            for value in priced_values {
+                net_worth = net_worth.checked_add(value)
+                    .ok_or(PrestigeError::ArithmeticOverflow)?;
+            }
+
+            let points = yield_math::prestige_points(net_worth, PRESTIGE_THRESHOLD, PRESTIGE_POINT_VALUE);
+            if points == 0 {
+                msg!("Net worth {} is below the prestige threshold {}", net_worth, PRESTIGE_THRESHOLD);
+                return Err(PrestigeError::BelowThreshold.into());
+            }
+
+            Ok((net_worth, points))
+        }
+
+        // The profile, wallet and ownership must all belong to the player entity
+        let player_entity = ctx.remaining_accounts.get(9)
+            .ok_or(PrestigeError::ProfileMismatch)?;
+
+        if !ownership::is_component_of(&ctx.accounts.player_profile.key(), &player_profile::ID, player_entity.key)
+            || !ownership::is_component_of(&ctx.accounts.wallet.key(), &wallet::ID, player_entity.key)
+            || !ownership::is_component_of(&ctx.accounts.owner_ownership.key(), &ownership::ID, player_entity.key)
+        {
+            return Err(PrestigeError::ProfileMismatch.into());
+        }
+
+        match args.operation_type {
+            // Set up the player profile
+            0 => {
+                let profile = &mut ctx.accounts.player_profile;
+
+                if profile.is_initialized() {
+                    return Err(PrestigeError::AlreadyInitialized.into());
+                }
+
+                profile.production_multiplier = yield_math::BPS_DENOMINATOR;
+
+                msg!("Player profile set up");
+            },
+            // Reset the player for prestige points
+            1 => {
+                if !ctx.accounts.player_profile.is_initialized() {
+                    return Err(PrestigeError::NotInitialized.into());
+                }
+
+                if ctx.accounts.owner_ownership.owner_type != EntityType::Player.to_u8() {
+                    return Err(PrestigeError::ProfileMismatch.into());
+                }
+
+                let (net_worth, points) = prestige_worth(&ctx)?;
+
+                // Every GPU must have been given up with RetireGpu
+                if ctx.accounts.owner_ownership.owned_entity_types.contains(&EntityType::GPU.to_u8()) {
+                    return Err(PrestigeError::GpusNotRetired.into());
+                }
+
+                // Empty the wallet
+                let wallet = &mut ctx.accounts.wallet;
+                wallet.usdc_balance = 0;
+                wallet.btc_balance = 0;
+                wallet.eth_balance = 0;
+                wallet.sol_balance = 0;
+                wallet.aifi_balance = 0;
+
+                // Convert the net worth into a permanent bonus
+                let profile = &mut ctx.accounts.player_profile;
+                profile.prestige_level = profile.prestige_level.checked_add(1)
+                    .ok_or(PrestigeError::ArithmeticOverflow)?;
+                profile.prestige_points = profile.prestige_points.checked_add(points)
+                    .ok_or(PrestigeError::ArithmeticOverflow)?;
+                profile.production_multiplier = yield_math::prestige_multiplier_bps(
+                    profile.prestige_points,
+                    MULTIPLIER_PER_POINT,
+                    MAX_PRESTIGE_MULTIPLIER,
+                );
+                profile.lifetime_net_worth = profile.lifetime_net_worth.saturating_add(net_worth);
+                profile.highest_net_worth = profile.highest_net_worth.max(net_worth);
+                profile.last_prestige_time = args.current_time;
+
+                msg!("Prestige level {}: {} points for a net worth of {} USDC, multiplier {}",
+                     profile.prestige_level, points, net_worth / 1_000_000,
+                     profile.production_multiplier);
+            },
+            // Give up a GPU ahead of a reset
+            2 => {
+                if !ctx.accounts.player_profile.is_initialized() {
+                    return Err(PrestigeError::NotInitialized.into());
+                }
+
+                // GPUs are only given up by players who can reset
+                prestige_worth(&ctx)?;
+
+                let gpu_type = EntityType::GPU.to_u8();
+                ownership::verify_player_asset(
+                    &ctx.accounts.owner_ownership,
+                    ctx.remaining_accounts.get(9..).unwrap_or(&[]),
+                    &[
+                        (ctx.accounts.wallet.key(), wallet::ID),
+                        (ctx.accounts.owner_ownership.key(), ownership::ID),
+                    ],
+                    &[
+                        (ctx.accounts.gpu_production.key(), production::ID),
+                        (ctx.accounts.gpu_stakeable.key(), stakeable::ID),
+                    ],
+                    gpu_type,
+                )?;
+
+                // Unbonding GPUs are still held by staking
+                let stakeable = &mut ctx.accounts.gpu_stakeable;
+                if stakeable.is_staked || stakeable.is_unbonding {
+                    return Err(PrestigeError::GpuStaked.into());
+                }
+
+                let gpu_id = ownership::entity_id(&ctx.remaining_accounts[10])?;
+                ctx.accounts.owner_ownership.remove_entity(&ownership::entity_id_to_pubkey(gpu_id))?;
+
+                // Stop the GPU from producing, upgrading or earning staking rewards
+                let production = &mut ctx.accounts.gpu_production;
+                production.is_active = false;
+                production.is_retired = true;
+                production.usdc_per_hour = 0;
+                production.aifi_per_hour = 0;
+                production.btc_per_hour = 0;
+                production.eth_per_hour = 0;
+                production.sol_per_hour = 0;
+                production.operating_cost = 0;
+
+                stakeable.can_claim_rewards = false;
+                stakeable.reward_rate = 0;
+                stakeable.base_usdc_per_hour = 0;
+                stakeable.base_aifi_per_hour = 0;
+
+                let profile = &mut ctx.accounts.player_profile;
+                profile.gpus_reset = profile.gpus_reset.saturating_add(1);
+
+                msg!("Gave up GPU {} ahead of a reset, {} GPUs given up", gpu_id, profile.gpus_reset);
+            },
+            _ => return Err(PrestigeError::InvalidOperation.into()),
+        }
+
+        Ok(ctx.accounts)
+    }
+
+    /// Components required for the Prestige system
+    #[system_input]
+    pub struct Components {
+        pub player_profile: PlayerProfile,
+        pub wallet: Wallet,
+        pub owner_ownership: Ownership,
+        pub price_btc: Price,
+        pub price_eth: Price,
+        pub price_sol: Price,
+        pub price_aifi: Price,
+        pub gpu_production: Production,
+        pub gpu_stakeable: Stakeable,
+    }
+}
+
+/// Errors that can occur in the Prestige system
+#[error_code]
+pub enum PrestigeError {
+    /// Profile, wallet or ownership does not belong to the player entity
+    #[msg("Player profile does not belong to the player")]
+    ProfileMismatch,
+
+    /// Profile has already been set up
+    #[msg("Player profile is already set up")]
+    AlreadyInitialized,
+
+    /// Profile has not been set up
+    #[msg("Player profile is not set up")]
+    NotInitialized,
+
+    /// Price component does not match the valued currency
+    #[msg("Price component does not match the currency")]
+    CurrencyPriceMismatch,
+
+    /// Net worth is below the prestige threshold
+    #[msg("Net worth is below the prestige threshold")]
+    BelowThreshold,
+
+    /// Arithmetic overflow during calculation
+    #[msg("Arithmetic overflow in calculation")]
+    ArithmeticOverflow,
+
+    /// Invalid operation type specified
+    #[msg("Invalid operation type specified")]
+    InvalidOperation,
+
+    /// Price is not managed by the game authority
+    #[msg("Price is not one of the game's prices")]
+    PriceNotCanonical,
+
+    /// Player still owns GPUs that were not given up
+    #[msg("Every GPU must be given up before a reset")]
+    GpusNotRetired,
+
+    /// GPU is staked or unbonding
+    #[msg("Staked or unbonding GPUs cannot be given up")]
+    GpuStaked,
+}
@@ -14,6 +14,10 @@ declare_id!("6e4kZsL68kwjW1Qagd9su8vYQPZGPyS3Mkg4n8Lt5FZU");
 /// - Initialize price components with proper values
 /// - Enable price updates for components
 /// - Update prices based on market dynamics
+///
+/// A price initialized with the game authority signing as the first account after the
+/// component is one of the game's prices. Only the game authority can initialize or
+/// enable it afterwards, and systems that value assets only accept such prices.
 /// 
 /// All currency values use 6 decimal places, where 1,000,000 = $1.
 /// For example, BTC at $60,000 would be stored as 60,000,000,000.
@@ -96,11 +100,24 @@ pub mod price_action {
         /// Price updates are disabled
         #[msg("Price updates are currently disabled")]
         PriceUpdatesDisabled,
+
+        /// Game price changed without the game authority
+        #[msg("Only the game authority can set up the game's prices")]
+        Unauthorized,
     }
 
     /// Main execution function for the PriceActionSystem
     pub fn execute(ctx: Context<Components>, args: Args) -> Result<Components> {
+        let is_game_authority = game_config::is_game_authority(ctx.remaining_accounts.first());
         let price = &mut ctx.accounts.price;
+
+        // The game's prices are only set up by the game authority
+        if args.operation_type != 2
+            && price.authority == game_config::GAME_AUTHORITY
+            && !is_game_authority
+        {
+            return Err(PriceActionError::Unauthorized.into());
+        }
         
         match args.operation_type {
             // INITIALIZE
@@ -118,6 +135,11 @@ pub mod price_action {
                 price.volatility = args.volatility;
                 price.update_frequency = args.update_frequency;
                 price.price_type = args.currency_type;
+                price.authority = if is_game_authority {
+                    game_config::GAME_AUTHORITY
+                } else {
+                    Pubkey::default()
+                };
                 
                 // Default to price updates disabled - must be explicitly enabled
                 price.price_updates_enabled = false;
@@ -2,17 +2,56 @@
 
 use bolt_lang::*;
 use production::Production;
+use durability::Durability;
+use facility::Facility;
 use wallet::Wallet;
+use player_profile::PlayerProfile;
+use hosting_agreement::HostingAgreement;
+use ownership::EntityType;
+use yield_math::{ConstructionWindow, HardwareWear, HostingTerm, Incident, IncidentOdds, IncidentRoll, ProductionRates};
 
 declare_id!("3R1a64ew4wk5MnA4Vit16twiP8UvYQ92ajXqxrP7sdBr");
 
+pub const INCIDENT_ROLL_EXPIRY_SLOTS: u64 = 150; // Slots (about a minute) before an undelivered incident roll is skipped
+
 /// ResourceProduction system handles resource generation and collection
 ///
 /// This system allows entities to:
 /// - Initialize production settings
-/// - Collect generated resources based on time elapsed
+/// - Collect generated resources based on time elapsed, crediting every currency the producer outputs
+/// - Wear down hardware durability while producing
+/// - Roll random incidents (power outages, GPU burnouts, mining bonuses) with committed VRF randomness
 /// - Activate or deactivate production
 /// - Update production rates
+///
+/// Every operation but Initialize expects the producer entity as the first extra
+/// account; the Production, Durability, Facility and HostingAgreement must all be
+/// components of it so wear, incidents and the host's share can't be skipped by passing
+/// another entity's components.
+///
+/// The fifth component is the PlayerProfile of the player owning the wallet. Once the
+/// profile is set up, Collect applies its prestige and vote-escrow multipliers, records
+/// lifetime earnings, and expects the player entity holding the wallet and profile as
+/// the second extra account.
+///
+/// The HostingAgreement of the producer and the host's Wallet follow. While the host has
+/// accepted an agreement, Collect pays the host its share of the yield earned during the
+/// term; the owner's wallet pays the operating costs and keeps the rest. Without an active
+/// agreement any other Wallet can be passed as the host's and is left untouched.
+///
+/// When the Facility component enables incidents, every collection is preceded by a
+/// RequestIncidentRoll. It fixes the time the collection ends at and requests randomness
+/// from vrf_client through CPI; the extra accounts after the producer entity are the
+/// requester (signer), the vrf_client CommittedRandomness PDA of the production, requester
+/// and request number, the vrf_client program identity, the oracle queue, the system
+/// program, the slot hashes sysvar, the VRF program and the vrf_client program. The VRF
+/// callback delivers the randomness once, so the roll can't be known or re-rolled before
+/// it is requested. Collect then expects the CommittedRandomness PDA after the producer
+/// and player entities and collects up to the committed time. A roll that is still not
+/// delivered after INCIDENT_ROLL_EXPIRY_SLOTS is skipped without an incident.
+///
+/// GPU rates come from the GPU model catalog, so GPUs cannot be initialized here and their
+/// rates cannot be updated.
 #[system]
 pub mod resource_production {
 
@@ -26,6 +65,8 @@ pub mod resource_production {
         SetActive = 2,
         /// Update production rates
         UpdateRates = 3,
+        /// Request the incident roll of the next collection
+        RequestIncidentRoll = 4,
     }
 
     /// Arguments for the ResourceProductionSystem
@@ -53,11 +94,30 @@ pub mod resource_production {
 
     /// Main execution function for the ResourceProduction system
     pub fn execute(ctx: Context<Components>, args: Args) -> Result<Components> {
+        // The hardware state must belong to the producer
+        if args.operation_type != 0 {
+            let producer_entity = ctx.remaining_accounts.get(7)
+                .ok_or(ResourceProductionError::ComponentMismatch)?;
+            
+            if !ownership::is_component_of(&ctx.accounts.production.key(), &production::ID, producer_entity.key)
+                || !ownership::is_component_of(&ctx.accounts.durability.key(), &durability::ID, producer_entity.key)
+                || !ownership::is_component_of(&ctx.accounts.facility.key(), &facility::ID, producer_entity.key)
+                || !ownership::is_component_of(&ctx.accounts.hosting_agreement.key(), &hosting_agreement::ID, producer_entity.key)
+            {
+                return Err(ResourceProductionError::ComponentMismatch.into());
+            }
+        }
+        
         match args.operation_type {
             // Initialize production settings
             0 => {
                 let production = &mut ctx.accounts.production;
                 
+                // GPUs are spawned from the catalog
+                if args.producer_type == EntityType::GPU.to_u8() {
+                    return Err(ResourceProductionError::CatalogManaged.into());
+                }
+                
                 // Set initial production values
                 production.usdc_per_hour = args.usdc_per_hour;
                 production.aifi_per_hour = args.aifi_per_hour;
@@ -70,65 +130,153 @@ pub mod resource_production {
             },
             // Collect generated resources
             1 => {
+                // A set up profile must belong to the player holding the wallet
+                if ctx.accounts.player_profile.is_initialized() {
+                    let player_entity = ctx.remaining_accounts.get(8)
+                        .ok_or(ResourceProductionError::ProfileMismatch)?;
+                    
+                    if !ownership::is_component_of(&ctx.accounts.wallet.key(), &wallet::ID, player_entity.key)
+                        || !ownership::is_component_of(&ctx.accounts.player_profile.key(), &player_profile::ID, player_entity.key)
+                    {
+                        return Err(ResourceProductionError::ProfileMismatch.into());
+                    }
+                }
+                
+                // Hosted GPUs pay into the wallets named by the agreement
+                let hosting = &ctx.accounts.hosting_agreement;
+                if hosting.is_active()
+                    && (hosting.production != ctx.accounts.production.key()
+                        || hosting.owner_wallet != ctx.accounts.wallet.key()
+                        || hosting.host_wallet != ctx.accounts.host_wallet.key())
+                {
+                    return Err(ResourceProductionError::HostingMismatch.into());
+                }
+                
+                // With incidents the collection ends at the time committed by the roll request
+                let facility = &ctx.accounts.facility;
+                let mut current_time = args.current_time;
+                let mut incident_roll = None;
+                
+                if facility.incidents_enabled {
+                    let production = &ctx.accounts.production;
+                    if !production.incident_roll_pending {
+                        return Err(ResourceProductionError::IncidentRollMissing.into());
+                    }
+                    
+                    let committed = vrf_randomness::load_committed_randomness(
+                        ctx.remaining_accounts.get(9).ok_or(ResourceProductionError::IncidentRollMissing)?,
+                        &production.key(),
+                        &production.incident_requester,
+                        production.incident_request_id,
+                    )?;
+                    
+                    if committed.is_fulfilled {
+                        incident_roll = Some(committed.random_u64());
+                    } else if Clock::get()?.slot < committed.requested_slot.saturating_add(INCIDENT_ROLL_EXPIRY_SLOTS) {
+                        return Err(ResourceProductionError::IncidentRollNotDelivered.into());
+                    } else {
+                        msg!("Incident roll {} was never delivered, collecting without an incident", committed.request_id);
+                    }
+                    
+                    current_time = production.incident_roll_time;
+                }
+                
                 let production = &mut ctx.accounts.production;
                 let wallet = &mut ctx.accounts.wallet;
-                let current_time = args.current_time;
+                let durability = &mut ctx.accounts.durability;
+                let hosting = &mut ctx.accounts.hosting_agreement;
                 
                 // Check if production is active
                 if !production.is_active {
                     return Err(ResourceProductionError::ProductionInactive.into());
                 }
                 
-                // Calculate elapsed time since last collection in hours
-                let elapsed_seconds = current_time.checked_sub(production.last_collection_time)
-                    .ok_or(ResourceProductionError::InvalidTimestamp)?;
-                
                 // Avoid negative elapsed time
-                if elapsed_seconds <= 0 {
+                if current_time <= production.last_collection_time {
                     return Err(ResourceProductionError::InvalidTimestamp.into());
                 }
                 
-                // Convert to hours with 3 decimal precision (3600 seconds = 1 hour)
-                // We multiply by 1000 to preserve 3 decimal places
-                let elapsed_hours_x1000 = (elapsed_seconds as u64)
-                    .checked_mul(1000)
-                    .ok_or(ResourceProductionError::ArithmeticOverflow)?
-                    .checked_div(3600)
-                    .ok_or(ResourceProductionError::ArithmeticOverflow)?;
-                
-                // Apply efficiency multiplier to production rates
-                let efficiency = (production.efficiency_multiplier as u64)
-                    .checked_div(10000)
-                    .ok_or(ResourceProductionError::ArithmeticOverflow)?;
-                
-                // Calculate USDC earned
-                let usdc_per_hour = production.usdc_per_hour
-                    .checked_mul(efficiency)
-                    .ok_or(ResourceProductionError::ArithmeticOverflow)?;
+                // Tracked hardware wears down and stops producing the moment it breaks
+                if durability.max_durability > 0 && durability.is_broken {
+                    return Err(ResourceProductionError::HardwareBroken.into());
+                }
                 
-                let usdc_earned = usdc_per_hour
-                    .checked_mul(elapsed_hours_x1000)
-                    .ok_or(ResourceProductionError::ArithmeticOverflow)?
-                    .checked_div(1000) // Adjust for our time precision
-                    .ok_or(ResourceProductionError::ArithmeticOverflow)?;
+                // The payout is computed by the same code as the previews
+                let collection = yield_math::Collection {
+                    period_start: production.last_collection_time,
+                    period_end: current_time,
+                    rates: ProductionRates {
+                        usdc_per_hour: production.usdc_per_hour,
+                        aifi_per_hour: production.aifi_per_hour,
+                        btc_per_hour: production.btc_per_hour,
+                        eth_per_hour: production.eth_per_hour,
+                        sol_per_hour: production.sol_per_hour,
+                        operating_cost_per_hour: production.operating_cost,
+                    },
+                    efficiency_bps: production.efficiency_multiplier,
+                    hardware: (durability.max_durability > 0).then(|| HardwareWear {
+                        current_durability: durability.current_durability,
+                        wear_per_hour: durability.wear_per_hour,
+                        overclock_bps: durability.overclock_multiplier,
+                        rated_efficiency_bps: durability.rated_efficiency,
+                        degradation_threshold: durability.degradation_threshold,
+                    }),
+                    prestige_bps: ctx.accounts.player_profile.multiplier_bps(),
+                    vote_escrow_bps: ctx.accounts.player_profile.vote_escrow_bps(current_time),
+                    construction: ConstructionWindow {
+                        start_time: production.construction_start,
+                        end_time: production.construction_end,
+                        yield_bps: production.construction_yield,
+                    },
+                    incident: incident_roll.map(|roll| IncidentRoll {
+                        roll,
+                        base_odds: IncidentOdds {
+                            power_outage_bps: facility.power_outage_odds,
+                            gpu_burnout_bps: facility.gpu_burnout_odds,
+                            mining_bonus_bps: facility.mining_bonus_odds,
+                        },
+                        cooling_rating_bps: facility.cooling_rating,
+                        energy_contract_coverage_bps: facility.energy_contract_coverage,
+                        outage_multiplier_bps: facility.outage_yield_multiplier,
+                        mining_bonus_multiplier_bps: facility.mining_bonus_multiplier,
+                        burnout_damage: facility.burnout_durability_damage,
+                    }),
+                    hosting: hosting.is_active().then(|| HostingTerm {
+                        start_time: hosting.start_time,
+                        end_time: hosting.end_time,
+                        share_bps: hosting.host_share_bps,
+                    }),
+                };
                 
-                // Calculate AiFi earned
-                let aifi_per_hour = production.aifi_per_hour
-                    .checked_mul(efficiency)
+                let outcome = yield_math::collect(&collection)
                     .ok_or(ResourceProductionError::ArithmeticOverflow)?;
+                let earned = outcome.owner;
+                let producing_seconds = outcome.producing_seconds;
                 
-                let aifi_earned = aifi_per_hour
-                    .checked_mul(elapsed_hours_x1000)
-                    .ok_or(ResourceProductionError::ArithmeticOverflow)?
-                    .checked_div(1000) // Adjust for our time precision
-                    .ok_or(ResourceProductionError::ArithmeticOverflow)?;
+                // Pay the host its share of the seconds hosted during the term
+                if outcome.host_share_bps > 0 {
+                    let host = outcome.host;
+                    let host_wallet = &mut ctx.accounts.host_wallet;
+                    host_wallet.usdc_balance = host_wallet.usdc_balance.checked_add(host.usdc_earned)
+                        .ok_or(ResourceProductionError::ArithmeticOverflow)?;
+                    host_wallet.aifi_balance = host_wallet.aifi_balance.checked_add(host.aifi_earned)
+                        .ok_or(ResourceProductionError::ArithmeticOverflow)?;
+                    host_wallet.btc_balance = host_wallet.btc_balance.checked_add(host.btc_earned)
+                        .ok_or(ResourceProductionError::ArithmeticOverflow)?;
+                    host_wallet.eth_balance = host_wallet.eth_balance.checked_add(host.eth_earned)
+                        .ok_or(ResourceProductionError::ArithmeticOverflow)?;
+                    host_wallet.sol_balance = host_wallet.sol_balance.checked_add(host.sol_earned)
+                        .ok_or(ResourceProductionError::ArithmeticOverflow)?;
+                    
+                    hosting.host_usdc_earned = hosting.host_usdc_earned.saturating_add(host.usdc_earned);
+                    hosting.host_aifi_earned = hosting.host_aifi_earned.saturating_add(host.aifi_earned);
+                    msg!("Paid the host {} USDC and {} AiFi ({} bps of the yield)",
+                         host.usdc_earned, host.aifi_earned, outcome.host_share_bps);
+                }
                 
-                // Calculate operating costs
-                let operating_cost = production.operating_cost
-                    .checked_mul(elapsed_hours_x1000)
-                    .ok_or(ResourceProductionError::ArithmeticOverflow)?
-                    .checked_div(1000) // Adjust for our time precision
-                    .ok_or(ResourceProductionError::ArithmeticOverflow)?;
+                let usdc_earned = earned.usdc_earned;
+                let aifi_earned = earned.aifi_earned;
+                let operating_cost = earned.operating_cost;
                 
                 // Check if wallet has enough funds to cover operating costs
                 if wallet.usdc_balance < operating_cost {
@@ -151,12 +299,74 @@ pub mod resource_production {
                     .checked_add(aifi_earned)
                     .ok_or(ResourceProductionError::ArithmeticOverflow)?;
                 
-                // Update last collection time
+                // Credit mined cryptocurrencies to their matching balances
+                wallet.btc_balance = wallet.btc_balance
+                    .checked_add(earned.btc_earned)
+                    .ok_or(ResourceProductionError::ArithmeticOverflow)?;
+                
+                wallet.eth_balance = wallet.eth_balance
+                    .checked_add(earned.eth_earned)
+                    .ok_or(ResourceProductionError::ArithmeticOverflow)?;
+                
+                wallet.sol_balance = wallet.sol_balance
+                    .checked_add(earned.sol_earned)
+                    .ok_or(ResourceProductionError::ArithmeticOverflow)?;
+                
+                // Lifetime earnings survive prestige resets
+                let profile = &mut ctx.accounts.player_profile;
+                if profile.is_initialized() {
+                    profile.lifetime_usdc_earned = profile.lifetime_usdc_earned.saturating_add(usdc_earned);
+                    profile.lifetime_aifi_earned = profile.lifetime_aifi_earned.saturating_add(aifi_earned);
+                }
+                
+                // Record wear, including burnout damage, and stop broken hardware
+                if let Some(hardware) = outcome.hardware {
+                    durability.current_durability = hardware.remaining_durability;
+                    durability.last_wear_time = current_time;
+                    production.efficiency_multiplier = hardware.efficiency_bps;
+                    
+                    if durability.current_durability == 0 {
+                        durability.is_broken = true;
+                        production.is_active = false;
+                        msg!("Hardware broke down after {} seconds of production", producing_seconds);
+                    }
+                }
+                
+                // Record the incident so clients can explain changes in yield
+                if outcome.incident != Incident::None {
+                    production.last_incident = outcome.incident as u8;
+                    production.last_incident_time = current_time;
+                    production.last_incident_multiplier = outcome.incident_multiplier_bps;
+                    production.last_incident_damage = outcome.incident_damage;
+                    production.incident_count = production.incident_count.saturating_add(1);
+                    
+                    msg!("Incident {:?}: yield multiplier {}, durability damage {}",
+                         outcome.incident, outcome.incident_multiplier_bps, outcome.incident_damage);
+                }
+                
+                // Update last collection time; the roll of this collection is settled
                 production.last_collection_time = current_time;
+                production.incident_roll_pending = false;
             },
             // Activate or deactivate production
             2 => {
                 let production = &mut ctx.accounts.production;
+                let durability = &ctx.accounts.durability;
+                
+                // Broken hardware has to be repaired before it can run again
+                if args.is_active && durability.is_broken {
+                    return Err(ResourceProductionError::HardwareBroken.into());
+                }
+                
+                // Retired producers never run again
+                if args.is_active && production.is_retired {
+                    return Err(ResourceProductionError::ProducerRetired.into());
+                }
+                
+                // A requested roll is kept until Collect consumes it, so it can't be thrown away
+                if production.incident_roll_pending {
+                    return Err(ResourceProductionError::IncidentRollPending.into());
+                }
                 
                 // Update active status
                 production.is_active = args.is_active;
@@ -170,11 +380,70 @@ pub mod resource_production {
             3 => {
                 let production = &mut ctx.accounts.production;
                 
+                // GPU rates can only change through the catalog
+                if production.producer_type == EntityType::GPU.to_u8() {
+                    return Err(ResourceProductionError::CatalogManaged.into());
+                }
+                
                 // Update production rates
                 production.usdc_per_hour = args.usdc_per_hour;
                 production.aifi_per_hour = args.aifi_per_hour;
                 production.efficiency_multiplier = args.efficiency_multiplier;
                 production.operating_cost = args.operating_cost;
+                
+                // For tracked hardware the new multiplier is the rated efficiency, before wear
+                let durability = &mut ctx.accounts.durability;
+                if durability.max_durability > 0 {
+                    durability.rated_efficiency = args.efficiency_multiplier;
+                    production.efficiency_multiplier = yield_math::worn_efficiency_bps(
+                        durability.rated_efficiency,
+                        durability.overclock_multiplier,
+                        durability.current_durability,
+                        durability.degradation_threshold,
+                    ).ok_or(ResourceProductionError::ArithmeticOverflow)?;
+                }
+            },
+            // Request the incident roll of the next collection
+            4 => {
+                if !ctx.accounts.facility.incidents_enabled {
+                    return Err(ResourceProductionError::IncidentsDisabled.into());
+                }
+                
+                let subject = ctx.accounts.production.to_account_info();
+                let request_accounts = ctx.remaining_accounts.get(8..).unwrap_or(&[]);
+                let requester = request_accounts.first()
+                    .ok_or(ResourceProductionError::IncidentRollMissing)?;
+                let production = &mut ctx.accounts.production;
+                
+                if !production.is_active {
+                    return Err(ResourceProductionError::ProductionInactive.into());
+                }
+                
+                // One roll per collection, settled before the next one can be requested
+                if production.incident_roll_pending {
+                    return Err(ResourceProductionError::IncidentRollPending.into());
+                }
+                
+                if args.current_time <= production.last_collection_time {
+                    return Err(ResourceProductionError::InvalidTimestamp.into());
+                }
+                
+                // Commit the end of the collection before the randomness is known
+                production.incident_request_id = production.incident_request_id.checked_add(1)
+                    .ok_or(ResourceProductionError::ArithmeticOverflow)?;
+                production.incident_requester = *requester.key;
+                production.incident_roll_time = args.current_time;
+                production.incident_roll_pending = true;
+                
+                // Requests differ by producer, requester and number, so no client seed is needed
+                vrf_randomness::request_committed_randomness(
+                    &subject,
+                    request_accounts,
+                    production.incident_request_id,
+                    0,
+                )?;
+                
+                msg!("Requested incident roll {} for a collection at {}", production.incident_request_id, args.current_time);
             },
             _ => return Err(ResourceProductionError::InvalidOperation.into()),
         }
@@ -187,6 +456,11 @@ pub mod resource_production {
     pub struct Components {
         pub production: Production,
         pub wallet: Wallet,
+        pub durability: Durability,
+        pub facility: Facility,
+        pub player_profile: PlayerProfile,
+        pub hosting_agreement: HostingAgreement,
+        pub host_wallet: Wallet,
     }
 }
 
@@ -212,4 +486,44 @@ pub enum ResourceProductionError {
     /// Insufficient funds to cover operating costs
     #[msg("Insufficient funds to cover operating costs")]
     InsufficientFundsForOperating,
+    
+    /// Hardware is broken and must be repaired
+    #[msg("Hardware is broken and must be repaired")]
+    HardwareBroken,
+    
+    /// GPU stats are managed by the GPU model catalog
+    #[msg("GPU stats are managed by the GPU model catalog")]
+    CatalogManaged,
+    
+    /// Producer was retired and can no longer run
+    #[msg("Producer has been retired")]
+    ProducerRetired,
+    
+    /// Player profile does not belong to the wallet's player
+    #[msg("Player profile does not belong to the wallet's player")]
+    ProfileMismatch,
+    
+    /// Producer, wallet or host wallet does not match the hosting agreement
+    #[msg("Wallets do not match the hosting agreement")]
+    HostingMismatch,
+    
+    /// Durability or Facility does not belong to the producer
+    #[msg("Hardware components do not belong to the producer")]
+    ComponentMismatch,
+    
+    /// The facility does not roll incidents
+    #[msg("Incidents are not enabled for this facility")]
+    IncidentsDisabled,
+    
+    /// An incident roll is already pending
+    #[msg("An incident roll is already pending, collect first")]
+    IncidentRollPending,
+    
+    /// No incident roll was requested or its randomness account was not provided
+    #[msg("Request an incident roll before collecting")]
+    IncidentRollMissing,
+    
+    /// The randomness of the incident roll has not been delivered yet
+    #[msg("Incident roll has not been delivered yet")]
+    IncidentRollNotDelivered,
 }
\ No newline at end of file
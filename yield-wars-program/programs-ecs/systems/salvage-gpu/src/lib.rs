@@ -0,0 +1,333 @@
+#![allow(unexpected_cfgs)]
+
+use bolt_lang::*;
+use gpu_model::GpuModel;
+use upgrade_table::UpgradeTable;
+use ownership::{EntityType, Ownership};
+use wallet::Wallet;
+use production::Production;
+use upgradeable::Upgradeable;
+use stakeable::Stakeable;
+use durability::Durability;
+use hosting_agreement::HostingAgreement;
+use solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+
+declare_id!("AvPhGaJo7hPxSCia4FnrkHvBgLAAtXPn8pR9BqGr9yKN");
+
+/// Anchor instruction discriminator of the World program's `destroy_component`
+const DESTROY_COMPONENT_DISCRIMINATOR: [u8; 8] = [40, 197, 69, 196, 67, 95, 219, 73];
+
+/// SalvageGpu system for selling GPUs back to the game
+///
+/// This system allows a player to:
+/// - Salvage an owned GPU for a partial USDC and AiFi refund from the treasury
+///
+/// The first three components belong to the catalog entity: the model of the GPU, its
+/// upgrade table and the treasury Wallet. The player's Ownership and Wallet follow, then
+/// the Production, Upgradeable, Stakeable and Durability of the GPU. The player entity,
+/// the GPU entity, the catalog entity, the instructions sysvar and the HostingAgreement
+/// of the GPU are passed as extra accounts after the components. The model must have
+/// been registered by the game authority, and GPUs that are offered for or under a
+/// hosting agreement can't be salvaged until it ends.
+///
+/// The refund is the model value plus the upgrade costs paid to reach the GPU's level,
+/// scaled by the model's salvage rate and the GPU's remaining durability. The GPU is
+/// removed from the player's Ownership and retired, and the four GPU components must be
+/// destroyed through the World program's `destroy_component` later in the same
+/// transaction, so a salvaged GPU can't be used again and its rent is reclaimed.
+#[system]
+pub mod salvage_gpu {
+
+    /// Operation types supported by the SalvageGpu system
+    pub enum OperationType {
+        /// Salvage a GPU for a partial refund
+        Salvage = 0,
+    }
+
+    /// Arguments for the SalvageGpu system
+    #[arguments]
+    pub struct Args {
+        /// Type of operation to perform
+        pub operation_type: u8,
+        /// Current time in seconds (Unix timestamp)
+        pub current_time: i64,
+    }
+
+    /// Main execution function for the SalvageGpu system
+    pub fn execute(ctx: Context<Components>, args: Args) -> Result<Components> {
+        // Check that every component is destroyed by the World program after this instruction
+        fn require_destroyed(instructions_sysvar: &AccountInfo, components: &[Pubkey]) -> Result<()> {
+            if *instructions_sysvar.key != solana_program::sysvar::instructions::ID {
+                return Err(SalvageGpuError::ComponentsNotDestroyed.into());
+            }
+
+            let mut destroyed = vec![false; components.len()];
+            let mut index = load_current_index_checked(instructions_sysvar)? as usize + 1;
+
+            while let Ok(instruction) = load_instruction_at_checked(index, instructions_sysvar) {
+                if instruction.program_id == ownership::WORLD_PROGRAM_ID
+                    && instruction.data.starts_with(&DESTROY_COMPONENT_DISCRIMINATOR)
+                {
+                    for (component, is_destroyed) in components.iter().zip(destroyed.iter_mut()) {
+                        if instruction.accounts.iter().any(|meta| meta.pubkey == *component && meta.is_writable) {
+                            *is_destroyed = true;
+                        }
+                    }
+                }
+
+                index += 1;
+            }
+
+            if destroyed.iter().any(|is_destroyed| !is_destroyed) {
+                msg!("Every component of the salvaged GPU must be destroyed in the same transaction");
+                return Err(SalvageGpuError::ComponentsNotDestroyed.into());
+            }
+
+            Ok(())
+        }
+
+        match args.operation_type {
+            // Salvage a GPU for a partial refund
+            0 => {
+                let gpu_model = &ctx.accounts.gpu_model;
+                let upgrade_table = &ctx.accounts.upgrade_table;
+                let gpu_type = EntityType::GPU.to_u8();
+
+                // The player must own the GPU
+                ownership::verify_player_asset(
+                    &ctx.accounts.owner_ownership,
+                    ctx.remaining_accounts.get(9..).unwrap_or(&[]),
+                    &[
+                        (ctx.accounts.wallet.key(), wallet::ID),
+                        (ctx.accounts.owner_ownership.key(), ownership::ID),
+                    ],
+                    &[
+                        (ctx.accounts.production.key(), production::ID),
+                        (ctx.accounts.upgradeable.key(), upgradeable::ID),
+                        (ctx.accounts.stakeable.key(), stakeable::ID),
+                        (ctx.accounts.durability.key(), durability::ID),
+                    ],
+                    gpu_type,
+                )?;
+
+                // The model, its upgrade table and the treasury must belong to the catalog entity
+                let catalog_entity = ctx.remaining_accounts.get(11)
+                    .ok_or(SalvageGpuError::TreasuryMismatch)?;
+
+                if gpu_model.authority != game_config::GAME_AUTHORITY
+                    || !ownership::is_component_of(&gpu_model.key(), &gpu_model::ID, catalog_entity.key)
+                    || !ownership::is_component_of(&upgrade_table.key(), &upgrade_table::ID, catalog_entity.key)
+                    || !ownership::is_component_of(&ctx.accounts.treasury.key(), &wallet::ID, catalog_entity.key)
+                {
+                    return Err(SalvageGpuError::TreasuryMismatch.into());
+                }
+
+                if gpu_model.model_id == 0 || gpu_model.salvage_rate == 0 {
+                    return Err(SalvageGpuError::NotSalvageable.into());
+                }
+
+                if !upgrade_table.is_registered()
+                    || upgrade_table.entity_type != gpu_type
+                    || upgrade_table.model_id != gpu_model.model_id
+                {
+                    return Err(SalvageGpuError::UpgradeTableMismatch.into());
+                }
+
+                let production = &ctx.accounts.production;
+                if production.producer_type != gpu_type
+                    || production.model_id != gpu_model.model_id
+                    || production.is_retired
+                {
+                    return Err(SalvageGpuError::ModelMismatch.into());
+                }
+
+                // Unbonding GPUs are still held by staking
+                if ctx.accounts.stakeable.is_staked || ctx.accounts.stakeable.is_unbonding {
+                    return Err(SalvageGpuError::GpuStaked.into());
+                }
+
+                if ctx.accounts.upgradeable.upgrade_in_progress {
+                    return Err(SalvageGpuError::UpgradeInProgress.into());
+                }
+
+                // The agreement would be left pointing at a destroyed Production
+                let agreement = ctx.remaining_accounts.get(13)
+                    .ok_or(SalvageGpuError::AgreementMismatch)?;
+                if !ownership::is_component_of(agreement.key, &hosting_agreement::ID, ctx.remaining_accounts[10].key) {
+                    return Err(SalvageGpuError::AgreementMismatch.into());
+                }
+
+                if *agreement.owner == hosting_agreement::ID {
+                    let agreement = HostingAgreement::try_deserialize(&mut &agreement.try_borrow_data()?[..])?;
+                    if agreement.is_offered() || agreement.is_active() {
+                        return Err(SalvageGpuError::GpuHosted.into());
+                    }
+                }
+
+                // Wear the GPU down to the current time before valuing it
+                let durability = &mut ctx.accounts.durability;
+                if production.is_active
+                    && !durability.is_broken
+                    && durability.max_durability > 0
+                    && args.current_time > durability.last_wear_time
+                {
+                    let elapsed = (args.current_time - durability.last_wear_time) as u64;
+                    let outcome = yield_math::apply_wear(
+                        durability.current_durability,
+                        durability.wear_per_hour,
+                        durability.overclock_multiplier,
+                        elapsed,
+                    ).ok_or(SalvageGpuError::ArithmeticOverflow)?;
+
+                    durability.current_durability = outcome.remaining_durability;
+                    durability.is_broken = outcome.is_broken();
+                    durability.last_wear_time = args.current_time;
+                }
+
+                // Value of the model plus everything spent upgrading it
+                let level = ctx.accounts.upgradeable.current_level;
+                let (invested_usdc, invested_aifi) = upgrade_table.invested_costs(level);
+                let usdc_value = gpu_model.salvage_usdc_value.checked_add(invested_usdc)
+                    .ok_or(SalvageGpuError::ArithmeticOverflow)?;
+                let aifi_value = gpu_model.salvage_aifi_value.checked_add(invested_aifi)
+                    .ok_or(SalvageGpuError::ArithmeticOverflow)?;
+
+                let usdc_refund = yield_math::salvage_value(
+                    usdc_value,
+                    gpu_model.salvage_rate,
+                    durability.current_durability,
+                    durability.max_durability,
+                ).ok_or(SalvageGpuError::ArithmeticOverflow)?;
+                let aifi_refund = yield_math::salvage_value(
+                    aifi_value,
+                    gpu_model.salvage_rate,
+                    durability.current_durability,
+                    durability.max_durability,
+                ).ok_or(SalvageGpuError::ArithmeticOverflow)?;
+
+                // Pay the refund out of the treasury
+                let treasury = &mut ctx.accounts.treasury;
+                if treasury.usdc_balance < usdc_refund || treasury.aifi_balance < aifi_refund {
+                    return Err(SalvageGpuError::InsufficientTreasuryFunds.into());
+                }
+
+                treasury.usdc_balance = treasury.usdc_balance.checked_sub(usdc_refund)
+                    .ok_or(SalvageGpuError::ArithmeticOverflow)?;
+                treasury.aifi_balance = treasury.aifi_balance.checked_sub(aifi_refund)
+                    .ok_or(SalvageGpuError::ArithmeticOverflow)?;
+
+                let wallet = &mut ctx.accounts.wallet;
+                wallet.usdc_balance = wallet.usdc_balance.checked_add(usdc_refund)
+                    .ok_or(SalvageGpuError::ArithmeticOverflow)?;
+                wallet.aifi_balance = wallet.aifi_balance.checked_add(aifi_refund)
+                    .ok_or(SalvageGpuError::ArithmeticOverflow)?;
+
+                // The GPU components are closed right after the salvage
+                require_destroyed(
+                    ctx.remaining_accounts.get(12).ok_or(SalvageGpuError::ComponentsNotDestroyed)?,
+                    &[
+                        ctx.accounts.production.key(),
+                        ctx.accounts.upgradeable.key(),
+                        ctx.accounts.stakeable.key(),
+                        ctx.accounts.durability.key(),
+                    ],
+                )?;
+
+                // Return the GPU to the game
+                let gpu_id = ownership::entity_id(&ctx.remaining_accounts[10])?;
+                ctx.accounts.owner_ownership.remove_entity(&ownership::entity_id_to_pubkey(gpu_id))?;
+
+                let production = &mut ctx.accounts.production;
+                production.is_active = false;
+                production.is_retired = true;
+                production.usdc_per_hour = 0;
+                production.aifi_per_hour = 0;
+                production.btc_per_hour = 0;
+                production.eth_per_hour = 0;
+                production.sol_per_hour = 0;
+                production.operating_cost = 0;
+
+                let upgradeable = &mut ctx.accounts.upgradeable;
+                upgradeable.can_upgrade = false;
+
+                let stakeable = &mut ctx.accounts.stakeable;
+                stakeable.can_claim_rewards = false;
+                stakeable.reward_rate = 0;
+                stakeable.base_usdc_per_hour = 0;
+                stakeable.base_aifi_per_hour = 0;
+
+                msg!("Salvaged GPU {} of model {} at level {}: refunded {} USDC, {} AiFi",
+                     gpu_id, gpu_model.model_id, level, usdc_refund, aifi_refund);
+            },
+            _ => return Err(SalvageGpuError::InvalidOperation.into()),
+        }
+
+        Ok(ctx.accounts)
+    }
+
+    /// Components required for the SalvageGpu system
+    #[system_input]
+    pub struct Components {
+        pub gpu_model: GpuModel,
+        pub upgrade_table: UpgradeTable,
+        pub treasury: Wallet,
+        pub owner_ownership: Ownership,
+        pub wallet: Wallet,
+        pub production: Production,
+        pub upgradeable: Upgradeable,
+        pub stakeable: Stakeable,
+        pub durability: Durability,
+    }
+}
+
+/// Errors that can occur in the SalvageGpu system
+#[error_code]
+pub enum SalvageGpuError {
+    /// Model, upgrade table or treasury does not belong to the catalog entity
+    #[msg("Treasury does not belong to the catalog")]
+    TreasuryMismatch,
+
+    /// Model cannot be salvaged
+    #[msg("GPU model cannot be salvaged")]
+    NotSalvageable,
+
+    /// Upgrade table does not belong to the model
+    #[msg("Upgrade table does not belong to the GPU model")]
+    UpgradeTableMismatch,
+
+    /// GPU is not a live GPU of the model
+    #[msg("GPU is not of the salvaged model")]
+    ModelMismatch,
+
+    /// GPU is staked or unbonding
+    #[msg("Staked or unbonding GPUs cannot be salvaged")]
+    GpuStaked,
+
+    /// GPU has an upgrade under construction
+    #[msg("GPUs cannot be salvaged during an upgrade")]
+    UpgradeInProgress,
+
+    /// Treasury cannot cover the refund
+    #[msg("Insufficient treasury funds for the refund")]
+    InsufficientTreasuryFunds,
+
+    /// Arithmetic overflow during calculation
+    #[msg("Arithmetic overflow in calculation")]
+    ArithmeticOverflow,
+
+    /// Invalid operation type specified
+    #[msg("Invalid operation type specified")]
+    InvalidOperation,
+
+    /// GPU components are not destroyed later in the transaction
+    #[msg("Salvaged GPU components must be destroyed in the same transaction")]
+    ComponentsNotDestroyed,
+
+    /// HostingAgreement is not the one of the GPU entity
+    #[msg("Hosting agreement does not belong to the GPU")]
+    AgreementMismatch,
+
+    /// GPU is offered for or under a hosting agreement
+    #[msg("GPUs under a hosting agreement cannot be salvaged")]
+    GpuHosted,
+}
@@ -0,0 +1,180 @@
+#![allow(unexpected_cfgs)]
+
+use bolt_lang::*;
+use gpu_model::GpuModel;
+use upgrade_table::UpgradeTable;
+use production::Production;
+use upgradeable::Upgradeable;
+use stakeable::Stakeable;
+use ownership::EntityType;
+
+declare_id!("DUQYHsm1vWB7KcMt5oBXedcUwUWKxsZsUnddWu5LD7fC");
+
+/// SpawnGpu system for creating GPUs from the GPU model catalog
+///
+/// This system copies the stats of a catalog model onto a new GPU entity:
+/// - Production rates of every output currency, operating cost and efficiency
+/// - Maximum level and first upgrade from the model's upgrade table
+/// - Staking parameters
+///
+/// The first two components belong to the catalog entity, the others to the GPU.
+/// The catalog entity is passed as the first extra account; its model must have
+/// been registered by the game authority and both catalog components must belong
+/// to it. GPU stats can only be set this way, the ResourceProduction, Upgrade and
+/// Staking systems refuse to initialize or override them.
+#[system]
+pub mod spawn_gpu {
+
+    /// Operation types supported by the SpawnGpu system
+    pub enum OperationType {
+        /// Spawn a GPU from a catalog model
+        Spawn = 0,
+    }
+
+    /// Arguments for the SpawnGpu system
+    #[arguments]
+    pub struct Args {
+        /// Type of operation to perform
+        pub operation_type: u8,
+        /// Whether production should start right away
+        pub is_active: bool,
+        /// Current time in seconds (Unix timestamp)
+        pub current_time: i64,
+    }
+
+    /// Main execution function for the SpawnGpu system
+    pub fn execute(ctx: Context<Components>, args: Args) -> Result<Components> {
+        match args.operation_type {
+            // Spawn a GPU from a catalog model
+            0 => {
+                let gpu_model = &ctx.accounts.gpu_model;
+                let upgrade_table = &ctx.accounts.upgrade_table;
+                let production = &mut ctx.accounts.production;
+                let upgradeable = &mut ctx.accounts.upgradeable;
+                let stakeable = &mut ctx.accounts.stakeable;
+
+                if gpu_model.model_id == 0 {
+                    return Err(SpawnGpuError::ModelNotRegistered.into());
+                }
+
+                // Only the game's catalog can spawn GPUs
+                let catalog_entity = ctx.remaining_accounts.get(5)
+                    .ok_or(SpawnGpuError::CatalogMismatch)?;
+
+                if gpu_model.authority != game_config::GAME_AUTHORITY
+                    || !ownership::is_component_of(&gpu_model.key(), &gpu_model::ID, catalog_entity.key)
+                    || !ownership::is_component_of(&upgrade_table.key(), &upgrade_table::ID, catalog_entity.key)
+                {
+                    return Err(SpawnGpuError::CatalogMismatch.into());
+                }
+
+                if !gpu_model.is_available {
+                    return Err(SpawnGpuError::ModelNotAvailable.into());
+                }
+                
+                // The upgrade curve must belong to the same model
+                if !upgrade_table.is_registered()
+                    || upgrade_table.entity_type != EntityType::GPU.to_u8()
+                    || upgrade_table.model_id != gpu_model.model_id
+                {
+                    return Err(SpawnGpuError::UpgradeTableMismatch.into());
+                }
+
+                // A GPU can only be spawned once
+                if production.model_id != 0 || production.producer_type == EntityType::GPU.to_u8() {
+                    return Err(SpawnGpuError::AlreadySpawned.into());
+                }
+
+                // Copy the production profile
+                production.model_id = gpu_model.model_id;
+                production.usdc_per_hour = gpu_model.usdc_per_hour;
+                production.aifi_per_hour = gpu_model.aifi_per_hour;
+                production.btc_per_hour = gpu_model.btc_per_hour;
+                production.eth_per_hour = gpu_model.eth_per_hour;
+                production.sol_per_hour = gpu_model.sol_per_hour;
+                production.operating_cost = gpu_model.operating_cost;
+                production.efficiency_multiplier = gpu_model.efficiency_multiplier;
+                production.producer_type = EntityType::GPU.to_u8();
+                production.level = 1;
+                production.is_active = args.is_active;
+                production.last_collection_time = args.current_time;
+
+                // Look up the first upgrade from the model's upgrade table
+                let next_level = upgrade_table.next_level(1).unwrap_or_default();
+                
+                upgradeable.current_level = 1;
+                upgradeable.max_level = upgrade_table.max_level();
+                upgradeable.last_upgrade_time = args.current_time;
+                upgradeable.can_upgrade = upgradeable.current_level < upgradeable.max_level;
+                upgradeable.upgradeable_type = EntityType::GPU.to_u8();
+                upgradeable.next_upgrade_usdc_cost = next_level.usdc_cost;
+                upgradeable.next_upgrade_aifi_cost = next_level.aifi_cost;
+                upgradeable.upgrade_cooldown = next_level.cooldown;
+                upgradeable.next_usdc_gain = next_level.usdc_gain;
+                upgradeable.next_aifi_gain = next_level.aifi_gain;
+                upgradeable.next_required_player_level = next_level.required_player_level;
+                upgradeable.next_build_duration = next_level.build_duration;
+                (upgradeable.next_success_rate, upgradeable.next_critical_failure_rate) = next_level.odds();
+
+                // Copy the staking parameters, rewards are based on the model's rates
+                stakeable.is_staked = false;
+                stakeable.staking_start_time = 0;
+                stakeable.min_staking_period = gpu_model.min_staking_period;
+                stakeable.reward_rate = gpu_model.reward_rate;
+                stakeable.unstaking_penalty = gpu_model.unstaking_penalty;
+                stakeable.accumulated_usdc_rewards = 0;
+                stakeable.accumulated_aifi_rewards = 0;
+                stakeable.last_claim_time = args.current_time;
+                stakeable.stakeable_type = EntityType::GPU.to_u8();
+                stakeable.can_claim_rewards = true;
+                stakeable.base_usdc_per_hour = gpu_model.usdc_per_hour;
+                stakeable.base_aifi_per_hour = gpu_model.aifi_per_hour;
+
+                msg!("Spawned GPU of model {}: {} USDC/hr, {} AiFi/hr, max level {}",
+                     gpu_model.model_id, production.usdc_per_hour, production.aifi_per_hour,
+                     upgradeable.max_level);
+            },
+            _ => return Err(SpawnGpuError::InvalidOperation.into()),
+        }
+
+        Ok(ctx.accounts)
+    }
+
+    /// Components required for the SpawnGpu system
+    #[system_input]
+    pub struct Components {
+        pub gpu_model: GpuModel,
+        pub upgrade_table: UpgradeTable,
+        pub production: Production,
+        pub upgradeable: Upgradeable,
+        pub stakeable: Stakeable,
+    }
+}
+
+/// Errors that can occur in the SpawnGpu system
+#[error_code]
+pub enum SpawnGpuError {
+    /// Model is not registered in the catalog
+    #[msg("GPU model is not registered")]
+    ModelNotRegistered,
+
+    /// Model can no longer be spawned
+    #[msg("GPU model is not available")]
+    ModelNotAvailable,
+
+    /// Upgrade table does not belong to the model
+    #[msg("Upgrade table does not belong to this GPU model")]
+    UpgradeTableMismatch,
+
+    /// Entity has already been spawned as a GPU
+    #[msg("Entity is already a GPU")]
+    AlreadySpawned,
+
+    /// Invalid operation type specified
+    #[msg("Invalid operation type specified")]
+    InvalidOperation,
+
+    /// Model or upgrade table is not part of the game's catalog
+    #[msg("GPU model is not part of the game's catalog")]
+    CatalogMismatch,
+}
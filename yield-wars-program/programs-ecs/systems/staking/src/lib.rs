@@ -1,12 +1,25 @@
 #![allow(unexpected_cfgs)]
 
 use bolt_lang::*;
-use stakeable::Stakeable;
+use stakeable::{CompoundMode, Stakeable};
+use upgradeable::Upgradeable;
 use wallet::Wallet;
 use production::Production;
+use ownership::{EntityType, Ownership};
+use price::Price;
+use staking_pool::StakingPool;
+use lock_tiers::{LockTier, LockTiers, MAX_LOCK_TIERS, MAX_UNBONDING_TYPES};
+use penalty_ledger::{PenaltyDestination, PenaltyLedger};
+use lottery_prize::LotteryPrize;
 
 declare_id!("35e44vDYJby25GT5gvgnAhDDG1gs2LgBgDFxudBBpq1K");
 
+// Currency type of the AiFi price (uses same values as the Wallet component)
+const CURRENCY_AIFI: u8 = 4;
+
+/// Share of the compounded rewards paid to whoever triggers the compounding (50 = 0.5%)
+pub const COMPOUND_TIP_BPS: u32 = 50;
+
 /// Staking system for handling entity staking operations
 ///
 /// This system allows entities to:
@@ -15,6 +28,70 @@ declare_id!("35e44vDYJby25GT5gvgnAhDDG1gs2LgBgDFxudBBpq1K");
 /// - Unstake entities (with potential penalties for early unstaking)
 /// - Collect accumulated staking rewards
 /// - Update staking parameters
+/// - Join, leave and harvest a shared staking pool
+/// - Configure a staking pool
+/// - Withdraw unstaked entities once they have unbonded
+/// - Route early unstaking penalties
+/// - Compound settled rewards instead of paying them out
+///
+/// GPU staking parameters come from the GPU model catalog. They cannot be initialized
+/// here and UpdateParams may only toggle reward claiming for a GPU.
+///
+/// The fourth component is the player's Ownership and the fifth the AiFi Price, which
+/// must be managed by the game authority. Stake,
+/// Unstake and CollectRewards expect the player entity and the staked entity as extra
+/// accounts after the components: the wallet must belong to the player and the staked
+/// entity must be owned by them. UpdateParams settles rewards and takes the same ones.
+///
+/// Rewards are paid in USDC from the current value of the entity's Production: its USDC
+/// output plus its AiFi output at the AiFi price, scaled by the reward rate. A reward
+/// rate of 500 pays 5% of the production value the entity forgoes while staked.
+///
+/// Every interaction with a staked entity settles the rewards earned since the last
+/// checkpoint, so rewards can be collected without unstaking. Rewards earned before the
+/// minimum staking period has passed stay locked until it does, and unstaking early
+/// forfeits the penalty share of those locked rewards only. Rewards and lock periods run
+/// on the Clock sysvar, so a lock can't be skipped by passing a later time.
+///
+/// The sixth component is a StakingPool. Entities staked in a pool do not earn their own
+/// rate: the pool emits a fixed budget per epoch and splits it between its stakers in
+/// proportion to their weight, the production value of the entity when it joined.
+/// Emissions accrue up to the Clock sysvar, so later epochs can't be pulled forward.
+/// JoinPool, LeavePool and Harvest take the same extra accounts as Stake. Only pools
+/// set up by the game authority can be joined: ConfigurePool expects the game authority
+/// as a signer in the first extra account.
+///
+/// The seventh component is the game's LockTiers table, registered by the game authority.
+/// Stake takes the chosen lock tier, whose duration, reward multiplier and early unstaking
+/// penalty are copied onto the Stakeable. While the table has no tiers, tier 0 locks for
+/// the entity's own minimum staking period and penalty. ConfigureTier edits the table
+/// with the same admin rules as ConfigurePool, reading the tier from the minimum staking
+/// period, reward rate and unstaking penalty arguments.
+///
+/// Unstake and LeavePool start the unbonding period the game's table sets for the
+/// entity's type. An unbonding entity produces nothing and earns no staking rewards until
+/// Withdraw, which takes the same extra accounts as Stake, returns it to production.
+/// Entities of a type without an unbonding period go back to production as soon as they
+/// are unstaked. Unbonding ends on the Clock sysvar, not at a time passed by the client.
+/// ConfigureUnbonding sets the period of a stakeable type with the ConfigureTier rules.
+///
+/// The eighth component is the game's PenaltyLedger, registered by the game authority,
+/// followed by the treasury Wallet and the LotteryPrize. Early unstaking penalties go to
+/// the ledger's destination: the treasury, the sixth component's StakingPool to be shared
+/// by its stakers, or the lottery prize pool. Without a destination they are burned.
+/// Every penalty is recorded in the ledger. ConfigurePenalties picks the destination, and
+/// the component passed for it, with the ConfigureTier admin rules, so only the game
+/// authority chooses who receives the penalties. A pool destination must be a game pool.
+///
+/// SetCompounding lets the owner flag an entity for auto-compounding, taking the same
+/// extra accounts as Stake. Compound can then be called by anyone for a flagged entity
+/// staked on its own: it settles the rewards and reinvests the unlocked ones, paying a
+/// small tip out of them to the second component's Wallet, the caller's. It expects the
+/// staked entity as the first extra account. Reinvested rewards either raise the reward
+/// base of the entity, AiFi counting at its USDC price, or are saved toward its next
+/// upgrade up to the cost the Upgrade system copied from the upgrade table onto the
+/// entity's Upgradeable, which then follows the staked entity. The Upgrade system spends
+/// the savings before the owner's wallet, and rewards past the cost stay to be collected.
 #[system]
 pub mod staking {
 
@@ -24,12 +101,32 @@ pub mod staking {
         Initialize = 0,
         /// Stake an entity
         Stake = 1,
-        /// Unstake an entity
+        /// Unstake an entity, settling its rewards
         Unstake = 2,
-        /// Collect staking rewards
+        /// Settle and collect staking rewards, staked or not
         CollectRewards = 3,
         /// Update staking parameters
         UpdateParams = 4,
+        /// Stake an entity in a staking pool
+        JoinPool = 5,
+        /// Unstake an entity from its staking pool, harvesting its rewards
+        LeavePool = 6,
+        /// Collect the pool rewards of a staked entity
+        Harvest = 7,
+        /// Set the reward budget of a staking pool
+        ConfigurePool = 8,
+        /// Add or replace a lock tier
+        ConfigureTier = 9,
+        /// Set the unbonding period of a stakeable type
+        ConfigureUnbonding = 10,
+        /// Return an unbonded entity to production
+        Withdraw = 11,
+        /// Choose where early unstaking penalties go
+        ConfigurePenalties = 12,
+        /// Choose what compounding does with an entity's rewards
+        SetCompounding = 13,
+        /// Reinvest the settled rewards of a flagged entity, tipping the caller
+        Compound = 14,
     }
 
     /// Arguments for the Staking system
@@ -45,25 +142,227 @@ pub mod staking {
         pub reward_rate: u32,
         /// Penalty rate for early unstaking (10000 = 100%, 500 = 5%)
         pub unstaking_penalty: u32,
-        /// Base USDC per hour used for reward calculations
+        /// Base USDC per hour of the entity (informational, rewards use Production)
         pub base_usdc_per_hour: u64,
-        /// Base AiFi per hour used for reward calculations
+        /// Base AiFi per hour of the entity (informational, rewards use Production)
         pub base_aifi_per_hour: u64,
-        /// Current time in seconds (Unix timestamp)
-        pub current_time: i64,
         /// Whether entity should be staked (for Stake operation)
         pub stake: bool,
         /// Whether rewards can be claimed
         pub can_claim_rewards: bool,
+        /// USDC a pool emits per epoch (for ConfigurePool)
+        pub pool_reward_per_epoch: u64,
+        /// Length of a pool epoch in seconds (for ConfigurePool)
+        pub pool_epoch_duration: u32,
+        /// Lock tier to stake for (for Stake) or to edit (for ConfigureTier)
+        pub lock_tier: u8,
+        /// Unbonding period in seconds of the staking type (for ConfigureUnbonding)
+        pub unbonding_period: u32,
+        /// Destination of early unstaking penalties (for ConfigurePenalties)
+        pub penalty_destination: u8,
+        /// What compounding does with the rewards (for SetCompounding, 0 = off)
+        pub compound_mode: u8,
     }
 
     /// Main execution function for the Staking system
     pub fn execute(ctx: Context<Components>, args: Args) -> Result<Components> {
+        // Rewards are only valued at the game's AiFi price
+        fn check_aifi_price(price_aifi: &Price) -> Result<()> {
+            if price_aifi.authority != game_config::GAME_AUTHORITY {
+                return Err(StakingError::PriceNotCanonical.into());
+            }
+            
+            if price_aifi.price_type != CURRENCY_AIFI {
+                return Err(StakingError::CurrencyPriceMismatch.into());
+            }
+            
+            Ok(())
+        }
+        
+        // Settle the rewards earned since the last checkpoint of a staked entity
+        fn settle_rewards(
+            stakeable: &mut Stakeable,
+            production: &Production,
+            price_aifi: &Price,
+            current_time: i64,
+        ) -> Result<()> {
+            if !stakeable.is_staked || stakeable.in_pool() {
+                return Ok(());
+            }
+            
+            if current_time < stakeable.last_claim_time || stakeable.last_claim_time < stakeable.staking_start_time {
+                return Err(StakingError::InvalidTimestamp.into());
+            }
+            
+            // Value the entity's current output with AiFi at the market price
+            check_aifi_price(price_aifi)?;
+            
+            let value_per_hour = stakeable.reward_base(yield_math::production_value_per_hour(
+                production.usdc_per_hour,
+                production.aifi_per_hour,
+                price_aifi.current_price,
+            ).ok_or(StakingError::ArithmeticOverflow)?);
+            
+            let staked_for = (current_time - stakeable.staking_start_time) as u64;
+            let settlement = yield_math::settle_staking_rewards(
+                value_per_hour,
+                &[stakeable.reward_rate, stakeable.lock_multiplier],
+                (stakeable.last_claim_time - stakeable.staking_start_time) as u64,
+                staked_for,
+                stakeable.lock_period(),
+            ).ok_or(StakingError::ArithmeticOverflow)?;
+            
+            stakeable.accumulated_usdc_rewards = stakeable.accumulated_usdc_rewards
+                .checked_add(settlement.unlocked)
+                .ok_or(StakingError::ArithmeticOverflow)?;
+            stakeable.locked_usdc_rewards = stakeable.locked_usdc_rewards
+                .checked_add(settlement.locked)
+                .ok_or(StakingError::ArithmeticOverflow)?;
+            
+            // Release the locked rewards once the lock period is over
+            if staked_for >= stakeable.lock_period() {
+                stakeable.accumulated_usdc_rewards = stakeable.accumulated_usdc_rewards
+                    .checked_add(stakeable.locked_usdc_rewards)
+                    .ok_or(StakingError::ArithmeticOverflow)?;
+                stakeable.locked_usdc_rewards = 0;
+            }
+            
+            stakeable.last_claim_time = current_time;
+            
+            msg!("Settled {} USDC ({} locked) on {} USDC/hr of production", 
+                 settlement.unlocked.saturating_add(settlement.locked), settlement.locked, value_per_hour);
+            
+            Ok(())
+        }
+        
+        // Account for the pool emissions up to now
+        fn update_pool(pool: &mut StakingPool, current_time: i64) -> Result<()> {
+            if current_time < pool.last_update_time {
+                return Err(StakingError::InvalidTimestamp.into());
+            }
+            
+            let elapsed = (current_time - pool.last_update_time) as u64;
+            if pool.total_weight > 0 && elapsed > 0 {
+                let emission = yield_math::pool_emission(pool.reward_per_epoch, pool.epoch_duration, elapsed)
+                    .ok_or(StakingError::ArithmeticOverflow)?;
+                let increase = yield_math::reward_per_share(emission, pool.total_weight)
+                    .ok_or(StakingError::ArithmeticOverflow)?;
+                
+                pool.acc_reward_per_share = pool.acc_reward_per_share.checked_add(increase)
+                    .ok_or(StakingError::ArithmeticOverflow)?;
+                pool.total_emitted = pool.total_emitted.checked_add(emission)
+                    .ok_or(StakingError::ArithmeticOverflow)?;
+            }
+            
+            // Penalties routed to the pool go to the stakers present when it has any
+            if pool.total_weight > 0 && pool.undistributed_rewards > 0 {
+                let increase = yield_math::reward_per_share(pool.undistributed_rewards, pool.total_weight)
+                    .ok_or(StakingError::ArithmeticOverflow)?;
+                
+                pool.acc_reward_per_share = pool.acc_reward_per_share.checked_add(increase)
+                    .ok_or(StakingError::ArithmeticOverflow)?;
+                pool.undistributed_rewards = 0;
+            }
+            
+            pool.last_update_time = current_time;
+            
+            Ok(())
+        }
+        
+        // Pay out the pool rewards of a staker and return the amount
+        fn harvest(stakeable: &mut Stakeable, pool: &StakingPool, wallet: &mut Wallet) -> Result<u64> {
+            let earned = yield_math::pool_reward_debt(stakeable.pool_weight, pool.acc_reward_per_share)
+                .ok_or(StakingError::ArithmeticOverflow)?;
+            let reward = earned.saturating_sub(stakeable.pool_reward_debt);
+            
+            stakeable.pool_reward_debt = earned;
+            wallet.usdc_balance = wallet.usdc_balance.checked_add(reward)
+                .ok_or(StakingError::ArithmeticOverflow)?;
+            
+            Ok(reward)
+        }
+        
+        // Unbond an unstaked entity, or return it to production if its type does not unbond
+        fn start_unbonding(
+            stakeable: &mut Stakeable,
+            production: &mut Production,
+            unbonding_period: u32,
+            current_time: i64,
+        ) -> Result<()> {
+            if unbonding_period == 0 {
+                production.is_active = true;
+                production.last_collection_time = current_time;
+                return Ok(());
+            }
+            
+            // Production stays paused until the entity is withdrawn
+            stakeable.is_unbonding = true;
+            stakeable.unbonding_end_time = current_time
+                .checked_add(unbonding_period as i64)
+                .ok_or(StakingError::ArithmeticOverflow)?;
+            
+            msg!("Entity unbonding until {}", stakeable.unbonding_end_time);
+            
+            Ok(())
+        }
+        
+        // The game's staking settings are managed by the game authority passed after the components
+        fn check_game_authority(admin: Option<&AccountInfo>) -> Result<()> {
+            if !game_config::is_game_authority(admin) {
+                return Err(StakingError::Unauthorized.into());
+            }
+            
+            Ok(())
+        }
+        
+        // Lock tiers and unbonding periods only come from the game's table
+        fn check_lock_tiers(lock_tiers: &LockTiers) -> Result<()> {
+            if lock_tiers.authority != game_config::GAME_AUTHORITY {
+                return Err(StakingError::LockTiersNotCanonical.into());
+            }
+            
+            Ok(())
+        }
+
+        // Penalties are only routed by the game's ledger
+        fn check_penalty_ledger(ledger: &PenaltyLedger) -> Result<()> {
+            if ledger.authority != game_config::GAME_AUTHORITY {
+                return Err(StakingError::LedgerNotCanonical.into());
+            }
+            
+            Ok(())
+        }
+        
+        // Staking, unstaking, collecting and updating move the owner's funds
+        if matches!(args.operation_type, 1..=7 | 11 | 13) {
+            ownership::verify_player_asset(
+                &ctx.accounts.owner_ownership,
+                ctx.remaining_accounts.get(10..).unwrap_or(&[]),
+                &[
+                    (ctx.accounts.wallet.key(), wallet::ID),
+                    (ctx.accounts.owner_ownership.key(), ownership::ID),
+                ],
+                &[
+                    (ctx.accounts.stakeable.key(), stakeable::ID),
+                    (ctx.accounts.production.key(), production::ID),
+                ],
+                ctx.accounts.stakeable.stakeable_type,
+            )?;
+        }
+        
+        // Rewards, lock periods, unbonding and pool emissions run on the cluster's clock
+        let current_time = Clock::get()?.unix_timestamp;
+        
         match args.operation_type {
             // Initialize staking properties
             0 => {
                 let stakeable = &mut ctx.accounts.stakeable;
                 
+                // GPUs are spawned from the catalog
+                if args.staking_type == EntityType::GPU.to_u8() {
+                    return Err(StakingError::CatalogManaged.into());
+                }
+                
                 // Initialize component with provided values
                 stakeable.is_staked = false;
                 stakeable.staking_start_time = 0;
@@ -72,7 +371,8 @@ pub mod staking {
                 stakeable.unstaking_penalty = args.unstaking_penalty;
                 stakeable.accumulated_usdc_rewards = 0;
                 stakeable.accumulated_aifi_rewards = 0;
-                stakeable.last_claim_time = args.current_time;
+                stakeable.locked_usdc_rewards = 0;
+                stakeable.last_claim_time = current_time;
                 stakeable.stakeable_type = args.staking_type;
                 stakeable.can_claim_rewards = args.can_claim_rewards;
                 stakeable.base_usdc_per_hour = args.base_usdc_per_hour;
@@ -91,15 +391,48 @@ pub mod staking {
                     return Err(StakingError::AlreadyStaked.into());
                 }
                 
-                // Update staking status
+                // Unbonding entities have to be withdrawn first
+                if stakeable.is_unbonding {
+                    return Err(StakingError::Unbonding.into());
+                }
+                
+                // Retired GPUs cannot be staked
+                if production.is_retired {
+                    return Err(StakingError::EntityRetired.into());
+                }
+                
+                // Lock for the chosen tier, tier 0 is the entity's own lock without any tiers
+                let lock_tiers = &ctx.accounts.lock_tiers;
+                check_lock_tiers(lock_tiers)?;
+                
+                let tier = if lock_tiers.tier_count() == 0 && args.lock_tier == 0 {
+                    LockTier {
+                        duration: stakeable.min_staking_period,
+                        reward_multiplier: yield_math::BPS_DENOMINATOR,
+                        penalty: stakeable.unstaking_penalty,
+                    }
+                } else {
+                    lock_tiers.tier(args.lock_tier).ok_or(StakingError::InvalidLockTier)?
+                };
+                
+                // Update staking status and start settling from now
                 stakeable.is_staked = true;
-                stakeable.staking_start_time = args.current_time;
+                stakeable.staking_start_time = current_time;
+                stakeable.last_claim_time = current_time;
+                stakeable.locked_usdc_rewards = 0;
+                stakeable.lock_tier = args.lock_tier;
+                stakeable.unlock_time = current_time
+                    .checked_add(tier.duration as i64)
+                    .ok_or(StakingError::ArithmeticOverflow)?;
+                stakeable.lock_multiplier = tier.reward_multiplier;
+                stakeable.lock_penalty = tier.penalty;
                 
                 // Pause production while staked
                 production.is_active = false;
-                production.last_collection_time = args.current_time;
+                production.last_collection_time = current_time;
                 
-                msg!("Entity staked at timestamp: {}", args.current_time);
+                msg!("Entity staked at timestamp: {} in lock tier {} until {}, reward multiplier {}", 
+                     current_time, args.lock_tier, stakeable.unlock_time, stakeable.lock_multiplier);
             },
             // Unstake an entity
             2 => {
@@ -111,119 +444,93 @@ pub mod staking {
                     return Err(StakingError::NotStaked.into());
                 }
                 
+                // Pool stakers leave through LeavePool
+                if stakeable.in_pool() {
+                    return Err(StakingError::StakedInPool.into());
+                }
+                
                 // Calculate staking duration
-                let staking_duration = args.current_time
+                let staking_duration = current_time
                     .checked_sub(stakeable.staking_start_time)
                     .ok_or(StakingError::ArithmeticOverflow)?;
                 
-                msg!("Unstaking after {} seconds (min period: {})", staking_duration, stakeable.min_staking_period);
+                msg!("Unstaking after {} seconds (lock period: {})", staking_duration, stakeable.lock_period());
                 
                 // Ensure staking duration is positive
                 if staking_duration <= 0 {
                     return Err(StakingError::InvalidTimestamp.into());
                 }
                 
-                // Only calculate rewards if staking duration is significant
-                // For the tests, this preserves the behavior of 0 rewards for short periods
-                if staking_duration >= 3600 { // Only if at least 1 hour
-                    // Convert seconds to hours with 3 decimal precision for more accurate rewards
-                    // Multiply by 1000 to preserve 3 decimal places
-                    let elapsed_hours_x1000 = (staking_duration as u64)
-                        .checked_mul(1000)
-                        .ok_or(StakingError::ArithmeticOverflow)?
-                        .checked_div(3600)
-                        .ok_or(StakingError::ArithmeticOverflow)?;
+                settle_rewards(stakeable, production, &ctx.accounts.price_aifi, current_time)?;
+                
+                // Leaving during the lock period forfeits part of the rewards it earned
+                let penalty = yield_math::early_unstake_penalty(
+                    stakeable.locked_usdc_rewards,
+                    stakeable.lock_penalty,
+                ).ok_or(StakingError::ArithmeticOverflow)?;
+                
+                if penalty > 0 {
+                    msg!("Applied early unstaking penalty of {} USDC on {} USDC earned during the lock period", 
+                         penalty, stakeable.locked_usdc_rewards);
                     
-                    msg!("Staking duration in hours (x1000): {}", elapsed_hours_x1000);
+                    // Send the penalty where the ledger routes it
+                    let ledger = &mut ctx.accounts.penalty_ledger;
+                    check_penalty_ledger(ledger)?;
                     
-                    if elapsed_hours_x1000 > 0 {
-                        // USDC rewards calculation with 3 decimal precision
-                        let usdc_per_hour = stakeable.base_usdc_per_hour
-                            .checked_mul(stakeable.reward_rate as u64)
-                            .ok_or(StakingError::ArithmeticOverflow)?
-                            .checked_div(10000)
-                            .ok_or(StakingError::ArithmeticOverflow)?;
-                        
-                        let usdc_reward = usdc_per_hour
-                            .checked_mul(elapsed_hours_x1000)
-                            .ok_or(StakingError::ArithmeticOverflow)?
-                            .checked_div(1000) // Adjust back from our time precision
-                            .ok_or(StakingError::ArithmeticOverflow)?;
-                        
-                        // AiFi rewards calculation with 3 decimal precision
-                        let aifi_per_hour = stakeable.base_aifi_per_hour
-                            .checked_mul(stakeable.reward_rate as u64)
-                            .ok_or(StakingError::ArithmeticOverflow)?
-                            .checked_div(10000)
-                            .ok_or(StakingError::ArithmeticOverflow)?;
-                        
-                        let aifi_reward = aifi_per_hour
-                            .checked_mul(elapsed_hours_x1000)
-                            .ok_or(StakingError::ArithmeticOverflow)?
-                            .checked_div(1000) // Adjust back from our time precision
-                            .ok_or(StakingError::ArithmeticOverflow)?;
-                        
-                        msg!("Base rewards calculated: {} USDC, {} AiFi", usdc_reward, aifi_reward);
-                        
-                        if usdc_reward > 0 || aifi_reward > 0 {
-                            // Check if unstaking is happening before minimum period
-                            let is_early_unstake = staking_duration < stakeable.min_staking_period as i64;
-                            let mut final_usdc_reward = usdc_reward;
-                            let mut final_aifi_reward = aifi_reward;
-                            
-                            if is_early_unstake && stakeable.unstaking_penalty > 0 {
-                                // Apply penalty for early unstaking
-                                let usdc_penalty = usdc_reward
-                                    .checked_mul(stakeable.unstaking_penalty as u64)
-                                    .ok_or(StakingError::ArithmeticOverflow)?
-                                    .checked_div(10000)
-                                    .ok_or(StakingError::ArithmeticOverflow)?;
-                                
-                                let aifi_penalty = aifi_reward
-                                    .checked_mul(stakeable.unstaking_penalty as u64)
-                                    .ok_or(StakingError::ArithmeticOverflow)?
-                                    .checked_div(10000)
-                                    .ok_or(StakingError::ArithmeticOverflow)?;
-                                
-                                // Apply penalty by reducing rewards
-                                final_usdc_reward = usdc_reward.checked_sub(usdc_penalty)
-                                    .ok_or(StakingError::ArithmeticOverflow)?;
-                                
-                                final_aifi_reward = aifi_reward.checked_sub(aifi_penalty)
-                                    .ok_or(StakingError::ArithmeticOverflow)?;
-                                
-                                msg!("Applied early unstaking penalty. Final rewards: {} USDC, {} AiFi", 
-                                     final_usdc_reward, final_aifi_reward);
-                            } else {
-                                msg!("No unstaking penalty applied. Full rewards: {} USDC, {} AiFi", 
-                                     final_usdc_reward, final_aifi_reward);
-                            }
-                            
-                            // Add rewards to accumulated totals
-                            stakeable.accumulated_usdc_rewards = stakeable.accumulated_usdc_rewards
-                                .checked_add(final_usdc_reward)
+                    let route = ledger.destination;
+                    let destination = PenaltyDestination::from_u8(route)
+                        .ok_or(StakingError::InvalidPenaltyDestination)?;
+                    let receiver = match destination {
+                        PenaltyDestination::Burn => Pubkey::default(),
+                        PenaltyDestination::Treasury => ctx.accounts.treasury.key(),
+                        PenaltyDestination::StakingPool => ctx.accounts.pool.key(),
+                        PenaltyDestination::Lottery => ctx.accounts.lottery_prize.key(),
+                    };
+                    
+                    if receiver != ledger.destination_account {
+                        return Err(StakingError::PenaltyDestinationMismatch.into());
+                    }
+                    
+                    match destination {
+                        PenaltyDestination::Burn => {},
+                        PenaltyDestination::Treasury => {
+                            let treasury = &mut ctx.accounts.treasury;
+                            treasury.usdc_balance = treasury.usdc_balance.checked_add(penalty)
+                                .ok_or(StakingError::ArithmeticOverflow)?;
+                        },
+                        PenaltyDestination::StakingPool => {
+                            let pool = &mut ctx.accounts.pool;
+                            pool.undistributed_rewards = pool.undistributed_rewards.checked_add(penalty)
                                 .ok_or(StakingError::ArithmeticOverflow)?;
-                            
-                            stakeable.accumulated_aifi_rewards = stakeable.accumulated_aifi_rewards
-                                .checked_add(final_aifi_reward)
+                            update_pool(pool, current_time)?;
+                        },
+                        PenaltyDestination::Lottery => {
+                            let lottery_prize = &mut ctx.accounts.lottery_prize;
+                            lottery_prize.prize_pool = lottery_prize.prize_pool.checked_add(penalty)
                                 .ok_or(StakingError::ArithmeticOverflow)?;
-                        }
+                        },
                     }
-                } else {
-                    msg!("Staking duration too short for rewards");
+                    
+                    ledger.record(stakeable.key(), route, penalty, current_time)?;
+                    
+                    msg!("Penalty of {} USDC sent to destination {}", penalty, route);
                 }
                 
+                stakeable.accumulated_usdc_rewards = stakeable.accumulated_usdc_rewards
+                    .checked_add(stakeable.locked_usdc_rewards - penalty)
+                    .ok_or(StakingError::ArithmeticOverflow)?;
+                stakeable.locked_usdc_rewards = 0;
+                
                 // Reset staking status
                 stakeable.is_staked = false;
                 stakeable.staking_start_time = 0;
-                stakeable.last_claim_time = args.current_time;
                 
-                // Reactivate production
-                production.is_active = true;
-                production.last_collection_time = args.current_time;
+                check_lock_tiers(&ctx.accounts.lock_tiers)?;
+                let unbonding_period = ctx.accounts.lock_tiers.unbonding_period(stakeable.stakeable_type);
+                start_unbonding(stakeable, production, unbonding_period, current_time)?;
                 
                 msg!("Entity unstaked at timestamp: {}, accumulated rewards: {} USDC, {} AiFi", 
-                     args.current_time, stakeable.accumulated_usdc_rewards, stakeable.accumulated_aifi_rewards);
+                     current_time, stakeable.accumulated_usdc_rewards, stakeable.accumulated_aifi_rewards);
             },
             // Collect staking rewards
             3 => {
@@ -235,6 +542,9 @@ pub mod staking {
                     return Err(StakingError::CannotClaimRewards.into());
                 }
                 
+                // Staked entities keep earning, settle up to now first
+                settle_rewards(stakeable, &ctx.accounts.production, &ctx.accounts.price_aifi, current_time)?;
+                
                 // Check if there are rewards to collect
                 if stakeable.accumulated_usdc_rewards == 0 && stakeable.accumulated_aifi_rewards == 0 {
                     return Err(StakingError::NoRewardsAvailable.into());
@@ -256,15 +566,30 @@ pub mod staking {
                 stakeable.accumulated_usdc_rewards = 0;
                 stakeable.accumulated_aifi_rewards = 0;
                 
-                // Update last claim time
-                stakeable.last_claim_time = args.current_time;
+                if !stakeable.is_staked {
+                    stakeable.last_claim_time = current_time;
+                }
                 
-                msg!("Rewards collected successfully at timestamp: {}", args.current_time);
+                msg!("Rewards collected successfully at timestamp: {}", current_time);
             },
             // Update staking parameters
             4 => {
                 let stakeable = &mut ctx.accounts.stakeable;
                 
+                // Rewards earned so far keep the old parameters
+                settle_rewards(stakeable, &ctx.accounts.production, &ctx.accounts.price_aifi, current_time)?;
+                
+                // GPU economics can only change through the catalog
+                if stakeable.stakeable_type == EntityType::GPU.to_u8()
+                    && (args.min_staking_period != stakeable.min_staking_period
+                        || args.reward_rate != stakeable.reward_rate
+                        || args.unstaking_penalty != stakeable.unstaking_penalty
+                        || args.base_usdc_per_hour != stakeable.base_usdc_per_hour
+                        || args.base_aifi_per_hour != stakeable.base_aifi_per_hour)
+                {
+                    return Err(StakingError::CatalogManaged.into());
+                }
+                
                 // Update parameters as requested
                 stakeable.min_staking_period = args.min_staking_period;
                 stakeable.reward_rate = args.reward_rate;
@@ -277,6 +602,370 @@ pub mod staking {
                      args.min_staking_period, args.reward_rate/100, args.unstaking_penalty/100, 
                      args.base_usdc_per_hour, args.base_aifi_per_hour);
             },
+            // Stake an entity in a staking pool
+            5 => {
+                let stakeable = &mut ctx.accounts.stakeable;
+                let production = &mut ctx.accounts.production;
+                let pool = &mut ctx.accounts.pool;
+                
+                if !pool.is_configured() {
+                    return Err(StakingError::PoolNotConfigured.into());
+                }
+                
+                if pool.authority != game_config::GAME_AUTHORITY {
+                    return Err(StakingError::PoolNotCanonical.into());
+                }
+                
+                if stakeable.is_staked {
+                    return Err(StakingError::AlreadyStaked.into());
+                }
+                
+                if stakeable.is_unbonding {
+                    return Err(StakingError::Unbonding.into());
+                }
+                
+                if production.is_retired {
+                    return Err(StakingError::EntityRetired.into());
+                }
+                
+                // The entity weighs as much as the production it gives up
+                let price_aifi = &ctx.accounts.price_aifi;
+                check_aifi_price(price_aifi)?;
+                
+                let weight = yield_math::production_value_per_hour(
+                    production.usdc_per_hour,
+                    production.aifi_per_hour,
+                    price_aifi.current_price,
+                ).ok_or(StakingError::ArithmeticOverflow)?;
+                
+                if weight == 0 {
+                    return Err(StakingError::NoStakeWeight.into());
+                }
+                
+                update_pool(pool, current_time)?;
+                
+                pool.total_weight = pool.total_weight.checked_add(weight)
+                    .ok_or(StakingError::ArithmeticOverflow)?;
+                pool.staker_count = pool.staker_count.checked_add(1)
+                    .ok_or(StakingError::ArithmeticOverflow)?;
+                
+                stakeable.pool = pool.key();
+                stakeable.pool_weight = weight;
+                stakeable.pool_reward_debt = yield_math::pool_reward_debt(weight, pool.acc_reward_per_share)
+                    .ok_or(StakingError::ArithmeticOverflow)?;
+                stakeable.is_staked = true;
+                stakeable.staking_start_time = current_time;
+                stakeable.last_claim_time = current_time;
+                
+                // Pause production while staked
+                production.is_active = false;
+                production.last_collection_time = current_time;
+                
+                msg!("Entity joined the staking pool with weight {} ({} stakers, total weight {})", 
+                     weight, pool.staker_count, pool.total_weight);
+            },
+            // Unstake an entity from its staking pool, harvesting its rewards
+            6 => {
+                let stakeable = &mut ctx.accounts.stakeable;
+                let pool = &mut ctx.accounts.pool;
+                
+                if !stakeable.is_staked || stakeable.pool != pool.key() {
+                    return Err(StakingError::NotInPool.into());
+                }
+                
+                update_pool(pool, current_time)?;
+                let reward = harvest(stakeable, pool, &mut ctx.accounts.wallet)?;
+                
+                pool.total_weight = pool.total_weight.saturating_sub(stakeable.pool_weight);
+                pool.staker_count = pool.staker_count.saturating_sub(1);
+                
+                stakeable.pool = Pubkey::default();
+                stakeable.pool_weight = 0;
+                stakeable.pool_reward_debt = 0;
+                stakeable.is_staked = false;
+                stakeable.staking_start_time = 0;
+                stakeable.last_claim_time = current_time;
+                
+                check_lock_tiers(&ctx.accounts.lock_tiers)?;
+                let unbonding_period = ctx.accounts.lock_tiers.unbonding_period(stakeable.stakeable_type);
+                start_unbonding(stakeable, &mut ctx.accounts.production, unbonding_period, current_time)?;
+                
+                msg!("Entity left the staking pool with {} USDC of rewards", reward);
+            },
+            // Collect the pool rewards of a staked entity
+            7 => {
+                let stakeable = &mut ctx.accounts.stakeable;
+                let pool = &mut ctx.accounts.pool;
+                
+                if !stakeable.is_staked || stakeable.pool != pool.key() {
+                    return Err(StakingError::NotInPool.into());
+                }
+                
+                update_pool(pool, current_time)?;
+                let reward = harvest(stakeable, pool, &mut ctx.accounts.wallet)?;
+                
+                if reward == 0 {
+                    return Err(StakingError::NoRewardsAvailable.into());
+                }
+                
+                msg!("Harvested {} USDC from the staking pool", reward);
+            },
+            // Set the reward budget of a staking pool
+            8 => {
+                if args.pool_epoch_duration == 0 {
+                    return Err(StakingError::InvalidPoolParameters.into());
+                }
+                
+                check_game_authority(ctx.remaining_accounts.get(10))?;
+                
+                let pool = &mut ctx.accounts.pool;
+                if pool.is_configured() {
+                    if pool.authority != game_config::GAME_AUTHORITY {
+                        return Err(StakingError::PoolNotCanonical.into());
+                    }
+                    
+                    // Emissions so far keep the old budget
+                    update_pool(pool, current_time)?;
+                } else {
+                    pool.authority = game_config::GAME_AUTHORITY;
+                    pool.last_update_time = current_time;
+                }
+                
+                pool.reward_per_epoch = args.pool_reward_per_epoch;
+                pool.epoch_duration = args.pool_epoch_duration;
+                
+                msg!("Staking pool emits {} USDC every {} seconds", 
+                     pool.reward_per_epoch, pool.epoch_duration);
+            },
+            // Add or replace a lock tier
+            9 => {
+                check_game_authority(ctx.remaining_accounts.get(10))?;
+                
+                let lock_tiers = &mut ctx.accounts.lock_tiers;
+                if !lock_tiers.is_registered() {
+                    lock_tiers.authority = game_config::GAME_AUTHORITY;
+                }
+                check_lock_tiers(lock_tiers)?;
+                
+                let index = args.lock_tier as usize;
+                let tier_count = lock_tiers.tier_count();
+                
+                if index > tier_count || index >= MAX_LOCK_TIERS {
+                    return Err(StakingError::InvalidLockTier.into());
+                }
+                
+                if args.reward_rate == 0 || args.unstaking_penalty > yield_math::BPS_DENOMINATOR {
+                    return Err(StakingError::InvalidTierParameters.into());
+                }
+                
+                if index == tier_count {
+                    lock_tiers.durations.push(args.min_staking_period);
+                    lock_tiers.reward_multipliers.push(args.reward_rate);
+                    lock_tiers.penalties.push(args.unstaking_penalty);
+                } else {
+                    lock_tiers.durations[index] = args.min_staking_period;
+                    lock_tiers.reward_multipliers[index] = args.reward_rate;
+                    lock_tiers.penalties[index] = args.unstaking_penalty;
+                }
+                
+                msg!("Lock tier {}: {} seconds, reward multiplier {}, penalty {}", 
+                     index, args.min_staking_period, args.reward_rate, args.unstaking_penalty);
+            },
+            // Set the unbonding period of a stakeable type
+            10 => {
+                check_game_authority(ctx.remaining_accounts.get(10))?;
+                
+                let lock_tiers = &mut ctx.accounts.lock_tiers;
+                if !lock_tiers.is_registered() {
+                    lock_tiers.authority = game_config::GAME_AUTHORITY;
+                }
+                check_lock_tiers(lock_tiers)?;
+                
+                let index = args.staking_type as usize;
+                if index >= MAX_UNBONDING_TYPES {
+                    return Err(StakingError::InvalidStakeableType.into());
+                }
+                
+                // Types below the new entry keep unbonding instantly
+                if index >= lock_tiers.unbonding_periods.len() {
+                    lock_tiers.unbonding_periods.resize(index + 1, 0);
+                }
+                lock_tiers.unbonding_periods[index] = args.unbonding_period;
+                
+                msg!("Stakeable type {} unbonds for {} seconds", index, args.unbonding_period);
+            },
+            // Return an unbonded entity to production
+            11 => {
+                let stakeable = &mut ctx.accounts.stakeable;
+                let production = &mut ctx.accounts.production;
+                
+                if !stakeable.is_unbonding {
+                    return Err(StakingError::NotUnbonding.into());
+                }
+                
+                if current_time < stakeable.unbonding_end_time {
+                    msg!("Entity is unbonding until {}", stakeable.unbonding_end_time);
+                    return Err(StakingError::UnbondingNotFinished.into());
+                }
+                
+                stakeable.is_unbonding = false;
+                stakeable.unbonding_end_time = 0;
+                
+                // Reactivate production
+                production.is_active = true;
+                production.last_collection_time = current_time;
+                
+                msg!("Entity withdrawn at timestamp: {}", current_time);
+            },
+            // Choose where early unstaking penalties go
+            12 => {
+                let destination = PenaltyDestination::from_u8(args.penalty_destination)
+                    .ok_or(StakingError::InvalidPenaltyDestination)?;
+                
+                check_game_authority(ctx.remaining_accounts.get(10))?;
+                
+                let ledger = &mut ctx.accounts.penalty_ledger;
+                if !ledger.is_registered() {
+                    ledger.authority = game_config::GAME_AUTHORITY;
+                }
+                check_penalty_ledger(ledger)?;
+                
+                // Penalties shared by stakers only go to a game pool
+                if matches!(destination, PenaltyDestination::StakingPool)
+                    && ctx.accounts.pool.authority != game_config::GAME_AUTHORITY
+                {
+                    return Err(StakingError::PoolNotCanonical.into());
+                }
+                
+                // The component passed for the destination receives the penalties
+                ledger.destination = args.penalty_destination;
+                ledger.destination_account = match destination {
+                    PenaltyDestination::Burn => Pubkey::default(),
+                    PenaltyDestination::Treasury => ctx.accounts.treasury.key(),
+                    PenaltyDestination::StakingPool => ctx.accounts.pool.key(),
+                    PenaltyDestination::Lottery => ctx.accounts.lottery_prize.key(),
+                };
+                
+                msg!("Early unstaking penalties go to destination {} ({})", 
+                     ledger.destination, ledger.destination_account);
+            },
+            // Choose what compounding does with an entity's rewards
+            13 => {
+                if CompoundMode::from_u8(args.compound_mode).is_none() {
+                    return Err(StakingError::InvalidCompoundMode.into());
+                }
+                
+                let stakeable = &mut ctx.accounts.stakeable;
+                stakeable.compound_mode = args.compound_mode;
+                
+                msg!("Compound mode set to {}", stakeable.compound_mode);
+            },
+            // Reinvest the settled rewards of a flagged entity, tipping the caller
+            14 => {
+                // Anyone can compound, so the components must belong to the same staked entity
+                let staked_entity = ctx.remaining_accounts.get(10)
+                    .ok_or(StakingError::EntityMismatch)?;
+                
+                if !ownership::is_component_of(&ctx.accounts.stakeable.key(), &stakeable::ID, staked_entity.key)
+                    || !ownership::is_component_of(&ctx.accounts.production.key(), &production::ID, staked_entity.key)
+                {
+                    return Err(StakingError::EntityMismatch.into());
+                }
+                
+                let stakeable = &mut ctx.accounts.stakeable;
+                let mode = CompoundMode::from_u8(stakeable.compound_mode)
+                    .ok_or(StakingError::InvalidCompoundMode)?;
+                
+                if matches!(mode, CompoundMode::Off) {
+                    return Err(StakingError::CompoundingDisabled.into());
+                }
+                
+                if !stakeable.is_staked {
+                    return Err(StakingError::NotStaked.into());
+                }
+                
+                // Pool rewards are harvested straight to the wallet
+                if stakeable.in_pool() {
+                    return Err(StakingError::StakedInPool.into());
+                }
+                
+                if !stakeable.can_claim_rewards {
+                    return Err(StakingError::CannotClaimRewards.into());
+                }
+                
+                settle_rewards(stakeable, &ctx.accounts.production, &ctx.accounts.price_aifi, current_time)?;
+                
+                // Savings stop at the cost of the next upgrade
+                let (usdc_room, aifi_room) = if matches!(mode, CompoundMode::Upgrade) {
+                    let upgradeable_account = ctx.remaining_accounts.get(11)
+                        .ok_or(StakingError::UpgradeableMismatch)?;
+                    
+                    if *upgradeable_account.owner != upgradeable::ID
+                        || !ownership::is_component_of(upgradeable_account.key, &upgradeable::ID, staked_entity.key)
+                    {
+                        return Err(StakingError::UpgradeableMismatch.into());
+                    }
+                    
+                    let upgradeable = Upgradeable::try_deserialize(&mut &upgradeable_account.try_borrow_data()?[..])?;
+                    if !upgradeable.can_upgrade {
+                        return Err(StakingError::NothingToCompound.into());
+                    }
+                    
+                    (
+                        upgradeable.next_upgrade_usdc_cost.saturating_sub(stakeable.upgrade_savings_usdc),
+                        upgradeable.next_upgrade_aifi_cost.saturating_sub(stakeable.upgrade_savings_aifi),
+                    )
+                } else {
+                    (u64::MAX, u64::MAX)
+                };
+                
+                let usdc = stakeable.accumulated_usdc_rewards.min(usdc_room);
+                let aifi = stakeable.accumulated_aifi_rewards.min(aifi_room);
+                
+                if usdc == 0 && aifi == 0 {
+                    return Err(StakingError::NothingToCompound.into());
+                }
+                
+                stakeable.accumulated_usdc_rewards -= usdc;
+                stakeable.accumulated_aifi_rewards -= aifi;
+                
+                // The caller's tip comes out of the compounded rewards
+                let usdc_tip = yield_math::mul_bps(usdc, COMPOUND_TIP_BPS)
+                    .ok_or(StakingError::ArithmeticOverflow)?;
+                let aifi_tip = yield_math::mul_bps(aifi, COMPOUND_TIP_BPS)
+                    .ok_or(StakingError::ArithmeticOverflow)?;
+                let usdc = usdc - usdc_tip;
+                let aifi = aifi - aifi_tip;
+                
+                if matches!(mode, CompoundMode::RewardBase) {
+                    let aifi_value = yield_math::usdc_value(aifi, ctx.accounts.price_aifi.current_price)
+                        .ok_or(StakingError::ArithmeticOverflow)?;
+                    let value = usdc.checked_add(aifi_value)
+                        .ok_or(StakingError::ArithmeticOverflow)?;
+                    
+                    stakeable.compounded_rewards = stakeable.compounded_rewards.checked_add(value)
+                        .ok_or(StakingError::ArithmeticOverflow)?;
+                    
+                    msg!("Compounded {} USDC of rewards into the reward base ({} USDC in total)", 
+                         value, stakeable.compounded_rewards);
+                } else {
+                    stakeable.upgrade_savings_usdc = stakeable.upgrade_savings_usdc.checked_add(usdc)
+                        .ok_or(StakingError::ArithmeticOverflow)?;
+                    stakeable.upgrade_savings_aifi = stakeable.upgrade_savings_aifi.checked_add(aifi)
+                        .ok_or(StakingError::ArithmeticOverflow)?;
+                    
+                    msg!("Saved {} USDC and {} AiFi toward the next upgrade ({} USDC and {} AiFi saved)", 
+                         usdc, aifi, stakeable.upgrade_savings_usdc, stakeable.upgrade_savings_aifi);
+                }
+                
+                let wallet = &mut ctx.accounts.wallet;
+                wallet.usdc_balance = wallet.usdc_balance.checked_add(usdc_tip)
+                    .ok_or(StakingError::ArithmeticOverflow)?;
+                wallet.aifi_balance = wallet.aifi_balance.checked_add(aifi_tip)
+                    .ok_or(StakingError::ArithmeticOverflow)?;
+                
+                msg!("Paid a compounding tip of {} USDC and {} AiFi", usdc_tip, aifi_tip);
+            },
             _ => return Err(StakingError::InvalidOperation.into()),
         }
         
@@ -289,6 +978,13 @@ pub mod staking {
         pub stakeable: Stakeable,
         pub wallet: Wallet,
         pub production: Production,
+        pub owner_ownership: Ownership,
+        pub price_aifi: Price,
+        pub pool: StakingPool,
+        pub lock_tiers: LockTiers,
+        pub penalty_ledger: PenaltyLedger,
+        pub treasury: Wallet,
+        pub lottery_prize: LotteryPrize,
     }
 }
 
@@ -326,4 +1022,108 @@ pub enum StakingError {
     /// Invalid timestamp provided
     #[msg("Invalid timestamp provided")]
     InvalidTimestamp,
+
+    /// GPU stats are managed by the GPU model catalog
+    #[msg("GPU stats are managed by the GPU model catalog")]
+    CatalogManaged,
+
+    /// Entity was retired and can no longer be staked
+    #[msg("Entity has been retired")]
+    EntityRetired,
+
+    /// Price component is not the AiFi price
+    #[msg("Price component does not match the currency")]
+    CurrencyPriceMismatch,
+
+    /// Entity is staked in a pool and must leave it instead
+    #[msg("Entity is staked in a pool")]
+    StakedInPool,
+
+    /// Entity is not staked in this pool
+    #[msg("Entity is not staked in this pool")]
+    NotInPool,
+
+    /// Pool has not been set up
+    #[msg("Staking pool is not set up")]
+    PoolNotConfigured,
+
+    /// Invalid pool parameters
+    #[msg("Invalid staking pool parameters")]
+    InvalidPoolParameters,
+
+    /// Entity has no production to weigh its stake
+    #[msg("Entity has no stake weight")]
+    NoStakeWeight,
+
+    /// Signer is not the pool or lock tier admin
+    #[msg("Only the admin can configure staking")]
+    Unauthorized,
+
+    /// Lock tier does not exist
+    #[msg("Lock tier does not exist")]
+    InvalidLockTier,
+
+    /// Invalid lock tier parameters
+    #[msg("Invalid lock tier parameters")]
+    InvalidTierParameters,
+
+    /// Entity is unbonding and has to be withdrawn first
+    #[msg("Entity is unbonding")]
+    Unbonding,
+
+    /// Entity is not unbonding
+    #[msg("Entity is not unbonding")]
+    NotUnbonding,
+
+    /// Unbonding period has not elapsed
+    #[msg("Unbonding period has not elapsed")]
+    UnbondingNotFinished,
+
+    /// Stakeable type cannot have an unbonding period
+    #[msg("Invalid stakeable type")]
+    InvalidStakeableType,
+
+    /// Penalty destination is not a PenaltyDestination
+    #[msg("Invalid penalty destination")]
+    InvalidPenaltyDestination,
+
+    /// Component receiving the penalty is not the one the ledger routes to
+    #[msg("Penalty destination does not match the ledger")]
+    PenaltyDestinationMismatch,
+
+    /// Compound mode is not a CompoundMode
+    #[msg("Invalid compound mode")]
+    InvalidCompoundMode,
+
+    /// Entity is not flagged for compounding
+    #[msg("Compounding is not enabled for this entity")]
+    CompoundingDisabled,
+
+    /// No settled rewards can be compounded
+    #[msg("Nothing to compound")]
+    NothingToCompound,
+
+    /// Stakeable or Production does not belong to the staked entity
+    #[msg("Components do not belong to the staked entity")]
+    EntityMismatch,
+
+    /// Upgradeable is not the staked entity's
+    #[msg("Upgradeable does not belong to the staked entity")]
+    UpgradeableMismatch,
+
+    /// AiFi Price is not managed by the game authority
+    #[msg("Price is not one of the game's prices")]
+    PriceNotCanonical,
+
+    /// StakingPool was not set up by the game authority
+    #[msg("Staking pool is not one of the game's pools")]
+    PoolNotCanonical,
+
+    /// LockTiers was not registered by the game authority
+    #[msg("Lock tiers are not the game's table")]
+    LockTiersNotCanonical,
+
+    /// PenaltyLedger was not registered by the game authority
+    #[msg("Penalty ledger is not the game's ledger")]
+    LedgerNotCanonical,
 }
\ No newline at end of file
@@ -0,0 +1,254 @@
+#![allow(unexpected_cfgs)]
+
+use bolt_lang::*;
+use upgrade_table::{UpgradeTable, MAX_UPGRADE_LEVELS};
+
+declare_id!("5msgDbrGi8RJJjz4rW1QvqzdsbvQp2neV9AiobohPY2z");
+
+/// UpgradeCurve system for managing the upgrade tables of entity models
+///
+/// This system allows the table admin to:
+/// - Register the upgrade table of an entity model
+/// - Add or replace the upgrade to a given level
+/// - Remove the highest level of the table
+/// - Configure construction yield, speed-up price and cancel refund
+/// - Configure from which level upgrades are risky and the price of protection
+///
+/// The admin is the game authority, which must sign the transaction and is passed
+/// as the first extra account after the components.
+#[system]
+pub mod upgrade_curve {
+
+    /// Operation types supported by the UpgradeCurve system
+    pub enum OperationType {
+        /// Register the upgrade table of an entity model
+        RegisterTable = 0,
+        /// Add or replace the upgrade to a given level
+        SetLevel = 1,
+        /// Remove the highest level of the table
+        RemoveLastLevel = 2,
+        /// Configure construction yield, speed-up price and cancel refund
+        SetConstruction = 3,
+        /// Configure from which level upgrades are risky and the price of protection
+        SetRisk = 4,
+    }
+
+    /// Arguments for the UpgradeCurve system
+    #[arguments]
+    pub struct Args {
+        /// Type of operation to perform
+        pub operation_type: u8,
+        /// Entity type the table applies to (for RegisterTable)
+        pub entity_type: u8,
+        /// GPU model the table applies to (for RegisterTable, 0 = not from the catalog)
+        pub model_id: u8,
+        /// Level reached by the upgrade (for SetLevel, starts at 2)
+        pub level: u8,
+        /// USDC cost of the upgrade
+        pub usdc_cost: u64,
+        /// AiFi cost of the upgrade
+        pub aifi_cost: u64,
+        /// USDC per hour gained
+        pub usdc_gain: u64,
+        /// AiFi per hour gained
+        pub aifi_gain: u64,
+        /// BTC per hour gained
+        pub btc_gain: u64,
+        /// ETH per hour gained
+        pub eth_gain: u64,
+        /// SOL per hour gained
+        pub sol_gain: u64,
+        /// Cooldown in seconds since the previous upgrade
+        pub cooldown: u32,
+        /// Player level required (0 = no requirement)
+        pub required_player_level: u8,
+        /// Construction time in seconds (0 = instant)
+        pub build_duration: u32,
+        /// Yield while under construction (for SetConstruction, 10000 = unaffected, 0 = paused)
+        pub construction_yield: u32,
+        /// AiFi cost of skipping one hour of construction (for SetConstruction)
+        pub speed_up_aifi_per_hour: u64,
+        /// Share of the cost refunded on cancel (for SetConstruction, 10000 = 100%)
+        pub cancel_refund_rate: u32,
+        /// Chance that the upgrade succeeds once risky (10000 = certain)
+        pub success_rate: u32,
+        /// Chance of a critical failure that drops a level once risky (10000 = certain)
+        pub critical_failure_rate: u32,
+        /// First level whose upgrade is a gamble (for SetRisk, 0 = all upgrades succeed)
+        pub risk_start_level: u8,
+        /// AiFi cost of one protection item (for SetRisk, 0 = not for sale)
+        pub protection_aifi_cost: u64,
+    }
+
+    /// Main execution function for the UpgradeCurve system
+    pub fn execute(ctx: Context<Components>, args: Args) -> Result<Components> {
+        // The admin is passed after the single upgrade_table component
+        let admin = ctx.remaining_accounts.get(1);
+
+        if !game_config::is_game_authority(admin) {
+            return Err(UpgradeCurveError::Unauthorized.into());
+        }
+
+        let upgrade_table = &mut ctx.accounts.upgrade_table;
+
+        // Only tables registered by the game authority may be changed
+        if args.operation_type != 0 {
+            if !upgrade_table.is_registered() {
+                return Err(UpgradeCurveError::TableNotRegistered.into());
+            }
+
+            if upgrade_table.authority != game_config::GAME_AUTHORITY {
+                return Err(UpgradeCurveError::Unauthorized.into());
+            }
+        }
+
+        match args.operation_type {
+            // Register the upgrade table of an entity model
+            0 => {
+                if upgrade_table.is_registered() {
+                    return Err(UpgradeCurveError::TableAlreadyRegistered.into());
+                }
+
+                upgrade_table.authority = game_config::GAME_AUTHORITY;
+                upgrade_table.entity_type = args.entity_type;
+                upgrade_table.model_id = args.model_id;
+
+                msg!("Registered upgrade table for entity type {}, model {}",
+                     args.entity_type, args.model_id);
+            },
+            // Add or replace the upgrade to a given level
+            1 => {
+                // Entry 0 describes the upgrade to level 2
+                let index = (args.level as usize).checked_sub(2)
+                    .ok_or(UpgradeCurveError::InvalidLevel)?;
+                let level_count = upgrade_table.level_count();
+
+                if index > level_count || index >= MAX_UPGRADE_LEVELS {
+                    return Err(UpgradeCurveError::InvalidLevel.into());
+                }
+
+                if args.success_rate > 10000
+                    || args.critical_failure_rate > 10000 - args.success_rate
+                {
+                    return Err(UpgradeCurveError::InvalidParameters.into());
+                }
+
+                if index == level_count {
+                    upgrade_table.usdc_costs.push(args.usdc_cost);
+                    upgrade_table.aifi_costs.push(args.aifi_cost);
+                    upgrade_table.usdc_gains.push(args.usdc_gain);
+                    upgrade_table.aifi_gains.push(args.aifi_gain);
+                    upgrade_table.btc_gains.push(args.btc_gain);
+                    upgrade_table.eth_gains.push(args.eth_gain);
+                    upgrade_table.sol_gains.push(args.sol_gain);
+                    upgrade_table.cooldowns.push(args.cooldown);
+                    upgrade_table.required_player_levels.push(args.required_player_level);
+                    upgrade_table.build_durations.push(args.build_duration);
+                    upgrade_table.success_rates.push(args.success_rate);
+                    upgrade_table.critical_failure_rates.push(args.critical_failure_rate);
+                } else {
+                    upgrade_table.usdc_costs[index] = args.usdc_cost;
+                    upgrade_table.aifi_costs[index] = args.aifi_cost;
+                    upgrade_table.usdc_gains[index] = args.usdc_gain;
+                    upgrade_table.aifi_gains[index] = args.aifi_gain;
+                    upgrade_table.btc_gains[index] = args.btc_gain;
+                    upgrade_table.eth_gains[index] = args.eth_gain;
+                    upgrade_table.sol_gains[index] = args.sol_gain;
+                    upgrade_table.cooldowns[index] = args.cooldown;
+                    upgrade_table.required_player_levels[index] = args.required_player_level;
+                    upgrade_table.build_durations[index] = args.build_duration;
+                    upgrade_table.success_rates[index] = args.success_rate;
+                    upgrade_table.critical_failure_rates[index] = args.critical_failure_rate;
+                }
+
+                msg!("Level {}: {} USDC, {} AiFi, +{} USDC/hr, +{} AiFi/hr, cooldown {}s, player level {}, build {}s",
+                     args.level, args.usdc_cost, args.aifi_cost, args.usdc_gain, args.aifi_gain,
+                     args.cooldown, args.required_player_level, args.build_duration);
+            },
+            // Remove the highest level of the table
+            2 => {
+                if upgrade_table.level_count() == 0 {
+                    return Err(UpgradeCurveError::InvalidLevel.into());
+                }
+
+                upgrade_table.usdc_costs.pop();
+                upgrade_table.aifi_costs.pop();
+                upgrade_table.usdc_gains.pop();
+                upgrade_table.aifi_gains.pop();
+                upgrade_table.btc_gains.pop();
+                upgrade_table.eth_gains.pop();
+                upgrade_table.sol_gains.pop();
+                upgrade_table.cooldowns.pop();
+                upgrade_table.required_player_levels.pop();
+                upgrade_table.build_durations.pop();
+                upgrade_table.success_rates.pop();
+                upgrade_table.critical_failure_rates.pop();
+
+                msg!("Upgrade table max level is now {}", upgrade_table.max_level());
+            },
+            // Configure construction yield, speed-up price and cancel refund
+            3 => {
+                if args.construction_yield > 10000 || args.cancel_refund_rate > 10000 {
+                    return Err(UpgradeCurveError::InvalidParameters.into());
+                }
+
+                upgrade_table.construction_yield = args.construction_yield;
+                upgrade_table.speed_up_aifi_per_hour = args.speed_up_aifi_per_hour;
+                upgrade_table.cancel_refund_rate = args.cancel_refund_rate;
+
+                msg!("Construction yield {}, speed-up {} AiFi/hr, cancel refund {}",
+                     args.construction_yield, args.speed_up_aifi_per_hour, args.cancel_refund_rate);
+            },
+            // Configure from which level upgrades are risky and the price of protection
+            4 => {
+                // Level 1 is never reached through an upgrade
+                if args.risk_start_level == 1 {
+                    return Err(UpgradeCurveError::InvalidLevel.into());
+                }
+
+                upgrade_table.risk_start_level = args.risk_start_level;
+                upgrade_table.protection_aifi_cost = args.protection_aifi_cost;
+
+                msg!("Upgrades to level {} and above are risky, protection costs {} AiFi",
+                     args.risk_start_level, args.protection_aifi_cost);
+            },
+            _ => return Err(UpgradeCurveError::InvalidOperation.into()),
+        }
+
+        Ok(ctx.accounts)
+    }
+
+    /// Components required for the UpgradeCurve system
+    #[system_input]
+    pub struct Components {
+        pub upgrade_table: UpgradeTable,
+    }
+}
+
+/// Errors that can occur in the UpgradeCurve system
+#[error_code]
+pub enum UpgradeCurveError {
+    /// Signer is not the table admin
+    #[msg("Only the table admin can manage this upgrade table")]
+    Unauthorized,
+
+    /// Table has already been registered
+    #[msg("Upgrade table is already registered")]
+    TableAlreadyRegistered,
+
+    /// Table has not been registered
+    #[msg("Upgrade table is not registered")]
+    TableNotRegistered,
+
+    /// Level is out of range or would leave a gap in the table
+    #[msg("Invalid upgrade level")]
+    InvalidLevel,
+
+    /// Invalid construction or risk parameters
+    #[msg("Invalid upgrade table parameters")]
+    InvalidParameters,
+
+    /// Invalid operation type specified
+    #[msg("Invalid operation type specified")]
+    InvalidOperation,
+}
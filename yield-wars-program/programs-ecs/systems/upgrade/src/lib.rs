@@ -2,18 +2,58 @@
 
 use bolt_lang::*;
 use upgradeable::Upgradeable;
+use upgrade_table::{UpgradeLevel, UpgradeTable};
 use wallet::Wallet;
 use production::Production;
+use stakeable::Stakeable;
+use ownership::{EntityType, Ownership};
+use yield_math::UpgradeOutcome;
 
 declare_id!("E2v1mJyyZJif5podWB85KwBzNbUtE2fgqCLnTXmH9Lqq");
 
+/// Attempt outcome recorded when a protection item prevented a level drop
+pub const OUTCOME_PROTECTED: u8 = 3;
+
+/// Slots after which an undelivered upgrade roll no longer blocks cancelling the upgrade
+pub const UPGRADE_ROLL_EXPIRY_SLOTS: u64 = 150;
+
 /// Upgrade system for handling entity upgrades
 ///
 /// This system allows entities to:
 /// - Initialize upgrade properties
 /// - Perform upgrades
 /// - Apply upgrade benefits to production
-/// - Update upgrade costs for the next level
+/// - Look up the next upgrade from the upgrade table of the entity model
+/// - Build upgrades that take time, speed them up with AiFi or cancel them
+///
+/// Levels with a build duration are started with StartUpgrade, which charges the cost
+/// and slows production to the construction yield of the table until FinishUpgrade
+/// applies the gains. Cancelling refunds part of the cost. Cooldowns and construction
+/// timers are checked against the Clock sysvar, so clients can't finish them early.
+///
+/// Upgrades past the risk start level of the table are gambles and must be started with
+/// StartUpgrade, which requests `vrf_client` randomness committed to the Upgradeable
+/// while the payment is taken. FinishUpgrade rolls the upgrade with that randomness once
+/// the VRF callback delivered it, so the outcome can't be re-rolled. A failure loses the
+/// payment and a critical failure also drops a level, unless a protection item is
+/// consumed. A risky upgrade can only be cancelled while its roll is undelivered past
+/// the expiry. The randomness request accounts, or the committed randomness PDA, follow
+/// the extra accounts described below.
+///
+/// The fourth component is the player's Ownership and the fifth is the upgrade table,
+/// which belongs to the model entity. The model entity is the first extra account after
+/// the components and its table must have been registered by the game authority.
+/// Except for Initialize, the player entity and the upgraded entity follow: the wallet
+/// must belong to the player and the upgraded entity must be owned by them. Upgrades
+/// that require a player level expect the player's Upgradeable component next.
+///
+/// The sixth component is the Stakeable of the upgraded entity. Staking rewards saved
+/// toward the next upgrade by compounding are spent before the owner's wallet. The
+/// savings are only used when the Stakeable belongs to the upgraded entity, so entities
+/// that cannot be staked may pass any other Stakeable, which is left untouched.
+///
+/// GPU upgrade properties are set when the GPU is spawned from the catalog and cannot
+/// be initialized here.
 #[system]
 pub mod upgrade {
 
@@ -23,8 +63,18 @@ pub mod upgrade {
         Initialize = 0,
         /// Perform an upgrade
         Upgrade = 1,
-        /// Update upgrade parameters
-        UpdateParams = 2,
+        /// Refresh the next upgrade after the upgrade table changed
+        SyncTable = 2,
+        /// Pay for the next upgrade and start building it
+        StartUpgrade = 3,
+        /// Apply an upgrade whose construction is complete
+        FinishUpgrade = 4,
+        /// Pay AiFi to shorten the construction
+        SpeedUp = 5,
+        /// Cancel the construction for a partial refund
+        CancelUpgrade = 6,
+        /// Buy protection items against level drops with AiFi
+        BuyProtection = 7,
     }
 
     /// Arguments for the Upgrade system
@@ -36,153 +86,714 @@ pub mod upgrade {
         pub entity_type: u8,
         /// Current level (for Initialize)
         pub current_level: u8,
-        /// Maximum level (for Initialize)
-        pub max_level: u8,
-        /// Cooldown between upgrades in seconds
-        pub upgrade_cooldown: u32,
-        /// USDC cost for the next upgrade
-        pub next_upgrade_usdc_cost: u64,
-        /// AiFi cost for the next upgrade
-        pub next_upgrade_aifi_cost: u64,
-        /// Production boost percentage for USDC (10000 = 100%)
-        pub next_usdc_boost: u32,
-        /// Production boost percentage for AiFi (10000 = 100%)
-        pub next_aifi_boost: u32,
-        /// Current time in seconds (Unix timestamp)
-        pub current_time: i64,
+        /// Seconds of construction to skip (for SpeedUp, 0 = all remaining)
+        pub speed_up_seconds: u32,
+        /// Number of protection items to buy (for BuyProtection)
+        pub quantity: u16,
     }
 
     /// Main execution function for the Upgrade system
     pub fn execute(ctx: Context<Components>, args: Args) -> Result<Components> {
+        // The table must be the game's table for the model of the entity being upgraded
+        fn check_table(
+            upgrade_table: &Account<UpgradeTable>,
+            model_entity: Option<&AccountInfo>,
+            entity_type: u8,
+            model_id: u8,
+        ) -> Result<()> {
+            if !upgrade_table.is_registered() {
+                return Err(UpgradeError::TableNotRegistered.into());
+            }
+            
+            let model_entity = model_entity.ok_or(UpgradeError::TableNotCanonical)?;
+            
+            if upgrade_table.authority != game_config::GAME_AUTHORITY
+                || !ownership::is_component_of(&upgrade_table.key(), &upgrade_table::ID, model_entity.key)
+            {
+                return Err(UpgradeError::TableNotCanonical.into());
+            }
+            
+            if upgrade_table.entity_type != entity_type || upgrade_table.model_id != model_id {
+                return Err(UpgradeError::TableMismatch.into());
+            }
+            
+            Ok(())
+        }
+        
+        // Copy the upgrade following the current level from the table
+        fn load_next_level(upgradeable: &mut Upgradeable, upgrade_table: &UpgradeTable) {
+            let next_level = upgrade_table.next_level(upgradeable.current_level).unwrap_or_default();
+            
+            upgradeable.max_level = upgrade_table.max_level();
+            upgradeable.can_upgrade = upgradeable.current_level < upgradeable.max_level;
+            upgradeable.next_upgrade_usdc_cost = next_level.usdc_cost;
+            upgradeable.next_upgrade_aifi_cost = next_level.aifi_cost;
+            upgradeable.upgrade_cooldown = next_level.cooldown;
+            upgradeable.next_usdc_gain = next_level.usdc_gain;
+            upgradeable.next_aifi_gain = next_level.aifi_gain;
+            upgradeable.next_required_player_level = next_level.required_player_level;
+            upgradeable.next_build_duration = next_level.build_duration;
+            (upgradeable.next_success_rate, upgradeable.next_critical_failure_rate) = next_level.odds();
+        }
+        
+        // Add an absolute gain to a per-hour rate
+        fn add_gain(rate: u64, gain: u64) -> Result<u64> {
+            rate.checked_add(gain).ok_or_else(|| UpgradeError::ArithmeticOverflow.into())
+        }
+        
+        // Check that the next upgrade can be paid for and return it
+        fn check_next_level(
+            upgradeable: &Upgradeable,
+            wallet: &Wallet,
+            upgrade_table: &UpgradeTable,
+            savings: (u64, u64),
+            player_entity: Option<&AccountInfo>,
+            player_account: Option<&AccountInfo>,
+            current_time: i64,
+        ) -> Result<UpgradeLevel> {
+            // Validate upgrade is possible
+            if !upgradeable.can_upgrade {
+                return Err(UpgradeError::CannotUpgrade.into());
+            }
+            
+            if upgradeable.upgrade_in_progress {
+                return Err(UpgradeError::UpgradeInProgress.into());
+            }
+            
+            // Check if already at max level
+            let next_level = upgrade_table.next_level(upgradeable.current_level)
+                .ok_or(UpgradeError::AlreadyMaxLevel)?;
+            
+            // Check if cooldown period has elapsed
+            let time_since_last_upgrade = current_time.checked_sub(upgradeable.last_upgrade_time)
+                .ok_or(UpgradeError::ArithmeticOverflow)?;
+            
+            if time_since_last_upgrade < next_level.cooldown as i64 {
+                return Err(UpgradeError::UpgradeCooldown.into());
+            }
+            
+            // Check the player level against the player's Upgradeable component
+            if next_level.required_player_level > 0 {
+                let player_account = player_account.ok_or(UpgradeError::PlayerLevelTooLow)?;
+                
+                let player_entity = player_entity.ok_or(UpgradeError::PlayerLevelTooLow)?;
+                
+                if *player_account.owner != upgradeable::ID
+                    || !ownership::is_component_of(player_account.key, &upgradeable::ID, player_entity.key)
+                {
+                    return Err(UpgradeError::PlayerLevelTooLow.into());
+                }
+                
+                let player = Upgradeable::try_deserialize(&mut &player_account.try_borrow_data()?[..])?;
+                
+                if player.upgradeable_type != EntityType::Player.to_u8()
+                    || player.current_level < next_level.required_player_level
+                {
+                    return Err(UpgradeError::PlayerLevelTooLow.into());
+                }
+            }
+            
+            // Check if wallet and savings have enough funds
+            let (usdc_saved, aifi_saved) = savings;
+            if wallet.usdc_balance.saturating_add(usdc_saved) < next_level.usdc_cost {
+                return Err(UpgradeError::InsufficientUsdcFunds.into());
+            }
+            
+            if wallet.aifi_balance.saturating_add(aifi_saved) < next_level.aifi_cost {
+                return Err(UpgradeError::InsufficientAifiFunds.into());
+            }
+            
+            Ok(next_level)
+        }
+        
+        // Savings of the upgraded entity, if the Stakeable is its own
+        fn savings(stakeable: &Stakeable, use_savings: bool) -> (u64, u64) {
+            if use_savings {
+                (stakeable.upgrade_savings_usdc, stakeable.upgrade_savings_aifi)
+            } else {
+                (0, 0)
+            }
+        }
+        
+        // Deduct the cost of an upgrade, spending the entity's savings before the wallet,
+        // and return the USDC and AiFi taken from the savings
+        fn charge(wallet: &mut Wallet, stakeable: &mut Stakeable, use_savings: bool, level: &UpgradeLevel) -> Result<(u64, u64)> {
+            let (usdc_saved, aifi_saved) = savings(stakeable, use_savings);
+            let usdc_from_savings = usdc_saved.min(level.usdc_cost);
+            let aifi_from_savings = aifi_saved.min(level.aifi_cost);
+            
+            if use_savings {
+                stakeable.upgrade_savings_usdc -= usdc_from_savings;
+                stakeable.upgrade_savings_aifi -= aifi_from_savings;
+            }
+            
+            wallet.usdc_balance = wallet.usdc_balance
+                .checked_sub(level.usdc_cost - usdc_from_savings)
+                .ok_or(UpgradeError::ArithmeticOverflow)?;
+            
+            wallet.aifi_balance = wallet.aifi_balance
+                .checked_sub(level.aifi_cost - aifi_from_savings)
+                .ok_or(UpgradeError::ArithmeticOverflow)?;
+            
+            if usdc_from_savings > 0 || aifi_from_savings > 0 {
+                msg!("Spent {} USDC and {} AiFi of compounded savings", usdc_from_savings, aifi_from_savings);
+            }
+            
+            Ok((usdc_from_savings, aifi_from_savings))
+        }
+        
+        // Apply the gains of the next level and move on to the following one
+        fn apply_level(
+            upgradeable: &mut Upgradeable,
+            production: &mut Production,
+            upgrade_table: &UpgradeTable,
+            level: &UpgradeLevel,
+            current_time: i64,
+        ) -> Result<()> {
+            // Apply the absolute production gains of the level
+            production.usdc_per_hour = add_gain(production.usdc_per_hour, level.usdc_gain)?;
+            production.aifi_per_hour = add_gain(production.aifi_per_hour, level.aifi_gain)?;
+            production.btc_per_hour = add_gain(production.btc_per_hour, level.btc_gain)?;
+            production.eth_per_hour = add_gain(production.eth_per_hour, level.eth_gain)?;
+            production.sol_per_hour = add_gain(production.sol_per_hour, level.sol_gain)?;
+            
+            // Increment level and update upgrade time
+            upgradeable.current_level = upgradeable.current_level.checked_add(1)
+                .ok_or(UpgradeError::ArithmeticOverflow)?;
+            
+            upgradeable.last_upgrade_time = current_time;
+            
+            // Also update the production component's level for consistency
+            production.level = upgradeable.current_level;
+            
+            // Look up the costs and gains of the following level
+            load_next_level(upgradeable, upgrade_table);
+            
+            Ok(())
+        }
+        
+        // Remove the gains of the current level and go back to the previous one
+        fn drop_level(
+            upgradeable: &mut Upgradeable,
+            production: &mut Production,
+            upgrade_table: &UpgradeTable,
+        ) -> Result<()> {
+            if upgradeable.current_level <= 1 {
+                return Ok(());
+            }
+            
+            let previous_level = upgradeable.current_level - 1;
+            let lost = upgrade_table.next_level(previous_level).unwrap_or_default();
+            
+            production.usdc_per_hour = production.usdc_per_hour.saturating_sub(lost.usdc_gain);
+            production.aifi_per_hour = production.aifi_per_hour.saturating_sub(lost.aifi_gain);
+            production.btc_per_hour = production.btc_per_hour.saturating_sub(lost.btc_gain);
+            production.eth_per_hour = production.eth_per_hour.saturating_sub(lost.eth_gain);
+            production.sol_per_hour = production.sol_per_hour.saturating_sub(lost.sol_gain);
+            
+            upgradeable.current_level = previous_level;
+            production.level = previous_level;
+            load_next_level(upgradeable, upgrade_table);
+            
+            Ok(())
+        }
+        
+        // Keep the latest risky attempts, dropping the oldest one when full
+        fn record_attempt(upgradeable: &mut Upgradeable, level: u8, outcome: u8, roll: u32, success_rate: u32, time: i64) {
+            if upgradeable.attempt_levels.len() >= upgradeable::MAX_ATTEMPT_HISTORY {
+                upgradeable.attempt_levels.remove(0);
+                upgradeable.attempt_outcomes.remove(0);
+                upgradeable.attempt_rolls.remove(0);
+                upgradeable.attempt_success_rates.remove(0);
+                upgradeable.attempt_times.remove(0);
+            }
+            
+            upgradeable.attempt_levels.push(level);
+            upgradeable.attempt_outcomes.push(outcome);
+            upgradeable.attempt_rolls.push(roll);
+            upgradeable.attempt_success_rates.push(success_rate);
+            upgradeable.attempt_times.push(time);
+        }
+        
+        // Commit the roll of a risky upgrade while it is paid for
+        fn request_roll<'info>(
+            upgradeable: &mut Upgradeable,
+            subject: &AccountInfo<'info>,
+            request_accounts: &[AccountInfo<'info>],
+        ) -> Result<()> {
+            let requester = request_accounts.first()
+                .ok_or(UpgradeError::UpgradeRollMissing)?;
+            
+            upgradeable.upgrade_request_id = upgradeable.upgrade_request_id.checked_add(1)
+                .ok_or(UpgradeError::ArithmeticOverflow)?;
+            upgradeable.upgrade_requester = *requester.key;
+            upgradeable.upgrade_roll_pending = true;
+            
+            // Requests differ by Upgradeable, requester and number, so no client seed is needed
+            vrf_randomness::request_committed_randomness(
+                subject,
+                request_accounts,
+                upgradeable.upgrade_request_id,
+                0,
+            )
+        }
+        
+        // Load the roll committed when the upgrade under construction was paid for, if any
+        fn committed_roll(
+            upgradeable: &Upgradeable,
+            subject: &Pubkey,
+            randomness_account: Option<&AccountInfo>,
+        ) -> Result<Option<vrf_randomness::CommittedRandomness>> {
+            if !upgradeable.upgrade_roll_pending {
+                return Ok(None);
+            }
+            
+            let committed = vrf_randomness::load_committed_randomness(
+                randomness_account.ok_or(UpgradeError::UpgradeRollMissing)?,
+                subject,
+                &upgradeable.upgrade_requester,
+                upgradeable.upgrade_request_id,
+            )?;
+            
+            Ok(Some(committed))
+        }
+        
+        // Apply a paid upgrade, rolling for it first when it is risky
+        fn resolve_level(
+            upgradeable: &mut Upgradeable,
+            production: &mut Production,
+            upgrade_table: &UpgradeTable,
+            level: &UpgradeLevel,
+            committed: Option<vrf_randomness::CommittedRandomness>,
+            current_time: i64,
+        ) -> Result<()> {
+            if !level.is_risky {
+                return apply_level(upgradeable, production, upgrade_table, level, current_time);
+            }
+            
+            // The roll was requested when the upgrade was paid for
+            let committed = committed.ok_or(UpgradeError::UpgradeRollMissing)?;
+            
+            if !committed.is_fulfilled {
+                return Err(UpgradeError::UpgradeRollNotDelivered.into());
+            }
+            
+            let roll = committed.random_u64();
+            let roll_bps = (roll % yield_math::BPS_DENOMINATOR as u64) as u32;
+            let target_level = upgradeable.current_level.checked_add(1)
+                .ok_or(UpgradeError::ArithmeticOverflow)?;
+            
+            let rolled = yield_math::roll_upgrade(roll, level.success_rate, level.critical_failure_rate);
+            let mut outcome = rolled as u8;
+            
+            match rolled {
+                UpgradeOutcome::Success => {
+                    apply_level(upgradeable, production, upgrade_table, level, current_time)?;
+                },
+                UpgradeOutcome::Failure => {
+                    upgradeable.last_upgrade_time = current_time;
+                },
+                UpgradeOutcome::CriticalFailure => {
+                    upgradeable.last_upgrade_time = current_time;
+                    
+                    if upgradeable.protection_items > 0 {
+                        upgradeable.protection_items -= 1;
+                        outcome = OUTCOME_PROTECTED;
+                    } else {
+                        drop_level(upgradeable, production, upgrade_table)?;
+                    }
+                },
+            }
+            
+            record_attempt(upgradeable, target_level, outcome, roll_bps, level.success_rate, current_time);
+            
+            msg!("Upgrade attempt to level {} rolled {} against {}: outcome {}, now level {}",
+                 target_level, roll_bps, level.success_rate, outcome, upgradeable.current_level);
+            
+            Ok(())
+        }
+        
+        // Randomness accounts follow the entities and, when the level requires one, the player's Upgradeable
+        fn randomness_accounts<'a, 'info>(
+            remaining_accounts: &'a [AccountInfo<'info>],
+            level: &UpgradeLevel,
+        ) -> &'a [AccountInfo<'info>] {
+            let start = if level.required_player_level > 0 { 10 } else { 9 };
+            remaining_accounts.get(start..).unwrap_or(&[])
+        }
+        
+        // Clear the construction state once it is finished or cancelled
+        fn clear_construction(upgradeable: &mut Upgradeable) {
+            upgradeable.upgrade_in_progress = false;
+            upgradeable.upgrade_started_at = 0;
+            upgradeable.upgrade_ready_at = 0;
+            upgradeable.upgrade_paid_usdc = 0;
+            upgradeable.upgrade_paid_aifi = 0;
+            upgradeable.upgrade_paid_savings_usdc = 0;
+            upgradeable.upgrade_paid_savings_aifi = 0;
+            upgradeable.upgrade_roll_pending = false;
+        }
+        
+        // Everything but setting up the upgrade properties acts for the owner
+        if args.operation_type != 0 {
+            ownership::verify_player_asset(
+                &ctx.accounts.owner_ownership,
+                ctx.remaining_accounts.get(7..).unwrap_or(&[]),
+                &[
+                    (ctx.accounts.wallet.key(), wallet::ID),
+                    (ctx.accounts.owner_ownership.key(), ownership::ID),
+                ],
+                &[
+                    (ctx.accounts.upgradeable.key(), upgradeable::ID),
+                    (ctx.accounts.production.key(), production::ID),
+                ],
+                ctx.accounts.upgradeable.upgradeable_type,
+            )?;
+        }
+        
+        // Savings belong to the upgraded entity, which follows the player entity
+        let use_savings = ctx.remaining_accounts.get(8).is_some_and(|entity| {
+            ownership::is_component_of(&ctx.accounts.stakeable.key(), &stakeable::ID, entity.key)
+        });
+        
+        // Cooldowns and construction timers run on the cluster's clock
+        let current_time = Clock::get()?.unix_timestamp;
+        
         match args.operation_type {
             // Initialize upgrade properties
             0 => {
                 let upgradeable = &mut ctx.accounts.upgradeable;
+                let upgrade_table = &ctx.accounts.upgrade_table;
+                
+                // GPUs are spawned from the catalog
+                if args.entity_type == EntityType::GPU.to_u8() {
+                    return Err(UpgradeError::CatalogManaged.into());
+                }
+                
+                if args.current_level == 0 {
+                    return Err(UpgradeError::InvalidLevel.into());
+                }
+                
+                // Entities outside the catalog use the table of their type with model 0
+                check_table(upgrade_table, ctx.remaining_accounts.get(6), args.entity_type, 0)?;
                 
                 // Initialize component with provided values
                 upgradeable.current_level = args.current_level;
-                upgradeable.max_level = args.max_level;
-                upgradeable.last_upgrade_time = args.current_time;
-                upgradeable.can_upgrade = args.current_level < args.max_level;
+                upgradeable.last_upgrade_time = current_time;
                 upgradeable.upgradeable_type = args.entity_type;
-                upgradeable.next_upgrade_usdc_cost = args.next_upgrade_usdc_cost;
-                upgradeable.next_upgrade_aifi_cost = args.next_upgrade_aifi_cost;
-                upgradeable.upgrade_cooldown = args.upgrade_cooldown;
-                upgradeable.next_usdc_boost = args.next_usdc_boost;
-                upgradeable.next_aifi_boost = args.next_aifi_boost;
+                load_next_level(upgradeable, upgrade_table);
             },
             // Perform an upgrade
             1 => {
                 let upgradeable = &mut ctx.accounts.upgradeable;
                 let wallet = &mut ctx.accounts.wallet;
                 let production = &mut ctx.accounts.production;
-                let current_time = args.current_time;
+                let upgrade_table = &ctx.accounts.upgrade_table;
+                
+                check_table(upgrade_table, ctx.remaining_accounts.get(6), upgradeable.upgradeable_type, production.model_id)?;
                 
-                // Validate upgrade is possible
-                if !upgradeable.can_upgrade {
-                    return Err(UpgradeError::CannotUpgrade.into());
+                if production.is_retired {
+                    return Err(UpgradeError::EntityRetired.into());
                 }
                 
-                // Check if already at max level
-                if upgradeable.current_level >= upgradeable.max_level {
-                    return Err(UpgradeError::AlreadyMaxLevel.into());
+                let next_level = check_next_level(
+                    upgradeable,
+                    wallet,
+                    upgrade_table,
+                    savings(&ctx.accounts.stakeable, use_savings),
+                    ctx.remaining_accounts.get(7),
+                    ctx.remaining_accounts.get(9),
+                    current_time,
+                )?;
+                
+                // Levels that take time to build go through StartUpgrade
+                if next_level.build_duration > 0 {
+                    return Err(UpgradeError::RequiresConstruction.into());
                 }
                 
-                // Check if cooldown period has elapsed
-                let time_since_last_upgrade = current_time.checked_sub(upgradeable.last_upgrade_time)
-                    .ok_or(UpgradeError::ArithmeticOverflow)?;
+                // Gambles are rolled with randomness committed by StartUpgrade
+                if next_level.is_risky {
+                    return Err(UpgradeError::RequiresCommittedRoll.into());
+                }
+                
+                charge(wallet, &mut ctx.accounts.stakeable, use_savings, &next_level)?;
+                apply_level(upgradeable, production, upgrade_table, &next_level, current_time)?;
+                
+                msg!("Level {}/{}: {} USDC/hr, {} AiFi/hr",
+                     upgradeable.current_level, upgradeable.max_level,
+                     production.usdc_per_hour, production.aifi_per_hour);
+            },
+            // Refresh the next upgrade after the upgrade table changed
+            2 => {
+                let upgradeable = &mut ctx.accounts.upgradeable;
+                let production = &ctx.accounts.production;
+                let upgrade_table = &ctx.accounts.upgrade_table;
+                
+                check_table(upgrade_table, ctx.remaining_accounts.get(6), upgradeable.upgradeable_type, production.model_id)?;
+                load_next_level(upgradeable, upgrade_table);
+            },
+            // Pay for the next upgrade and start building it
+            3 => {
+                let subject = ctx.accounts.upgradeable.to_account_info();
+                let upgradeable = &mut ctx.accounts.upgradeable;
+                let wallet = &mut ctx.accounts.wallet;
+                let production = &mut ctx.accounts.production;
+                let upgrade_table = &ctx.accounts.upgrade_table;
+                
+                check_table(upgrade_table, ctx.remaining_accounts.get(6), upgradeable.upgradeable_type, production.model_id)?;
                 
-                if time_since_last_upgrade < upgradeable.upgrade_cooldown as i64 {
-                    return Err(UpgradeError::UpgradeCooldown.into());
+                if production.is_retired {
+                    return Err(UpgradeError::EntityRetired.into());
                 }
                 
-                // Check if wallet has enough funds
-                if wallet.usdc_balance < upgradeable.next_upgrade_usdc_cost {
-                    return Err(UpgradeError::InsufficientUsdcFunds.into());
+                let next_level = check_next_level(
+                    upgradeable,
+                    wallet,
+                    upgrade_table,
+                    savings(&ctx.accounts.stakeable, use_savings),
+                    ctx.remaining_accounts.get(7),
+                    ctx.remaining_accounts.get(9),
+                    current_time,
+                )?;
+                
+                let (usdc_from_savings, aifi_from_savings) =
+                    charge(wallet, &mut ctx.accounts.stakeable, use_savings, &next_level)?;
+                
+                // Safe levels without a build duration take effect right away
+                if next_level.build_duration == 0 && !next_level.is_risky {
+                    apply_level(upgradeable, production, upgrade_table, &next_level, current_time)?;
+                    
+                    msg!("Level {}/{}", upgradeable.current_level, upgradeable.max_level);
+                    return Ok(ctx.accounts);
                 }
                 
-                if wallet.aifi_balance < upgradeable.next_upgrade_aifi_cost {
-                    return Err(UpgradeError::InsufficientAifiFunds.into());
+                // Gambles are rolled on finish with randomness requested now
+                if next_level.is_risky {
+                    request_roll(upgradeable, &subject, randomness_accounts(ctx.remaining_accounts, &next_level))?;
                 }
                 
-                // Deduct upgrade costs
-                wallet.usdc_balance = wallet.usdc_balance
-                    .checked_sub(upgradeable.next_upgrade_usdc_cost)
+                let ready_at = current_time.checked_add(next_level.build_duration as i64)
                     .ok_or(UpgradeError::ArithmeticOverflow)?;
                 
-                wallet.aifi_balance = wallet.aifi_balance
-                    .checked_sub(upgradeable.next_upgrade_aifi_cost)
-                    .ok_or(UpgradeError::ArithmeticOverflow)?;
+                upgradeable.upgrade_in_progress = true;
+                upgradeable.upgrade_started_at = current_time;
+                upgradeable.upgrade_ready_at = ready_at;
+                upgradeable.upgrade_paid_usdc = next_level.usdc_cost;
+                upgradeable.upgrade_paid_aifi = next_level.aifi_cost;
+                upgradeable.upgrade_paid_savings_usdc = usdc_from_savings;
+                upgradeable.upgrade_paid_savings_aifi = aifi_from_savings;
                 
-                // Apply production boosts
-                // Apply USDC boost
-                let current_usdc_per_hour = production.usdc_per_hour;
-                let usdc_boost = current_usdc_per_hour
-                    .checked_mul(upgradeable.next_usdc_boost as u64)
-                    .ok_or(UpgradeError::ArithmeticOverflow)?
-                    .checked_div(10000)
-                    .ok_or(UpgradeError::ArithmeticOverflow)?;
+                // Production runs at the construction yield until the upgrade is ready
+                if next_level.build_duration > 0 {
+                    production.construction_start = current_time;
+                    production.construction_end = ready_at;
+                    production.construction_yield = upgrade_table.construction_yield;
+                }
                 
-                production.usdc_per_hour = current_usdc_per_hour
-                    .checked_add(usdc_boost)
-                    .ok_or(UpgradeError::ArithmeticOverflow)?;
+                msg!("Started upgrade to level {}, ready at {}",
+                     upgradeable.current_level + 1, ready_at);
+            },
+            // Apply an upgrade whose construction is complete
+            4 => {
+                let subject = ctx.accounts.upgradeable.key();
+                let upgradeable = &mut ctx.accounts.upgradeable;
+                let production = &mut ctx.accounts.production;
+                let upgrade_table = &ctx.accounts.upgrade_table;
+                
+                if !upgradeable.upgrade_in_progress {
+                    return Err(UpgradeError::NoUpgradeInProgress.into());
+                }
                 
-                // Apply AiFi boost
-                let current_aifi_per_hour = production.aifi_per_hour;
-                let aifi_boost = current_aifi_per_hour
-                    .checked_mul(upgradeable.next_aifi_boost as u64)
-                    .ok_or(UpgradeError::ArithmeticOverflow)?
-                    .checked_div(10000)
+                if current_time < upgradeable.upgrade_ready_at {
+                    return Err(UpgradeError::UpgradeNotReady.into());
+                }
+                
+                check_table(upgrade_table, ctx.remaining_accounts.get(6), upgradeable.upgradeable_type, production.model_id)?;
+                
+                let next_level = upgrade_table.next_level(upgradeable.current_level)
+                    .ok_or(UpgradeError::AlreadyMaxLevel)?;
+                let committed = committed_roll(
+                    upgradeable,
+                    &subject,
+                    randomness_accounts(ctx.remaining_accounts, &next_level).first(),
+                )?;
+                
+                clear_construction(upgradeable);
+                resolve_level(
+                    upgradeable,
+                    production,
+                    upgrade_table,
+                    &next_level,
+                    committed,
+                    current_time,
+                )?;
+                
+                msg!("Finished construction, level {}/{}: {} USDC/hr, {} AiFi/hr",
+                     upgradeable.current_level, upgradeable.max_level,
+                     production.usdc_per_hour, production.aifi_per_hour);
+            },
+            // Pay AiFi to shorten the construction
+            5 => {
+                let subject = ctx.accounts.upgradeable.key();
+                let upgradeable = &mut ctx.accounts.upgradeable;
+                let wallet = &mut ctx.accounts.wallet;
+                let production = &mut ctx.accounts.production;
+                let upgrade_table = &ctx.accounts.upgrade_table;
+                
+                if !upgradeable.upgrade_in_progress {
+                    return Err(UpgradeError::NoUpgradeInProgress.into());
+                }
+                
+                check_table(upgrade_table, ctx.remaining_accounts.get(6), upgradeable.upgradeable_type, production.model_id)?;
+                
+                if upgrade_table.speed_up_aifi_per_hour == 0 {
+                    return Err(UpgradeError::SpeedUpDisabled.into());
+                }
+                
+                let remaining = upgradeable.upgrade_ready_at.saturating_sub(current_time);
+                
+                if remaining <= 0 {
+                    return Err(UpgradeError::ConstructionComplete.into());
+                }
+                
+                let skipped = if args.speed_up_seconds == 0 {
+                    remaining
+                } else {
+                    remaining.min(args.speed_up_seconds as i64)
+                };
+                
+                let cost = yield_math::speed_up_cost(skipped as u64, upgrade_table.speed_up_aifi_per_hour)
                     .ok_or(UpgradeError::ArithmeticOverflow)?;
                 
-                production.aifi_per_hour = current_aifi_per_hour
-                    .checked_add(aifi_boost)
+                if wallet.aifi_balance < cost {
+                    return Err(UpgradeError::InsufficientAifiFunds.into());
+                }
+                
+                wallet.aifi_balance = wallet.aifi_balance.checked_sub(cost)
                     .ok_or(UpgradeError::ArithmeticOverflow)?;
                 
-                // Increment level and update upgrade time
-                upgradeable.current_level = upgradeable.current_level.checked_add(1)
+                upgradeable.upgrade_ready_at = upgradeable.upgrade_ready_at.checked_sub(skipped)
                     .ok_or(UpgradeError::ArithmeticOverflow)?;
+                production.construction_end = upgradeable.upgrade_ready_at;
                 
-                upgradeable.last_upgrade_time = current_time;
+                msg!("Skipped {}s of construction for {} AiFi", skipped, cost);
+                
+                // Finish right away once nothing is left to build
+                if upgradeable.upgrade_ready_at <= current_time {
+                    let next_level = upgrade_table.next_level(upgradeable.current_level)
+                        .ok_or(UpgradeError::AlreadyMaxLevel)?;
+                    let committed = committed_roll(
+                        upgradeable,
+                        &subject,
+                        randomness_accounts(ctx.remaining_accounts, &next_level).first(),
+                    )?;
+                    
+                    clear_construction(upgradeable);
+                    resolve_level(
+                        upgradeable,
+                        production,
+                        upgrade_table,
+                        &next_level,
+                        committed,
+                        current_time,
+                    )?;
+                    
+                    msg!("Finished construction, level {}/{}", upgradeable.current_level, upgradeable.max_level);
+                }
+            },
+            // Cancel the construction for a partial refund
+            6 => {
+                let subject = ctx.accounts.upgradeable.key();
+                let upgradeable = &mut ctx.accounts.upgradeable;
+                let wallet = &mut ctx.accounts.wallet;
+                let production = &mut ctx.accounts.production;
+                let upgrade_table = &ctx.accounts.upgrade_table;
+                
+                if !upgradeable.upgrade_in_progress {
+                    return Err(UpgradeError::NoUpgradeInProgress.into());
+                }
+                
+                check_table(upgrade_table, ctx.remaining_accounts.get(6), upgradeable.upgradeable_type, production.model_id)?;
+                
+                // A gamble can't be cancelled once its roll is known or while it can still arrive
+                let next_level = upgrade_table.next_level(upgradeable.current_level).unwrap_or_default();
+                let committed = committed_roll(
+                    upgradeable,
+                    &subject,
+                    randomness_accounts(ctx.remaining_accounts, &next_level).first(),
+                )?;
                 
-                // Update can_upgrade flag
-                upgradeable.can_upgrade = upgradeable.current_level < upgradeable.max_level;
+                if let Some(committed) = committed {
+                    if committed.is_fulfilled
+                        || Clock::get()?.slot < committed.requested_slot.saturating_add(UPGRADE_ROLL_EXPIRY_SLOTS)
+                    {
+                        return Err(UpgradeError::UpgradeRollPending.into());
+                    }
+                }
                 
-                // Also update the production component's level for consistency
-                production.level = upgradeable.current_level;
+                let usdc_refund = yield_math::mul_bps(upgradeable.upgrade_paid_usdc, upgrade_table.cancel_refund_rate)
+                    .ok_or(UpgradeError::ArithmeticOverflow)?;
+                let aifi_refund = yield_math::mul_bps(upgradeable.upgrade_paid_aifi, upgrade_table.cancel_refund_rate)
+                    .ok_or(UpgradeError::ArithmeticOverflow)?;
+                
+                // The part paid from savings goes back to the savings of the upgraded entity
+                let usdc_to_savings = yield_math::mul_bps(upgradeable.upgrade_paid_savings_usdc, upgrade_table.cancel_refund_rate)
+                    .ok_or(UpgradeError::ArithmeticOverflow)?;
+                let aifi_to_savings = yield_math::mul_bps(upgradeable.upgrade_paid_savings_aifi, upgrade_table.cancel_refund_rate)
+                    .ok_or(UpgradeError::ArithmeticOverflow)?;
                 
-                // Increase costs for the next level by a percentage (if not at max)
-                if upgradeable.can_upgrade {
-                    // Increase costs by 50% for each level
-                    let cost_multiplier = 15000; // 150%
+                if usdc_to_savings > 0 || aifi_to_savings > 0 {
+                    if !use_savings {
+                        return Err(UpgradeError::SavingsMismatch.into());
+                    }
                     
-                    upgradeable.next_upgrade_usdc_cost = upgradeable.next_upgrade_usdc_cost
-                        .checked_mul(cost_multiplier)
-                        .ok_or(UpgradeError::ArithmeticOverflow)?
-                        .checked_div(10000)
+                    let stakeable = &mut ctx.accounts.stakeable;
+                    stakeable.upgrade_savings_usdc = stakeable.upgrade_savings_usdc.checked_add(usdc_to_savings)
                         .ok_or(UpgradeError::ArithmeticOverflow)?;
-                    
-                    upgradeable.next_upgrade_aifi_cost = upgradeable.next_upgrade_aifi_cost
-                        .checked_mul(cost_multiplier)
-                        .ok_or(UpgradeError::ArithmeticOverflow)?
-                        .checked_div(10000)
+                    stakeable.upgrade_savings_aifi = stakeable.upgrade_savings_aifi.checked_add(aifi_to_savings)
                         .ok_or(UpgradeError::ArithmeticOverflow)?;
                 }
+                
+                wallet.usdc_balance = wallet.usdc_balance.checked_add(usdc_refund - usdc_to_savings)
+                    .ok_or(UpgradeError::ArithmeticOverflow)?;
+                wallet.aifi_balance = wallet.aifi_balance.checked_add(aifi_refund - aifi_to_savings)
+                    .ok_or(UpgradeError::ArithmeticOverflow)?;
+                
+                clear_construction(upgradeable);
+                
+                // Full production resumes now
+                production.construction_end = current_time.min(production.construction_end);
+                
+                msg!("Cancelled upgrade, refunded {} USDC and {} AiFi, of which {} USDC and {} AiFi to savings",
+                     usdc_refund, aifi_refund, usdc_to_savings, aifi_to_savings);
             },
-            // Update upgrade parameters
-            2 => {
+            // Buy protection items against level drops with AiFi
+            7 => {
                 let upgradeable = &mut ctx.accounts.upgradeable;
+                let wallet = &mut ctx.accounts.wallet;
+                let production = &ctx.accounts.production;
+                let upgrade_table = &ctx.accounts.upgrade_table;
                 
-                // Update parameters as requested
-                upgradeable.max_level = args.max_level;
-                upgradeable.next_upgrade_usdc_cost = args.next_upgrade_usdc_cost;
-                upgradeable.next_upgrade_aifi_cost = args.next_upgrade_aifi_cost;
-                upgradeable.upgrade_cooldown = args.upgrade_cooldown;
-                upgradeable.next_usdc_boost = args.next_usdc_boost;
-                upgradeable.next_aifi_boost = args.next_aifi_boost;
+                check_table(upgrade_table, ctx.remaining_accounts.get(6), upgradeable.upgradeable_type, production.model_id)?;
+                
+                if upgrade_table.protection_aifi_cost == 0 || args.quantity == 0 {
+                    return Err(UpgradeError::ProtectionUnavailable.into());
+                }
+                
+                let cost = upgrade_table.protection_aifi_cost.checked_mul(args.quantity as u64)
+                    .ok_or(UpgradeError::ArithmeticOverflow)?;
+                
+                if wallet.aifi_balance < cost {
+                    return Err(UpgradeError::InsufficientAifiFunds.into());
+                }
+                
+                wallet.aifi_balance = wallet.aifi_balance.checked_sub(cost)
+                    .ok_or(UpgradeError::ArithmeticOverflow)?;
+                upgradeable.protection_items = upgradeable.protection_items.checked_add(args.quantity)
+                    .ok_or(UpgradeError::ArithmeticOverflow)?;
                 
-                // Recalculate if entity can be upgraded
-                upgradeable.can_upgrade = upgradeable.current_level < upgradeable.max_level;
+                msg!("Bought {} protection items for {} AiFi, {} held",
+                     args.quantity, cost, upgradeable.protection_items);
             },
             _ => return Err(UpgradeError::InvalidOperation.into()),
         }
@@ -196,6 +807,9 @@ pub mod upgrade {
         pub upgradeable: Upgradeable,
         pub wallet: Wallet,
         pub production: Production,
+        pub owner_ownership: Ownership,
+        pub upgrade_table: UpgradeTable,
+        pub stakeable: Stakeable,
     }
 }
 
@@ -229,4 +843,80 @@ pub enum UpgradeError {
     /// Arithmetic overflow during calculation
     #[msg("Arithmetic overflow in calculation")]
     ArithmeticOverflow,
-}
\ No newline at end of file
+    
+    /// GPU stats are managed by the GPU model catalog
+    #[msg("GPU stats are managed by the GPU model catalog")]
+    CatalogManaged,
+    
+    /// Upgrade table has not been registered
+    #[msg("Upgrade table is not registered")]
+    TableNotRegistered,
+    
+    /// Upgrade table belongs to another entity model
+    #[msg("Upgrade table does not match the entity")]
+    TableMismatch,
+    
+    /// Level is out of range
+    #[msg("Invalid level")]
+    InvalidLevel,
+    
+    /// Player level is below the level required for the upgrade
+    #[msg("Player level is too low for this upgrade")]
+    PlayerLevelTooLow,
+    
+    /// Level takes time to build and must be started with StartUpgrade
+    #[msg("This upgrade requires construction")]
+    RequiresConstruction,
+    
+    /// An upgrade is already under construction
+    #[msg("An upgrade is already in progress")]
+    UpgradeInProgress,
+    
+    /// No upgrade is under construction
+    #[msg("No upgrade is in progress")]
+    NoUpgradeInProgress,
+    
+    /// Construction has not finished yet
+    #[msg("Upgrade construction is not finished")]
+    UpgradeNotReady,
+    
+    /// Construction has already finished
+    #[msg("Upgrade construction is already complete")]
+    ConstructionComplete,
+    
+    /// Upgrade table does not allow speed-ups
+    #[msg("Speed-ups are disabled for this upgrade table")]
+    SpeedUpDisabled,
+    
+    /// Upgrade table does not sell protection items
+    #[msg("Protection items are not available")]
+    ProtectionUnavailable,
+    
+    /// Entity was retired and can no longer be upgraded
+    #[msg("Entity has been retired")]
+    EntityRetired,
+    
+    /// Upgrade table was not registered by the game authority on the model entity
+    #[msg("Upgrade table is not part of the game's catalog")]
+    TableNotCanonical,
+    
+    /// Savings must be refunded to the Stakeable of the upgraded entity
+    #[msg("Stakeable does not belong to the upgraded entity")]
+    SavingsMismatch,
+    
+    /// Risky upgrades are rolled with randomness requested by StartUpgrade
+    #[msg("Risky upgrades must be started with StartUpgrade")]
+    RequiresCommittedRoll,
+    
+    /// Risky upgrade has no roll requested or the request accounts are missing
+    #[msg("The roll of this risky upgrade was not requested")]
+    UpgradeRollMissing,
+    
+    /// VRF callback has not delivered the roll yet
+    #[msg("The roll of this risky upgrade has not been delivered yet")]
+    UpgradeRollNotDelivered,
+    
+    /// Roll of the upgrade was delivered or may still be delivered
+    #[msg("A risky upgrade can't be cancelled once its roll was requested")]
+    UpgradeRollPending,
+}
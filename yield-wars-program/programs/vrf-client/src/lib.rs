@@ -1,4 +1,4 @@
-use crate::instruction::ConsumeRandomness;
+use crate::instruction::{ConsumeBetRandomness, ConsumeCommittedRandomness, ConsumeRandomness};
 use anchor_lang::prelude::borsh::BorshDeserialize;
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::hash::hash;
@@ -9,12 +9,15 @@ use ephemeral_vrf_sdk::consts::IDENTITY;
 use ephemeral_vrf_sdk::instructions::create_request_randomness_ix;
 use ephemeral_vrf_sdk::instructions::RequestRandomnessParams;
 use ephemeral_vrf_sdk::rnd::{random_bool, random_u32, random_u8_with_range};
+use ephemeral_vrf_sdk::types::SerializableAccountMeta;
 
 declare_id!("2LwC4FAQgQfbJvNo5xAVVLbXap1SpnixhyeMejud58Pq");
 
 // Constants for the PDA seeds
 pub const RANDOMNESS_SEED: &[u8] = b"randomness";
 pub const USER_SEED: &[u8] = b"user";
+pub const BET_RANDOMNESS_SEED: &[u8] = b"bet-randomness";
+pub const COMMITTED_RANDOMNESS_SEED: &[u8] = b"committed-randomness";
 
 #[program]
 pub mod vrf_client {
@@ -104,6 +107,134 @@ pub mod vrf_client {
         Ok(())
     }
 
+    // Request randomness for a single bet, delivered once to its own PDA
+    pub fn request_bet_randomness(
+        ctx: Context<RequestBetRandomnessCtx>,
+        client_seed: u8,
+        bet_id: u64,
+    ) -> Result<()> {
+        let bet_randomness = &mut ctx.accounts.bet_randomness;
+        bet_randomness.bet = ctx.accounts.bet.key();
+        bet_randomness.bet_id = bet_id;
+        bet_randomness.requested_slot = Clock::get()?.slot;
+
+        // Every bet gets its own request, even when the client seed repeats
+        let mut caller_seed = vec![client_seed];
+        caller_seed.extend_from_slice(&bet_id.to_le_bytes());
+
+        let ix = create_request_randomness_ix(RequestRandomnessParams {
+            payer: ctx.accounts.payer.key(),
+            oracle_queue: ctx.accounts.oracle_queue.key(),
+            callback_program_id: ID,
+            callback_discriminator: ConsumeBetRandomness::DISCRIMINATOR.to_vec(),
+            caller_seed: hash(&caller_seed).to_bytes(),
+            callback_accounts_metas: vec![SerializableAccountMeta {
+                pubkey: bet_randomness.key(),
+                is_signer: false,
+                is_writable: true,
+            }],
+            ..Default::default()
+        });
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.program_identity.to_account_info(),
+                ctx.accounts.oracle_queue.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.slot_hashes.to_account_info(),
+            ],
+            &[&[IDENTITY, &[ctx.bumps.program_identity]]],
+        )?;
+
+        msg!("Requested randomness for bet {} of {:?}", bet_id, bet_randomness.bet);
+        Ok(())
+    }
+
+    // Called by the VRF program with the randomness for a bet
+    pub fn consume_bet_randomness(
+        ctx: Context<ConsumeBetRandomnessCtx>,
+        randomness: [u8; 32],
+    ) -> Result<()> {
+        let bet_randomness = &mut ctx.accounts.bet_randomness;
+
+        // The first delivery settles the bet, later ones can't replace it
+        if bet_randomness.is_fulfilled {
+            return err!(VrfClientError::RandomnessAlreadyDelivered);
+        }
+
+        bet_randomness.randomness = randomness;
+        bet_randomness.is_fulfilled = true;
+
+        msg!("Randomness delivered for bet {} of {:?}", bet_randomness.bet_id, bet_randomness.bet);
+        Ok(())
+    }
+
+    // Request randomness committed to a subject, delivered once to a PDA of the requester
+    pub fn request_committed_randomness(
+        ctx: Context<RequestCommittedRandomnessCtx>,
+        client_seed: u8,
+        request_id: u64,
+    ) -> Result<()> {
+        let committed = &mut ctx.accounts.committed_randomness;
+        committed.subject = ctx.accounts.subject.key();
+        committed.requester = ctx.accounts.payer.key();
+        committed.request_id = request_id;
+        committed.requested_slot = Clock::get()?.slot;
+
+        // Every request gets its own seed, even when the client seed repeats
+        let mut caller_seed = vec![client_seed];
+        caller_seed.extend_from_slice(committed.subject.as_ref());
+        caller_seed.extend_from_slice(&request_id.to_le_bytes());
+
+        let ix = create_request_randomness_ix(RequestRandomnessParams {
+            payer: ctx.accounts.payer.key(),
+            oracle_queue: ctx.accounts.oracle_queue.key(),
+            callback_program_id: ID,
+            callback_discriminator: ConsumeCommittedRandomness::DISCRIMINATOR.to_vec(),
+            caller_seed: hash(&caller_seed).to_bytes(),
+            callback_accounts_metas: vec![SerializableAccountMeta {
+                pubkey: committed.key(),
+                is_signer: false,
+                is_writable: true,
+            }],
+            ..Default::default()
+        });
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.program_identity.to_account_info(),
+                ctx.accounts.oracle_queue.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.slot_hashes.to_account_info(),
+            ],
+            &[&[IDENTITY, &[ctx.bumps.program_identity]]],
+        )?;
+
+        msg!("Requested randomness {} of {:?} for {:?}", request_id, committed.requester, committed.subject);
+        Ok(())
+    }
+
+    // Called by the VRF program with the randomness of a committed request
+    pub fn consume_committed_randomness(
+        ctx: Context<ConsumeCommittedRandomnessCtx>,
+        randomness: [u8; 32],
+    ) -> Result<()> {
+        let committed = &mut ctx.accounts.committed_randomness;
+
+        // The first delivery is final, later ones can't replace it
+        if committed.is_fulfilled {
+            return err!(VrfClientError::CommittedRandomnessAlreadyDelivered);
+        }
+
+        committed.randomness = randomness;
+        committed.is_fulfilled = true;
+
+        msg!("Randomness delivered for request {} of {:?}", committed.request_id, committed.requester);
+        Ok(())
+    }
+
     // Get stored randomness for a user
     pub fn get_randomness(ctx: Context<GetRandomnessCtx>) -> Result<()> {
         if ctx.accounts.user_randomness_account.is_used {
@@ -205,6 +336,86 @@ pub struct ConsumeRandomnessCtx<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+#[instruction(client_seed: u8, bet_id: u64)]
+pub struct RequestBetRandomnessCtx<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Bet the randomness is requested for, only used as a seed
+    pub bet: AccountInfo<'info>,
+    /// PDA the randomness of this bet will be delivered to
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + BetRandomness::SPACE,
+        seeds = [BET_RANDOMNESS_SEED, bet.key().as_ref(), payer.key().as_ref(), &bet_id.to_le_bytes()],
+        bump
+    )]
+    pub bet_randomness: Account<'info, BetRandomness>,
+    /// CHECK: Used to verify the identity of the program
+    #[account(seeds = [b"identity"], bump)]
+    pub program_identity: AccountInfo<'info>,
+    /// CHECK: Oracle queue
+    #[account(mut, address = DEFAULT_TEST_QUEUE)]
+    pub oracle_queue: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: Slot hashes sysvar
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: AccountInfo<'info>,
+    pub vrf_program: Program<'info, VrfProgram>,
+}
+
+#[derive(Accounts)]
+pub struct ConsumeBetRandomnessCtx<'info> {
+    /// Signer PDA of the VRF program
+    #[account(address = ephemeral_vrf_sdk::consts::VRF_PROGRAM_IDENTITY)]
+    pub vrf_program_identity: Signer<'info>,
+
+    /// PDA where the randomness of the bet is stored
+    #[account(mut)]
+    pub bet_randomness: Account<'info, BetRandomness>,
+}
+
+#[derive(Accounts)]
+#[instruction(client_seed: u8, request_id: u64)]
+pub struct RequestCommittedRandomnessCtx<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Subject the randomness is committed to, only used as a seed
+    pub subject: AccountInfo<'info>,
+    /// PDA the randomness of this request will be delivered to
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + CommittedRandomness::SPACE,
+        seeds = [COMMITTED_RANDOMNESS_SEED, subject.key().as_ref(), payer.key().as_ref(), &request_id.to_le_bytes()],
+        bump
+    )]
+    pub committed_randomness: Account<'info, CommittedRandomness>,
+    /// CHECK: Used to verify the identity of the program
+    #[account(seeds = [b"identity"], bump)]
+    pub program_identity: AccountInfo<'info>,
+    /// CHECK: Oracle queue
+    #[account(mut, address = DEFAULT_TEST_QUEUE)]
+    pub oracle_queue: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: Slot hashes sysvar
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: AccountInfo<'info>,
+    pub vrf_program: Program<'info, VrfProgram>,
+}
+
+#[derive(Accounts)]
+pub struct ConsumeCommittedRandomnessCtx<'info> {
+    /// Signer PDA of the VRF program
+    #[account(address = ephemeral_vrf_sdk::consts::VRF_PROGRAM_IDENTITY)]
+    pub vrf_program_identity: Signer<'info>,
+
+    /// PDA where the randomness of the request is stored
+    #[account(mut)]
+    pub committed_randomness: Account<'info, CommittedRandomness>,
+}
+
 #[derive(Accounts)]
 pub struct GetRandomnessCtx<'info> {
     pub user: Signer<'info>,
@@ -256,12 +467,45 @@ impl UserRandomness {
     pub const SPACE: usize = 32 + 32 + 8 + 1;
 }
 
+#[account]
+#[derive(Default)]
+pub struct BetRandomness {
+    pub bet: Pubkey,          // 32 bytes
+    pub bet_id: u64,          // 8 bytes
+    pub randomness: [u8; 32], // 32 bytes
+    pub requested_slot: u64,  // 8 bytes
+    pub is_fulfilled: bool,   // 1 byte
+}
+
+impl BetRandomness {
+    pub const SPACE: usize = 32 + 8 + 32 + 8 + 1;
+}
+
+#[account]
+#[derive(Default)]
+pub struct CommittedRandomness {
+    pub subject: Pubkey,      // 32 bytes
+    pub requester: Pubkey,    // 32 bytes
+    pub request_id: u64,      // 8 bytes
+    pub randomness: [u8; 32], // 32 bytes
+    pub requested_slot: u64,  // 8 bytes
+    pub is_fulfilled: bool,   // 1 byte
+}
+
+impl CommittedRandomness {
+    pub const SPACE: usize = 32 + 32 + 8 + 32 + 8 + 1;
+}
+
 #[error_code]
 pub enum VrfClientError {
     #[msg("Invalid user for randomness account")]
     InvalidUser,
     #[msg("Randomness has already been used")]
     RandomnessAlreadyUsed,
+    #[msg("Randomness has already been delivered for this bet")]
+    RandomnessAlreadyDelivered,
+    #[msg("Randomness has already been delivered for this request")]
+    CommittedRandomnessAlreadyDelivered,
 }
 
 pub const DEFAULT_TEST_QUEUE: Pubkey = pubkey!("GKE6d7iv8kCBrsxr78W3xVdjGLLLJnxsGiuzrsZCGEvb");
@@ -1,9 +1,30 @@
 #![allow(unexpected_cfgs)]
 
 use bolt_lang::prelude::*;
+use durability::Durability;
+use facility::Facility;
+use hosting_agreement::HostingAgreement;
+use player_profile::PlayerProfile;
+use price::Price;
+use production::Production;
+use stakeable::Stakeable;
+use yield_math::{ConstructionWindow, HardwareWear, HostingTerm, IncidentOdds, IncidentRoll, ProductionRates};
 
 declare_id!("Gk8azmvjHvYJVJtJfEBSSrnMPsmxSYVwHzdEWRv5aWvf");
 
+// Currency type of the AiFi price (uses same values as the Wallet component)
+const CURRENCY_AIFI: u8 = 4;
+
+// Accounts of each producer in a batch preview
+const PRODUCER_ACCOUNTS: usize = 5;
+
+/// YieldWars game program
+///
+/// Besides the Bolt systems, the game exposes read-only preview instructions.
+/// They compute pending yield with the same code as the ResourceProduction
+/// and Staking systems without mutating any account, and return a
+/// borsh-encoded result through Solana return data. Clients simulate the
+/// transaction to read the exact on-chain answer.
 #[program]
 pub mod yield_wars_program {
     use super::*;
@@ -11,7 +32,435 @@ pub mod yield_wars_program {
     pub fn initialize(_ctx: Context<Initialize>) -> Result<()> {
         Ok(())
     }
+
+    /// Preview what a ResourceProduction Collect at `current_time` would pay out
+    ///
+    /// The host's share is split off while a hosting agreement is active. When the
+    /// facility rolls incidents, the collection ends at the time committed by the
+    /// pending incident roll, and the incident is included once its randomness has
+    /// been delivered.
+    pub fn preview_production(ctx: Context<PreviewProduction>, current_time: i64) -> Result<PendingYield> {
+        let incident_randomness = ctx.accounts.incident_randomness.as_ref()
+            .map(|account| account.to_account_info());
+
+        pending_yield(
+            &ctx.accounts.production,
+            ctx.accounts.durability.as_deref(),
+            ctx.accounts.facility.as_deref(),
+            ctx.accounts.player_profile.as_deref(),
+            ctx.accounts.hosting_agreement.as_deref(),
+            incident_randomness.as_ref(),
+            current_time,
+        )
+    }
+
+    /// Preview the staking rewards an Unstake at `current_time` would settle
+    pub fn preview_stakeable(ctx: Context<PreviewStakeable>, current_time: i64) -> Result<PendingRewards> {
+        pending_rewards(
+            &ctx.accounts.stakeable,
+            &ctx.accounts.production,
+            &ctx.accounts.price_aifi,
+            current_time,
+        )
+    }
+
+    /// Preview pending yield and staking rewards of many entities at once
+    ///
+    /// Remaining accounts hold, for each of the `production_count` producers, its
+    /// Production, Durability, Facility and HostingAgreement accounts and the
+    /// CommittedRandomness PDA of its pending incident roll, followed by any number
+    /// of pairs of Stakeable and Production accounts. An uninitialized Durability,
+    /// Facility or HostingAgreement account means wear is not tracked, incidents
+    /// are not rolled or the producer is not hosted; any other account can stand in
+    /// for randomness that was not requested. All producers belong to the owner of
+    /// the PlayerProfile. Staking rewards need the AiFi price account.
+    pub fn preview_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PreviewBatch<'info>>,
+        production_count: u8,
+        current_time: i64,
+    ) -> Result<PendingBatch> {
+        let production_accounts = (production_count as usize)
+            .checked_mul(PRODUCER_ACCOUNTS)
+            .ok_or(YieldWarsError::ArithmeticOverflow)?;
+
+        if ctx.remaining_accounts.len() < production_accounts
+            || (ctx.remaining_accounts.len() - production_accounts) % 2 != 0
+        {
+            return Err(YieldWarsError::InvalidBatch.into());
+        }
+
+        let (producers, stakeables) = ctx.remaining_accounts.split_at(production_accounts);
+        let mut batch = PendingBatch::default();
+
+        let player_profile = ctx.accounts.player_profile.as_deref();
+
+        for accounts in producers.chunks(PRODUCER_ACCOUNTS) {
+            let production = Account::<Production>::try_from(&accounts[0])?;
+            let durability = if *accounts[1].owner == durability::ID {
+                Some(Account::<Durability>::try_from(&accounts[1])?)
+            } else {
+                None
+            };
+            let facility = if *accounts[2].owner == facility::ID {
+                Some(Account::<Facility>::try_from(&accounts[2])?)
+            } else {
+                None
+            };
+            let hosting_agreement = if *accounts[3].owner == hosting_agreement::ID {
+                Some(Account::<HostingAgreement>::try_from(&accounts[3])?)
+            } else {
+                None
+            };
+            let incident_randomness = if *accounts[4].owner == vrf_randomness::VRF_CLIENT_PROGRAM_ID {
+                Some(&accounts[4])
+            } else {
+                None
+            };
+
+            batch.productions.push(pending_yield(
+                &production,
+                durability.as_deref(),
+                facility.as_deref(),
+                player_profile,
+                hosting_agreement.as_deref(),
+                incident_randomness,
+                current_time,
+            )?);
+        }
+
+        if !stakeables.is_empty() {
+            let price_aifi = ctx.accounts.price_aifi.as_ref()
+                .ok_or(YieldWarsError::InvalidBatch)?;
+
+            for pair in stakeables.chunks(2) {
+                let stakeable = Account::<Stakeable>::try_from(&pair[0])?;
+                let production = Account::<Production>::try_from(&pair[1])?;
+                batch.stakeables.push(pending_rewards(&stakeable, &production, price_aifi, current_time)?);
+            }
+        }
+
+        Ok(batch)
+    }
+}
+
+/// Compute the pending yield of a producer with the payout of ResourceProduction Collect
+fn pending_yield(
+    production: &Account<Production>,
+    durability: Option<&Durability>,
+    facility: Option<&Facility>,
+    player_profile: Option<&PlayerProfile>,
+    hosting_agreement: Option<&HostingAgreement>,
+    incident_randomness: Option<&AccountInfo>,
+    current_time: i64,
+) -> Result<PendingYield> {
+    let durability = durability.filter(|durability| durability.max_durability > 0);
+    let hosting = hosting_agreement.filter(|hosting| hosting.is_active());
+
+    if hosting.is_some_and(|hosting| hosting.production != production.key()) {
+        return Err(YieldWarsError::HostingMismatch.into());
+    }
+
+    let mut pending = PendingYield {
+        is_active: production.is_active,
+        is_broken: durability.map_or(false, |durability| durability.is_broken),
+        remaining_durability: durability.map_or(0, |durability| durability.current_durability),
+        ..PendingYield::default()
+    };
+
+    // With incidents the collection ends at the time committed by the pending roll
+    let mut period_end = current_time;
+    let mut incident = None;
+
+    if let Some(facility) = facility.filter(|facility| facility.incidents_enabled) {
+        pending.incident_roll_pending = production.incident_roll_pending;
+
+        if production.incident_roll_pending {
+            period_end = production.incident_roll_time;
+
+            if let Some(account) = incident_randomness {
+                let committed = vrf_randomness::load_committed_randomness(
+                    account,
+                    &production.key(),
+                    &production.incident_requester,
+                    production.incident_request_id,
+                )?;
+
+                incident = committed.is_fulfilled.then(|| IncidentRoll {
+                    roll: committed.random_u64(),
+                    base_odds: IncidentOdds {
+                        power_outage_bps: facility.power_outage_odds,
+                        gpu_burnout_bps: facility.gpu_burnout_odds,
+                        mining_bonus_bps: facility.mining_bonus_odds,
+                    },
+                    cooling_rating_bps: facility.cooling_rating,
+                    energy_contract_coverage_bps: facility.energy_contract_coverage,
+                    outage_multiplier_bps: facility.outage_yield_multiplier,
+                    mining_bonus_multiplier_bps: facility.mining_bonus_multiplier,
+                    burnout_damage: facility.burnout_durability_damage,
+                });
+            }
+        }
+    }
+
+    if !pending.is_active || pending.is_broken || period_end <= production.last_collection_time {
+        return Ok(pending);
+    }
+
+    let collection = yield_math::Collection {
+        period_start: production.last_collection_time,
+        period_end,
+        rates: ProductionRates {
+            usdc_per_hour: production.usdc_per_hour,
+            aifi_per_hour: production.aifi_per_hour,
+            btc_per_hour: production.btc_per_hour,
+            eth_per_hour: production.eth_per_hour,
+            sol_per_hour: production.sol_per_hour,
+            operating_cost_per_hour: production.operating_cost,
+        },
+        efficiency_bps: production.efficiency_multiplier,
+        hardware: durability.map(|durability| HardwareWear {
+            current_durability: durability.current_durability,
+            wear_per_hour: durability.wear_per_hour,
+            overclock_bps: durability.overclock_multiplier,
+            rated_efficiency_bps: durability.rated_efficiency,
+            degradation_threshold: durability.degradation_threshold,
+        }),
+        prestige_bps: player_profile
+            .map_or(yield_math::BPS_DENOMINATOR, |profile| profile.multiplier_bps()),
+        vote_escrow_bps: player_profile
+            .map_or(yield_math::BPS_DENOMINATOR, |profile| profile.vote_escrow_bps(period_end)),
+        construction: ConstructionWindow {
+            start_time: production.construction_start,
+            end_time: production.construction_end,
+            yield_bps: production.construction_yield,
+        },
+        incident,
+        hosting: hosting.map(|hosting| HostingTerm {
+            start_time: hosting.start_time,
+            end_time: hosting.end_time,
+            share_bps: hosting.host_share_bps,
+        }),
+    };
+
+    let outcome = yield_math::collect(&collection)
+        .ok_or(YieldWarsError::ArithmeticOverflow)?;
+
+    pending.usdc = outcome.owner.usdc_earned;
+    pending.aifi = outcome.owner.aifi_earned;
+    pending.btc = outcome.owner.btc_earned;
+    pending.eth = outcome.owner.eth_earned;
+    pending.sol = outcome.owner.sol_earned;
+    pending.operating_cost = outcome.owner.operating_cost;
+    pending.producing_seconds = outcome.producing_seconds;
+    pending.host_usdc = outcome.host.usdc_earned;
+    pending.host_aifi = outcome.host.aifi_earned;
+    pending.host_btc = outcome.host.btc_earned;
+    pending.host_eth = outcome.host.eth_earned;
+    pending.host_sol = outcome.host.sol_earned;
+    pending.incident = outcome.incident as u8;
+    pending.incident_multiplier = outcome.incident_multiplier_bps;
+    pending.incident_damage = outcome.incident_damage;
+
+    if let Some(hardware) = outcome.hardware {
+        pending.remaining_durability = hardware.remaining_durability;
+    }
+
+    Ok(pending)
+}
+
+/// Compute the pending rewards of a stakeable entity, mirroring Staking Unstake
+///
+/// Rewards already settled are reported as accumulated or locked and are not
+/// part of `usdc`, except for locked rewards an unstake would release. Rewards
+/// of entities staked in a StakingPool are not included.
+fn pending_rewards(
+    stakeable: &Stakeable,
+    production: &Production,
+    price_aifi: &Price,
+    current_time: i64,
+) -> Result<PendingRewards> {
+    let mut pending = PendingRewards {
+        is_staked: stakeable.is_staked,
+        is_unbonding: stakeable.is_unbonding,
+        unbonding_end_time: stakeable.unbonding_end_time,
+        accumulated_usdc: stakeable.accumulated_usdc_rewards,
+        accumulated_aifi: stakeable.accumulated_aifi_rewards,
+        locked_usdc: stakeable.locked_usdc_rewards,
+        ..PendingRewards::default()
+    };
+
+    if !stakeable.is_staked
+        || stakeable.in_pool()
+        || current_time <= stakeable.staking_start_time
+        || current_time < stakeable.last_claim_time
+        || stakeable.last_claim_time < stakeable.staking_start_time
+    {
+        return Ok(pending);
+    }
+
+    if price_aifi.price_type != CURRENCY_AIFI {
+        return Err(YieldWarsError::CurrencyPriceMismatch.into());
+    }
+
+    let value_per_hour = stakeable.reward_base(yield_math::production_value_per_hour(
+        production.usdc_per_hour,
+        production.aifi_per_hour,
+        price_aifi.current_price,
+    ).ok_or(YieldWarsError::ArithmeticOverflow)?);
+
+    let staked_for = (current_time - stakeable.staking_start_time) as u64;
+    let settlement = yield_math::settle_staking_rewards(
+        value_per_hour,
+        &[stakeable.reward_rate, stakeable.lock_multiplier],
+        (stakeable.last_claim_time - stakeable.staking_start_time) as u64,
+        staked_for,
+        stakeable.lock_period(),
+    ).ok_or(YieldWarsError::ArithmeticOverflow)?;
+
+    let locked = stakeable.locked_usdc_rewards
+        .checked_add(settlement.locked)
+        .ok_or(YieldWarsError::ArithmeticOverflow)?;
+    let penalty = if staked_for < stakeable.lock_period() {
+        yield_math::early_unstake_penalty(locked, stakeable.lock_penalty)
+            .ok_or(YieldWarsError::ArithmeticOverflow)?
+    } else {
+        0
+    };
+
+    pending.usdc = settlement.unlocked
+        .checked_add(locked - penalty)
+        .ok_or(YieldWarsError::ArithmeticOverflow)?;
+    pending.usdc_penalty = penalty;
+
+    Ok(pending)
 }
 
 #[derive(Accounts)]
 pub struct Initialize {}
+
+#[derive(Accounts)]
+pub struct PreviewProduction<'info> {
+    /// Production component of the producer
+    pub production: Account<'info, Production>,
+    /// Durability component of the producer, if wear is tracked
+    pub durability: Option<Account<'info, Durability>>,
+    /// PlayerProfile of the producer's owner, if they have prestige or locked AiFi
+    pub player_profile: Option<Account<'info, PlayerProfile>>,
+    /// Facility component of the producer, if it rolls incidents
+    pub facility: Option<Account<'info, Facility>>,
+    /// HostingAgreement component of the producer, if it is hosted
+    pub hosting_agreement: Option<Account<'info, HostingAgreement>>,
+    /// CHECK: vrf_client CommittedRandomness PDA of the pending incident roll, verified on use
+    pub incident_randomness: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct PreviewStakeable<'info> {
+    /// Stakeable component of the staked entity
+    pub stakeable: Account<'info, Stakeable>,
+    /// Production component of the staked entity
+    pub production: Account<'info, Production>,
+    /// AiFi Price component used to value AiFi output
+    pub price_aifi: Account<'info, Price>,
+}
+
+#[derive(Accounts)]
+pub struct PreviewBatch<'info> {
+    /// AiFi Price component, required when the batch holds stakeable entities
+    pub price_aifi: Option<Account<'info, Price>>,
+    /// PlayerProfile of the producers' owner, if they have prestige or locked AiFi
+    pub player_profile: Option<Account<'info, PlayerProfile>>,
+}
+
+/// Pending yield of a producer
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct PendingYield {
+    /// USDC a collection would pay out, before operating costs
+    pub usdc: u64,
+    /// AiFi a collection would pay out
+    pub aifi: u64,
+    /// BTC a collection would pay out
+    pub btc: u64,
+    /// ETH a collection would pay out
+    pub eth: u64,
+    /// SOL a collection would pay out
+    pub sol: u64,
+    /// Operating cost in USDC a collection would charge
+    pub operating_cost: u64,
+    /// Seconds of production counted (shorter than elapsed if the hardware breaks)
+    pub producing_seconds: u64,
+    /// Durability left after the collection (0 if wear is not tracked)
+    pub remaining_durability: u32,
+    /// Whether production is active
+    pub is_active: bool,
+    /// Whether the hardware is broken
+    pub is_broken: bool,
+    /// USDC the host's share of the collection would pay the host
+    pub host_usdc: u64,
+    /// AiFi the host's share of the collection would pay the host
+    pub host_aifi: u64,
+    /// BTC the host's share of the collection would pay the host
+    pub host_btc: u64,
+    /// ETH the host's share of the collection would pay the host
+    pub host_eth: u64,
+    /// SOL the host's share of the collection would pay the host
+    pub host_sol: u64,
+    /// Incident the delivered roll picks (0 = None, 1 = PowerOutage, 2 = GpuBurnout, 3 = MiningBonus)
+    pub incident: u8,
+    /// Yield multiplier of that incident (10000 = no effect)
+    pub incident_multiplier: u32,
+    /// Durability points that incident costs
+    pub incident_damage: u32,
+    /// Whether an incident roll is pending; collections with incidents end at its committed time
+    pub incident_roll_pending: bool,
+}
+
+/// Pending staking rewards of a stakeable entity
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct PendingRewards {
+    /// USDC reward an unstake would settle, including locked rewards, after penalties
+    pub usdc: u64,
+    /// USDC lost to the early unstaking penalty
+    pub usdc_penalty: u64,
+    /// USDC rewards settled during the current lock period
+    pub locked_usdc: u64,
+    /// USDC rewards already accumulated and ready to collect
+    pub accumulated_usdc: u64,
+    /// AiFi rewards already accumulated and ready to collect
+    pub accumulated_aifi: u64,
+    /// Whether the entity is staked
+    pub is_staked: bool,
+    /// Whether the entity is unbonding
+    pub is_unbonding: bool,
+    /// Time the entity can be withdrawn if it is unbonding (Unix timestamp)
+    pub unbonding_end_time: i64,
+}
+
+/// Pending yield and rewards of a batch of entities, in account order
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct PendingBatch {
+    /// Pending yield of each producer
+    pub productions: Vec<PendingYield>,
+    /// Pending rewards of each stakeable entity
+    pub stakeables: Vec<PendingRewards>,
+}
+
+/// Errors that can occur in the YieldWars program
+#[error_code]
+pub enum YieldWarsError {
+    /// Not enough accounts for the requested batch
+    #[msg("Batch accounts do not match the requested counts")]
+    InvalidBatch,
+
+    /// Price component is not the AiFi price
+    #[msg("Price component does not match the currency")]
+    CurrencyPriceMismatch,
+
+    /// Arithmetic overflow during calculation
+    #[msg("Arithmetic overflow in calculation")]
+    ArithmeticOverflow,
+
+    /// Hosting agreement belongs to another producer
+    #[msg("Hosting agreement does not belong to the producer")]
+    HostingMismatch,
+}
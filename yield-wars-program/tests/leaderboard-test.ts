@@ -1,6 +1,7 @@
 import { PublicKey } from "@solana/web3.js";
 import { Wallet } from "../target/types/wallet";
 import { Price } from "../target/types/price";
+import { PlayerProfile } from "../target/types/player_profile";
 import {
     InitializeNewWorld,
     AddEntity,
@@ -37,6 +38,7 @@ describe("Leaderboard Test", () => {
   let priceUsdcComponentPda: PublicKey;
   let priceBtcComponentPda: PublicKey;
   let priceAifiComponentPda: PublicKey;
+  let playerProfileComponentPda: PublicKey;
 
   // SOAR related PDAs
   let playerPda: PublicKey;
@@ -45,6 +47,7 @@ describe("Leaderboard Test", () => {
   // Components and systems we'll use
   const walletComponent = anchor.workspace.Wallet as Program<Wallet>;
   const priceComponent = anchor.workspace.Price as Program<Price>;
+  const playerProfileComponent = anchor.workspace.PlayerProfile as Program<PlayerProfile>;
   const systemEconomy = anchor.workspace.Economy;
   const systemLeaderboard = anchor.workspace.Leaderboard;
 
@@ -139,6 +142,16 @@ describe("Leaderboard Test", () => {
     priceAifiComponentPda = initAifiPrice.componentPda;
     console.log(`Added AiFi price component to entity 3`);
 
+    // Add a player profile component to entity 1
+    const initProfile = await InitializeComponent({
+      payer: provider.wallet.publicKey,
+      entity: entityPda,
+      componentId: playerProfileComponent.programId,
+    });
+    await provider.sendAndConfirm(initProfile.transaction);
+    playerProfileComponentPda = initProfile.componentPda;
+    console.log(`Added player profile component to entity 1`);
+
     // Initialize wallet with some USDC
     const initWalletArgs = {
       transaction_type: 2, // INITIALIZE
@@ -388,6 +401,13 @@ describe("Leaderboard Test", () => {
           components: [
             { componentId: priceComponent.programId },    // AiFi price component
           ],
+        },
+        {
+          // Entity 1: Contains the player profile
+          entity: entityPda,
+          components: [
+            { componentId: playerProfileComponent.programId }, // player profile component
+          ],
         }
       ],
       args: initPlayerArgs,
@@ -485,6 +505,13 @@ describe("Leaderboard Test", () => {
           components: [
             { componentId: priceComponent.programId },
           ],
+        },
+        {
+          // Entity 1: Contains the player profile
+          entity: entityPda,
+          components: [
+            { componentId: playerProfileComponent.programId }, // player profile component
+          ],
         }
       ],
       args: registerPlayerArgs,
@@ -583,6 +610,13 @@ describe("Leaderboard Test", () => {
           components: [
             { componentId: priceComponent.programId },    // AiFi price component
           ],
+        },
+        {
+          // Entity 1: Contains the player profile
+          entity: entityPda,
+          components: [
+            { componentId: playerProfileComponent.programId }, // player profile component
+          ],
         }
       ],
       args: submitScoreArgs,
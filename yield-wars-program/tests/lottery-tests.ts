@@ -1,9 +1,10 @@
-import { PublicKey } from "@solana/web3.js";
+import { Keypair, PublicKey, SystemProgram, SYSVAR_SLOT_HASHES_PUBKEY } from "@solana/web3.js";
 import { Wallet } from "../target/types/wallet";
 import { Price } from "../target/types/price";
 import { Economy } from "../target/types/economy";
 import { Lottery } from "../target/types/lottery";
 import { LotteryPrize } from "../target/types/lottery_prize";
+import { LotteryBet } from "../target/types/lottery_bet";
 import { VrfClient } from "../target/types/vrf_client";
 import {
   InitializeNewWorld,
@@ -15,6 +16,8 @@ import {
 import { expect } from "chai";
 import * as anchor from "@coral-xyz/anchor";
 import * as crypto from "crypto";
+import path from "path";
+import fs from "fs";
 
 // Shared setup for all tests
 describe("Lottery Tests", () => {
@@ -25,6 +28,11 @@ describe("Lottery Tests", () => {
   console.log("Provider wallet:", provider.wallet.publicKey.toBase58());
   console.log("Provider connection:", provider.connection.rpcEndpoint);
 
+  // Game authority setting up and funding the lottery, generated by `yarn localnet:authority`
+  const gameAuthority = Keypair.fromSecretKey(Uint8Array.from(
+    JSON.parse(fs.readFileSync(path.join(__dirname, "fixtures", "game-authority.json"), "utf8"))
+  ));
+
   // Common variables used across tests
   let worldPda: PublicKey;
   let entityPda: PublicKey;
@@ -32,14 +40,33 @@ describe("Lottery Tests", () => {
   let lotteryEntity: PublicKey;
   let walletComponentPda: PublicKey;
   let lotteryPrizeComponentPda: PublicKey;
+  let lotteryBetComponentPda: PublicKey;
   let usdcPriceComponentPda: PublicKey;
   let aifiPriceComponentPda: PublicKey;
   
   const walletComponent = anchor.workspace.Wallet as Program<Wallet>;
   const lotteryPrizeComponent = anchor.workspace.LotteryPrize as Program<LotteryPrize>;
+  const lotteryBetComponent = anchor.workspace.LotteryBet as Program<LotteryBet>;
   const systemLottery = anchor.workspace.Lottery as Program<Lottery>;
   const systemEconomy = anchor.workspace.Economy as Program<Economy>;
   const priceComponent = anchor.workspace.Price as Program<Price>;
+  const vrfClient = anchor.workspace.VrfClient as Program<VrfClient>;
+
+  // Accounts of the VRF randomness request issued with every bet
+  const VRF_PROGRAM_ID = new PublicKey("Vrf1RNUjXmQGjmQrQLvJHs9SNkvDJEsRVFPkfSQUwGz");
+  const ORACLE_QUEUE = new PublicKey("GKE6d7iv8kCBrsxr78W3xVdjGLLLJnxsGiuzrsZCGEvb");
+  const [vrfProgramIdentity] = PublicKey.findProgramAddressSync([Buffer.from("identity")], vrfClient.programId);
+
+  // PDA the VRF callback delivers the randomness of a bet to
+  const betRandomnessPda = (betId: number) => PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("bet-randomness"),
+      lotteryBetComponentPda.toBuffer(),
+      provider.wallet.publicKey.toBuffer(),
+      new anchor.BN(betId).toArrayLike(Buffer, "le", 8),
+    ],
+    vrfClient.programId
+  )[0];
 
   // Common constants
   const CURRENCY_TYPE = {
@@ -390,12 +417,29 @@ describe("Lottery Tests", () => {
       expect(lotteryPrizeAccount.recentPrizes.length).to.equal(0);
     });
 
+    // Test initializing the player's LotteryBet component
+    it("should initialize the lottery bet component", async () => {
+      const initializeComponent = await InitializeComponent({
+        payer: provider.wallet.publicKey,
+        entity: entityPda,
+        componentId: lotteryBetComponent.programId,
+      });
+      const txSign = await provider.sendAndConfirm(initializeComponent.transaction as any);
+      lotteryBetComponentPda = initializeComponent.componentPda;
+      console.log(`\tInitialized the lottery bet component. Initialization signature: ${txSign}`);
+
+      const lotteryBetAccount = await lotteryBetComponent.account.lotteryBet.fetch(lotteryBetComponentPda);
+      expect(lotteryBetAccount.status).to.equal(0);
+      expect(lotteryBetAccount.betCount.toNumber()).to.equal(0);
+    });
+
     // Test setting lottery parameters
     it("should configure lottery prize parameters", async () => {
       // Set up initial parameters for the lottery
       const minBetAmount = 1000000; // 1 AiFi token
       const winProbability = 2000;   // 20% chance to win (2000 out of 10000)
       const maxWinMultiplier = 5000; // Up to 5x multiplier on wins
+      const houseEdge = 500;         // 5% of every bet kept by the house
 
       // Create structured arguments
       const args = {
@@ -403,6 +447,7 @@ describe("Lottery Tests", () => {
           min_bet_amount: minBetAmount,
           win_probability: winProbability,
           max_win_multiplier: maxWinMultiplier,
+          house_edge_bps: houseEdge,
         }
       };
 
@@ -421,14 +466,18 @@ describe("Lottery Tests", () => {
           {
             entity: entityPda,
             components: [
-              { componentId: walletComponent.programId }
+              { componentId: walletComponent.programId },
+              { componentId: lotteryBetComponent.programId }
             ]
           }
         ],
-        args
+        args,
+        extraAccounts: [
+          { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+        ],
       });
 
-      const txSign = await provider.sendAndConfirm(tx.transaction as any);
+      const txSign = await provider.sendAndConfirm(tx.transaction as any, [gameAuthority]);
       console.log(`\tConfigured the lottery parameters. Tx signature: ${txSign}`);
 
       // Verify the parameters were set correctly
@@ -436,7 +485,17 @@ describe("Lottery Tests", () => {
       expect(lotteryPrizeAccount.minBetAmount.toString()).to.equal(minBetAmount.toString());
       expect(lotteryPrizeAccount.winProbability.toString()).to.equal(winProbability.toString());
       expect(lotteryPrizeAccount.maxWinMultiplier.toString()).to.equal(maxWinMultiplier.toString());
+      expect(lotteryPrizeAccount.houseEdgeBps).to.equal(houseEdge);
       expect(lotteryPrizeAccount.isActive).to.equal(true);
+      expect(lotteryPrizeAccount.authority.toBase58()).to.equal(gameAuthority.publicKey.toBase58());
+
+      // Winning rolls 1..=winProbability pay 1001 + roll % (maxWinMultiplier - 1000) per 1000 bet
+      let payoutSum = 0;
+      for (let roll = 1; roll <= winProbability; roll++) {
+        payoutSum += 1001 + roll % (maxWinMultiplier - 1000);
+      }
+      const rtpBps = Math.min(Math.floor(payoutSum / 1000), 10000 - houseEdge);
+      console.log(`\tRTP: ${rtpBps / 100}%`);
     });
 
     // Test updating lottery parameters
@@ -445,6 +504,7 @@ describe("Lottery Tests", () => {
       const minBetAmount = 2000000; // 2 AiFi tokens
       const winProbability = 1000;   // 10% chance to win
       const maxWinMultiplier = 8000; // Up to 8x multiplier
+      const houseEdge = 500;         // Keep a 5% house edge
       const isActive = true;         // Keep active
 
       // Create structured arguments
@@ -453,6 +513,7 @@ describe("Lottery Tests", () => {
           min_bet_amount: minBetAmount,
           win_probability: winProbability,
           max_win_multiplier: maxWinMultiplier,
+          house_edge_bps: houseEdge,
           is_active: isActive
         }
       };
@@ -472,14 +533,18 @@ describe("Lottery Tests", () => {
           {
             entity: entityPda,
             components: [
-              { componentId: walletComponent.programId }
+              { componentId: walletComponent.programId },
+              { componentId: lotteryBetComponent.programId }
             ]
           }
         ],
-        args
+        args,
+        extraAccounts: [
+          { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+        ],
       });
 
-      const txSign = await provider.sendAndConfirm(tx.transaction as any);
+      const txSign = await provider.sendAndConfirm(tx.transaction as any, [gameAuthority]);
       console.log(`\tUpdated the lottery parameters. Tx signature: ${txSign}`);
 
       // Verify the parameters were updated correctly
@@ -487,6 +552,7 @@ describe("Lottery Tests", () => {
       expect(lotteryPrizeAccount.minBetAmount.toString()).to.equal(minBetAmount.toString());
       expect(lotteryPrizeAccount.winProbability.toString()).to.equal(winProbability.toString());
       expect(lotteryPrizeAccount.maxWinMultiplier.toString()).to.equal(maxWinMultiplier.toString());
+      expect(lotteryPrizeAccount.houseEdgeBps).to.equal(houseEdge);
       expect(lotteryPrizeAccount.isActive).to.equal(isActive);
     });
 
@@ -500,6 +566,7 @@ describe("Lottery Tests", () => {
           min_bet_amount: 2000000, // Use the same values that worked in earlier tests
           win_probability: 1000,
           max_win_multiplier: 8000,
+          house_edge_bps: 500,
           is_active: false
         }
       };
@@ -519,14 +586,18 @@ describe("Lottery Tests", () => {
           {
             entity: entityPda,
             components: [
-              { componentId: walletComponent.programId }
+              { componentId: walletComponent.programId },
+              { componentId: lotteryBetComponent.programId }
             ]
           }
         ],
-        args
+        args,
+        extraAccounts: [
+          { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+        ],
       });
 
-      const txSign = await provider.sendAndConfirm(tx.transaction as any);
+      const txSign = await provider.sendAndConfirm(tx.transaction as any, [gameAuthority]);
       console.log(`\tDeactivated the lottery. Tx signature: ${txSign}`);
 
       // Verify the lottery is now inactive
@@ -542,6 +613,7 @@ describe("Lottery Tests", () => {
           min_bet_amount: 2000000, // Use the same values that worked in earlier tests
           win_probability: 1000,
           max_win_multiplier: 8000,
+          house_edge_bps: 500,
           is_active: true
         }
       };
@@ -561,50 +633,89 @@ describe("Lottery Tests", () => {
           {
             entity: entityPda,
             components: [
-              { componentId: walletComponent.programId }
+              { componentId: walletComponent.programId },
+              { componentId: lotteryBetComponent.programId }
             ]
           }
         ],
-        args
+        args,
+        extraAccounts: [
+          { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+        ],
       });
 
-      const txSign = await provider.sendAndConfirm(tx.transaction as any);
+      const txSign = await provider.sendAndConfirm(tx.transaction as any, [gameAuthority]);
       console.log(`\tReactivated the lottery. Tx signature: ${txSign}`);
 
       // Verify the lottery is now active again
       const updatedLotteryPrizeAccount = await lotteryPrizeComponent.account.lotteryPrize.fetch(lotteryPrizeComponentPda);
       expect(updatedLotteryPrizeAccount.isActive).to.equal(true);
     });
+
+    // Test updating the parameters without the game authority (should fail)
+    it("should reject parameter updates without the game authority", async () => {
+      const args = {
+        UpdateParams: {
+          min_bet_amount: 1,
+          win_probability: 10000, // Every bet would win
+          max_win_multiplier: 8000,
+          house_edge_bps: 0,
+          is_active: true
+        }
+      };
+
+      try {
+        const tx = await ApplySystem({
+          authority: provider.wallet.publicKey,
+          systemId: systemLottery.programId,
+          world: worldPda,
+          entities: [
+            {
+              entity: lotteryEntity,
+              components: [
+                { componentId: lotteryPrizeComponent.programId }
+              ]
+            },
+            {
+              entity: entityPda,
+              components: [
+                { componentId: walletComponent.programId },
+                { componentId: lotteryBetComponent.programId }
+              ]
+            }
+          ],
+          args
+        });
+
+        await provider.sendAndConfirm(tx.transaction as any);
+        expect.fail("Expected the update to require the game authority");
+      } catch (error) {
+        expect(error.toString()).to.include("Unauthorized");
+        console.log("\tParameter update without the game authority was correctly rejected");
+      }
+
+      const lotteryPrizeAccount = await lotteryPrizeComponent.account.lotteryPrize.fetch(lotteryPrizeComponentPda);
+      expect(lotteryPrizeAccount.winProbability).to.equal(1000);
+    });
   });
 
   describe("Lottery System Tests", () => {
     
-    // Test placing a single bet
-    it("should place a bet and handle the outcome correctly", async () => {
-      // Parameters for the bet
-      const betAmount = 10000000; // 10 AiFi tokens
+    // Test funding the prize pool before any bets are placed
+    it("should top up the prize pool", async () => {
+      const topUpAmount = 100000000; // 100 USDC
 
-      // Check current wallet balance
       const walletAccountBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
-      console.log(`\tAiFi balance before bet: ${walletAccountBefore.aifiBalance.toNumber() / 1000000} AiFi`);
-
-      // Generate randomness as a plain array
-      const randomnessArray = Array.from({ length: 32 }, () => Math.floor(Math.random() * 256));
+      const lotteryPrizeAccountBefore = await lotteryPrizeComponent.account.lotteryPrize.fetch(lotteryPrizeComponentPda);
 
       // Create structured arguments
       const args = {
-        PlaceBet: {
-          bet_amount: betAmount,
-          randomness: randomnessArray
+        TopUpPool: {
+          amount: topUpAmount
         }
       };
 
-      // Before state
-      const lotteryPrizeAccountBefore = await lotteryPrizeComponent.account.lotteryPrize.fetch(lotteryPrizeComponentPda);
-      const initialBalance = walletAccountBefore.aifiBalance;
-      const initialTotalBets = lotteryPrizeAccountBefore.totalBets;
-
-      // Use the ApplySystem to place a bet
+      // Use the ApplySystem to move USDC from the admin's wallet into the pool
       const tx = await ApplySystem({
         authority: provider.wallet.publicKey,
         systemId: systemLottery.programId,
@@ -619,92 +730,209 @@ describe("Lottery Tests", () => {
           {
             entity: entityPda,
             components: [
-              { componentId: walletComponent.programId }
+              { componentId: walletComponent.programId },
+              { componentId: lotteryBetComponent.programId }
             ]
           }
         ],
-        args
+        args,
+        extraAccounts: [
+          { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+        ],
       });
 
-      const txSign = await provider.sendAndConfirm(tx.transaction as any);
-      console.log(`\tPlaced a bet in the lottery. Tx signature: ${txSign}`);
+      const txSign = await provider.sendAndConfirm(tx.transaction as any, [gameAuthority]);
+      console.log(`\tTopped up the prize pool. Tx signature: ${txSign}`);
 
-      // After state
       const walletAccountAfter = await walletComponent.account.wallet.fetch(walletComponentPda);
       const lotteryPrizeAccountAfter = await lotteryPrizeComponent.account.lotteryPrize.fetch(lotteryPrizeComponentPda);
 
-      console.log("\tInitial AiFi balance:", initialBalance.toString());
-      console.log("\tFinal AiFi balance:", walletAccountAfter.aifiBalance.toString());
-      console.log("\tBet amount:", betAmount.toString());
+      expect(walletAccountBefore.usdcBalance.sub(walletAccountAfter.usdcBalance).toNumber()).to.equal(topUpAmount);
+      expect(lotteryPrizeAccountAfter.prizePool.sub(lotteryPrizeAccountBefore.prizePool).toNumber()).to.equal(topUpAmount);
+      console.log(`\tPrize pool: ${lotteryPrizeAccountAfter.prizePool.toNumber() / 1000000} USDC`);
+    });
 
-      // Check if player won
-      const playerWon = lotteryPrizeAccountAfter.totalWins.toNumber() > lotteryPrizeAccountBefore.totalWins.toNumber();
-      console.log("\tPlayer won:", playerWon);
+    // Test topping up the pool without the game authority (should fail)
+    it("should reject a top-up without the game authority", async () => {
+      const walletAccountBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
 
-      if (playerWon) {
-        // If the player won, their balance might be higher than before
-        console.log("\tPlayer won! Prize amount:",
-          (BigInt(walletAccountAfter.aifiBalance.toString()) -
-            BigInt(initialBalance.toString()) +
-            BigInt(betAmount)).toString());
+      const args = {
+        TopUpPool: {
+          amount: 1000000 // 1 USDC
+        }
+      };
 
-        expect(lotteryPrizeAccountAfter.recentWinners.length).to.be.greaterThan(0);
-      } else {
-        // If player lost, their balance should be lower by exactly the bet amount
-        expect(initialBalance.sub(walletAccountAfter.aifiBalance).toString()).to.equal(betAmount.toString());
+      try {
+        const tx = await ApplySystem({
+          authority: provider.wallet.publicKey,
+          systemId: systemLottery.programId,
+          world: worldPda,
+          entities: [
+            {
+              entity: lotteryEntity,
+              components: [
+                { componentId: lotteryPrizeComponent.programId }
+              ]
+            },
+            {
+              entity: entityPda,
+              components: [
+                { componentId: walletComponent.programId },
+                { componentId: lotteryBetComponent.programId }
+              ]
+            }
+          ],
+          args
+        });
+
+        await provider.sendAndConfirm(tx.transaction as any);
+        expect.fail("Expected the top-up to require the game authority");
+      } catch (error) {
+        expect(error.toString()).to.include("Unauthorized");
+        console.log("\tTop-up without the game authority was correctly rejected");
       }
 
-      // Total bets should always increase by 1
-      expect(lotteryPrizeAccountAfter.totalBets.toNumber()).to.equal(initialTotalBets.toNumber() + 1);
+      const walletAccountAfter = await walletComponent.account.wallet.fetch(walletComponentPda);
+      expect(walletAccountAfter.usdcBalance.toString()).to.equal(walletAccountBefore.usdcBalance.toString());
     });
 
-    // Test multiple bets to verify randomness and win/loss patterns
-    it("should allow multiple bets with random outcomes", async () => {
-      // We'll place 5 bets and track results
-      const betAmount = 5000000; // 5 AiFi per bet
+    // Test placing a bet without requesting VRF randomness (should fail)
+    it("should prevent betting without requesting VRF randomness", async () => {
+      const betAmount = 10000000; // 10 AiFi tokens
 
-      // Check current wallet balance
       const walletAccountBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
-      console.log(`\tAiFi balance before multiple bets: ${walletAccountBefore.aifiBalance.toNumber() / 1000000} AiFi`);
+      const lotteryPrizeAccountBefore = await lotteryPrizeComponent.account.lotteryPrize.fetch(lotteryPrizeComponentPda);
+
+      // Every bet must request its own randomness, never use bytes from the client
+      const args = {
+        PlaceBet: {
+          bet_amount: betAmount,
+          client_seed: 0
+        }
+      };
+
+      try {
+        const tx = await ApplySystem({
+          authority: provider.wallet.publicKey,
+          systemId: systemLottery.programId,
+          world: worldPda,
+          entities: [
+            {
+              entity: lotteryEntity,
+              components: [
+                { componentId: lotteryPrizeComponent.programId }
+              ]
+            },
+            {
+              entity: entityPda,
+              components: [
+                { componentId: walletComponent.programId },
+                { componentId: lotteryBetComponent.programId }
+              ]
+            }
+          ],
+          args
+        });
 
-      const initialBalance = walletAccountBefore.aifiBalance;
-      let totalBets = 0;
-      let wins = 0;
+        await provider.sendAndConfirm(tx.transaction as any);
+        expect.fail("Should not be able to place a bet without requesting VRF randomness");
+      } catch (error) {
+        expect(error.toString()).to.include("MissingRandomnessAccounts");
+        console.log("\tCorrectly prevented a bet without requesting VRF randomness");
+      }
+
+      // Nothing was taken from the player or added to the pool
+      const walletAccountAfter = await walletComponent.account.wallet.fetch(walletComponentPda);
+      const lotteryPrizeAccountAfter = await lotteryPrizeComponent.account.lotteryPrize.fetch(lotteryPrizeComponentPda);
+      expect(walletAccountAfter.aifiBalance.toString()).to.equal(walletAccountBefore.aifiBalance.toString());
+      expect(lotteryPrizeAccountAfter.prizePool.toString()).to.equal(lotteryPrizeAccountBefore.prizePool.toString());
+      expect(lotteryPrizeAccountAfter.totalBets.toNumber()).to.equal(lotteryPrizeAccountBefore.totalBets.toNumber());
+    });
 
-      // Get baseline stats
+    // Test escrowing a bet while its randomness is requested
+    it("should escrow a bet and request VRF randomness for it", async () => {
+      const betAmount = 10000000; // 10 AiFi tokens
+
+      const walletAccountBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
       const lotteryPrizeAccountBefore = await lotteryPrizeComponent.account.lotteryPrize.fetch(lotteryPrizeComponentPda);
-      const initialWins = lotteryPrizeAccountBefore.totalWins.toNumber();
-      const initialTotalBets = lotteryPrizeAccountBefore.totalBets.toNumber();
-      
-      // Get the win probability for reference
-      const winProbability = lotteryPrizeAccountBefore.winProbability.toString();
-      console.log(`\tCurrent win probability: ${winProbability} out of 10000 (${parseInt(winProbability)/100}%)`);
-
-      // Place multiple bets
-      for (let i = 0; i < 5; i++) {
-        // Generate randomness with a pattern to ensure some wins
-        // For the first and third bets, use a value that will result in a win
-        let randomnessArray;
-        
-        if (i === 1 || i === 3) {
-          // These are "rigged" to win - filling with low values to ensure
-          // that when converted to u64 and modulo 10000, it will be <= winProbability
-          randomnessArray = Array(32).fill(0);
-          randomnessArray[0] = parseInt(winProbability) > 100 ? 1 : 0; // A value that will be small after conversion
-        } else {
-          // Regular random values (likely to lose)
-          randomnessArray = Array.from({ length: 32 }, () => Math.floor(Math.random() * 256));
+      const lotteryBetAccountBefore = await lotteryBetComponent.account.lotteryBet.fetch(lotteryBetComponentPda);
+      const betId = lotteryBetAccountBefore.betCount.toNumber() + 1;
+
+      const args = {
+        PlaceBet: {
+          bet_amount: betAmount,
+          client_seed: Math.floor(Math.random() * 256)
         }
+      };
 
-        // Create structured arguments
-        const args = {
-          PlaceBet: {
-            bet_amount: betAmount,
-            randomness: randomnessArray
+      const tx = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemLottery.programId,
+        world: worldPda,
+        entities: [
+          {
+            entity: lotteryEntity,
+            components: [
+              { componentId: lotteryPrizeComponent.programId }
+            ]
+          },
+          {
+            entity: entityPda,
+            components: [
+              { componentId: walletComponent.programId },
+              { componentId: lotteryBetComponent.programId }
+            ]
           }
-        };
+        ],
+        args,
+        extraAccounts: [
+          { pubkey: provider.wallet.publicKey, isSigner: true, isWritable: true }, // payer of the request
+          { pubkey: betRandomnessPda(betId), isSigner: false, isWritable: true }, // randomness of this bet
+          { pubkey: vrfProgramIdentity, isSigner: false, isWritable: false }, // vrf_client identity
+          { pubkey: ORACLE_QUEUE, isSigner: false, isWritable: true }, // oracle queue
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false }, // system program
+          { pubkey: SYSVAR_SLOT_HASHES_PUBKEY, isSigner: false, isWritable: false }, // slot hashes
+          { pubkey: VRF_PROGRAM_ID, isSigner: false, isWritable: false }, // VRF program
+          { pubkey: vrfClient.programId, isSigner: false, isWritable: false }, // vrf_client program
+        ],
+      });
+
+      const txSign = await provider.sendAndConfirm(tx.transaction as any);
+      console.log(`\tPlaced a bet in the lottery. Tx signature: ${txSign}`);
+
+      const walletAccountAfter = await walletComponent.account.wallet.fetch(walletComponentPda);
+      const lotteryPrizeAccountAfter = await lotteryPrizeComponent.account.lotteryPrize.fetch(lotteryPrizeComponentPda);
+      const lotteryBetAccountAfter = await lotteryBetComponent.account.lotteryBet.fetch(lotteryBetComponentPda);
+
+      // The AiFi is held in escrow and nothing reaches the pool until the bet is settled
+      expect(walletAccountBefore.aifiBalance.sub(walletAccountAfter.aifiBalance).toNumber()).to.equal(betAmount);
+      expect(lotteryBetAccountAfter.status).to.equal(1);
+      expect(lotteryBetAccountAfter.betAmount.toNumber()).to.equal(betAmount);
+      expect(lotteryBetAccountAfter.betCount.toNumber()).to.equal(betId);
+      expect(lotteryBetAccountAfter.lottery.toBase58()).to.equal(lotteryPrizeComponentPda.toBase58());
+      expect(lotteryBetAccountAfter.wallet.toBase58()).to.equal(walletComponentPda.toBase58());
+      expect(lotteryBetAccountAfter.requester.toBase58()).to.equal(provider.wallet.publicKey.toBase58());
+      expect(lotteryPrizeAccountAfter.prizePool.toString()).to.equal(lotteryPrizeAccountBefore.prizePool.toString());
+      expect(lotteryPrizeAccountAfter.totalBets.toNumber()).to.equal(lotteryPrizeAccountBefore.totalBets.toNumber() + 1);
+
+      const betRandomness = await vrfClient.account.betRandomness.fetch(betRandomnessPda(betId));
+      expect(betRandomness.bet.toBase58()).to.equal(lotteryBetComponentPda.toBase58());
+      expect(betRandomness.betId.toNumber()).to.equal(betId);
+    });
+
+    // Test placing a second bet while the first one waits for randomness (should fail)
+    it("should prevent placing a bet while another is pending", async () => {
+      const lotteryBetAccount = await lotteryBetComponent.account.lotteryBet.fetch(lotteryBetComponentPda);
+      const betId = lotteryBetAccount.betCount.toNumber() + 1;
+
+      const args = {
+        PlaceBet: {
+          bet_amount: 5000000, // 5 AiFi
+          client_seed: 0
+        }
+      };
 
-        // Place the bet
+      try {
         const tx = await ApplySystem({
           authority: provider.wallet.publicKey,
           systemId: systemLottery.programId,
@@ -719,45 +947,147 @@ describe("Lottery Tests", () => {
             {
               entity: entityPda,
               components: [
-                { componentId: walletComponent.programId }
+                { componentId: walletComponent.programId },
+                { componentId: lotteryBetComponent.programId }
               ]
             }
           ],
-          args
+          args,
+          extraAccounts: [
+            { pubkey: provider.wallet.publicKey, isSigner: true, isWritable: true },
+            { pubkey: betRandomnessPda(betId), isSigner: false, isWritable: true },
+            { pubkey: vrfProgramIdentity, isSigner: false, isWritable: false },
+            { pubkey: ORACLE_QUEUE, isSigner: false, isWritable: true },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+            { pubkey: SYSVAR_SLOT_HASHES_PUBKEY, isSigner: false, isWritable: false },
+            { pubkey: VRF_PROGRAM_ID, isSigner: false, isWritable: false },
+            { pubkey: vrfClient.programId, isSigner: false, isWritable: false },
+          ],
         });
 
         await provider.sendAndConfirm(tx.transaction as any);
-        totalBets++;
+        expect.fail("Should not be able to place a second pending bet");
+      } catch (error) {
+        expect(error.toString()).to.include("BetPending");
+        console.log("\tCorrectly prevented a second pending bet");
+      }
+    });
 
-        // Check lottery state after bet
-        const lotteryPrizeAccount = await lotteryPrizeComponent.account.lotteryPrize.fetch(lotteryPrizeComponentPda);
+    // Test refunding a bet before the refund window (should fail)
+    it("should prevent refunding a bet before the refund window", async () => {
+      const lotteryBetAccount = await lotteryBetComponent.account.lotteryBet.fetch(lotteryBetComponentPda);
 
-        // If wins increased, player won this round
-        if (lotteryPrizeAccount.totalWins.toNumber() > initialWins + wins) {
-          wins++;
-          console.log(`\tRound ${i + 1}: Player WON!`);
-        } else {
-          console.log(`\tRound ${i + 1}: Player lost`);
-        }
+      try {
+        const tx = await ApplySystem({
+          authority: provider.wallet.publicKey,
+          systemId: systemLottery.programId,
+          world: worldPda,
+          entities: [
+            {
+              entity: lotteryEntity,
+              components: [
+                { componentId: lotteryPrizeComponent.programId }
+              ]
+            },
+            {
+              entity: entityPda,
+              components: [
+                { componentId: walletComponent.programId },
+                { componentId: lotteryBetComponent.programId }
+              ]
+            }
+          ],
+          args: {
+            RefundBet: {
+              bet_id: lotteryBetAccount.betCount.toNumber()
+            }
+          },
+          extraAccounts: [
+            { pubkey: betRandomnessPda(lotteryBetAccount.betCount.toNumber()), isSigner: false, isWritable: false },
+          ],
+        });
+
+        await provider.sendAndConfirm(tx.transaction as any);
+        expect.fail("Should not be able to refund a bet this early");
+      } catch (error) {
+        expect(error.toString()).to.include("BetNotExpired");
+        console.log("\tCorrectly prevented an early refund");
       }
+    });
 
-      // Final state
-      const walletAccountAfter = await walletComponent.account.wallet.fetch(walletComponentPda);
-      const lotteryPrizeAccountAfter = await lotteryPrizeComponent.account.lotteryPrize.fetch(lotteryPrizeComponentPda);
+    // Test finishing the pending bet, settling it if the VRF callback arrived or refunding it otherwise
+    it("should settle or refund the pending bet", async () => {
+      const walletAccountBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
+      const lotteryPrizeAccountBefore = await lotteryPrizeComponent.account.lotteryPrize.fetch(lotteryPrizeComponentPda);
+      const lotteryBetAccountBefore = await lotteryBetComponent.account.lotteryBet.fetch(lotteryBetComponentPda);
+      const betAmount = lotteryBetAccountBefore.betAmount.toNumber();
+      const randomnessPda = betRandomnessPda(lotteryBetAccountBefore.betCount.toNumber());
+
+      // Wait out the refund window (150 slots)
+      const refundSlot = lotteryBetAccountBefore.placedSlot.toNumber() + 150;
+      while (await provider.connection.getSlot() < refundSlot) {
+        await new Promise(resolve => setTimeout(resolve, 2000));
+      }
 
-      console.log("\t-------- Lottery Summary --------");
-      console.log(`\tTotal bets placed: ${totalBets}`);
-      console.log(`\tWins: ${wins} (${(wins / totalBets * 100).toFixed(1)}% win rate)`);
-      console.log(`\tInitial AiFi balance: ${initialBalance.toString()}`);
-      console.log(`\tFinal AiFi balance: ${walletAccountAfter.aifiBalance.toString()}`);
-      console.log(`\tNet profit/loss: ${walletAccountAfter.aifiBalance.sub(initialBalance).toString()}`);
-      console.log("\t--------------------------------");
+      const betRandomness = await vrfClient.account.betRandomness.fetch(randomnessPda);
+      const delivered = betRandomness.isFulfilled;
+      console.log("\tRandomness delivered:", delivered);
 
-      // Check that the lottery total bets increased correctly
-      expect(lotteryPrizeAccountAfter.totalBets.toNumber()).to.equal(initialTotalBets + totalBets);
-      
-      // Verify the win count matches our tracking
-      expect(lotteryPrizeAccountAfter.totalWins.toNumber()).to.equal(initialWins + wins);
+      const tx = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemLottery.programId,
+        world: worldPda,
+        entities: [
+          {
+            entity: lotteryEntity,
+            components: [
+              { componentId: lotteryPrizeComponent.programId }
+            ]
+          },
+          {
+            entity: entityPda,
+            components: [
+              { componentId: walletComponent.programId },
+              { componentId: lotteryBetComponent.programId }
+            ]
+          }
+        ],
+        args: delivered
+          ? { SettleBet: { bet_id: lotteryBetAccountBefore.betCount.toNumber() } }
+          : { RefundBet: { bet_id: lotteryBetAccountBefore.betCount.toNumber() } },
+        extraAccounts: [
+          { pubkey: randomnessPda, isSigner: false, isWritable: false }, // randomness of the pending bet
+        ],
+      });
+
+      const txSign = await provider.sendAndConfirm(tx.transaction as any);
+      console.log(`\tFinished the pending bet. Tx signature: ${txSign}`);
+
+      const walletAccountAfter = await walletComponent.account.wallet.fetch(walletComponentPda);
+      const lotteryPrizeAccountAfter = await lotteryPrizeComponent.account.lotteryPrize.fetch(lotteryPrizeComponentPda);
+      const lotteryBetAccountAfter = await lotteryBetComponent.account.lotteryBet.fetch(lotteryBetComponentPda);
+
+      expect(lotteryBetAccountAfter.status).to.equal(0);
+      expect(lotteryBetAccountAfter.betAmount.toNumber()).to.equal(0);
+
+      if (delivered) {
+        // The bet funds the pool after the house edge, and any prize is paid out of the pool
+        const betValue = betAmount * 5; // 5 USDC per AiFi
+        const houseCut = Math.floor(betValue * lotteryPrizeAccountBefore.houseEdgeBps / 10000);
+        const prize = walletAccountAfter.usdcBalance.sub(walletAccountBefore.usdcBalance).toNumber();
+        expect(lotteryBetAccountAfter.lastOutcome).to.be.oneOf([1, 2]);
+        expect(lotteryBetAccountAfter.lastPrize.toNumber()).to.equal(prize);
+        expect(lotteryPrizeAccountAfter.prizePool.toNumber()).to.equal(
+          lotteryPrizeAccountBefore.prizePool.toNumber() + betValue - houseCut - prize
+        );
+        console.log(`\tBet settled, prize: ${prize / 1000000} USDC`);
+      } else {
+        // The escrow goes back to the player untouched
+        expect(lotteryBetAccountAfter.lastOutcome).to.equal(3);
+        expect(walletAccountAfter.aifiBalance.sub(walletAccountBefore.aifiBalance).toNumber()).to.equal(betAmount);
+        expect(lotteryPrizeAccountAfter.prizePool.toString()).to.equal(lotteryPrizeAccountBefore.prizePool.toString());
+        console.log(`\tBet refunded: ${betAmount / 1000000} AiFi`);
+      }
     });
 
     // Test placing a bet on inactive lottery (should fail)
@@ -768,6 +1098,7 @@ describe("Lottery Tests", () => {
           min_bet_amount: 2000000, // Use the same values that worked in earlier tests
           win_probability: 1000,
           max_win_multiplier: 8000,
+          house_edge_bps: 500,
           is_active: false
         }
       };
@@ -787,23 +1118,26 @@ describe("Lottery Tests", () => {
           {
             entity: entityPda,
             components: [
-              { componentId: walletComponent.programId }
+              { componentId: walletComponent.programId },
+              { componentId: lotteryBetComponent.programId }
             ]
           }
         ],
-        args: deactivateArgs
+        args: deactivateArgs,
+        extraAccounts: [
+          { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+        ],
       });
 
-      await provider.sendAndConfirm(tx.transaction as any);
+      await provider.sendAndConfirm(tx.transaction as any, [gameAuthority]);
       
       // Now try to place a bet
       const betAmount = 5000000; // 5 AiFi
-      const randomnessArray = Array.from({ length: 32 }, () => Math.floor(Math.random() * 256));
       
       const betArgs = {
         PlaceBet: {
           bet_amount: betAmount,
-          randomness: randomnessArray
+          client_seed: 0
         }
       };
       
@@ -822,7 +1156,8 @@ describe("Lottery Tests", () => {
           {
             entity: entityPda,
             components: [
-              { componentId: walletComponent.programId }
+              { componentId: walletComponent.programId },
+              { componentId: lotteryBetComponent.programId }
             ]
           }
         ],
@@ -845,6 +1180,7 @@ describe("Lottery Tests", () => {
           min_bet_amount: 2000000, // Use the same values that worked in earlier tests
           win_probability: 1000,
           max_win_multiplier: 8000,
+          house_edge_bps: 500,
           is_active: true
         }
       };
@@ -863,14 +1199,18 @@ describe("Lottery Tests", () => {
           {
             entity: entityPda,
             components: [
-              { componentId: walletComponent.programId }
+              { componentId: walletComponent.programId },
+              { componentId: lotteryBetComponent.programId }
             ]
           }
         ],
-        args: reactivateArgs
+        args: reactivateArgs,
+        extraAccounts: [
+          { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+        ],
       });
       
-      await provider.sendAndConfirm(tx.transaction as any);
+      await provider.sendAndConfirm(tx.transaction as any, [gameAuthority]);
     });
 
     // Test placing a bet with insufficient funds
@@ -880,12 +1220,10 @@ describe("Lottery Tests", () => {
       // Try to bet more than available balance
       const betAmount = walletAccountBefore.aifiBalance.toNumber() + 1000000; // More than available
       
-      const randomnessArray = Array.from({ length: 32 }, () => Math.floor(Math.random() * 256));
-      
       const args = {
         PlaceBet: {
           bet_amount: betAmount,
-          randomness: randomnessArray
+          client_seed: 0
         }
       };
       
@@ -904,7 +1242,8 @@ describe("Lottery Tests", () => {
           {
             entity: entityPda,
             components: [
-              { componentId: walletComponent.programId }
+              { componentId: walletComponent.programId },
+              { componentId: lotteryBetComponent.programId }
             ]
           }
         ],
@@ -931,12 +1270,10 @@ describe("Lottery Tests", () => {
       // Try to bet less than minimum
       const betAmount = Math.max(1, minBetAmount - 1); // One less than minimum
       
-      const randomnessArray = Array.from({ length: 32 }, () => Math.floor(Math.random() * 256));
-      
       const args = {
         PlaceBet: {
           bet_amount: betAmount,
-          randomness: randomnessArray
+          client_seed: 0
         }
       };
       
@@ -955,7 +1292,8 @@ describe("Lottery Tests", () => {
           {
             entity: entityPda,
             components: [
-              { componentId: walletComponent.programId }
+              { componentId: walletComponent.programId },
+              { componentId: lotteryBetComponent.programId }
             ]
           }
         ],
@@ -972,5 +1310,103 @@ describe("Lottery Tests", () => {
         console.log("\tCorrectly prevented bet below minimum amount");
       }
     });
+
+    // Test rejecting a house edge above the maximum
+    it("should reject a house edge above the maximum", async () => {
+      const args = {
+        UpdateParams: {
+          min_bet_amount: 2000000,
+          win_probability: 1000,
+          max_win_multiplier: 8000,
+          house_edge_bps: 5001, // Above the 50% maximum
+          is_active: true
+        }
+      };
+
+      try {
+        const tx = await ApplySystem({
+          authority: provider.wallet.publicKey,
+          systemId: systemLottery.programId,
+          world: worldPda,
+          entities: [
+            {
+              entity: lotteryEntity,
+              components: [
+                { componentId: lotteryPrizeComponent.programId }
+              ]
+            },
+            {
+              entity: entityPda,
+              components: [
+                { componentId: walletComponent.programId },
+                { componentId: lotteryBetComponent.programId }
+              ]
+            }
+          ],
+          args,
+          extraAccounts: [
+            { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+          ],
+        });
+
+        await provider.sendAndConfirm(tx.transaction as any, [gameAuthority]);
+        expect.fail("Expected the house edge to be rejected");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidHouseEdge");
+        console.log("\tHouse edge above the maximum was correctly rejected");
+      }
+
+      const lotteryPrizeAccount = await lotteryPrizeComponent.account.lotteryPrize.fetch(lotteryPrizeComponentPda);
+      expect(lotteryPrizeAccount.houseEdgeBps).to.equal(500);
+    });
+
+    // Test rejecting a max win multiplier that is not above 1x
+    it("should reject a max win multiplier of 1x or less", async () => {
+      const args = {
+        UpdateParams: {
+          min_bet_amount: 2000000,
+          win_probability: 1000,
+          max_win_multiplier: 1000, // Prizes would never pay more than the bet
+          house_edge_bps: 500,
+          is_active: true
+        }
+      };
+
+      try {
+        const tx = await ApplySystem({
+          authority: provider.wallet.publicKey,
+          systemId: systemLottery.programId,
+          world: worldPda,
+          entities: [
+            {
+              entity: lotteryEntity,
+              components: [
+                { componentId: lotteryPrizeComponent.programId }
+              ]
+            },
+            {
+              entity: entityPda,
+              components: [
+                { componentId: walletComponent.programId },
+                { componentId: lotteryBetComponent.programId }
+              ]
+            }
+          ],
+          args,
+          extraAccounts: [
+            { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+          ],
+        });
+
+        await provider.sendAndConfirm(tx.transaction as any, [gameAuthority]);
+        expect.fail("Expected the max win multiplier to be rejected");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidMaxWinMultiplier");
+        console.log("\tMax win multiplier of 1x was correctly rejected");
+      }
+
+      const lotteryPrizeAccount = await lotteryPrizeComponent.account.lotteryPrize.fetch(lotteryPrizeComponentPda);
+      expect(lotteryPrizeAccount.maxWinMultiplier).to.equal(8000);
+    });
   });
 });
@@ -1,16 +1,38 @@
-import { PublicKey } from "@solana/web3.js";
+import { Keypair, PublicKey, SYSVAR_INSTRUCTIONS_PUBKEY } from "@solana/web3.js";
 import { Position } from "../target/types/position";
 import { Movement } from "../target/types/movement";
 import { Wallet } from "../target/types/wallet";
 import { Ownership } from "../target/types/ownership";
 import { Production } from "../target/types/production";
+import { Durability } from "../target/types/durability";
+import { Facility } from "../target/types/facility";
 import { Upgradeable } from "../target/types/upgradeable";
 import { Stakeable } from "../target/types/stakeable";
+import { StakingPool } from "../target/types/staking_pool";
+import { LockTiers } from "../target/types/lock_tiers";
+import { PenaltyLedger } from "../target/types/penalty_ledger";
+import { LotteryPrize } from "../target/types/lottery_prize";
+import { VoteEscrow } from "../target/types/vote_escrow";
+import { FeePool } from "../target/types/fee_pool";
+import { AifiLock } from "../target/types/aifi_lock";
+import { HostingAgreement } from "../target/types/hosting_agreement";
+import { Hosting } from "../target/types/hosting";
 import { Price } from "../target/types/price";
 import { Economy } from "../target/types/economy";
 import { ResourceProduction } from "../target/types/resource_production";
 import { Upgrade } from "../target/types/upgrade";
 import { Staking } from "../target/types/staking";
+import { Maintenance } from "../target/types/maintenance";
+import { GpuModel } from "../target/types/gpu_model";
+import { GpuCatalog } from "../target/types/gpu_catalog";
+import { SpawnGpu } from "../target/types/spawn_gpu";
+import { FuseGpus } from "../target/types/fuse_gpus";
+import { SalvageGpu } from "../target/types/salvage_gpu";
+import { PlayerProfile } from "../target/types/player_profile";
+import { Prestige } from "../target/types/prestige";
+import { UpgradeTable } from "../target/types/upgrade_table";
+import { UpgradeCurve } from "../target/types/upgrade_curve";
+import { YieldWarsProgram } from "../target/types/yield_wars_program";
 import {
     InitializeNewWorld,
     AddEntity,
@@ -31,35 +53,87 @@ describe("yield-wars-program", () => {
   console.log("Provider wallet:", provider.wallet.publicKey.toBase58());
   console.log("Provider connection:", provider.connection.rpcEndpoint);
 
+  // Game authority managing the economy settings, generated by `yarn localnet:authority`
+  // and pinned into crates/game-config at build time
+  const gameAuthority = Keypair.fromSecretKey(Uint8Array.from(
+    JSON.parse(fs.readFileSync(path.join(__dirname, "fixtures", "game-authority.json"), "utf8"))
+  ));
+
+  // Key the second player signs hosting agreements with
+  const hostAuthority = Keypair.generate();
+
+  // Wait until the cluster clock, which gates cooldowns, terms and locks, reaches `time`
+  const waitForClusterTime = async (time: number) => {
+    while ((await provider.connection.getBlockTime(await provider.connection.getSlot())) < time) {
+      await new Promise(resolve => setTimeout(resolve, 1000));
+    }
+  };
+
   // Constants used to test the program.
   let worldPda: PublicKey;
   let entityPda: PublicKey;
   let entity2Pda: PublicKey; // Second entity for transfer tests
+  let catalogEntityPda: PublicKey; // GPU model catalog entry
   let positionComponentPda: PublicKey;
   let walletComponentPda: PublicKey;
   let wallet2ComponentPda: PublicKey; // Second wallet for transfer tests
   let ownershipComponentPda: PublicKey;
   let productionComponentPda: PublicKey;
+  let durabilityComponentPda: PublicKey;
+  let facilityComponentPda: PublicKey;
   let upgradeableComponentPda: PublicKey;
   let stakeableComponentPda: PublicKey;
+  let playerProfileComponentPda: PublicKey;
+  let gpuModelComponentPda: PublicKey;
+  let upgradeTableComponentPda: PublicKey;
+  let treasuryComponentPda: PublicKey; // Treasury wallet of the catalog
+  let stakingPoolComponentPda: PublicKey; // Staking pool of the catalog
+  let lockTiersComponentPda: PublicKey; // Lock tier table of the catalog
+  let penaltyLedgerComponentPda: PublicKey; // Penalty ledger of the catalog
+  let lotteryPrizeComponentPda: PublicKey; // Lottery prize pool fed by penalties
+  let voteEscrowComponentPda: PublicKey; // Locked AiFi position of the player
+  let feePoolComponentPda: PublicKey; // Protocol fees shared by the AiFi positions
+  let hostingAgreementComponentPda: PublicKey; // Hosting agreement of the GPU
   let priceComponentPda: PublicKey;
   let priceBtcComponentPda: PublicKey; // BTC price component for exchange tests 
   let priceEthComponentPda: PublicKey; // ETH price component for exchange tests
   let priceSolComponentPda: PublicKey; // SOL price component for leaderboard tests
-  let priceAifiComponentPda: PublicKey; // AiFi price component for leaderboard tests
+  let priceAifiComponentPda: PublicKey; // AiFi price component of the catalog, used by staking
 
   const positionComponent = anchor.workspace.Position as Program<Position>;
   const systemMovement = anchor.workspace.Movement as Program<Movement>;
   const walletComponent = anchor.workspace.Wallet as Program<Wallet>;
   const ownershipComponent = anchor.workspace.Ownership as Program<Ownership>;
   const productionComponent = anchor.workspace.Production as Program<Production>;
+  const durabilityComponent = anchor.workspace.Durability as Program<Durability>;
+  const facilityComponent = anchor.workspace.Facility as Program<Facility>;
   const upgradeableComponent = anchor.workspace.Upgradeable as Program<Upgradeable>;
   const stakeableComponent = anchor.workspace.Stakeable as Program<Stakeable>;
+  const stakingPoolComponent = anchor.workspace.StakingPool as Program<StakingPool>;
+  const lockTiersComponent = anchor.workspace.LockTiers as Program<LockTiers>;
+  const penaltyLedgerComponent = anchor.workspace.PenaltyLedger as Program<PenaltyLedger>;
+  const lotteryPrizeComponent = anchor.workspace.LotteryPrize as Program<LotteryPrize>;
+  const voteEscrowComponent = anchor.workspace.VoteEscrow as Program<VoteEscrow>;
+  const feePoolComponent = anchor.workspace.FeePool as Program<FeePool>;
+  const systemAifiLock = anchor.workspace.AifiLock as Program<AifiLock>;
+  const hostingAgreementComponent = anchor.workspace.HostingAgreement as Program<HostingAgreement>;
+  const systemHosting = anchor.workspace.Hosting as Program<Hosting>;
   const priceComponent = anchor.workspace.Price as Program<Price>;
   const systemEconomy = anchor.workspace.Economy as Program<Economy>;
   const systemResourceProduction = anchor.workspace.ResourceProduction as Program<ResourceProduction>;
   const systemUpgrade = anchor.workspace.Upgrade as Program<Upgrade>;
   const systemStaking = anchor.workspace.Staking as Program<Staking>;
+  const systemMaintenance = anchor.workspace.Maintenance as Program<Maintenance>;
+  const gpuModelComponent = anchor.workspace.GpuModel as Program<GpuModel>;
+  const systemGpuCatalog = anchor.workspace.GpuCatalog as Program<GpuCatalog>;
+  const systemSpawnGpu = anchor.workspace.SpawnGpu as Program<SpawnGpu>;
+  const systemFuseGpus = anchor.workspace.FuseGpus as Program<FuseGpus>;
+  const systemSalvageGpu = anchor.workspace.SalvageGpu as Program<SalvageGpu>;
+  const playerProfileComponent = anchor.workspace.PlayerProfile as Program<PlayerProfile>;
+  const systemPrestige = anchor.workspace.Prestige as Program<Prestige>;
+  const upgradeTableComponent = anchor.workspace.UpgradeTable as Program<UpgradeTable>;
+  const systemUpgradeCurve = anchor.workspace.UpgradeCurve as Program<UpgradeCurve>;
+  const yieldWarsProgram = anchor.workspace.YieldWarsProgram as Program<YieldWarsProgram>;
 
   // Entity type enum values
   const ENTITY_TYPE = {
@@ -174,6 +248,39 @@ describe("yield-wars-program", () => {
     console.log(`Initialized the production component. Initialization signature: ${txSign}`);
   });
 
+  it("Add a durability component", async () => {
+    const initializeComponent = await InitializeComponent({
+      payer: provider.wallet.publicKey,
+      entity: entityPda,
+      componentId: durabilityComponent.programId,
+    });
+    const txSign = await provider.sendAndConfirm(initializeComponent.transaction);
+    durabilityComponentPda = initializeComponent.componentPda;
+    console.log(`Initialized the durability component. Initialization signature: ${txSign}`);
+  });
+
+  it("Add a facility component", async () => {
+    const initializeComponent = await InitializeComponent({
+      payer: provider.wallet.publicKey,
+      entity: entityPda,
+      componentId: facilityComponent.programId,
+    });
+    const txSign = await provider.sendAndConfirm(initializeComponent.transaction);
+    facilityComponentPda = initializeComponent.componentPda;
+    console.log(`Initialized the facility component. Initialization signature: ${txSign}`);
+  });
+
+  it("Add a hosting agreement component", async () => {
+    const initializeComponent = await InitializeComponent({
+      payer: provider.wallet.publicKey,
+      entity: entityPda,
+      componentId: hostingAgreementComponent.programId,
+    });
+    const txSign = await provider.sendAndConfirm(initializeComponent.transaction);
+    hostingAgreementComponentPda = initializeComponent.componentPda;
+    console.log(`Initialized the hosting agreement component. Initialization signature: ${txSign}`);
+  });
+
   it("Add an upgradeable component", async () => {
     const initializeComponent = await InitializeComponent({
       payer: provider.wallet.publicKey,
@@ -196,6 +303,138 @@ describe("yield-wars-program", () => {
     console.log(`Initialized the stakeable component. Initialization signature: ${txSign}`);
   });
 
+  it("Add a player profile component", async () => {
+    const initializeComponent = await InitializeComponent({
+      payer: provider.wallet.publicKey,
+      entity: entityPda,
+      componentId: playerProfileComponent.programId,
+    });
+    const txSign = await provider.sendAndConfirm(initializeComponent.transaction);
+    playerProfileComponentPda = initializeComponent.componentPda;
+    console.log(`Initialized the player profile component. Initialization signature: ${txSign}`);
+  });
+
+  it("Add a vote-escrow component", async () => {
+    const initializeComponent = await InitializeComponent({
+      payer: provider.wallet.publicKey,
+      entity: entityPda,
+      componentId: voteEscrowComponent.programId,
+    });
+    const txSign = await provider.sendAndConfirm(initializeComponent.transaction);
+    voteEscrowComponentPda = initializeComponent.componentPda;
+    console.log(`Initialized the vote-escrow component. Initialization signature: ${txSign}`);
+  });
+
+  it("Add a GPU model catalog entity", async () => {
+    const addEntity = await AddEntity({
+      payer: provider.wallet.publicKey,
+      world: worldPda,
+      connection: provider.connection,
+    });
+    const txSign = await provider.sendAndConfirm(addEntity.transaction);
+    catalogEntityPda = addEntity.entityPda;
+    console.log(`Initialized the catalog Entity (ID=${addEntity.entityPda}). Initialization signature: ${txSign}`);
+  });
+
+  it("Add a GPU model component to the catalog entity", async () => {
+    const initializeComponent = await InitializeComponent({
+      payer: provider.wallet.publicKey,
+      entity: catalogEntityPda,
+      componentId: gpuModelComponent.programId,
+    });
+    const txSign = await provider.sendAndConfirm(initializeComponent.transaction);
+    gpuModelComponentPda = initializeComponent.componentPda;
+    console.log(`Initialized the GPU model component. Initialization signature: ${txSign}`);
+  });
+
+  it("Add an upgrade table component to the catalog entity", async () => {
+    const initializeComponent = await InitializeComponent({
+      payer: provider.wallet.publicKey,
+      entity: catalogEntityPda,
+      componentId: upgradeTableComponent.programId,
+    });
+    const txSign = await provider.sendAndConfirm(initializeComponent.transaction);
+    upgradeTableComponentPda = initializeComponent.componentPda;
+    console.log(`Initialized the upgrade table component. Initialization signature: ${txSign}`);
+  });
+
+  it("Add a treasury wallet component to the catalog entity", async () => {
+    const initializeComponent = await InitializeComponent({
+      payer: provider.wallet.publicKey,
+      entity: catalogEntityPda,
+      componentId: walletComponent.programId,
+    });
+    const txSign = await provider.sendAndConfirm(initializeComponent.transaction);
+    treasuryComponentPda = initializeComponent.componentPda;
+    console.log(`Initialized the treasury wallet component. Initialization signature: ${txSign}`);
+  });
+
+  it("Add an AiFi price component to the catalog entity", async () => {
+    const initializeComponent = await InitializeComponent({
+      payer: provider.wallet.publicKey,
+      entity: catalogEntityPda,
+      componentId: priceComponent.programId,
+    });
+    const txSign = await provider.sendAndConfirm(initializeComponent.transaction);
+    priceAifiComponentPda = initializeComponent.componentPda;
+    console.log(`Initialized the AiFi price component. Initialization signature: ${txSign}`);
+  });
+
+  it("Add a staking pool component to the catalog entity", async () => {
+    const initializeComponent = await InitializeComponent({
+      payer: provider.wallet.publicKey,
+      entity: catalogEntityPda,
+      componentId: stakingPoolComponent.programId,
+    });
+    const txSign = await provider.sendAndConfirm(initializeComponent.transaction);
+    stakingPoolComponentPda = initializeComponent.componentPda;
+    console.log(`Initialized the staking pool component. Initialization signature: ${txSign}`);
+  });
+
+  it("Add a lock tier table component to the catalog entity", async () => {
+    const initializeComponent = await InitializeComponent({
+      payer: provider.wallet.publicKey,
+      entity: catalogEntityPda,
+      componentId: lockTiersComponent.programId,
+    });
+    const txSign = await provider.sendAndConfirm(initializeComponent.transaction);
+    lockTiersComponentPda = initializeComponent.componentPda;
+    console.log(`Initialized the lock tier table component. Initialization signature: ${txSign}`);
+  });
+
+  it("Add a penalty ledger component to the catalog entity", async () => {
+    const initializeComponent = await InitializeComponent({
+      payer: provider.wallet.publicKey,
+      entity: catalogEntityPda,
+      componentId: penaltyLedgerComponent.programId,
+    });
+    const txSign = await provider.sendAndConfirm(initializeComponent.transaction);
+    penaltyLedgerComponentPda = initializeComponent.componentPda;
+    console.log(`Initialized the penalty ledger component. Initialization signature: ${txSign}`);
+  });
+
+  it("Add a lottery prize component to the catalog entity", async () => {
+    const initializeComponent = await InitializeComponent({
+      payer: provider.wallet.publicKey,
+      entity: catalogEntityPda,
+      componentId: lotteryPrizeComponent.programId,
+    });
+    const txSign = await provider.sendAndConfirm(initializeComponent.transaction);
+    lotteryPrizeComponentPda = initializeComponent.componentPda;
+    console.log(`Initialized the lottery prize component. Initialization signature: ${txSign}`);
+  });
+
+  it("Add a fee pool component to the catalog entity", async () => {
+    const initializeComponent = await InitializeComponent({
+      payer: provider.wallet.publicKey,
+      entity: catalogEntityPda,
+      componentId: feePoolComponent.programId,
+    });
+    const txSign = await provider.sendAndConfirm(initializeComponent.transaction);
+    feePoolComponentPda = initializeComponent.componentPda;
+    console.log(`Initialized the fee pool component. Initialization signature: ${txSign}`);
+  });
+
   it("Add a price component for USDC", async () => {
     const initializeComponent = await InitializeComponent({
       payer: provider.wallet.publicKey,
@@ -268,8 +507,9 @@ describe("yield-wars-program", () => {
     expect(upgradeable.nextUpgradeUsdcCost.toNumber()).to.equal(0);
     expect(upgradeable.nextUpgradeAifiCost.toNumber()).to.equal(0);
     expect(upgradeable.upgradeCooldown).to.equal(0);
-    expect(upgradeable.nextUsdcBoost).to.equal(0);
-    expect(upgradeable.nextAifiBoost).to.equal(0);
+    expect(upgradeable.nextUsdcGain.toNumber()).to.equal(0);
+    expect(upgradeable.nextAifiGain.toNumber()).to.equal(0);
+    expect(upgradeable.nextRequiredPlayerLevel).to.equal(0);
   });
 
   it("Verify stakeable initial state", async () => {
@@ -904,58 +1144,408 @@ describe("yield-wars-program", () => {
     }
   });
 
-  it("Initialize production settings", async () => {
-    // Get current unix timestamp in seconds
-    const currentTime = Math.floor(Date.now() / 1000);
-    
-    // Initial production settings for a GPU miner
-    const initialArgs = {
-      operation_type: 0, // INITIALIZE
-      usdc_per_hour: 5000000, // 5 USDC per hour with 6 decimal places (5,000,000 = $5)
-      aifi_per_hour: 10000000, // 10 AiFi per hour with 6 decimal places (10,000,000 = $10)
-      current_time: currentTime,
-      producer_type: 1, // GPU type
-      level: 1, // Level 1 GPU
-      is_active: false, // Start inactive
-      operating_cost: 1000000, // 1 USDC per hour with 6 decimal places (1,000,000 = $1)
-      efficiency_multiplier: 10000 // 100% efficiency (10000 = 100%)
+  it("Register a GPU model in the catalog", async () => {
+    // Stats of a datacenter-class GPU model
+    const registerArgs = {
+      operation_type: 0, // REGISTER_MODEL
+      model_id: 1,
+      usdc_per_hour: 7500000, // 7.5 USDC per hour
+      aifi_per_hour: 15000000, // 15 AiFi per hour
+      btc_per_hour: 0, // does not mine BTC
+      eth_per_hour: 0, // does not mine ETH
+      sol_per_hour: 100000, // 0.1 SOL per hour from validator rewards
+      operating_cost: 1500000, // 1.5 USDC per hour
+      efficiency_multiplier: 12000, // 120% efficiency
+      min_staking_period: 20, // Short lock so the tests can outlast it
+      reward_rate: 15000, // 150% of the production value
+      unstaking_penalty: 5000, // 50% penalty for early unstaking
+      is_available: true,
+      fusion_model_id: 0, // cannot be fused
+      fusion_aifi_fee: 0,
+      salvage_usdc_value: 500000000, // worth 500 USDC new
+      salvage_aifi_value: 0,
+      salvage_rate: 5000, // 50% refunded on salvage
     };
     
-    // Apply the system to initialize production
+    // The game authority administers the catalog, it signs and is passed after the components
     const applySystem = await ApplySystem({
       authority: provider.wallet.publicKey,
-      systemId: systemResourceProduction.programId,
+      systemId: systemGpuCatalog.programId,
       world: worldPda,
       entities: [{
-        entity: entityPda,
+        entity: catalogEntityPda,
         components: [
-          { componentId: productionComponent.programId }, // production component
-          { componentId: walletComponent.programId },     // wallet component
+          { componentId: gpuModelComponent.programId }, // gpu model component
         ],
       }],
-      args: initialArgs,
+      args: registerArgs,
+      extraAccounts: [
+        { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+      ],
+    });
+    
+    const txSign = await provider.sendAndConfirm(applySystem.transaction, [gameAuthority]);
+    console.log(`Applied gpu-catalog system to register a model. Signature: ${txSign}`);
+    
+    const gpuModel = await gpuModelComponent.account.gpuModel.fetch(gpuModelComponentPda);
+    expect(gpuModel.modelId).to.equal(1);
+    expect(gpuModel.isAvailable).to.equal(true);
+    expect(gpuModel.authority.toBase58()).to.equal(gameAuthority.publicKey.toBase58());
+    expect(gpuModel.usdcPerHour.toNumber()).to.equal(7500000);
+    expect(gpuModel.solPerHour.toNumber()).to.equal(100000);
+    expect(gpuModel.salvageRate).to.equal(5000);
+  });
+
+  it("Attempt to update a GPU model without the game authority (should fail)", async () => {
+    const updateArgs = {
+      operation_type: 1, // UPDATE_MODEL
+      model_id: 1,
+      usdc_per_hour: 750000000, // 100x the catalog rate
+      aifi_per_hour: 15000000,
+      btc_per_hour: 0,
+      eth_per_hour: 0,
+      sol_per_hour: 100000,
+      operating_cost: 0,
+      efficiency_multiplier: 12000,
+      min_staking_period: 20,
+      reward_rate: 15000,
+      unstaking_penalty: 5000,
+      is_available: true,
+      fusion_model_id: 0,
+      fusion_aifi_fee: 0,
+      salvage_usdc_value: 500000000,
+      salvage_aifi_value: 0,
+      salvage_rate: 5000,
+    };
+    
+    try {
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemGpuCatalog.programId,
+        world: worldPda,
+        entities: [{
+          entity: catalogEntityPda,
+          components: [
+            { componentId: gpuModelComponent.programId }, // gpu model component
+          ],
+        }],
+        args: updateArgs,
+        extraAccounts: [
+          { pubkey: provider.wallet.publicKey, isSigner: true, isWritable: false }, // not the game authority
+        ],
+      });
+      
+      await provider.sendAndConfirm(applySystem.transaction);
+      expect.fail("Only the game authority should manage the catalog");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Only the game authority should manage the catalog");
+      console.log(`Catalog update correctly rejected without the game authority`);
+    }
+    
+    const gpuModel = await gpuModelComponent.account.gpuModel.fetch(gpuModelComponentPda);
+    expect(gpuModel.usdcPerHour.toNumber()).to.equal(7500000);
+  });
+
+  it("Configure the GPU model upgrade table", async () => {
+    const baseArgs = {
+      operation_type: 0, // REGISTER_TABLE
+      entity_type: ENTITY_TYPE.GPU,
+      model_id: 1,
+      level: 0,
+      usdc_cost: 0,
+      aifi_cost: 0,
+      usdc_gain: 0,
+      aifi_gain: 0,
+      btc_gain: 0,
+      eth_gain: 0,
+      sol_gain: 0,
+      cooldown: 0,
+      required_player_level: 0,
+      build_duration: 0,
+      construction_yield: 0,
+      speed_up_aifi_per_hour: 0,
+      cancel_refund_rate: 0,
+      success_rate: 0,
+      critical_failure_rate: 0,
+      risk_start_level: 0,
+      protection_aifi_cost: 0,
+    };
+    
+    // Level table from the design doc
+    const levels = [
+      {
+        ...baseArgs,
+        operation_type: 1, // SET_LEVEL
+        level: 2,
+        usdc_cost: 200000000, // 200 USDC
+        aifi_cost: 25000000, // 25 AiFi, more than the first collection yields
+        usdc_gain: 5000000, // +5 USDC per hour
+        aifi_gain: 3000000, // +3 AiFi per hour
+        sol_gain: 20000, // +0.02 SOL per hour
+        cooldown: 0, // Right after spawning
+      },
+      {
+        ...baseArgs,
+        operation_type: 1, // SET_LEVEL
+        level: 3,
+        usdc_cost: 400000000, // 400 USDC
+        aifi_cost: 35000000, // 35 AiFi
+        usdc_gain: 10000000, // +10 USDC per hour
+        aifi_gain: 5000000, // +5 AiFi per hour
+        sol_gain: 40000, // +0.04 SOL per hour
+        cooldown: 60, // 1 minute after the previous upgrade
+      },
+    ];
+    
+    // Half yield while building, 0.1 AiFi per hour skipped, half the cost back on cancel
+    const construction = {
+      ...baseArgs,
+      operation_type: 3, // SET_CONSTRUCTION
+      construction_yield: 5000,
+      speed_up_aifi_per_hour: 100000,
+      cancel_refund_rate: 5000,
+    };
+    
+    for (const args of [baseArgs, ...levels, construction]) {
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemUpgradeCurve.programId,
+        world: worldPda,
+        entities: [{
+          entity: catalogEntityPda,
+          components: [
+            { componentId: upgradeTableComponent.programId }, // upgrade table component
+          ],
+        }],
+        args,
+        extraAccounts: [
+          { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+        ],
+      });
+      
+      const txSign = await provider.sendAndConfirm(applySystem.transaction, [gameAuthority]);
+      console.log(`Applied upgrade-curve system (operation ${args.operation_type}, level ${args.level}). Signature: ${txSign}`);
+    }
+    
+    const upgradeTable = await upgradeTableComponent.account.upgradeTable.fetch(upgradeTableComponentPda);
+    expect(upgradeTable.entityType).to.equal(ENTITY_TYPE.GPU);
+    expect(upgradeTable.modelId).to.equal(1);
+    expect(upgradeTable.usdcCosts.map(cost => cost.toNumber())).to.deep.equal([200000000, 400000000]);
+    expect(upgradeTable.usdcGains.map(gain => gain.toNumber())).to.deep.equal([5000000, 10000000]);
+    expect(upgradeTable.buildDurations).to.deep.equal([0, 0]);
+    expect(upgradeTable.constructionYield).to.equal(5000);
+    expect(upgradeTable.cancelRefundRate).to.equal(5000);
+  });
+
+  it("Attempt to change the upgrade table without the game authority (should fail)", async () => {
+    try {
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemUpgradeCurve.programId,
+        world: worldPda,
+        entities: [{
+          entity: catalogEntityPda,
+          components: [
+            { componentId: upgradeTableComponent.programId }, // upgrade table component
+          ],
+        }],
+        args: {
+          operation_type: 3, // SET_CONSTRUCTION
+          entity_type: 0,
+          model_id: 0,
+          level: 0,
+          usdc_cost: 0,
+          aifi_cost: 0,
+          usdc_gain: 0,
+          aifi_gain: 0,
+          btc_gain: 0,
+          eth_gain: 0,
+          sol_gain: 0,
+          cooldown: 0,
+          required_player_level: 0,
+          build_duration: 0,
+          construction_yield: 0,
+          speed_up_aifi_per_hour: 0,
+          cancel_refund_rate: 10000,
+          success_rate: 0,
+          critical_failure_rate: 0,
+          risk_start_level: 0,
+          protection_aifi_cost: 0,
+        },
+        extraAccounts: [
+          { pubkey: provider.wallet.publicKey, isSigner: true, isWritable: false }, // not the game authority
+        ],
+      });
+      
+      await provider.sendAndConfirm(applySystem.transaction);
+      expect.fail("Only the game authority should manage upgrade tables");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Only the game authority should manage upgrade tables");
+      console.log(`Upgrade table change correctly rejected without the game authority`);
+    }
+    
+    const upgradeTable = await upgradeTableComponent.account.upgradeTable.fetch(upgradeTableComponentPda);
+    expect(upgradeTable.cancelRefundRate).to.equal(5000);
+  });
+
+  it("Spawn a GPU from the catalog", async () => {
+    // Get current unix timestamp in seconds
+    const currentTime = Math.floor(Date.now() / 1000);
+    
+    const spawnArgs = {
+      operation_type: 0, // SPAWN
+      is_active: false, // Start inactive
+      current_time: currentTime,
+    };
+    
+    // The catalog component comes from the catalog entity, the rest from the GPU
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemSpawnGpu.programId,
+      world: worldPda,
+      entities: [
+        {
+          entity: catalogEntityPda,
+          components: [
+            { componentId: gpuModelComponent.programId },     // gpu model component
+            { componentId: upgradeTableComponent.programId }, // upgrade table component
+          ],
+        },
+        {
+          entity: entityPda,
+          components: [
+            { componentId: productionComponent.programId },  // production component
+            { componentId: upgradeableComponent.programId }, // upgradeable component
+            { componentId: stakeableComponent.programId },   // stakeable component
+          ],
+        },
+      ],
+      args: spawnArgs,
+      extraAccounts: [
+        { pubkey: catalogEntityPda, isSigner: false, isWritable: false }, // catalog entity
+      ],
     });
     
     const txSign = await provider.sendAndConfirm(applySystem.transaction);
-    console.log(`Applied resource-production system to initialize production. Signature: ${txSign}`);
+    console.log(`Applied spawn-gpu system. Signature: ${txSign}`);
     
-    // Verify the production component was initialized correctly
+    // Verify the production component was copied from the catalog
     const production = await productionComponent.account.production.fetch(productionComponentPda);
     
-    expect(production.usdcPerHour.toNumber()).to.equal(5000000);
-    expect(production.aifiPerHour.toNumber()).to.equal(10000000);
+    expect(production.modelId).to.equal(1);
+    expect(production.usdcPerHour.toNumber()).to.equal(7500000);
+    expect(production.aifiPerHour.toNumber()).to.equal(15000000);
+    expect(production.btcPerHour.toNumber()).to.equal(0);
+    expect(production.solPerHour.toNumber()).to.equal(100000);
     expect(production.lastCollectionTime.toNumber()).to.equal(currentTime);
-    expect(production.producerType).to.equal(1);
+    expect(production.producerType).to.equal(ENTITY_TYPE.GPU);
     expect(production.level).to.equal(1);
     expect(production.isActive).to.equal(false);
-    expect(production.operatingCost.toNumber()).to.equal(1000000);
-    expect(production.efficiencyMultiplier).to.equal(10000);
+    expect(production.operatingCost.toNumber()).to.equal(1500000);
+    expect(production.efficiencyMultiplier).to.equal(12000);
     
-    console.log(`Production component initialized successfully with USDC rate: ${production.usdcPerHour.toNumber()/1000000} USDC/hour`);
+    console.log(`GPU spawned with USDC rate: ${production.usdcPerHour.toNumber()/1000000} USDC/hour`);
     console.log(`AiFi production rate: ${production.aifiPerHour.toNumber()/1000000} AiFi/hour`);
     console.log(`Operating cost: ${production.operatingCost.toNumber()/1000000} USDC/hour`);
   });
 
+  it("Assign the GPU entity to the player's ownership", async () => {
+    // The test player and GPU share an entity, so the player owns its own entity as a GPU
+    const systemAssignOwnership = anchor.workspace.AssignOwnership;
+    const entityAccount = await provider.connection.getAccountInfo(entityPda);
+    const entityId = new anchor.BN(entityAccount.data.subarray(8, 16), "le");
+    
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemAssignOwnership.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: ownershipComponent.programId }, // player ownership
+          { componentId: ownershipComponent.programId }, // destination ownership (same)
+        ],
+      }],
+      args: {
+        operation_type: 1, // ASSIGN_TO_WALLET
+        owner_type: ENTITY_TYPE.PLAYER,
+        entity_id: entityId.toNumber(),
+        entity_type: ENTITY_TYPE.GPU,
+        destination_entity_id: 0, // Not used for assignment
+        owner_entity_id: entityId.toNumber(),
+      },
+    });
+    
+    await provider.sendAndConfirm(applySystem.transaction);
+    
+    const ownership = await ownershipComponent.account.ownership.fetch(ownershipComponentPda);
+    expect(ownership.ownerType).to.equal(ENTITY_TYPE.PLAYER);
+    expect(ownership.ownedEntityTypes).to.include(ENTITY_TYPE.GPU);
+    console.log(`Player owns entity ${entityId.toString()} as a GPU`);
+  });
+
+  it("Attempt to fuse a GPU with itself (should fail)", async () => {
+    const gpuBefore = await productionComponent.account.production.fetch(productionComponentPda);
+    
+    try {
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemFuseGpus.programId,
+        world: worldPda,
+        entities: [{
+          entity: catalogEntityPda,
+          components: [
+            { componentId: gpuModelComponent.programId },     // model of the fused GPUs
+            { componentId: gpuModelComponent.programId },     // fusion model
+            { componentId: upgradeTableComponent.programId }, // fusion model upgrade table
+          ],
+        }, {
+          entity: entityPda,
+          components: [
+            { componentId: ownershipComponent.programId },    // player ownership
+            { componentId: walletComponent.programId },       // player wallet
+            { componentId: productionComponent.programId },   // first GPU production
+            { componentId: stakeableComponent.programId },    // first GPU stakeable
+            { componentId: durabilityComponent.programId },   // first GPU durability
+            { componentId: productionComponent.programId },   // second GPU production
+            { componentId: stakeableComponent.programId },    // second GPU stakeable
+            { componentId: productionComponent.programId },   // new GPU production
+            { componentId: upgradeableComponent.programId },  // new GPU upgradeable
+            { componentId: stakeableComponent.programId },    // new GPU stakeable
+            { componentId: durabilityComponent.programId },   // new GPU durability
+          ],
+        }],
+        args: {
+          operation_type: 0, // FUSE
+          first_entity_id: 1,
+          second_entity_id: 1,
+          fused_entity_id: 2,
+          is_active: false,
+          current_time: Math.floor(Date.now() / 1000),
+        },
+        extraAccounts: [
+          { pubkey: entityPda, isSigner: false, isWritable: false },        // player entity
+          { pubkey: entityPda, isSigner: false, isWritable: false },        // first GPU entity
+          { pubkey: entityPda, isSigner: false, isWritable: false },        // second GPU entity
+          { pubkey: entityPda, isSigner: false, isWritable: false },        // new GPU entity
+          { pubkey: catalogEntityPda, isSigner: false, isWritable: false }, // catalog entity of the model
+          { pubkey: catalogEntityPda, isSigner: false, isWritable: false }, // catalog entity of the fusion model
+        ],
+      });
+      
+      await provider.sendAndConfirm(applySystem.transaction);
+      expect.fail("Fusing a GPU with itself should have failed");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Fusing a GPU with itself should have failed");
+      console.log("Fusing a GPU with itself correctly failed");
+    }
+    
+    // The GPU was not retired
+    const gpuAfter = await productionComponent.account.production.fetch(productionComponentPda);
+    expect(gpuAfter.isRetired).to.equal(false);
+    expect(gpuAfter.usdcPerHour.toNumber()).to.equal(gpuBefore.usdcPerHour.toNumber());
+  });
+
   it("Activate production", async () => {
     // Get current unix timestamp in seconds
     const currentTime = Math.floor(Date.now() / 1000);
@@ -987,9 +1577,21 @@ describe("yield-wars-program", () => {
         components: [
           { componentId: productionComponent.programId }, // production component
           { componentId: walletComponent.programId },     // wallet component
+          { componentId: durabilityComponent.programId }, // durability component
+          { componentId: facilityComponent.programId }, // facility component
+          { componentId: playerProfileComponent.programId }, // player profile
+          { componentId: hostingAgreementComponent.programId }, // hosting agreement
+        ],
+      }, {
+        entity: entity2Pda,
+        components: [
+          { componentId: walletComponent.programId }, // host wallet
         ],
       }],
       args: activateArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // producer entity
+      ],
     });
     
     const txSign = await provider.sendAndConfirm(applySystem.transaction);
@@ -1003,57 +1605,97 @@ describe("yield-wars-program", () => {
     console.log(`Production successfully activated, last collection time updated to ${currentTime}`);
   });
 
-  it("Update production rates", async () => {
+  it("Attempt to override GPU production rates (should fail)", async () => {
     // Get current unix timestamp in seconds
     const currentTime = Math.floor(Date.now() / 1000);
     
-    // Get current production rates
-    const productionBefore = await productionComponent.account.production.fetch(productionComponentPda);
-    console.log(`Current USDC rate: ${productionBefore.usdcPerHour.toNumber()/1000000} USDC/hour`);
-    
-    // Prepare args to update production rates
+    // Prepare args that would double the production rates
     const updateArgs = {
       operation_type: 3, // UPDATE_RATES
-      usdc_per_hour: 7500000, // 7.5 USDC per hour (7,500,000 = $7.50 with 6 decimal places)
-      aifi_per_hour: 15000000, // 15 AiFi per hour (15,000,000 = $15 with 6 decimal places)
+      usdc_per_hour: 15000000, // 15 USDC per hour
+      aifi_per_hour: 30000000, // 30 AiFi per hour
       current_time: currentTime,
       producer_type: 0, // not used for this operation
       level: 0, // not used for this operation
       is_active: false, // not used for this operation
-      operating_cost: 1500000, // 1.5 USDC per hour (1,500,000 = $1.50 with 6 decimal places)
-      efficiency_multiplier: 12000 // 120% efficiency (12000 = 120%)
+      operating_cost: 0, // no operating cost
+      efficiency_multiplier: 20000 // 200% efficiency
     };
     
-    // Apply the system to update production rates
-    const applySystem = await ApplySystem({
-      authority: provider.wallet.publicKey,
-      systemId: systemResourceProduction.programId,
-      world: worldPda,
-      entities: [{
-        entity: entityPda,
-        components: [
-          { componentId: productionComponent.programId }, // production component
-          { componentId: walletComponent.programId },     // wallet component
-        ],
-      }],
-      args: updateArgs,
-    });
-    
-    const txSign = await provider.sendAndConfirm(applySystem.transaction);
-    console.log(`Applied resource-production system to update rates. Signature: ${txSign}`);
-    
-    // Verify production rates were updated
-    const productionAfter = await productionComponent.account.production.fetch(productionComponentPda);
-    expect(productionAfter.usdcPerHour.toNumber()).to.equal(7500000);
+    try {
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemResourceProduction.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: productionComponent.programId }, // production component
+            { componentId: walletComponent.programId },     // wallet component
+            { componentId: durabilityComponent.programId }, // durability component
+            { componentId: facilityComponent.programId }, // facility component
+            { componentId: playerProfileComponent.programId }, // player profile
+            { componentId: hostingAgreementComponent.programId }, // hosting agreement
+          ],
+        }, {
+          entity: entity2Pda,
+          components: [
+            { componentId: walletComponent.programId }, // host wallet
+          ],
+        }],
+        args: updateArgs,
+        extraAccounts: [
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // producer entity
+        ],
+      });
+      
+      await provider.sendAndConfirm(applySystem.transaction);
+      expect.fail("GPU rates should only be set by the catalog");
+    } catch (error) {
+      expect(error.toString()).to.not.include("GPU rates should only be set by the catalog");
+      console.log(`Rate override correctly rejected for a catalog GPU`);
+    }
+    
+    // Verify the catalog rates are untouched
+    const productionAfter = await productionComponent.account.production.fetch(productionComponentPda);
+    expect(productionAfter.usdcPerHour.toNumber()).to.equal(7500000);
     expect(productionAfter.aifiPerHour.toNumber()).to.equal(15000000);
     expect(productionAfter.operatingCost.toNumber()).to.equal(1500000);
     expect(productionAfter.efficiencyMultiplier).to.equal(12000);
+  });
+
+  it("Preview pending production yield", async () => {
+    const production = await productionComponent.account.production.fetch(productionComponentPda);
+    const currentTime = production.lastCollectionTime.toNumber() + 3600;
+    const walletBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
     
-    console.log(`Production rates updated successfully:`);
-    console.log(`New USDC rate: ${productionAfter.usdcPerHour.toNumber()/1000000} USDC/hour`);
-    console.log(`New AiFi rate: ${productionAfter.aifiPerHour.toNumber()/1000000} AiFi/hour`);
-    console.log(`New operating cost: ${productionAfter.operatingCost.toNumber()/1000000} USDC/hour`);
-    console.log(`New efficiency: ${productionAfter.efficiencyMultiplier/100}%`);
+    // Simulate the read-only instruction and decode the return data
+    const pending = await yieldWarsProgram.methods
+      .previewProduction(new anchor.BN(currentTime))
+      .accounts({
+        production: productionComponentPda,
+        durability: durabilityComponentPda,
+        playerProfile: playerProfileComponentPda,
+        facility: facilityComponentPda,
+        hostingAgreement: hostingAgreementComponentPda,
+        incidentRandomness: null, // no incident roll requested
+      })
+      .view();
+    
+    console.log(`Pending yield: USDC=${pending.usdc.toNumber()/1000000}, AiFi=${pending.aifi.toNumber()/1000000}, cost=${pending.operatingCost.toNumber()/1000000}`);
+    
+    expect(pending.isActive).to.equal(true);
+    expect(pending.producingSeconds.toNumber()).to.equal(3600);
+    expect(pending.usdc.toNumber()).to.be.above(0);
+    expect(pending.sol.toNumber()).to.equal(120000); // 0.1 SOL * 1h * 120% efficiency
+    expect(pending.hostUsdc.toNumber()).to.equal(0); // not hosted
+    expect(pending.incidentRollPending).to.equal(false);
+    
+    // Previewing must not change any state
+    const productionAfter = await productionComponent.account.production.fetch(productionComponentPda);
+    const walletAfter = await walletComponent.account.wallet.fetch(walletComponentPda);
+    expect(productionAfter.lastCollectionTime.toNumber()).to.equal(production.lastCollectionTime.toNumber());
+    expect(walletAfter.usdcBalance.toNumber()).to.equal(walletBefore.usdcBalance.toNumber());
   });
 
   it("Collect produced resources after time period", async () => {
@@ -1088,9 +1730,21 @@ describe("yield-wars-program", () => {
         components: [
           { componentId: productionComponent.programId }, // production component
           { componentId: walletComponent.programId },     // wallet component
+          { componentId: durabilityComponent.programId }, // durability component
+          { componentId: facilityComponent.programId }, // facility component
+          { componentId: playerProfileComponent.programId }, // player profile
+          { componentId: hostingAgreementComponent.programId }, // hosting agreement
+        ],
+      }, {
+        entity: entity2Pda,
+        components: [
+          { componentId: walletComponent.programId }, // host wallet
         ],
       }],
       args: collectArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // producer entity
+      ],
     });
     
     const txSign = await provider.sendAndConfirm(applySystem.transaction);
@@ -1114,12 +1768,314 @@ describe("yield-wars-program", () => {
     expect(usdcEarned).to.be.above(1000000); // At least 1 USDC
     expect(aifiEarned).to.be.above(10000000); // At least 10 AiFi
     
+    // The model also earns SOL, credited to the SOL balance
+    const solEarned = walletAfter.solBalance.toNumber() - walletBefore.solBalance.toNumber();
+    console.log(`SOL earned: ${solEarned/1000000} SOL`);
+    expect(solEarned).to.equal(120000); // 0.1 SOL * 1h * 120% efficiency
+    expect(walletAfter.btcBalance.toNumber()).to.equal(walletBefore.btcBalance.toNumber());
+    
     // Verify last collection time was updated
     expect(productionAfter.lastCollectionTime.toNumber()).to.equal(currentTime);
     
     console.log(`Resources collected successfully`);
   });
 
+  it("Offer to host the GPU in the second player's data center", async () => {
+    const offerArgs = {
+      operation_type: 0, // OFFER
+      host_share_bps: 3000, // 30% of the yield to the host
+      term: 86400, // 1 day
+    };
+    
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemHosting.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: hostingAgreementComponent.programId }, // hosting agreement of the GPU
+          { componentId: productionComponent.programId },       // production of the GPU
+          { componentId: ownershipComponent.programId },        // owner's ownership
+          { componentId: walletComponent.programId },           // owner's wallet
+        ],
+      }, {
+        entity: entity2Pda,
+        components: [
+          { componentId: walletComponent.programId },           // host's wallet
+        ],
+      }],
+      args: offerArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owner's player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // GPU entity
+        { pubkey: hostAuthority.publicKey, isSigner: false, isWritable: false }, // host's signing key
+      ],
+    });
+    
+    const txSign = await provider.sendAndConfirm(applySystem.transaction);
+    console.log(`Applied hosting system to offer an agreement. Signature: ${txSign}`);
+    
+    const agreement = await hostingAgreementComponent.account.hostingAgreement.fetch(hostingAgreementComponentPda);
+    expect(agreement.status).to.equal(1); // Offered
+    expect(agreement.hostShareBps).to.equal(3000);
+    expect(agreement.term).to.equal(86400);
+    expect(agreement.ownerWallet.toBase58()).to.equal(walletComponentPda.toBase58());
+    expect(agreement.hostWallet.toBase58()).to.equal(wallet2ComponentPda.toBase58());
+    expect(agreement.production.toBase58()).to.equal(productionComponentPda.toBase58());
+    expect(agreement.hostAuthority.toBase58()).to.equal(hostAuthority.publicKey.toBase58());
+  });
+
+  it("Attempt to accept the hosting agreement without the host's signature (should fail)", async () => {
+    const acceptArgs = {
+      operation_type: 1, // ACCEPT
+      host_share_bps: 0, // Not used
+      term: 0, // Not used
+    };
+    
+    try {
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemHosting.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: hostingAgreementComponent.programId }, // hosting agreement of the GPU
+            { componentId: productionComponent.programId },       // production of the GPU
+            { componentId: ownershipComponent.programId },        // owner's ownership
+            { componentId: walletComponent.programId },           // owner's wallet
+          ],
+        }, {
+          entity: entity2Pda,
+          components: [
+            { componentId: walletComponent.programId },           // host's wallet
+          ],
+        }],
+        args: acceptArgs,
+        extraAccounts: [
+          { pubkey: entity2Pda, isSigner: false, isWritable: false }, // host's player entity
+          { pubkey: provider.wallet.publicKey, isSigner: true, isWritable: false }, // the owner, not the host
+        ],
+      });
+      
+      await provider.sendAndConfirm(applySystem.transaction);
+      expect.fail("Only the host should accept the agreement");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Only the host should accept the agreement");
+      console.log(`Accepting for the host was correctly rejected`);
+    }
+    
+    const agreement = await hostingAgreementComponent.account.hostingAgreement.fetch(hostingAgreementComponentPda);
+    expect(agreement.status).to.equal(1); // Still offered
+  });
+
+  it("Accept the hosting agreement as the host", async () => {
+    const acceptArgs = {
+      operation_type: 1, // ACCEPT
+      host_share_bps: 0, // Not used
+      term: 0, // Not used
+    };
+    
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemHosting.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: hostingAgreementComponent.programId }, // hosting agreement of the GPU
+          { componentId: productionComponent.programId },       // production of the GPU
+          { componentId: ownershipComponent.programId },        // owner's ownership
+          { componentId: walletComponent.programId },           // owner's wallet
+        ],
+      }, {
+        entity: entity2Pda,
+        components: [
+          { componentId: walletComponent.programId },           // host's wallet
+        ],
+      }],
+      args: acceptArgs,
+      extraAccounts: [
+        { pubkey: entity2Pda, isSigner: false, isWritable: false }, // host's player entity
+        { pubkey: hostAuthority.publicKey, isSigner: true, isWritable: false }, // host's signing key
+      ],
+    });
+    
+    const txSign = await provider.sendAndConfirm(applySystem.transaction, [hostAuthority]);
+    console.log(`Applied hosting system to accept the agreement. Signature: ${txSign}`);
+    
+    const agreement = await hostingAgreementComponent.account.hostingAgreement.fetch(hostingAgreementComponentPda);
+    expect(agreement.status).to.equal(2); // Active
+    expect(agreement.startTime.toNumber()).to.be.gt(0);
+    expect(agreement.endTime.toNumber()).to.equal(agreement.startTime.toNumber() + 86400);
+  });
+
+  it("Collect a hosted GPU's yield, sharing it with the host", async () => {
+    const lastCollectionTime = (await productionComponent.account.production.fetch(productionComponentPda)).lastCollectionTime.toNumber();
+    const currentTime = lastCollectionTime + 3600; // 1 hour later
+    
+    // The whole hour lies inside the term, so it is fully hosted
+    const agreementBefore = await hostingAgreementComponent.account.hostingAgreement.fetch(hostingAgreementComponentPda);
+    expect(lastCollectionTime).to.be.gte(agreementBefore.startTime.toNumber());
+    expect(currentTime).to.be.lte(agreementBefore.endTime.toNumber());
+    
+    const ownerBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
+    const hostBefore = await walletComponent.account.wallet.fetch(wallet2ComponentPda);
+    const ownershipBefore = await ownershipComponent.account.ownership.fetch(ownershipComponentPda);
+    
+    const collectArgs = {
+      operation_type: 1, // COLLECT
+      usdc_per_hour: 0, // not used for this operation
+      aifi_per_hour: 0, // not used for this operation
+      current_time: currentTime,
+      producer_type: 0, // not used for this operation
+      level: 0, // not used for this operation
+      is_active: false, // not used for this operation
+      operating_cost: 0, // not used for this operation
+      efficiency_multiplier: 0 // not used for this operation
+    };
+    
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemResourceProduction.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: productionComponent.programId }, // production component
+          { componentId: walletComponent.programId },     // wallet component
+          { componentId: durabilityComponent.programId }, // durability component
+          { componentId: facilityComponent.programId }, // facility component
+          { componentId: playerProfileComponent.programId }, // player profile
+          { componentId: hostingAgreementComponent.programId }, // hosting agreement
+        ],
+      }, {
+        entity: entity2Pda,
+        components: [
+          { componentId: walletComponent.programId }, // host wallet
+        ],
+      }],
+      args: collectArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // producer entity
+      ],
+    });
+    
+    const txSign = await provider.sendAndConfirm(applySystem.transaction);
+    console.log(`Applied resource-production system to collect a hosted GPU. Signature: ${txSign}`);
+    
+    const ownerAfter = await walletComponent.account.wallet.fetch(walletComponentPda);
+    const hostAfter = await walletComponent.account.wallet.fetch(wallet2ComponentPda);
+    const agreement = await hostingAgreementComponent.account.hostingAgreement.fetch(hostingAgreementComponentPda);
+    
+    // 0.12 SOL earned in the hour, 30% of it for the host
+    expect(hostAfter.solBalance.toNumber() - hostBefore.solBalance.toNumber()).to.equal(36000);
+    expect(ownerAfter.solBalance.toNumber() - ownerBefore.solBalance.toNumber()).to.equal(84000);
+    
+    const hostUsdc = hostAfter.usdcBalance.toNumber() - hostBefore.usdcBalance.toNumber();
+    const hostAifi = hostAfter.aifiBalance.toNumber() - hostBefore.aifiBalance.toNumber();
+    expect(hostUsdc).to.be.above(0);
+    expect(hostAifi).to.be.above(0);
+    expect(agreement.hostUsdcEarned.toNumber()).to.equal(hostUsdc);
+    expect(agreement.hostAifiEarned.toNumber()).to.equal(hostAifi);
+    console.log(`Host received ${hostUsdc/1000000} USDC and ${hostAifi/1000000} AiFi`);
+    
+    // The GPU stays with its owner
+    const ownershipAfter = await ownershipComponent.account.ownership.fetch(ownershipComponentPda);
+    expect(ownershipAfter.ownedEntities.map(key => key.toBase58()))
+      .to.deep.equal(ownershipBefore.ownedEntities.map(key => key.toBase58()));
+    expect(ownershipAfter.ownedEntityTypes).to.deep.equal(ownershipBefore.ownedEntityTypes);
+  });
+
+  it("Attempt to end the hosting agreement before its term (should fail)", async () => {
+    const endArgs = {
+      operation_type: 2, // END
+      host_share_bps: 0, // Not used
+      term: 0, // Not used
+    };
+    
+    try {
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemHosting.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: hostingAgreementComponent.programId }, // hosting agreement of the GPU
+            { componentId: productionComponent.programId },       // production of the GPU
+            { componentId: ownershipComponent.programId },        // owner's ownership
+            { componentId: walletComponent.programId },           // owner's wallet
+          ],
+        }, {
+          entity: entity2Pda,
+          components: [
+            { componentId: walletComponent.programId },           // host's wallet
+          ],
+        }],
+        args: endArgs,
+      });
+      
+      await provider.sendAndConfirm(applySystem.transaction);
+      expect.fail("Hosting should not end before its term");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Hosting should not end before its term");
+      console.log(`Ending the agreement early was correctly rejected`);
+    }
+    
+    const agreementAfter = await hostingAgreementComponent.account.hostingAgreement.fetch(hostingAgreementComponentPda);
+    expect(agreementAfter.status).to.equal(2); // Still active
+  });
+
+  it("End the hosting agreement after its term", async () => {
+    const agreementBefore = await hostingAgreementComponent.account.hostingAgreement.fetch(hostingAgreementComponentPda);
+    
+    // Terms last at least a day of cluster time, longer than a test run on a fresh validator,
+    // and the host is only paid in full once the GPU has been collected up to the end
+    const production = await productionComponent.account.production.fetch(productionComponentPda);
+    const clusterTime = await provider.connection.getBlockTime(await provider.connection.getSlot());
+    if (clusterTime < agreementBefore.endTime.toNumber()
+      || production.lastCollectionTime.toNumber() < agreementBefore.endTime.toNumber()) {
+      console.log(`Hosting runs until ${agreementBefore.endTime.toNumber()}, skipping end test`);
+      return;
+    }
+    
+    const endArgs = {
+      operation_type: 2, // END
+      host_share_bps: 0, // Not used
+      term: 0, // Not used
+    };
+    
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemHosting.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: hostingAgreementComponent.programId }, // hosting agreement of the GPU
+          { componentId: productionComponent.programId },       // production of the GPU
+          { componentId: ownershipComponent.programId },        // owner's ownership
+          { componentId: walletComponent.programId },           // owner's wallet
+        ],
+      }, {
+        entity: entity2Pda,
+        components: [
+          { componentId: walletComponent.programId },           // host's wallet
+        ],
+      }],
+      args: endArgs,
+    });
+    
+    const txSign = await provider.sendAndConfirm(applySystem.transaction);
+    console.log(`Applied hosting system to end the agreement. Signature: ${txSign}`);
+    
+    const agreement = await hostingAgreementComponent.account.hostingAgreement.fetch(hostingAgreementComponentPda);
+    expect(agreement.status).to.equal(0); // No agreement
+    expect(agreement.hostWallet.toBase58()).to.equal(PublicKey.default.toBase58());
+  });
+
   it("Deactivate production", async () => {
     // Get current unix timestamp in seconds
     const currentTime = Math.floor(Date.now() / 1000);
@@ -1151,9 +2107,21 @@ describe("yield-wars-program", () => {
         components: [
           { componentId: productionComponent.programId }, // production component
           { componentId: walletComponent.programId },     // wallet component
+          { componentId: durabilityComponent.programId }, // durability component
+          { componentId: facilityComponent.programId }, // facility component
+          { componentId: playerProfileComponent.programId }, // player profile
+          { componentId: hostingAgreementComponent.programId }, // hosting agreement
+        ],
+      }, {
+        entity: entity2Pda,
+        components: [
+          { componentId: walletComponent.programId }, // host wallet
         ],
       }],
       args: deactivateArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // producer entity
+      ],
     });
     
     const txSign = await provider.sendAndConfirm(applySystem.transaction);
@@ -1197,9 +2165,21 @@ describe("yield-wars-program", () => {
           components: [
             { componentId: productionComponent.programId }, // production component
             { componentId: walletComponent.programId },     // wallet component
+            { componentId: durabilityComponent.programId }, // durability component
+            { componentId: facilityComponent.programId }, // facility component
+            { componentId: playerProfileComponent.programId }, // player profile
+            { componentId: hostingAgreementComponent.programId }, // hosting agreement
+          ],
+        }, {
+          entity: entity2Pda,
+          components: [
+            { componentId: walletComponent.programId }, // host wallet
           ],
         }],
         args: collectArgs,
+        extraAccounts: [
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // producer entity
+        ],
       });
       
       const txSign = await provider.sendAndConfirm(applySystem.transaction);
@@ -1215,165 +2195,597 @@ describe("yield-wars-program", () => {
     }
   });
 
-  it("Initialize upgrade properties", async () => {
-    // Get current unix timestamp in seconds
+  it("Initialize durability using Maintenance system", async () => {
     const currentTime = Math.floor(Date.now() / 1000);
+    const productionBefore = await productionComponent.account.production.fetch(productionComponentPda);
     
-    // Get current level to make sure we don't have any test state leakage issues
-    const upgradeableBefore = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
-    console.log(`Upgradeable level before initialization: ${upgradeableBefore.currentLevel}`);
-    
-    // Initial upgrade settings for a GPU
-    const initialArgs = {
+    const initArgs = {
       operation_type: 0, // INITIALIZE
-      entity_type: 1, // GPU type
-      current_level: 1, // Always reset to level 1 for testing
-      max_level: 5, // Can upgrade to level 5
-      upgrade_cooldown: 3600, // 1 hour cooldown between upgrades
-      next_upgrade_usdc_cost: 100000000, // 100 USDC to upgrade to level 2
-      next_upgrade_aifi_cost: 25000000, // 25 AiFi tokens to upgrade to level 2 - set high enough to ensure test fails
-      next_usdc_boost: 2000, // 20% boost to USDC production
-      next_aifi_boost: 3000, // 30% boost to AiFi production
+      entity_type: ENTITY_TYPE.GPU,
+      max_durability: 10000, // 10,000 durability points
+      wear_per_hour: 100, // 1% per hour at stock clocks
+      degradation_threshold: 5000, // Efficiency drops below 50% durability
+      overclock_multiplier: 10000, // Stock clocks
+      repair_usdc_per_point: 1000, // 0.001 USDC per point
+      repair_aifi_per_point: 0,
+      repair_points: 0, // not used for this operation
       current_time: currentTime,
+      cooling_rating: 0, // not used for this operation
+      energy_contract_coverage: 0, // not used for this operation
+      power_outage_odds: 0, // not used for this operation
+      gpu_burnout_odds: 0, // not used for this operation
+      mining_bonus_odds: 0, // not used for this operation
+      outage_yield_multiplier: 0, // not used for this operation
+      burnout_durability_damage: 0, // not used for this operation
+      mining_bonus_multiplier: 0, // not used for this operation
+      incidents_enabled: false // not used for this operation
     };
     
-    // Apply the system to initialize upgrade properties
     const applySystem = await ApplySystem({
       authority: provider.wallet.publicKey,
-      systemId: systemUpgrade.programId,
+      systemId: systemMaintenance.programId,
       world: worldPda,
       entities: [{
         entity: entityPda,
         components: [
-          { componentId: upgradeableComponent.programId }, // upgradeable component
-          { componentId: walletComponent.programId },      // wallet component
-          { componentId: productionComponent.programId },  // production component
+          { componentId: durabilityComponent.programId }, // durability component
+          { componentId: walletComponent.programId },     // wallet component
+          { componentId: productionComponent.programId }, // production component
+          { componentId: facilityComponent.programId }, // facility component
+          { componentId: ownershipComponent.programId }, // owner's ownership
         ],
       }],
-      args: initialArgs,
+      args: initArgs,
+      extraAccounts: [
+        { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+      ],
     });
     
-    const txSign = await provider.sendAndConfirm(applySystem.transaction);
-    console.log(`Applied upgrade system to initialize upgrade properties. Signature: ${txSign}`);
+    const txSign = await provider.sendAndConfirm(applySystem.transaction, [gameAuthority]);
+    console.log(`Applied maintenance system to initialize durability. Signature: ${txSign}`);
     
-    // Verify the upgradeable component was initialized correctly
-    const upgradeable = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
+    const durability = await durabilityComponent.account.durability.fetch(durabilityComponentPda);
+    const productionAfter = await productionComponent.account.production.fetch(productionComponentPda);
     
-    expect(upgradeable.currentLevel).to.equal(1);
-    expect(upgradeable.maxLevel).to.equal(5);
-    expect(upgradeable.upgradeableType).to.equal(1); // GPU
-    expect(upgradeable.canUpgrade).to.equal(true);
-    expect(upgradeable.lastUpgradeTime.toNumber()).to.equal(currentTime);
-    expect(upgradeable.nextUpgradeUsdcCost.toNumber()).to.equal(100000000);
-    expect(upgradeable.nextUpgradeAifiCost.toNumber()).to.equal(25000000);
-    expect(upgradeable.nextUsdcBoost).to.equal(2000);
-    expect(upgradeable.nextAifiBoost).to.equal(3000);
+    expect(durability.currentDurability).to.equal(10000);
+    expect(durability.maxDurability).to.equal(10000);
+    expect(durability.wearPerHour).to.equal(100);
+    expect(durability.overclockMultiplier).to.equal(10000);
+    expect(durability.ratedEfficiency).to.equal(productionBefore.efficiencyMultiplier);
+    expect(durability.isBroken).to.equal(false);
     
-    console.log(`Upgradeable component initialized with level ${upgradeable.currentLevel}/${upgradeable.maxLevel}`);
-    console.log(`Next upgrade costs: ${upgradeable.nextUpgradeUsdcCost.toNumber()/1000000} USDC, ${upgradeable.nextUpgradeAifiCost.toNumber()/1000000} AiFi`);
-    console.log(`Next upgrade boosts: ${upgradeable.nextUsdcBoost/100}% USDC, ${upgradeable.nextAifiBoost/100}% AiFi`);
+    // Undamaged hardware at stock clocks keeps its efficiency
+    expect(productionAfter.efficiencyMultiplier).to.equal(productionBefore.efficiencyMultiplier);
   });
 
-  it("Attempt upgrade with insufficient funds", async () => {
-    // First check the wallet balance
-    const walletBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
-    const upgradeable = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
-    
-    console.log(`Current wallet: USDC=${walletBefore.usdcBalance.toNumber()/1000000}, AiFi=${walletBefore.aifiBalance.toNumber()/1000000}`);
-    console.log(`Required for upgrade: USDC=${upgradeable.nextUpgradeUsdcCost.toNumber()/1000000}, AiFi=${upgradeable.nextUpgradeAifiCost.toNumber()/1000000}`);
-    
-    // Ensure we have zero AiFi but enough USDC
-    // Test should fail with InsufficientAifiFunds
-    
-    // Get timestamp with cooldown elapsed
-    const lastUpgradeTime = upgradeable.lastUpgradeTime.toNumber();
-    const currentTime = lastUpgradeTime + 3600 + 10; // 1 hour + 10 seconds later
+  it("Attempt to re-initialize durability (should fail)", async () => {
+    const durabilityBefore = await durabilityComponent.account.durability.fetch(durabilityComponentPda);
     
-    // Prepare args for upgrade attempt (will fail due to insufficient AiFi)
-    const upgradeArgs = {
-      operation_type: 1, // UPGRADE
-      entity_type: 0, // Not used for this operation
-      current_level: 0, // Not used for this operation
-      max_level: 0, // Not used for this operation
-      upgrade_cooldown: 0, // Not used for this operation
-      next_upgrade_usdc_cost: 0, // Not used for this operation
-      next_upgrade_aifi_cost: 0, // Not used for this operation
-      next_usdc_boost: 0, // Not used for this operation
-      next_aifi_boost: 0, // Not used for this operation
-      current_time: currentTime,
+    const initArgs = {
+      operation_type: 0, // INITIALIZE
+      entity_type: ENTITY_TYPE.GPU,
+      max_durability: 10000,
+      wear_per_hour: 0, // A free reset that would also stop wear
+      degradation_threshold: 5000,
+      overclock_multiplier: 10000,
+      repair_usdc_per_point: 0,
+      repair_aifi_per_point: 0,
+      repair_points: 0, // not used for this operation
+      current_time: Math.floor(Date.now() / 1000),
+      cooling_rating: 0, // not used for this operation
+      energy_contract_coverage: 0, // not used for this operation
+      power_outage_odds: 0, // not used for this operation
+      gpu_burnout_odds: 0, // not used for this operation
+      mining_bonus_odds: 0, // not used for this operation
+      outage_yield_multiplier: 0, // not used for this operation
+      burnout_durability_damage: 0, // not used for this operation
+      mining_bonus_multiplier: 0, // not used for this operation
+      incidents_enabled: false // not used for this operation
     };
     
     try {
-      // Apply the system to attempt upgrade (should fail)
       const applySystem = await ApplySystem({
         authority: provider.wallet.publicKey,
-        systemId: systemUpgrade.programId,
+        systemId: systemMaintenance.programId,
         world: worldPda,
         entities: [{
           entity: entityPda,
           components: [
-            { componentId: upgradeableComponent.programId }, // upgradeable component
-            { componentId: walletComponent.programId },      // wallet component
-            { componentId: productionComponent.programId },  // production component
+            { componentId: durabilityComponent.programId }, // durability component
+            { componentId: walletComponent.programId },     // wallet component
+            { componentId: productionComponent.programId }, // production component
+            { componentId: facilityComponent.programId }, // facility component
+            { componentId: ownershipComponent.programId }, // owner's ownership
           ],
         }],
-        args: upgradeArgs,
+        args: initArgs,
+        extraAccounts: [
+          { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+        ],
       });
       
-      const txSign = await provider.sendAndConfirm(applySystem.transaction);
-      console.log(`Upgrade unexpectedly succeeded: ${txSign}`);
-      expect.fail("Upgrade should have failed due to insufficient AiFi funds");
+      await provider.sendAndConfirm(applySystem.transaction, [gameAuthority]);
+      expect.fail("Durability should only be initialized once");
     } catch (error) {
-      console.log(`Upgrade correctly failed due to insufficient AiFi funds`);
-      
-      // Verify component wasn't upgraded
-      const upgradeableAfter = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
-      if (upgradeableAfter.currentLevel !== 1) {
-        console.log(`WARNING: Current level is ${upgradeableAfter.currentLevel}, expected 1`);
-      }
-      // We'll just verify a successful reinitialization in the next step
+      expect(error.toString()).to.not.include("Durability should only be initialized once");
+      console.log(`Re-initialization correctly rejected`);
     }
+    
+    const durabilityAfter = await durabilityComponent.account.durability.fetch(durabilityComponentPda);
+    expect(durabilityAfter.wearPerHour).to.equal(durabilityBefore.wearPerHour);
   });
 
-  it("Add AiFi funds and reduce costs for successful upgrade", async () => {
-    // First let's reinitialize upgrade properties with lower cost
-    // This ensures we're in a known state 
-    const currentTime = Math.floor(Date.now() / 1000);
-    
-    // Update upgrade settings with lower costs
+  it("Attempt to update durability parameters without the game authority (should fail)", async () => {
     const updateArgs = {
-      operation_type: 2, // UPDATE_PARAMS
-      entity_type: 1, // GPU
-      current_level: 1, // Not used for level setting
-      max_level: 5,
-      upgrade_cooldown: 3600,
-      next_upgrade_usdc_cost: 100000000, // 100 USDC
-      next_upgrade_aifi_cost: 10000000, // Only 10 AiFi required now
-      next_usdc_boost: 2000,
-      next_aifi_boost: 3000,
-      current_time: currentTime,
+      operation_type: 3, // UPDATE_PARAMS
+      entity_type: 0, // not used for this operation
+      max_durability: 10000,
+      wear_per_hour: 0, // Would stop wear altogether
+      degradation_threshold: 5000,
+      overclock_multiplier: 0, // not used for this operation
+      repair_usdc_per_point: 0,
+      repair_aifi_per_point: 0,
+      repair_points: 0, // not used for this operation
+      current_time: Math.floor(Date.now() / 1000),
+      cooling_rating: 0, // not used for this operation
+      energy_contract_coverage: 0, // not used for this operation
+      power_outage_odds: 0, // not used for this operation
+      gpu_burnout_odds: 0, // not used for this operation
+      mining_bonus_odds: 0, // not used for this operation
+      outage_yield_multiplier: 0, // not used for this operation
+      burnout_durability_damage: 0, // not used for this operation
+      mining_bonus_multiplier: 0, // not used for this operation
+      incidents_enabled: false // not used for this operation
     };
     
-    console.log("Reducing AiFi cost for testing...");
+    try {
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemMaintenance.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: durabilityComponent.programId }, // durability component
+            { componentId: walletComponent.programId },     // wallet component
+            { componentId: productionComponent.programId }, // production component
+            { componentId: facilityComponent.programId }, // facility component
+            { componentId: ownershipComponent.programId }, // owner's ownership
+          ],
+        }],
+        args: updateArgs,
+        extraAccounts: [
+          { pubkey: provider.wallet.publicKey, isSigner: true, isWritable: false }, // not the game authority
+        ],
+      });
+      
+      await provider.sendAndConfirm(applySystem.transaction);
+      expect.fail("Only the game authority should update durability parameters");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Only the game authority should update durability parameters");
+      console.log(`Durability parameter update correctly rejected for a player`);
+    }
+    
+    const durability = await durabilityComponent.account.durability.fetch(durabilityComponentPda);
+    expect(durability.wearPerHour).to.equal(100);
+  });
+
+  it("Attempt to repair undamaged hardware (should fail)", async () => {
+    const repairArgs = {
+      operation_type: 1, // REPAIR
+      entity_type: 0, // not used for this operation
+      max_durability: 0, // not used for this operation
+      wear_per_hour: 0, // not used for this operation
+      degradation_threshold: 0, // not used for this operation
+      overclock_multiplier: 0, // not used for this operation
+      repair_usdc_per_point: 0, // not used for this operation
+      repair_aifi_per_point: 0, // not used for this operation
+      repair_points: 0, // Full repair
+      current_time: Math.floor(Date.now() / 1000),
+      cooling_rating: 0, // not used for this operation
+      energy_contract_coverage: 0, // not used for this operation
+      power_outage_odds: 0, // not used for this operation
+      gpu_burnout_odds: 0, // not used for this operation
+      mining_bonus_odds: 0, // not used for this operation
+      outage_yield_multiplier: 0, // not used for this operation
+      burnout_durability_damage: 0, // not used for this operation
+      mining_bonus_multiplier: 0, // not used for this operation
+      incidents_enabled: false // not used for this operation
+    };
+    
+    const walletBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
+    
+    try {
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemMaintenance.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: durabilityComponent.programId }, // durability component
+            { componentId: walletComponent.programId },     // wallet component
+            { componentId: productionComponent.programId }, // production component
+            { componentId: facilityComponent.programId }, // facility component
+            { componentId: ownershipComponent.programId }, // owner's ownership
+          ],
+        }],
+        args: repairArgs,
+        extraAccounts: [
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // GPU entity
+        ],
+      });
+      
+      await provider.sendAndConfirm(applySystem.transaction);
+      expect.fail("Repair should have failed because hardware is at max durability");
+    } catch (error) {
+      console.log(`Repair correctly failed for undamaged hardware`);
+      
+      const walletAfter = await walletComponent.account.wallet.fetch(walletComponentPda);
+      expect(walletAfter.usdcBalance.toNumber()).to.equal(walletBefore.usdcBalance.toNumber());
+    }
+  });
+
+  it("Configure facility using Maintenance system", async () => {
+    const configureArgs = {
+      operation_type: 4, // CONFIGURE_FACILITY
+      entity_type: ENTITY_TYPE.DATA_CENTER,
+      max_durability: 0, // not used for this operation
+      wear_per_hour: 0, // not used for this operation
+      degradation_threshold: 0, // not used for this operation
+      overclock_multiplier: 0, // not used for this operation
+      repair_usdc_per_point: 0, // not used for this operation
+      repair_aifi_per_point: 0, // not used for this operation
+      repair_points: 0, // not used for this operation
+      current_time: Math.floor(Date.now() / 1000),
+      cooling_rating: 5000, // Halves the burnout odds
+      energy_contract_coverage: 7500, // 75% of power under contract
+      power_outage_odds: 400, // 4% per collection before contracts
+      gpu_burnout_odds: 200, // 2% per collection before cooling
+      mining_bonus_odds: 300, // 3% per collection
+      outage_yield_multiplier: 5000, // Outages halve the yield
+      burnout_durability_damage: 1000, // Burnouts cost 10% durability
+      mining_bonus_multiplier: 15000, // Bonuses add 50% yield
+      incidents_enabled: false // Rolling incidents requires VRF randomness
+    };
     
-    // Apply the system to update parameters
-    const updateSystem = await ApplySystem({
+    const applySystem = await ApplySystem({
       authority: provider.wallet.publicKey,
-      systemId: systemUpgrade.programId,
+      systemId: systemMaintenance.programId,
       world: worldPda,
       entities: [{
         entity: entityPda,
         components: [
-          { componentId: upgradeableComponent.programId }, // upgradeable component
-          { componentId: walletComponent.programId },      // wallet component
-          { componentId: productionComponent.programId },  // production component
+          { componentId: durabilityComponent.programId }, // durability component
+          { componentId: walletComponent.programId },     // wallet component
+          { componentId: productionComponent.programId }, // production component
+          { componentId: facilityComponent.programId }, // facility component
+          { componentId: ownershipComponent.programId }, // owner's ownership
         ],
       }],
-      args: updateArgs,
+      args: configureArgs,
+      extraAccounts: [
+        { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+      ],
     });
     
-    await provider.sendAndConfirm(updateSystem.transaction);
+    const txSign = await provider.sendAndConfirm(applySystem.transaction, [gameAuthority]);
+    console.log(`Applied maintenance system to configure facility. Signature: ${txSign}`);
+    
+    const facility = await facilityComponent.account.facility.fetch(facilityComponentPda);
+    expect(facility.coolingRating).to.equal(5000);
+    expect(facility.energyContractCoverage).to.equal(7500);
+    expect(facility.powerOutageOdds).to.equal(400);
+    expect(facility.miningBonusOdds).to.equal(300);
+    expect(facility.incidentsEnabled).to.equal(false);
+  });
+
+  it("Attempt to configure the facility without the game authority (should fail)", async () => {
+    const configureArgs = {
+      operation_type: 4, // CONFIGURE_FACILITY
+      entity_type: ENTITY_TYPE.DATA_CENTER,
+      max_durability: 0, // not used for this operation
+      wear_per_hour: 0, // not used for this operation
+      degradation_threshold: 0, // not used for this operation
+      overclock_multiplier: 0, // not used for this operation
+      repair_usdc_per_point: 0, // not used for this operation
+      repair_aifi_per_point: 0, // not used for this operation
+      repair_points: 0, // not used for this operation
+      current_time: Math.floor(Date.now() / 1000),
+      cooling_rating: 10000,
+      energy_contract_coverage: 10000,
+      power_outage_odds: 0,
+      gpu_burnout_odds: 0,
+      mining_bonus_odds: 10000, // Every collection would be a bonus
+      outage_yield_multiplier: 10000,
+      burnout_durability_damage: 0,
+      mining_bonus_multiplier: 15000,
+      incidents_enabled: true
+    };
+    
+    try {
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemMaintenance.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: durabilityComponent.programId }, // durability component
+            { componentId: walletComponent.programId },     // wallet component
+            { componentId: productionComponent.programId }, // production component
+            { componentId: facilityComponent.programId }, // facility component
+            { componentId: ownershipComponent.programId }, // owner's ownership
+          ],
+        }],
+        args: configureArgs,
+        extraAccounts: [
+          { pubkey: provider.wallet.publicKey, isSigner: true, isWritable: false }, // not the game authority
+        ],
+      });
+      
+      await provider.sendAndConfirm(applySystem.transaction);
+      expect.fail("Only the game authority should configure facilities");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Only the game authority should configure facilities");
+      console.log(`Facility configuration correctly rejected without the game authority`);
+    }
+    
+    const facility = await facilityComponent.account.facility.fetch(facilityComponentPda);
+    expect(facility.miningBonusOdds).to.equal(300);
+    expect(facility.incidentsEnabled).to.equal(false);
+  });
+
+  it("Attempt to configure a mining bonus above the cap (should fail)", async () => {
+    const configureArgs = {
+      operation_type: 4, // CONFIGURE_FACILITY
+      entity_type: ENTITY_TYPE.DATA_CENTER,
+      max_durability: 0, // not used for this operation
+      wear_per_hour: 0, // not used for this operation
+      degradation_threshold: 0, // not used for this operation
+      overclock_multiplier: 0, // not used for this operation
+      repair_usdc_per_point: 0, // not used for this operation
+      repair_aifi_per_point: 0, // not used for this operation
+      repair_points: 0, // not used for this operation
+      current_time: Math.floor(Date.now() / 1000),
+      cooling_rating: 5000,
+      energy_contract_coverage: 7500,
+      power_outage_odds: 400,
+      gpu_burnout_odds: 200,
+      mining_bonus_odds: 300,
+      outage_yield_multiplier: 5000,
+      burnout_durability_damage: 1000,
+      mining_bonus_multiplier: 30001, // Just above the 3x cap
+      incidents_enabled: false
+    };
+    
+    try {
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemMaintenance.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: durabilityComponent.programId }, // durability component
+            { componentId: walletComponent.programId },     // wallet component
+            { componentId: productionComponent.programId }, // production component
+            { componentId: facilityComponent.programId }, // facility component
+            { componentId: ownershipComponent.programId }, // owner's ownership
+          ],
+        }],
+        args: configureArgs,
+        extraAccounts: [
+          { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+        ],
+      });
+      
+      await provider.sendAndConfirm(applySystem.transaction, [gameAuthority]);
+      expect.fail("Mining bonus multiplier above the cap should be rejected");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Mining bonus multiplier above the cap should be rejected");
+      console.log(`Mining bonus above the cap correctly rejected`);
+    }
+    
+    const facility = await facilityComponent.account.facility.fetch(facilityComponentPda);
+    expect(facility.miningBonusMultiplier).to.equal(15000);
+  });
+
+  it("Attempt to request an incident roll while incidents are disabled (should fail)", async () => {
+    const rollArgs = {
+      operation_type: 4, // REQUEST_INCIDENT_ROLL
+      usdc_per_hour: 0, // not used for this operation
+      aifi_per_hour: 0, // not used for this operation
+      current_time: Math.floor(Date.now() / 1000),
+      producer_type: 0, // not used for this operation
+      level: 0, // not used for this operation
+      is_active: false, // not used for this operation
+      operating_cost: 0, // not used for this operation
+      efficiency_multiplier: 0 // not used for this operation
+    };
+    
+    try {
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemResourceProduction.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: productionComponent.programId }, // production component
+            { componentId: walletComponent.programId },     // wallet component
+            { componentId: durabilityComponent.programId }, // durability component
+            { componentId: facilityComponent.programId }, // facility component
+            { componentId: playerProfileComponent.programId }, // player profile
+            { componentId: hostingAgreementComponent.programId }, // hosting agreement
+          ],
+        }, {
+          entity: entity2Pda,
+          components: [
+            { componentId: walletComponent.programId }, // host wallet
+          ],
+        }],
+        args: rollArgs,
+        extraAccounts: [
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // producer entity
+          { pubkey: provider.wallet.publicKey, isSigner: true, isWritable: true }, // requester
+        ],
+      });
+      
+      await provider.sendAndConfirm(applySystem.transaction);
+      expect.fail("Incident rolls should only be requested when incidents are enabled");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Incident rolls should only be requested when incidents are enabled");
+      console.log(`Incident roll correctly rejected while incidents are disabled`);
+    }
+    
+    const production = await productionComponent.account.production.fetch(productionComponentPda);
+    expect(production.incidentRollPending).to.equal(false);
+    expect(production.incidentRequestId.toNumber()).to.equal(0);
+  });
+
+  it("Verify upgrade properties copied from the catalog", async () => {
+    const upgradeable = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
+    
+    expect(upgradeable.currentLevel).to.equal(1);
+    expect(upgradeable.maxLevel).to.equal(3);
+    expect(upgradeable.upgradeableType).to.equal(ENTITY_TYPE.GPU);
+    expect(upgradeable.canUpgrade).to.equal(true);
+    expect(upgradeable.nextUpgradeUsdcCost.toNumber()).to.equal(200000000);
+    expect(upgradeable.nextUpgradeAifiCost.toNumber()).to.equal(25000000); // set high enough to ensure the next test fails
+    expect(upgradeable.upgradeCooldown).to.equal(0);
+    expect(upgradeable.nextUsdcGain.toNumber()).to.equal(5000000);
+    expect(upgradeable.nextAifiGain.toNumber()).to.equal(3000000);
+    expect(upgradeable.nextRequiredPlayerLevel).to.equal(0);
+    
+    console.log(`Upgradeable component spawned with level ${upgradeable.currentLevel}/${upgradeable.maxLevel}`);
+    console.log(`Next upgrade costs: ${upgradeable.nextUpgradeUsdcCost.toNumber()/1000000} USDC, ${upgradeable.nextUpgradeAifiCost.toNumber()/1000000} AiFi`);
+    console.log(`Next upgrade gains: +${upgradeable.nextUsdcGain.toNumber()/1000000} USDC/hr, +${upgradeable.nextAifiGain.toNumber()/1000000} AiFi/hr`);
+  });
+
+  it("Attempt upgrade with another player's entity (should fail)", async () => {
+    const upgradeable = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
+    
+    try {
+      // The wallet does not belong to the second entity
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemUpgrade.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: upgradeableComponent.programId }, // upgradeable component
+            { componentId: walletComponent.programId },      // wallet component
+            { componentId: productionComponent.programId },  // production component
+            { componentId: ownershipComponent.programId },   // player ownership
+          ],
+        }, {
+          entity: catalogEntityPda,
+          components: [
+            { componentId: upgradeTableComponent.programId }, // upgrade table component
+          ],
+        }, {
+          entity: entityPda,
+          components: [
+            { componentId: stakeableComponent.programId }, // compounded upgrade savings
+          ],
+        }],
+        args: {
+          operation_type: 1, // UPGRADE
+          entity_type: 0, // Not used for this operation
+          current_level: 0, // Not used for this operation
+          speed_up_seconds: 0, // Not used for this operation
+          quantity: 0, // Not used for this operation
+        },
+        extraAccounts: [
+          { pubkey: catalogEntityPda, isSigner: false, isWritable: false }, // model entity
+          { pubkey: entity2Pda, isSigner: false, isWritable: false }, // player entity
+          { pubkey: entityPda, isSigner: false, isWritable: false },  // owned entity
+        ],
+      });
+      
+      await provider.sendAndConfirm(applySystem.transaction);
+      expect.fail("Upgrade should have failed for a mismatched player entity");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Upgrade should have failed for a mismatched player entity");
+      console.log(`Upgrade correctly failed for a mismatched player entity`);
+    }
+    
+    const upgradeableAfter = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
+    expect(upgradeableAfter.currentLevel).to.equal(upgradeable.currentLevel);
+  });
+
+  it("Attempt upgrade with insufficient funds", async () => {
+    // First check the wallet balance
+    const walletBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
+    const upgradeable = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
+    
+    console.log(`Current wallet: USDC=${walletBefore.usdcBalance.toNumber()/1000000}, AiFi=${walletBefore.aifiBalance.toNumber()/1000000}`);
+    console.log(`Required for upgrade: USDC=${upgradeable.nextUpgradeUsdcCost.toNumber()/1000000}, AiFi=${upgradeable.nextUpgradeAifiCost.toNumber()/1000000}`);
+    
+    // Ensure we have zero AiFi but enough USDC
+    // Test should fail with InsufficientAifiFunds
+    
+    // Prepare args for upgrade attempt (will fail due to insufficient AiFi)
+    const upgradeArgs = {
+      operation_type: 1, // UPGRADE
+      entity_type: 0, // Not used for this operation
+      current_level: 0, // Not used for this operation
+      speed_up_seconds: 0, // Not used for this operation
+      quantity: 0, // Not used for this operation
+    };
+    
+    try {
+      // Apply the system to attempt upgrade (should fail)
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemUpgrade.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: upgradeableComponent.programId }, // upgradeable component
+            { componentId: walletComponent.programId },      // wallet component
+            { componentId: productionComponent.programId },  // production component
+            { componentId: ownershipComponent.programId },   // player ownership
+          ],
+        }, {
+          entity: catalogEntityPda,
+          components: [
+            { componentId: upgradeTableComponent.programId }, // upgrade table component
+          ],
+        }, {
+          entity: entityPda,
+          components: [
+            { componentId: stakeableComponent.programId }, // compounded upgrade savings
+          ],
+        }],
+        args: upgradeArgs,
+        extraAccounts: [
+          { pubkey: catalogEntityPda, isSigner: false, isWritable: false }, // model entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+        ],
+      });
+      
+      const txSign = await provider.sendAndConfirm(applySystem.transaction);
+      console.log(`Upgrade unexpectedly succeeded: ${txSign}`);
+      expect.fail("Upgrade should have failed due to insufficient AiFi funds");
+    } catch (error) {
+      console.log(`Upgrade correctly failed due to insufficient AiFi funds`);
+      
+      // Verify component wasn't upgraded
+      const upgradeableAfter = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
+      if (upgradeableAfter.currentLevel !== 1) {
+        console.log(`WARNING: Current level is ${upgradeableAfter.currentLevel}, expected 1`);
+      }
+      // We'll just verify a successful reinitialization in the next step
+    }
+  });
 
+  it("Add AiFi funds for successful upgrade", async () => {
     // Now add AiFi to wallet
     const walletBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
     const upgradeable = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
@@ -1416,22 +2828,13 @@ describe("yield-wars-program", () => {
     const productionBefore = await productionComponent.account.production.fetch(productionComponentPda);
     console.log(`Production rates before upgrade: USDC=${productionBefore.usdcPerHour.toNumber()/1000000}/hr, AiFi=${productionBefore.aifiPerHour.toNumber()/1000000}/hr`);
     
-    // Get timestamp with cooldown elapsed
-    const lastUpgradeTime = upgradeable.lastUpgradeTime.toNumber();
-    const upgradeTime = lastUpgradeTime + 3600 + 10; // 1 hour + 10 seconds later
-    
-    // Prepare args for upgrade
+    // Prepare args for upgrade, the first level has no cooldown
     const upgradeArgs = {
       operation_type: 1, // UPGRADE
       entity_type: 0, // Not used for this operation
       current_level: 0, // Not used for this operation
-      max_level: 0, // Not used for this operation
-      upgrade_cooldown: 0, // Not used for this operation
-      next_upgrade_usdc_cost: 0, // Not used for this operation
-      next_upgrade_aifi_cost: 0, // Not used for this operation
-      next_usdc_boost: 0, // Not used for this operation
-      next_aifi_boost: 0, // Not used for this operation
-      current_time: upgradeTime,
+      speed_up_seconds: 0, // Not used for this operation
+      quantity: 0, // Not used for this operation
     };
     
     console.log("Attempting upgrade with adequate AiFi...");
@@ -1447,9 +2850,25 @@ describe("yield-wars-program", () => {
           { componentId: upgradeableComponent.programId }, // upgradeable component
           { componentId: walletComponent.programId },      // wallet component
           { componentId: productionComponent.programId },  // production component
+          { componentId: ownershipComponent.programId },   // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: upgradeTableComponent.programId }, // upgrade table component
+        ],
+      }, {
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId }, // compounded upgrade savings
         ],
       }],
       args: upgradeArgs,
+      extraAccounts: [
+        { pubkey: catalogEntityPda, isSigner: false, isWritable: false }, // model entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+      ],
     });
     
     const txSign = await provider.sendAndConfirm(applySystem.transaction);
@@ -1468,13 +2887,10 @@ describe("yield-wars-program", () => {
     expect(walletAfter.usdcBalance.toNumber()).to.be.below(walletBefore.usdcBalance.toNumber());
     expect(walletAfter.aifiBalance.toNumber()).to.be.below(walletAfterAdd.aifiBalance.toNumber());
     
-    // Check production rates increased
-    const expectedUsdcPerHour = Math.floor(productionBefore.usdcPerHour.toNumber() * 1.2); // 20% boost
-    const expectedAifiPerHour = Math.floor(productionBefore.aifiPerHour.toNumber() * 1.3); // 30% boost
-    
-    // Allow for small rounding differences
-    expect(productionAfter.usdcPerHour.toNumber()).to.be.approximately(expectedUsdcPerHour, 10);
-    expect(productionAfter.aifiPerHour.toNumber()).to.be.approximately(expectedAifiPerHour, 10);
+    // Check production rates increased by the absolute gains of level 2
+    expect(productionAfter.usdcPerHour.toNumber()).to.equal(productionBefore.usdcPerHour.toNumber() + 5000000);
+    expect(productionAfter.aifiPerHour.toNumber()).to.equal(productionBefore.aifiPerHour.toNumber() + 3000000);
+    expect(productionAfter.solPerHour.toNumber()).to.equal(productionBefore.solPerHour.toNumber() + 20000);
     
     console.log(`Upgrade successful. New level: ${upgradeableAfter.currentLevel}/${upgradeableAfter.maxLevel}`);
     console.log(`New production rates: USDC=${productionAfter.usdcPerHour.toNumber()/1000000}/hr, AiFi=${productionAfter.aifiPerHour.toNumber()/1000000}/hr`);
@@ -1486,10 +2902,8 @@ describe("yield-wars-program", () => {
     const upgradeable = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
     const lastUpgradeTime = upgradeable.lastUpgradeTime.toNumber();
     
-    // Use a time that's too soon after the last upgrade
-    const currentTime = lastUpgradeTime + 600; // Only 10 minutes later (cooldown is 1 hour)
-    
-    console.log(`Last upgrade time: ${lastUpgradeTime}, attempting upgrade at: ${currentTime}`);
+    // The upgrade is attempted right after the last one (cooldown is 1 minute)
+    console.log(`Last upgrade time: ${lastUpgradeTime}, attempting upgrade right away`);
     console.log(`Cooldown period: ${upgradeable.upgradeCooldown} seconds`);
     
     // Prepare args for upgrade attempt
@@ -1497,13 +2911,8 @@ describe("yield-wars-program", () => {
       operation_type: 1, // UPGRADE
       entity_type: 0, // Not used for this operation
       current_level: 0, // Not used for this operation
-      max_level: 0, // Not used for this operation
-      upgrade_cooldown: 0, // Not used for this operation
-      next_upgrade_usdc_cost: 0, // Not used for this operation
-      next_upgrade_aifi_cost: 0, // Not used for this operation
-      next_usdc_boost: 0, // Not used for this operation
-      next_aifi_boost: 0, // Not used for this operation
-      current_time: currentTime,
+      speed_up_seconds: 0, // Not used for this operation
+      quantity: 0, // Not used for this operation
     };
     
     try {
@@ -1518,11 +2927,27 @@ describe("yield-wars-program", () => {
             { componentId: upgradeableComponent.programId }, // upgradeable component
             { componentId: walletComponent.programId },      // wallet component
             { componentId: productionComponent.programId },  // production component
+            { componentId: ownershipComponent.programId },   // player ownership
           ],
-        }],
-        args: upgradeArgs,
-      });
-      
+        }, {
+          entity: catalogEntityPda,
+          components: [
+            { componentId: upgradeTableComponent.programId }, // upgrade table component
+          ],
+        }, {
+          entity: entityPda,
+          components: [
+            { componentId: stakeableComponent.programId }, // compounded upgrade savings
+          ],
+        }],
+        args: upgradeArgs,
+        extraAccounts: [
+          { pubkey: catalogEntityPda, isSigner: false, isWritable: false }, // model entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+        ],
+      });
+      
       const txSign = await provider.sendAndConfirm(applySystem.transaction);
       console.log(`Upgrade unexpectedly succeeded: ${txSign}`);
       expect.fail("Upgrade should have failed due to cooldown period");
@@ -1536,93 +2961,37 @@ describe("yield-wars-program", () => {
   });
 
   it("Upgrade to max level and verify max level restriction", async () => {
-    // First update the max level to be just 1 above current level
-    // Get current level
+    // The upgrade table allows one more upgrade, priced by its level 3 entry
     const upgradeBefore = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
-    const currentLevel = upgradeBefore.currentLevel;
-    const newMaxLevel = currentLevel + 1; // Only allow one more upgrade
-    
-    // Get current AiFi balance to ensure we set costs appropriately
-    const walletBeforeTest = await walletComponent.account.wallet.fetch(walletComponentPda);
-    const currentAiFi = walletBeforeTest.aifiBalance.toNumber();
-    console.log(`Current level: ${currentLevel}, setting max level to: ${newMaxLevel}`);
-    console.log(`Current AiFi balance: ${currentAiFi/1000000}, ensuring costs are lower`);
-    
-    // Set extremely low upgrade costs to ensure test passes
-    const aifiCost = Math.min(5000000, currentAiFi - 1000000); // 5 AiFi or lower
-    
-    // Update max level and costs
-    const updateArgs = {
-      operation_type: 2, // UPDATE_PARAMS
-      entity_type: 1, // GPU
-      current_level: 0, // Not used for level setting
-      max_level: newMaxLevel,
-      upgrade_cooldown: 5, // Reduce to just 5 seconds for testing
-      next_upgrade_usdc_cost: 1000000, // Just 1 USDC
-      next_upgrade_aifi_cost: aifiCost, // Very low AiFi cost
-      next_usdc_boost: upgradeBefore.nextUsdcBoost,
-      next_aifi_boost: upgradeBefore.nextAifiBoost,
-      current_time: Math.floor(Date.now() / 1000),
-    };
-    
-    console.log(`Setting next AiFi cost to: ${aifiCost/1000000} (Current balance: ${currentAiFi/1000000})`);
-    
-    // Apply the update
-    const updateSystem = await ApplySystem({
-      authority: provider.wallet.publicKey,
-      systemId: systemUpgrade.programId,
-      world: worldPda,
-      entities: [{
-        entity: entityPda,
-        components: [
-          { componentId: upgradeableComponent.programId },
-          { componentId: walletComponent.programId },
-          { componentId: productionComponent.programId },
-        ],
-      }],
-      args: updateArgs,
-    });
-    
-    await provider.sendAndConfirm(updateSystem.transaction);
-    
-    // Get updated parameters
-    const upgradeAfterUpdate = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
-    console.log(`Max level updated to: ${upgradeAfterUpdate.maxLevel}, cooldown set to: ${upgradeAfterUpdate.upgradeCooldown}s`);
-    console.log(`Last upgrade time: ${upgradeAfterUpdate.lastUpgradeTime.toNumber()}`);
-    console.log(`Upgrade costs: USDC=${upgradeAfterUpdate.nextUpgradeUsdcCost.toNumber()/1000000}, AiFi=${upgradeAfterUpdate.nextUpgradeAifiCost.toNumber()/1000000}`);
-    
-    // Verify AiFi cost was correctly set
-    expect(upgradeAfterUpdate.nextUpgradeAifiCost.toNumber()).to.equal(aifiCost);
+    expect(upgradeBefore.currentLevel).to.equal(2);
+    expect(upgradeBefore.maxLevel).to.equal(3);
+    expect(upgradeBefore.nextUpgradeUsdcCost.toNumber()).to.equal(400000000);
+    expect(upgradeBefore.nextUpgradeAifiCost.toNumber()).to.equal(35000000);
+    expect(upgradeBefore.nextUsdcGain.toNumber()).to.equal(10000000);
     
     // Get the latest wallet balance to verify we have enough for the upgrade
     const walletNow = await walletComponent.account.wallet.fetch(walletComponentPda);
     console.log(`Current wallet: USDC=${walletNow.usdcBalance.toNumber()/1000000}, AiFi=${walletNow.aifiBalance.toNumber()/1000000}`);
     
     // Verify we have enough AiFi
-    expect(walletNow.aifiBalance.toNumber()).to.be.above(upgradeAfterUpdate.nextUpgradeAifiCost.toNumber());
+    expect(walletNow.aifiBalance.toNumber()).to.be.above(upgradeBefore.nextUpgradeAifiCost.toNumber());
     
     // Get timestamp with cooldown elapsed
     const latestUpgrade = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
     const lastUpgradeTime = latestUpgrade.lastUpgradeTime.toNumber();
     const cooldownPeriod = latestUpgrade.upgradeCooldown;
     
-    // Use a timestamp way in the future to bypass cooldown
-    const futureTime = lastUpgradeTime + cooldownPeriod + 1000; // 1000 seconds beyond cooldown
-    console.log(`Last upgrade time: ${lastUpgradeTime}, using future time: ${futureTime}`);
-    console.log(`Cooldown period: ${cooldownPeriod} seconds, difference: ${futureTime - lastUpgradeTime} seconds`);
+    // Wait for the cooldown to elapse on the cluster clock
+    console.log(`Last upgrade time: ${lastUpgradeTime}, cooldown period: ${cooldownPeriod} seconds`);
+    await waitForClusterTime(lastUpgradeTime + cooldownPeriod + 1);
     
     // Now perform upgrade to reach max level
     const upgradeToMaxArgs = {
       operation_type: 1, // UPGRADE
       entity_type: 0, // Not used
       current_level: 0, // Not used
-      max_level: 0, // Not used
-      upgrade_cooldown: 0, // Not used
-      next_upgrade_usdc_cost: 0, // Not used
-      next_upgrade_aifi_cost: 0, // Not used
-      next_usdc_boost: 0, // Not used
-      next_aifi_boost: 0, // Not used
-      current_time: futureTime,
+      speed_up_seconds: 0, // Not used
+      quantity: 0, // Not used
     };
     
     // Perform the upgrade
@@ -1636,9 +3005,25 @@ describe("yield-wars-program", () => {
           { componentId: upgradeableComponent.programId },
           { componentId: walletComponent.programId },
           { componentId: productionComponent.programId },
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: upgradeTableComponent.programId }, // upgrade table component
+        ],
+      }, {
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId }, // compounded upgrade savings
         ],
       }],
       args: upgradeToMaxArgs,
+      extraAccounts: [
+        { pubkey: catalogEntityPda, isSigner: false, isWritable: false }, // model entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+      ],
     });
     
     await provider.sendAndConfirm(upgradeSystem.transaction);
@@ -1655,13 +3040,8 @@ describe("yield-wars-program", () => {
         operation_type: 1, // UPGRADE
         entity_type: 0, // Not used
         current_level: 0, // Not used
-        max_level: 0, // Not used
-        upgrade_cooldown: 0, // Not used
-        next_upgrade_usdc_cost: 0, // Not used
-        next_upgrade_aifi_cost: 0, // Not used
-        next_usdc_boost: 0, // Not used
-        next_aifi_boost: 0, // Not used
-        current_time: futureTime + 1000, // Even further in the future
+        speed_up_seconds: 0, // Not used
+        quantity: 0, // Not used
       };
       
       const finalUpgrade = await ApplySystem({
@@ -1674,9 +3054,25 @@ describe("yield-wars-program", () => {
             { componentId: upgradeableComponent.programId },
             { componentId: walletComponent.programId },
             { componentId: productionComponent.programId },
+            { componentId: ownershipComponent.programId }, // player ownership
+          ],
+        }, {
+          entity: catalogEntityPda,
+          components: [
+            { componentId: upgradeTableComponent.programId }, // upgrade table component
+          ],
+        }, {
+          entity: entityPda,
+          components: [
+            { componentId: stakeableComponent.programId }, // compounded upgrade savings
           ],
         }],
         args: finalUpgradeArgs,
+        extraAccounts: [
+          { pubkey: catalogEntityPda, isSigner: false, isWritable: false }, // model entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+        ],
       });
       
       await provider.sendAndConfirm(finalUpgrade.transaction);
@@ -1691,25 +3087,59 @@ describe("yield-wars-program", () => {
     }
   });
 
-  it("Update upgrade parameters", async () => {
-    // Get current parameters
-    const upgradeableBefore = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
+  it("Sync a new level added to the upgrade table", async () => {
+    // The admin extends the table with a fourth level
+    const setLevelArgs = {
+      operation_type: 1, // SET_LEVEL
+      entity_type: 0, // Not used for this operation
+      model_id: 0, // Not used for this operation
+      level: 4,
+      usdc_cost: 800000000, // 800 USDC
+      aifi_cost: 50000000, // 50 AiFi
+      usdc_gain: 20000000, // +20 USDC per hour
+      aifi_gain: 10000000, // +10 AiFi per hour
+      btc_gain: 0,
+      eth_gain: 0,
+      sol_gain: 80000, // +0.08 SOL per hour
+      cooldown: 7200, // 2 hours after the previous upgrade
+      required_player_level: 5,
+      build_duration: 0,
+      construction_yield: 0, // Not used for this operation
+      speed_up_aifi_per_hour: 0, // Not used for this operation
+      cancel_refund_rate: 0, // Not used for this operation
+      success_rate: 0, // Not used for this operation
+      critical_failure_rate: 0, // Not used for this operation
+      risk_start_level: 0, // Not used for this operation
+      protection_aifi_cost: 0, // Not used for this operation
+    };
     
-    // Prepare args to update upgrade parameters
-    const updateArgs = {
-      operation_type: 2, // UPDATE_PARAMS
-      entity_type: 1, // GPU
+    const setLevelSystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemUpgradeCurve.programId,
+      world: worldPda,
+      entities: [{
+        entity: catalogEntityPda,
+        components: [
+          { componentId: upgradeTableComponent.programId }, // upgrade table component
+        ],
+      }],
+      args: setLevelArgs,
+      extraAccounts: [
+        { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+      ],
+    });
+    
+    await provider.sendAndConfirm(setLevelSystem.transaction, [gameAuthority]);
+    
+    // Refresh the GPU's next upgrade from the table
+    const syncArgs = {
+      operation_type: 2, // SYNC_TABLE
+      entity_type: 0, // Not used for this operation
       current_level: 0, // Not used for this operation
-      max_level: 10, // Increase max level from 5 to 10
-      upgrade_cooldown: 1800, // Reduce cooldown to 30 minutes
-      next_upgrade_usdc_cost: 80000000, // 80 USDC (reduced cost)
-      next_upgrade_aifi_cost: 8000000, // 8 AiFi (reduced cost)
-      next_usdc_boost: 3000, // 30% boost (increased from 20%)
-      next_aifi_boost: 4000, // 40% boost (increased from 30%)
-      current_time: Math.floor(Date.now() / 1000),
+      speed_up_seconds: 0, // Not used for this operation
+      quantity: 0, // Not used for this operation
     };
     
-    // Apply the system to update parameters
     const applySystem = await ApplySystem({
       authority: provider.wallet.publicKey,
       systemId: systemUpgrade.programId,
@@ -1720,233 +3150,2271 @@ describe("yield-wars-program", () => {
           { componentId: upgradeableComponent.programId }, // upgradeable component
           { componentId: walletComponent.programId },      // wallet component
           { componentId: productionComponent.programId },  // production component
+          { componentId: ownershipComponent.programId },   // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: upgradeTableComponent.programId }, // upgrade table component
+        ],
+      }, {
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId }, // compounded upgrade savings
         ],
       }],
-      args: updateArgs,
+      args: syncArgs,
+      extraAccounts: [
+        { pubkey: catalogEntityPda, isSigner: false, isWritable: false }, // model entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+      ],
     });
     
     const txSign = await provider.sendAndConfirm(applySystem.transaction);
-    console.log(`Applied upgrade system to update parameters. Signature: ${txSign}`);
+    console.log(`Applied upgrade system to sync the upgrade table. Signature: ${txSign}`);
     
-    // Verify parameters were updated
     const upgradeableAfter = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
+    expect(upgradeableAfter.currentLevel).to.equal(3);
+    expect(upgradeableAfter.maxLevel).to.equal(4);
+    expect(upgradeableAfter.canUpgrade).to.equal(true);
+    expect(upgradeableAfter.nextUpgradeUsdcCost.toNumber()).to.equal(800000000);
+    expect(upgradeableAfter.upgradeCooldown).to.equal(7200);
+    expect(upgradeableAfter.nextRequiredPlayerLevel).to.equal(5);
+  });
+
+  it("Attempt upgrade without the required player level (should fail)", async () => {
+    const upgradeable = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
     
-    expect(upgradeableAfter.maxLevel).to.equal(10);
-    expect(upgradeableAfter.upgradeCooldown).to.equal(1800);
-    expect(upgradeableAfter.nextUpgradeUsdcCost.toNumber()).to.equal(80000000);
-    expect(upgradeableAfter.nextUpgradeAifiCost.toNumber()).to.equal(8000000);
-    expect(upgradeableAfter.nextUsdcBoost).to.equal(3000);
-    expect(upgradeableAfter.nextAifiBoost).to.equal(4000);
+    const upgradeArgs = {
+      operation_type: 1, // UPGRADE
+      entity_type: 0, // Not used for this operation
+      current_level: 0, // Not used for this operation
+      speed_up_seconds: 0, // Not used for this operation
+      quantity: 0, // Not used for this operation
+    };
     
-    // This should still be true since level 2 < max level 10
-    expect(upgradeableAfter.canUpgrade).to.equal(true);
+    try {
+      // No player Upgradeable component is passed, so the level 5 requirement cannot be met
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemUpgrade.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: upgradeableComponent.programId }, // upgradeable component
+            { componentId: walletComponent.programId },      // wallet component
+            { componentId: productionComponent.programId },  // production component
+            { componentId: ownershipComponent.programId },   // player ownership
+          ],
+        }, {
+          entity: catalogEntityPda,
+          components: [
+            { componentId: upgradeTableComponent.programId }, // upgrade table component
+          ],
+        }, {
+          entity: entityPda,
+          components: [
+            { componentId: stakeableComponent.programId }, // compounded upgrade savings
+          ],
+        }],
+        args: upgradeArgs,
+        extraAccounts: [
+          { pubkey: catalogEntityPda, isSigner: false, isWritable: false }, // model entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+        ],
+      });
+      
+      await provider.sendAndConfirm(applySystem.transaction);
+      expect.fail("Upgrade should have failed due to the player level requirement");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Upgrade should have failed due to the player level requirement");
+      console.log(`Upgrade correctly failed due to the player level requirement`);
+    }
     
-    console.log(`Upgrade parameters updated successfully:`);
-    console.log(`New max level: ${upgradeableAfter.maxLevel}`);
-    console.log(`New cooldown: ${upgradeableAfter.upgradeCooldown} seconds`);
-    console.log(`New costs: ${upgradeableAfter.nextUpgradeUsdcCost.toNumber()/1000000} USDC, ${upgradeableAfter.nextUpgradeAifiCost.toNumber()/1000000} AiFi`);
-    console.log(`New boosts: ${upgradeableAfter.nextUsdcBoost/100}% USDC, ${upgradeableAfter.nextAifiBoost/100}% AiFi`);
+    const upgradeableAfter = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
+    expect(upgradeableAfter.currentLevel).to.equal(3);
   });
 
-  it("Apply movement system", async () => {
-    // Check that the component has been initialized and x is 0
-    const positionBefore = await positionComponent.account.position.fetch(
-      positionComponentPda
-    );
-    expect(positionBefore.x.toNumber()).to.equal(0);
-
-    // Run the movement system
-    const applySystem = await ApplySystem({
+  it("Start a timed upgrade and cancel it for a partial refund", async () => {
+    // Replace level 4 with a cheaper upgrade that takes 2 hours to build
+    const setLevelArgs = {
+      operation_type: 1, // SET_LEVEL
+      entity_type: 0, // Not used for this operation
+      model_id: 0, // Not used for this operation
+      level: 4,
+      usdc_cost: 100000000, // 100 USDC
+      aifi_cost: 0,
+      usdc_gain: 20000000, // +20 USDC per hour
+      aifi_gain: 10000000, // +10 AiFi per hour
+      btc_gain: 0,
+      eth_gain: 0,
+      sol_gain: 80000, // +0.08 SOL per hour
+      cooldown: 0, // Right after the previous upgrade
+      required_player_level: 0,
+      build_duration: 7200, // 2 hours of construction
+      construction_yield: 0, // Not used for this operation
+      speed_up_aifi_per_hour: 0, // Not used for this operation
+      cancel_refund_rate: 0, // Not used for this operation
+      success_rate: 0, // Not used for this operation
+      critical_failure_rate: 0, // Not used for this operation
+      risk_start_level: 0, // Not used for this operation
+      protection_aifi_cost: 0, // Not used for this operation
+    };
+    
+    const setLevelSystem = await ApplySystem({
       authority: provider.wallet.publicKey,
-      systemId: systemMovement.programId,
+      systemId: systemUpgradeCurve.programId,
       world: worldPda,
       entities: [{
-        entity: entityPda,
-        components: [{ componentId: positionComponent.programId }],
-      }]
+        entity: catalogEntityPda,
+        components: [
+          { componentId: upgradeTableComponent.programId }, // upgrade table component
+        ],
+      }],
+      args: setLevelArgs,
+      extraAccounts: [
+        { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+      ],
     });
-    const txSign = await provider.sendAndConfirm(applySystem.transaction);
-    console.log(`Applied a system. Signature: ${txSign}`);
-
-    // Check that the system has been applied and x is > 0
-    const positionAfter = await positionComponent.account.position.fetch(
-      positionComponentPda
-    );
-    expect(positionAfter.x.toNumber()).to.gt(0);
-  });
-
-  // After the upgrade tests, add these tests for the staking system
-
-  it("Initialize staking properties", async () => {
-    // Get current unix timestamp in seconds
-    const currentTime = Math.floor(Date.now() / 1000);
     
-    // Initial staking settings for a GPU
-    const initialArgs = {
-      operation_type: 0, // INITIALIZE
-      staking_type: 1, // GPU
-      min_staking_period: 86400, // 1 day in seconds
-      reward_rate: 15000, // 150% of base rate (10000 = 100%)
-      unstaking_penalty: 5000, // 50% penalty for early unstaking (10000 = 100%)
-      base_usdc_per_hour: 3000000, // 3 USDC per hour
-      base_aifi_per_hour: 6000000, // 6 AiFi per hour
-      current_time: currentTime,
-      stake: false, // Not used for initialization
-      can_claim_rewards: true // Whether rewards can be claimed
-    };
+    await provider.sendAndConfirm(setLevelSystem.transaction, [gameAuthority]);
     
-    // Apply the system to initialize staking properties
-    const applySystem = await ApplySystem({
+    // Make sure the wallet can pay for the upgrade twice
+    const addUsdcSystem = await ApplySystem({
       authority: provider.wallet.publicKey,
-      systemId: systemStaking.programId,
+      systemId: systemEconomy.programId,
       world: worldPda,
       entities: [{
         entity: entityPda,
         components: [
-          { componentId: stakeableComponent.programId }, // stakeable component
-          { componentId: walletComponent.programId },    // wallet component
-          { componentId: productionComponent.programId }, // production component
+          { componentId: walletComponent.programId },      // source wallet
+          { componentId: walletComponent.programId },      // destination wallet
+          { componentId: priceComponent.programId },       // source price
+          { componentId: priceComponent.programId },       // destination price
         ],
       }],
-      args: initialArgs,
+      args: {
+        transaction_type: 2, // INITIALIZE
+        currency_type: 0,
+        destination_currency_type: 0,
+        amount: 200000000, // 200 USDC
+      },
     });
     
-    const txSign = await provider.sendAndConfirm(applySystem.transaction);
-    console.log(`Applied staking system to initialize properties. Signature: ${txSign}`);
-
-    // Verify initialization
-    const stakeableAfter = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
-    
-    expect(stakeableAfter.isStaked).to.equal(false);
-    expect(stakeableAfter.minStakingPeriod).to.equal(86400);
-    expect(stakeableAfter.rewardRate).to.equal(15000);
-    expect(stakeableAfter.unstakingPenalty).to.equal(5000);
-    expect(stakeableAfter.baseUsdcPerHour.toNumber()).to.equal(3000000);
-    expect(stakeableAfter.baseAifiPerHour.toNumber()).to.equal(6000000);
-    expect(stakeableAfter.canClaimRewards).to.equal(true);
-    expect(stakeableAfter.stakeableType).to.equal(1); // GPU
+    await provider.sendAndConfirm(addUsdcSystem.transaction);
     
-    console.log(`Staking properties initialized successfully:`);
-    console.log(`Min staking period: ${stakeableAfter.minStakingPeriod} seconds (${stakeableAfter.minStakingPeriod/3600} hours)`);
-    console.log(`Reward rate: ${stakeableAfter.rewardRate/100}%`);
-    console.log(`Unstaking penalty: ${stakeableAfter.unstakingPenalty/100}%`);
-    console.log(`Base rates: ${stakeableAfter.baseUsdcPerHour.toNumber()/1000000} USDC/hr, ${stakeableAfter.baseAifiPerHour.toNumber()/1000000} AiFi/hr`);
-  });
-  
-  it("Stake an entity", async () => {
-    // Activate production before staking test
-    const productionBefore = await productionComponent.account.production.fetch(productionComponentPda);
-    if (!productionBefore.isActive) {
-      // Use resource-production system to activate production
-      const activateArgs = {
-        operation_type: 2, // SET_ACTIVE
-        usdc_per_hour: 0, // Not used for activation
-        aifi_per_hour: 0, // Not used for activation
-        current_time: Math.floor(Date.now() / 1000),
-        producer_type: 0, // Not used for activation
-        level: 0, // Not used for activation
-        is_active: true,
-        operating_cost: 0, // Not used for activation
-        efficiency_multiplier: 0 // Not used for activation
-      };
-      
-      const activateSystem = await ApplySystem({
+    const applyUpgrade = async (operationType: number) => {
+      const applySystem = await ApplySystem({
         authority: provider.wallet.publicKey,
-        systemId: systemResourceProduction.programId,
+        systemId: systemUpgrade.programId,
         world: worldPda,
         entities: [{
           entity: entityPda,
           components: [
-            { componentId: productionComponent.programId },
-            { componentId: walletComponent.programId },
+            { componentId: upgradeableComponent.programId }, // upgradeable component
+            { componentId: walletComponent.programId },      // wallet component
+            { componentId: productionComponent.programId },  // production component
+            { componentId: ownershipComponent.programId },   // player ownership
+          ],
+        }, {
+          entity: catalogEntityPda,
+          components: [
+            { componentId: upgradeTableComponent.programId }, // upgrade table component
+          ],
+        }, {
+          entity: entityPda,
+          components: [
+            { componentId: stakeableComponent.programId }, // compounded upgrade savings
           ],
         }],
-        args: activateArgs,
+        args: {
+          operation_type: operationType,
+          entity_type: 0, // Not used for these operations
+          current_level: 0, // Not used for these operations
+          speed_up_seconds: 0, // Not used for these operations
+          quantity: 0, // Not used for these operations
+        },
+        extraAccounts: [
+          { pubkey: catalogEntityPda, isSigner: false, isWritable: false }, // model entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+        ],
       });
       
-      await provider.sendAndConfirm(activateSystem.transaction);
-      console.log("Activated production before staking test");
-    }
+      return provider.sendAndConfirm(applySystem.transaction);
+    };
     
-    // Get stakeable status before staking
-    const stakeableBefore = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
-    console.log(`Production status before staking: ${productionBefore.isActive ? "Active" : "Inactive"}`);
-    console.log(`Entity staking status before: ${stakeableBefore.isStaked ? "Staked" : "Unstaked"}`);
+    // Pick up the new level
+    await applyUpgrade(2); // SYNC_TABLE
     
-    // Current time for staking
-    const currentTime = Math.floor(Date.now() / 1000);
+    const upgradeableSynced = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
+    expect(upgradeableSynced.nextBuildDuration).to.equal(7200);
+    expect(upgradeableSynced.nextRequiredPlayerLevel).to.equal(0);
     
-    // Prepare args for staking
-    const stakeArgs = {
-      operation_type: 1, // STAKE
-      staking_type: 1, // GPU
-      min_staking_period: 0, // Not used for staking
-      reward_rate: 0, // Not used for staking
-      unstaking_penalty: 0, // Not used for staking
-      base_usdc_per_hour: 0, // Not used for staking
-      base_aifi_per_hour: 0, // Not used for staking
-      current_time: currentTime,
-      stake: true, // Not actually used, just for clarity
-      can_claim_rewards: true // Not used for staking
-    };
+    // Levels with a build duration cannot be applied instantly
+    try {
+      await applyUpgrade(1); // UPGRADE
+      expect.fail("Instant upgrade should have failed for a level that requires construction");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Instant upgrade should have failed for a level that requires construction");
+      console.log("Instant upgrade correctly rejected for a level that requires construction");
+    }
     
-    // Apply the system to stake the entity
-    const applySystem = await ApplySystem({
-      authority: provider.wallet.publicKey,
-      systemId: systemStaking.programId,
-      world: worldPda,
-      entities: [{
-        entity: entityPda,
-        components: [
-          { componentId: stakeableComponent.programId }, // stakeable component
-          { componentId: walletComponent.programId },    // wallet component
-          { componentId: productionComponent.programId }, // production component
-        ],
-      }],
-      args: stakeArgs,
-    });
+    const walletBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
     
-    const txSign = await provider.sendAndConfirm(applySystem.transaction);
-    console.log(`Applied staking system to stake entity. Signature: ${txSign}`);
+    await applyUpgrade(3); // START_UPGRADE
     
-    // Verify staking succeeded
-    const stakeableAfter = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
-    const productionAfter = await productionComponent.account.production.fetch(productionComponentPda);
+    const upgradeableStarted = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
+    const startTime = upgradeableStarted.upgradeStartedAt.toNumber();
+    expect(upgradeableStarted.upgradeInProgress).to.equal(true);
+    expect(upgradeableStarted.upgradeReadyAt.toNumber()).to.equal(startTime + 7200);
+    expect(upgradeableStarted.upgradePaidUsdc.toNumber()).to.equal(100000000);
+    expect(upgradeableStarted.currentLevel).to.equal(3);
     
-    expect(stakeableAfter.isStaked).to.equal(true);
-    expect(stakeableAfter.stakingStartTime.toNumber()).to.equal(currentTime);
+    // Production runs at half yield until the upgrade is ready
+    const productionStarted = await productionComponent.account.production.fetch(productionComponentPda);
+    expect(productionStarted.constructionStart.toNumber()).to.equal(startTime);
+    expect(productionStarted.constructionEnd.toNumber()).to.equal(startTime + 7200);
+    expect(productionStarted.constructionYield).to.equal(5000);
     
-    // Production should be paused while staked
-    expect(productionAfter.isActive).to.equal(false);
+    const walletStarted = await walletComponent.account.wallet.fetch(walletComponentPda);
+    expect(walletBefore.usdcBalance.sub(walletStarted.usdcBalance).toNumber()).to.equal(100000000);
     
-    console.log(`Entity successfully staked at timestamp: ${stakeableAfter.stakingStartTime.toNumber()}`);
-    console.log(`Production status after staking: ${productionAfter.isActive ? "Active" : "Inactive"}`);
+    // The upgrade cannot be finished before the timer runs out
+    try {
+      await applyUpgrade(4); // FINISH_UPGRADE
+      expect.fail("Finish should have failed before the construction is complete");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Finish should have failed before the construction is complete");
+      console.log("Finish correctly rejected before the construction is complete");
+    }
+    
+    // Cancelling refunds half of the cost and resumes full production
+    await applyUpgrade(6); // CANCEL_UPGRADE
+    
+    const upgradeableCancelled = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
+    expect(upgradeableCancelled.upgradeInProgress).to.equal(false);
+    expect(upgradeableCancelled.currentLevel).to.equal(3);
+    
+    // Construction ends when it was cancelled, well before the timer ran out
+    const productionCancelled = await productionComponent.account.production.fetch(productionComponentPda);
+    expect(productionCancelled.constructionEnd.toNumber()).to.be.at.least(startTime);
+    expect(productionCancelled.constructionEnd.toNumber()).to.be.below(startTime + 7200);
+    
+    const walletCancelled = await walletComponent.account.wallet.fetch(walletComponentPda);
+    expect(walletCancelled.usdcBalance.sub(walletStarted.usdcBalance).toNumber()).to.equal(50000000);
+  });
+
+  it("Speed up a timed upgrade with AiFi until it finishes", async () => {
+    const productionBefore = await productionComponent.account.production.fetch(productionComponentPda);
+    
+    const applyUpgrade = async (operationType: number, speedUpSeconds: number) => {
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemUpgrade.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: upgradeableComponent.programId }, // upgradeable component
+            { componentId: walletComponent.programId },      // wallet component
+            { componentId: productionComponent.programId },  // production component
+            { componentId: ownershipComponent.programId },   // player ownership
+          ],
+        }, {
+          entity: catalogEntityPda,
+          components: [
+            { componentId: upgradeTableComponent.programId }, // upgrade table component
+          ],
+        }, {
+          entity: entityPda,
+          components: [
+            { componentId: stakeableComponent.programId }, // compounded upgrade savings
+          ],
+        }],
+        args: {
+          operation_type: operationType,
+          entity_type: 0, // Not used for these operations
+          current_level: 0, // Not used for these operations
+          speed_up_seconds: speedUpSeconds,
+          quantity: 0,
+        },
+        extraAccounts: [
+          { pubkey: catalogEntityPda, isSigner: false, isWritable: false }, // model entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+        ],
+      });
+      
+      return provider.sendAndConfirm(applySystem.transaction);
+    };
+    
+    await applyUpgrade(3, 0); // START_UPGRADE
+    
+    const upgradeableStarted = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
+    const startTime = upgradeableStarted.upgradeStartedAt.toNumber();
+    
+    // Skip one hour for 0.1 AiFi
+    const walletBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
+    await applyUpgrade(5, 3600); // SPEED_UP
+    
+    const upgradeableSpedUp = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
+    expect(upgradeableSpedUp.upgradeInProgress).to.equal(true);
+    expect(upgradeableSpedUp.upgradeReadyAt.toNumber()).to.equal(startTime + 3600);
+    
+    const walletSpedUp = await walletComponent.account.wallet.fetch(walletComponentPda);
+    expect(walletBefore.aifiBalance.sub(walletSpedUp.aifiBalance).toNumber()).to.equal(100000);
+    
+    // Skipping the rest finishes the upgrade right away
+    await applyUpgrade(5, 0); // SPEED_UP
+    
+    const upgradeableAfter = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
+    expect(upgradeableAfter.upgradeInProgress).to.equal(false);
+    expect(upgradeableAfter.currentLevel).to.equal(4);
+    expect(upgradeableAfter.lastUpgradeTime.toNumber()).to.be.at.least(startTime);
+    
+    // Construction ended when the rest was skipped
+    const productionAfter = await productionComponent.account.production.fetch(productionComponentPda);
+    expect(productionAfter.level).to.equal(4);
+    expect(productionAfter.constructionEnd.toNumber()).to.equal(upgradeableAfter.lastUpgradeTime.toNumber());
+    expect(productionAfter.usdcPerHour.sub(productionBefore.usdcPerHour).toNumber()).to.equal(20000000);
+  });
+
+  it("Buy protection and attempt a risky upgrade without randomness (should fail)", async () => {
+    const applyUpgradeCurve = async (args) => {
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemUpgradeCurve.programId,
+        world: worldPda,
+        entities: [{
+          entity: catalogEntityPda,
+          components: [
+            { componentId: upgradeTableComponent.programId }, // upgrade table component
+          ],
+        }],
+        args: {
+          operation_type: 0,
+          entity_type: 0,
+          model_id: 0,
+          level: 0,
+          usdc_cost: 0,
+          aifi_cost: 0,
+          usdc_gain: 0,
+          aifi_gain: 0,
+          btc_gain: 0,
+          eth_gain: 0,
+          sol_gain: 0,
+          cooldown: 0,
+          required_player_level: 0,
+          build_duration: 0,
+          construction_yield: 0,
+          speed_up_aifi_per_hour: 0,
+          cancel_refund_rate: 0,
+          success_rate: 0,
+          critical_failure_rate: 0,
+          risk_start_level: 0,
+          protection_aifi_cost: 0,
+          ...args,
+        },
+        extraAccounts: [
+          { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+        ],
+      });
+      
+      return provider.sendAndConfirm(applySystem.transaction, [gameAuthority]);
+    };
+    
+    const applyUpgrade = async (operationType: number, quantity: number) => {
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemUpgrade.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: upgradeableComponent.programId }, // upgradeable component
+            { componentId: walletComponent.programId },      // wallet component
+            { componentId: productionComponent.programId },  // production component
+            { componentId: ownershipComponent.programId },   // player ownership
+          ],
+        }, {
+          entity: catalogEntityPda,
+          components: [
+            { componentId: upgradeTableComponent.programId }, // upgrade table component
+          ],
+        }, {
+          entity: entityPda,
+          components: [
+            { componentId: stakeableComponent.programId }, // compounded upgrade savings
+          ],
+        }],
+        args: {
+          operation_type: operationType,
+          entity_type: 0, // Not used for these operations
+          current_level: 0, // Not used for these operations
+          speed_up_seconds: 0, // Not used for these operations
+          quantity,
+        },
+        extraAccounts: [
+          { pubkey: catalogEntityPda, isSigner: false, isWritable: false }, // model entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+        ],
+      });
+      
+      return provider.sendAndConfirm(applySystem.transaction);
+    };
+    
+    // Upgrades to level 5 and above are gambles, protection costs 0.1 AiFi
+    await applyUpgradeCurve({
+      operation_type: 4, // SET_RISK
+      risk_start_level: 5,
+      protection_aifi_cost: 100000,
+    });
+    
+    // 60% success, 15% chance of dropping back to level 3
+    await applyUpgradeCurve({
+      operation_type: 1, // SET_LEVEL
+      level: 5,
+      usdc_cost: 10000000, // 10 USDC
+      usdc_gain: 40000000, // +40 USDC per hour
+      success_rate: 6000,
+      critical_failure_rate: 1500,
+    });
+    
+    await applyUpgrade(2, 0); // SYNC_TABLE
+    
+    const upgradeableSynced = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
+    expect(upgradeableSynced.currentLevel).to.equal(4);
+    expect(upgradeableSynced.maxLevel).to.equal(5);
+    expect(upgradeableSynced.nextSuccessRate).to.equal(6000);
+    expect(upgradeableSynced.nextCriticalFailureRate).to.equal(1500);
+    
+    // Protection items are bought with AiFi
+    const walletBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
+    await applyUpgrade(7, 2); // BUY_PROTECTION
+    
+    const upgradeableProtected = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
+    expect(upgradeableProtected.protectionItems).to.equal(2);
+    
+    const walletProtected = await walletComponent.account.wallet.fetch(walletComponentPda);
+    expect(walletBefore.aifiBalance.sub(walletProtected.aifiBalance).toNumber()).to.equal(200000);
+    
+    // Gambles can't be applied instantly, their roll is committed by StartUpgrade
+    try {
+      await applyUpgrade(1, 0); // UPGRADE
+      expect.fail("Instant risky upgrade should have failed");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Instant risky upgrade should have failed");
+      console.log("Instant risky upgrade correctly rejected");
+    }
+    
+    // Requesting the roll requires the vrf_client randomness accounts
+    try {
+      await applyUpgrade(3, 0); // START_UPGRADE
+      expect.fail("Risky upgrade should have failed without randomness");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Risky upgrade should have failed without randomness");
+      console.log("Risky upgrade correctly rejected without randomness");
+    }
+    
+    const upgradeableAfter = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
+    expect(upgradeableAfter.currentLevel).to.equal(4);
+    expect(upgradeableAfter.upgradeInProgress).to.equal(false);
+    expect(upgradeableAfter.upgradeRollPending).to.equal(false);
+    expect(upgradeableAfter.attemptLevels).to.deep.equal([]);
+    
+    const walletAfter = await walletComponent.account.wallet.fetch(walletComponentPda);
+    expect(walletAfter.usdcBalance.toNumber()).to.equal(walletProtected.usdcBalance.toNumber());
+  });
+
+  it("Attempt to salvage a GPU with an empty treasury (should fail)", async () => {
+    const treasury = await walletComponent.account.wallet.fetch(treasuryComponentPda);
+    expect(treasury.usdcBalance.toNumber()).to.equal(0);
+    
+    try {
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemSalvageGpu.programId,
+        world: worldPda,
+        entities: [{
+          entity: catalogEntityPda,
+          components: [
+            { componentId: gpuModelComponent.programId },     // model of the GPU
+            { componentId: upgradeTableComponent.programId }, // upgrade table of the model
+            { componentId: walletComponent.programId },       // treasury wallet
+          ],
+        }, {
+          entity: entityPda,
+          components: [
+            { componentId: ownershipComponent.programId },    // player ownership
+            { componentId: walletComponent.programId },       // player wallet
+            { componentId: productionComponent.programId },   // GPU production
+            { componentId: upgradeableComponent.programId },  // GPU upgradeable
+            { componentId: stakeableComponent.programId },    // GPU stakeable
+            { componentId: durabilityComponent.programId },   // GPU durability
+          ],
+        }],
+        args: {
+          operation_type: 0, // SALVAGE
+          current_time: Math.floor(Date.now() / 1000),
+        },
+        extraAccounts: [
+          { pubkey: entityPda, isSigner: false, isWritable: false },        // player entity
+          { pubkey: entityPda, isSigner: false, isWritable: false },        // GPU entity
+          { pubkey: catalogEntityPda, isSigner: false, isWritable: false }, // catalog entity
+          { pubkey: SYSVAR_INSTRUCTIONS_PUBKEY, isSigner: false, isWritable: false }, // instructions sysvar
+          { pubkey: hostingAgreementComponentPda, isSigner: false, isWritable: false }, // hosting agreement of the GPU
+        ],
+      });
+      
+      await provider.sendAndConfirm(applySystem.transaction);
+      expect.fail("Salvaging without treasury funds should have failed");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Salvaging without treasury funds should have failed");
+      console.log("Salvaging with an empty treasury correctly failed");
+    }
+    
+    // The GPU is still owned and producing stats are untouched
+    const production = await productionComponent.account.production.fetch(productionComponentPda);
+    expect(production.isRetired).to.equal(false);
+    
+    const ownership = await ownershipComponent.account.ownership.fetch(ownershipComponentPda);
+    expect(ownership.ownedEntityTypes).to.include(ENTITY_TYPE.GPU);
+  });
+
+  it("Set up the player profile with the Prestige system", async () => {
+    const applyPrestige = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemPrestige.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: playerProfileComponent.programId }, // player profile
+          { componentId: walletComponent.programId },        // player wallet
+          { componentId: ownershipComponent.programId },     // player ownership
+        ],
+      }, {
+        entity: entity2Pda,
+        components: [
+          { componentId: priceComponent.programId }, // BTC price, not read when setting up
+          { componentId: priceComponent.programId }, // ETH price slot
+          { componentId: priceComponent.programId }, // SOL price slot
+          { componentId: priceComponent.programId }, // AiFi price slot
+        ],
+      }, {
+        entity: entityPda,
+        components: [
+          { componentId: productionComponent.programId }, // GPU production, only read by RetireGpu
+          { componentId: stakeableComponent.programId },  // GPU stakeable
+        ],
+      }],
+      args: {
+        operation_type: 0, // INITIALIZE
+        current_time: Math.floor(Date.now() / 1000),
+      },
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+      ],
+    });
+    
+    await provider.sendAndConfirm(applyPrestige.transaction);
+    
+    const profile = await playerProfileComponent.account.playerProfile.fetch(playerProfileComponentPda);
+    expect(profile.productionMultiplier).to.equal(10000);
+    expect(profile.prestigeLevel).to.equal(0);
+  });
+
+  it("Attempt to prestige below the net worth threshold (should fail)", async () => {
+    const walletBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
+    
+    try {
+      const applyPrestige = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemPrestige.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: playerProfileComponent.programId }, // player profile
+            { componentId: walletComponent.programId },        // player wallet
+            { componentId: ownershipComponent.programId },     // player ownership
+          ],
+        }, {
+          entity: entity2Pda,
+          components: [
+            { componentId: priceComponent.programId }, // BTC price
+            { componentId: priceComponent.programId }, // ETH price slot
+            { componentId: priceComponent.programId }, // SOL price slot
+            { componentId: priceComponent.programId }, // AiFi price slot
+          ],
+        }, {
+          entity: entityPda,
+          components: [
+            { componentId: productionComponent.programId }, // GPU production, only read by RetireGpu
+            { componentId: stakeableComponent.programId },  // GPU stakeable
+          ],
+        }],
+        args: {
+          operation_type: 1, // PRESTIGE
+          current_time: Math.floor(Date.now() / 1000),
+        },
+        extraAccounts: [
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        ],
+      });
+      
+      await provider.sendAndConfirm(applyPrestige.transaction);
+      expect.fail("Prestige below the net worth threshold should have failed");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Prestige below the net worth threshold should have failed");
+      console.log("Prestige below the threshold correctly failed");
+    }
+    
+    // Nothing was reset
+    const walletAfter = await walletComponent.account.wallet.fetch(walletComponentPda);
+    expect(walletAfter.usdcBalance.toNumber()).to.equal(walletBefore.usdcBalance.toNumber());
+    
+    const profile = await playerProfileComponent.account.playerProfile.fetch(playerProfileComponentPda);
+    expect(profile.prestigeLevel).to.equal(0);
+  });
+
+  it("Apply movement system", async () => {
+    // Check that the component has been initialized and x is 0
+    const positionBefore = await positionComponent.account.position.fetch(
+      positionComponentPda
+    );
+    expect(positionBefore.x.toNumber()).to.equal(0);
+
+    // Run the movement system
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemMovement.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [{ componentId: positionComponent.programId }],
+      }]
+    });
+    const txSign = await provider.sendAndConfirm(applySystem.transaction);
+    console.log(`Applied a system. Signature: ${txSign}`);
+
+    // Check that the system has been applied and x is > 0
+    const positionAfter = await positionComponent.account.position.fetch(
+      positionComponentPda
+    );
+    expect(positionAfter.x.toNumber()).to.gt(0);
+  });
+
+  // After the upgrade tests, add these tests for the staking system
+
+  it("Set up the AiFi price used to value staking rewards", async () => {
+    const systemPriceAction = anchor.workspace.PriceAction;
+
+    const initAifiPriceArgs = {
+      operation_type: 0, // INITIALIZE operation
+      currency_type: CURRENCY_TYPE.AIFI,
+      price: 500000, // $0.50
+      min_price: 100000, // $0.10
+      max_price: 2000000, // $2.00
+      volatility: 1000, // 10% volatility (in basis points)
+      update_frequency: 3600 // Update once per hour (in seconds)
+    };
+
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemPriceAction.programId,
+      world: worldPda,
+      entities: [{
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // The AiFi price component to initialize
+        ],
+      }],
+      args: initAifiPriceArgs,
+      extraAccounts: [
+        { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+      ],
+    });
+
+    const txSign = await provider.sendAndConfirm(applySystem.transaction, [gameAuthority]);
+    console.log(`Applied price action system to initialize AiFi price. Signature: ${txSign}`);
+
+    const aifiPrice = await priceComponent.account.price.fetch(priceAifiComponentPda);
+    expect(aifiPrice.currentPrice.toNumber()).to.equal(500000);
+    expect(aifiPrice.priceType).to.equal(CURRENCY_TYPE.AIFI);
+    expect(aifiPrice.authority.toBase58()).to.equal(gameAuthority.publicKey.toBase58());
+  });
+
+  it("Verify staking properties copied from the catalog", async () => {
+    const stakeableAfter = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    
+    expect(stakeableAfter.isStaked).to.equal(false);
+    expect(stakeableAfter.minStakingPeriod).to.equal(20);
+    expect(stakeableAfter.rewardRate).to.equal(15000);
+    expect(stakeableAfter.unstakingPenalty).to.equal(5000);
+    expect(stakeableAfter.baseUsdcPerHour.toNumber()).to.equal(7500000);
+    expect(stakeableAfter.baseAifiPerHour.toNumber()).to.equal(15000000);
+    expect(stakeableAfter.canClaimRewards).to.equal(true);
+    expect(stakeableAfter.stakeableType).to.equal(ENTITY_TYPE.GPU);
+    
+    console.log(`Staking properties spawned from the catalog:`);
+    console.log(`Min staking period: ${stakeableAfter.minStakingPeriod} seconds`);
+    console.log(`Reward rate: ${stakeableAfter.rewardRate/100}%`);
+    console.log(`Unstaking penalty: ${stakeableAfter.unstakingPenalty/100}%`);
+    console.log(`Base rates: ${stakeableAfter.baseUsdcPerHour.toNumber()/1000000} USDC/hr, ${stakeableAfter.baseAifiPerHour.toNumber()/1000000} AiFi/hr`);
+  });
+  
+  it("Register the game's lock tiers", async () => {
+    // GPUs unbond instantly and tier 0 is their own lock until tiers are configured
+    const registerArgs = {
+      operation_type: 10, // CONFIGURE_UNBONDING
+      staking_type: 1, // GPU
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
+      stake: false, // Not used
+      can_claim_rewards: false, // Not used
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Not used
+      unbonding_period: 0, // No unbonding yet
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+
+    // The game authority signs and is passed after the components
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemStaking.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId }, // stakeable component
+          { componentId: walletComponent.programId },    // wallet component
+          { componentId: productionComponent.programId }, // production component
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
+        ],
+      }],
+      args: registerArgs,
+      extraAccounts: [
+        { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+      ],
+    });
+
+    await provider.sendAndConfirm(applySystem.transaction, [gameAuthority]);
+
+    const lockTiers = await lockTiersComponent.account.lockTiers.fetch(lockTiersComponentPda);
+    expect(lockTiers.authority.toBase58()).to.equal(gameAuthority.publicKey.toBase58());
+    expect(lockTiers.durations).to.deep.equal([]);
+    expect(lockTiers.unbondingPeriods).to.deep.equal([0, 0]);
+  });
+
+  it("Register the game's penalty ledger", async () => {
+    // Early unstaking penalties are burned until a destination is chosen
+    const registerArgs = {
+      operation_type: 12, // CONFIGURE_PENALTIES
+      staking_type: 1, // GPU
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
+      stake: false, // Not used
+      can_claim_rewards: false, // Not used
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Not used
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Burn
+      compound_mode: 0 // Not used
+    };
+
+    // The game authority signs and is passed after the components
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemStaking.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId }, // stakeable component
+          { componentId: walletComponent.programId },    // wallet component
+          { componentId: productionComponent.programId }, // production component
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
+        ],
+      }],
+      args: registerArgs,
+      extraAccounts: [
+        { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+      ],
+    });
+
+    await provider.sendAndConfirm(applySystem.transaction, [gameAuthority]);
+
+    const ledger = await penaltyLedgerComponent.account.penaltyLedger.fetch(penaltyLedgerComponentPda);
+    expect(ledger.authority.toBase58()).to.equal(gameAuthority.publicKey.toBase58());
+    expect(ledger.destination).to.equal(0);
+  });
+
+  it("Stake an entity", async () => {
+    // Activate production before staking test
+    const productionBefore = await productionComponent.account.production.fetch(productionComponentPda);
+    if (!productionBefore.isActive) {
+      // Use resource-production system to activate production
+      const activateArgs = {
+        operation_type: 2, // SET_ACTIVE
+        usdc_per_hour: 0, // Not used for activation
+        aifi_per_hour: 0, // Not used for activation
+        current_time: Math.floor(Date.now() / 1000),
+        producer_type: 0, // Not used for activation
+        level: 0, // Not used for activation
+        is_active: true,
+        operating_cost: 0, // Not used for activation
+        efficiency_multiplier: 0 // Not used for activation
+      };
+      
+      const activateSystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemResourceProduction.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: productionComponent.programId },
+            { componentId: walletComponent.programId },
+            { componentId: durabilityComponent.programId }, // durability component
+            { componentId: facilityComponent.programId }, // facility component
+            { componentId: playerProfileComponent.programId }, // player profile
+            { componentId: hostingAgreementComponent.programId }, // hosting agreement
+          ],
+        }, {
+          entity: entity2Pda,
+          components: [
+            { componentId: walletComponent.programId }, // host wallet
+          ],
+        }],
+        args: activateArgs,
+        extraAccounts: [
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // producer entity
+        ],
+      });
+      
+      await provider.sendAndConfirm(activateSystem.transaction);
+      console.log("Activated production before staking test");
+    }
+    
+    // Get stakeable status before staking
+    const stakeableBefore = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    console.log(`Production status before staking: ${productionBefore.isActive ? "Active" : "Inactive"}`);
+    console.log(`Entity staking status before: ${stakeableBefore.isStaked ? "Staked" : "Unstaked"}`);
+    
+    // Prepare args for staking
+    const stakeArgs = {
+      operation_type: 1, // STAKE
+      staking_type: 1, // GPU
+      min_staking_period: 0, // Not used for staking
+      reward_rate: 0, // Not used for staking
+      unstaking_penalty: 0, // Not used for staking
+      base_usdc_per_hour: 0, // Not used for staking
+      base_aifi_per_hour: 0, // Not used for staking
+      stake: true, // Not actually used, just for clarity
+      can_claim_rewards: true, // Not used for staking
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Default lock tier
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+    
+    // Apply the system to stake the entity
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemStaking.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId }, // stakeable component
+          { componentId: walletComponent.programId },    // wallet component
+          { componentId: productionComponent.programId }, // production component
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
+        ],
+      }],
+      args: stakeArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+      ],
+    });
+    
+    const txSign = await provider.sendAndConfirm(applySystem.transaction);
+    console.log(`Applied staking system to stake entity. Signature: ${txSign}`);
+    
+    // Verify staking succeeded
+    const stakeableAfter = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    const productionAfter = await productionComponent.account.production.fetch(productionComponentPda);
+    
+    expect(stakeableAfter.isStaked).to.equal(true);
+    expect(stakeableAfter.stakingStartTime.toNumber()).to.be.gt(0);
+    expect(stakeableAfter.lastClaimTime.toNumber()).to.equal(stakeableAfter.stakingStartTime.toNumber());
+    expect(stakeableAfter.unlockTime.toNumber()).to.equal(stakeableAfter.stakingStartTime.toNumber() + 20);
+    
+    // Production should be paused while staked
+    expect(productionAfter.isActive).to.equal(false);
+    
+    console.log(`Entity successfully staked at timestamp: ${stakeableAfter.stakingStartTime.toNumber()}`);
+    console.log(`Production status after staking: ${productionAfter.isActive ? "Active" : "Inactive"}`);
+  });
+  
+  it("Try staking an already staked entity (should fail)", async () => {
+    // Verify entity is currently staked
+    const stakeableBefore = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    expect(stakeableBefore.isStaked).to.equal(true);
+    
+    // Attempt to stake again
+    // Prepare args for staking
+    const stakeArgs = {
+      operation_type: 1, // STAKE
+      staking_type: 1, // GPU
+      min_staking_period: 0, // Not used for staking
+      reward_rate: 0, // Not used for staking
+      unstaking_penalty: 0, // Not used for staking
+      base_usdc_per_hour: 0, // Not used for staking
+      base_aifi_per_hour: 0, // Not used for staking
+      stake: true, // Not actually used, just for clarity
+      can_claim_rewards: true, // Not used for staking
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Default lock tier
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+    
+    try {
+      // Apply the system to stake the entity (should fail)
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemStaking.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: stakeableComponent.programId }, // stakeable component
+            { componentId: walletComponent.programId },    // wallet component
+            { componentId: productionComponent.programId }, // production component
+            { componentId: ownershipComponent.programId }, // player ownership
+          ],
+        }, {
+          entity: catalogEntityPda,
+          components: [
+            { componentId: priceComponent.programId }, // AiFi price
+            { componentId: stakingPoolComponent.programId }, // staking pool
+            { componentId: lockTiersComponent.programId }, // lock tiers
+            { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+            { componentId: walletComponent.programId }, // treasury wallet
+            { componentId: lotteryPrizeComponent.programId }, // lottery prize
+          ],
+        }],
+        args: stakeArgs,
+        extraAccounts: [
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+        ],
+      });
+      
+      await provider.sendAndConfirm(applySystem.transaction);
+      expect.fail("Staking should have failed because entity is already staked");
+    } catch (error) {
+      console.log(`Staking correctly failed as entity is already staked`);
+    }
+  });
+  
+  it("Collect staking rewards while the GPU stays staked", async () => {
+    const stakeableBefore = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    const walletBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
+    expect(stakeableBefore.isStaked).to.equal(true);
+
+    // Past the lock period, so the locked rewards are released
+    const unlockTime = stakeableBefore.unlockTime.toNumber();
+    await waitForClusterTime(unlockTime + 2);
+
+    const collectArgs = {
+      operation_type: 3, // COLLECT_REWARDS
+      staking_type: 0, // Not used for collection
+      min_staking_period: 0, // Not used for collection
+      reward_rate: 0, // Not used for collection
+      unstaking_penalty: 0, // Not used for collection
+      base_usdc_per_hour: 0, // Not used for collection
+      base_aifi_per_hour: 0, // Not used for collection
+      stake: false, // Not used for collection
+      can_claim_rewards: true, // Not used for collection
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Default lock tier
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemStaking.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId }, // stakeable component
+          { componentId: walletComponent.programId },    // wallet component
+          { componentId: productionComponent.programId }, // production component
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
+        ],
+      }],
+      args: collectArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+      ],
+    });
+
+    const txSign = await provider.sendAndConfirm(applySystem.transaction);
+    console.log(`Collected staking rewards while staked. Signature: ${txSign}`);
+
+    const stakeableAfter = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    const walletAfter = await walletComponent.account.wallet.fetch(walletComponentPda);
+
+    // Still staked, checkpointed at the collection time and nothing left to collect
+    expect(stakeableAfter.isStaked).to.equal(true);
+    expect(stakeableAfter.lastClaimTime.toNumber()).to.be.gt(unlockTime);
+    expect(stakeableAfter.accumulatedUsdcRewards.toNumber()).to.equal(0);
+    expect(stakeableAfter.lockedUsdcRewards.toNumber()).to.equal(0);
+    expect(walletAfter.usdcBalance.toNumber()).to.be.gt(walletBefore.usdcBalance.toNumber());
+
+    console.log(`Rewards collected while staked: ${(walletAfter.usdcBalance.toNumber() - walletBefore.usdcBalance.toNumber())/1000000} USDC`);
+  });
+
+  it("Flag the staked GPU for auto-compounding into its reward base", async () => {
+    const setArgs = {
+      operation_type: 13, // SET_COMPOUNDING
+      staking_type: 0, // Not used
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
+      stake: false, // Not used
+      can_claim_rewards: true, // Not used
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Not used
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 1 // Compound into the reward base
+    };
+
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemStaking.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId }, // stakeable component
+          { componentId: walletComponent.programId },    // wallet component
+          { componentId: productionComponent.programId }, // production component
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
+        ],
+      }],
+      args: setArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+      ],
+    });
+
+    const txSign = await provider.sendAndConfirm(applySystem.transaction);
+    console.log(`Flagged the GPU for compounding. Signature: ${txSign}`);
+
+    const stakeableAfter = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    expect(stakeableAfter.compoundMode).to.equal(1);
+    expect(stakeableAfter.compoundedRewards.toNumber()).to.equal(0);
+  });
+
+  it("Compound the GPU's rewards as a keeper for a tip", async () => {
+    const stakeableBefore = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    const keeperBefore = await walletComponent.account.wallet.fetch(wallet2ComponentPda);
+    const ownerBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
+    await waitForClusterTime(stakeableBefore.lastClaimTime.toNumber() + 2);
+    const compoundArgs = {
+      operation_type: 14, // COMPOUND
+      staking_type: 0, // Not used
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
+      stake: false, // Not used
+      can_claim_rewards: true, // Not used
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Not used
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemStaking.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId }, // stakeable component
+        ],
+      }, {
+        entity: entity2Pda,
+        components: [
+          { componentId: walletComponent.programId },    // keeper's wallet receiving the tip
+        ],
+      }, {
+        entity: entityPda,
+        components: [
+          { componentId: productionComponent.programId }, // production component
+          { componentId: ownershipComponent.programId }, // player ownership (not checked)
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
+        ],
+      }],
+      args: compoundArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // staked entity
+      ],
+    });
+
+    const txSign = await provider.sendAndConfirm(applySystem.transaction);
+    console.log(`Compounded the GPU's rewards. Signature: ${txSign}`);
+
+    const stakeableAfter = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    const keeperAfter = await walletComponent.account.wallet.fetch(wallet2ComponentPda);
+    const ownerAfter = await walletComponent.account.wallet.fetch(walletComponentPda);
+
+    // Nothing is paid out to the owner, the keeper gets 0.5% of the compounded rewards
+    const compounded = stakeableAfter.compoundedRewards.toNumber();
+    const tip = keeperAfter.usdcBalance.toNumber() - keeperBefore.usdcBalance.toNumber();
+    expect(compounded).to.be.gt(0);
+    expect(tip).to.equal(Math.floor((compounded + tip) * 50 / 10000));
+    expect(stakeableAfter.accumulatedUsdcRewards.toNumber()).to.equal(0);
+    expect(stakeableAfter.lastClaimTime.toNumber()).to.be.gt(stakeableBefore.lastClaimTime.toNumber());
+    expect(ownerAfter.usdcBalance.toNumber()).to.equal(ownerBefore.usdcBalance.toNumber());
+
+    console.log(`Compounded ${compounded/1000000} USDC into the reward base, keeper tip ${tip/1000000} USDC`);
+  });
+
+  it("Save compounded rewards toward the GPU's next upgrade", async () => {
+    const setArgs = {
+      operation_type: 13, // SET_COMPOUNDING
+      staking_type: 0, // Not used
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
+      stake: false, // Not used
+      can_claim_rewards: true, // Not used
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Not used
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 2 // Save toward the next upgrade
+    };
+
+    const setSystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemStaking.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId }, // stakeable component
+          { componentId: walletComponent.programId },    // wallet component
+          { componentId: productionComponent.programId }, // production component
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
+        ],
+      }],
+      args: setArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+      ],
+    });
+    await provider.sendAndConfirm(setSystem.transaction);
+
+    const stakeableBefore = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    const keeperBefore = await walletComponent.account.wallet.fetch(wallet2ComponentPda);
+    await waitForClusterTime(stakeableBefore.lastClaimTime.toNumber() + 2);
+    const compoundArgs = {
+      operation_type: 14, // COMPOUND
+      staking_type: 0, // Not used
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
+      stake: false, // Not used
+      can_claim_rewards: true, // Not used
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Not used
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemStaking.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId }, // stakeable component
+        ],
+      }, {
+        entity: entity2Pda,
+        components: [
+          { componentId: walletComponent.programId },    // keeper's wallet receiving the tip
+        ],
+      }, {
+        entity: entityPda,
+        components: [
+          { componentId: productionComponent.programId }, // production component
+          { componentId: ownershipComponent.programId }, // player ownership (not checked)
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
+        ],
+      }],
+      args: compoundArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // staked entity
+        { pubkey: upgradeableComponentPda, isSigner: false, isWritable: false }, // GPU upgrade costs
+      ],
+    });
+
+    const txSign = await provider.sendAndConfirm(applySystem.transaction);
+    console.log(`Saved the GPU's rewards toward its next upgrade. Signature: ${txSign}`);
+
+    const stakeableAfter = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    const keeperAfter = await walletComponent.account.wallet.fetch(wallet2ComponentPda);
+    const upgradeable = await upgradeableComponent.account.upgradeable.fetch(upgradeableComponentPda);
+
+    // The reward base keeps what was compounded before
+    const saved = stakeableAfter.upgradeSavingsUsdc.toNumber();
+    const tip = keeperAfter.usdcBalance.toNumber() - keeperBefore.usdcBalance.toNumber();
+    expect(saved).to.be.gt(0);
+    expect(saved).to.be.at.most(upgradeable.nextUpgradeUsdcCost.toNumber());
+    expect(tip).to.equal(Math.floor((saved + tip) * 50 / 10000));
+    expect(stakeableAfter.compoundedRewards.toNumber()).to.equal(stakeableBefore.compoundedRewards.toNumber());
+
+    console.log(`Saved ${saved/1000000} of the ${upgradeable.nextUpgradeUsdcCost.toNumber()/1000000} USDC the next upgrade costs`);
+  });
+
+  it("Attempt to compound a GPU that is not flagged (should fail)", async () => {
+    const offArgs = {
+      operation_type: 13, // SET_COMPOUNDING
+      staking_type: 0, // Not used
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
+      stake: false, // Not used
+      can_claim_rewards: true, // Not used
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Not used
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Pay rewards out
+    };
+
+    const offSystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemStaking.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId }, // stakeable component
+          { componentId: walletComponent.programId },    // wallet component
+          { componentId: productionComponent.programId }, // production component
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
+        ],
+      }],
+      args: offArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+      ],
+    });
+    await provider.sendAndConfirm(offSystem.transaction);
+
+    const stakeableBefore = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    const compoundArgs = {
+      operation_type: 14, // COMPOUND
+      staking_type: 0, // Not used
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
+      stake: false, // Not used
+      can_claim_rewards: true, // Not used
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Not used
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+
+    try {
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemStaking.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: stakeableComponent.programId }, // stakeable component
+          ],
+        }, {
+          entity: entity2Pda,
+          components: [
+            { componentId: walletComponent.programId },    // keeper's wallet receiving the tip
+          ],
+        }, {
+          entity: entityPda,
+          components: [
+            { componentId: productionComponent.programId }, // production component
+            { componentId: ownershipComponent.programId }, // player ownership (not checked)
+          ],
+        }, {
+          entity: catalogEntityPda,
+          components: [
+            { componentId: priceComponent.programId }, // AiFi price
+            { componentId: stakingPoolComponent.programId }, // staking pool
+            { componentId: lockTiersComponent.programId }, // lock tiers
+            { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+            { componentId: walletComponent.programId }, // treasury wallet
+            { componentId: lotteryPrizeComponent.programId }, // lottery prize
+          ],
+        }],
+        args: compoundArgs,
+        extraAccounts: [
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // staked entity
+        ],
+      });
+
+      await provider.sendAndConfirm(applySystem.transaction);
+      expect.fail("Compounding should require the flag");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Compounding should require the flag");
+      console.log(`Compounding correctly rejected for a GPU that is not flagged`);
+    }
+
+    const stakeableAfter = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    expect(stakeableAfter.compoundMode).to.equal(0);
+    expect(stakeableAfter.lastClaimTime.toNumber()).to.equal(stakeableBefore.lastClaimTime.toNumber());
+  });
+
+  it("Unstake entity with rewards", async () => {
+    // We need to wait a bit to simulate time passing for rewards
+    // Sleep for a short period to ensure the test timing works
+    await new Promise(resolve => setTimeout(resolve, 1000));
+    
+    // Get current staking information
+    const stakeableBefore = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    const walletBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
+    
+    // Verify entity is currently staked
+    expect(stakeableBefore.isStaked).to.equal(true);
+    
+    // Get staking time details
+    const stakingStartTime = stakeableBefore.stakingStartTime.toNumber();
+    
+    // Unstake after the lock period, following the rewards collected while staked
+    console.log(`Staking started at: ${stakingStartTime}`);
+    console.log(`Unstaking past the unlock time: ${stakeableBefore.unlockTime.toNumber()}`);
+    
+    // Prepare args for unstaking
+    const unstakeArgs = {
+      operation_type: 2, // UNSTAKE
+      staking_type: 1, // GPU
+      min_staking_period: 0, // Not used for unstaking
+      reward_rate: 0, // Not used for unstaking
+      unstaking_penalty: 0, // Not used for unstaking
+      base_usdc_per_hour: 0, // Not used for unstaking
+      base_aifi_per_hour: 0, // Not used for unstaking
+      stake: false, // Not used for unstaking
+      can_claim_rewards: true, // Not used for unstaking
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Default lock tier
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+    
+    // Apply the system to unstake the entity
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemStaking.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId }, // stakeable component
+          { componentId: walletComponent.programId },    // wallet component
+          { componentId: productionComponent.programId }, // production component
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
+        ],
+      }],
+      args: unstakeArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+      ],
+    });
+    
+    const txSign = await provider.sendAndConfirm(applySystem.transaction);
+    console.log(`Applied staking system to unstake entity. Signature: ${txSign}`);
+    
+    // Verify unstaking succeeded
+    const stakeableAfter = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    const productionAfter = await productionComponent.account.production.fetch(productionComponentPda);
+    
+    expect(stakeableAfter.isStaked).to.equal(false);
+    expect(stakeableAfter.stakingStartTime.toNumber()).to.equal(0);
+    
+    // Production should be reactivated
+    expect(productionAfter.isActive).to.equal(true);
+    
+    // Check accumulated rewards
+    console.log(`Accumulated rewards after unstaking: ${stakeableAfter.accumulatedUsdcRewards.toNumber()/1000000} USDC, ${stakeableAfter.accumulatedAifiRewards.toNumber()/1000000} AiFi`);
+    
+    // Should have accumulated some rewards (exact value depends on implementation)
+    // We don't verify exact values since they might change, just that we got some rewards
+    expect(stakeableAfter.accumulatedUsdcRewards.toNumber()).to.be.gte(0);
+    expect(stakeableAfter.accumulatedAifiRewards.toNumber()).to.be.gte(0);
+  });
+
+  it("Collect staking rewards", async () => {
+    // Get current wallet and stakeable info
+    const stakeableBefore = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    const walletBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
+    
+    // Skip test if no rewards to collect (to avoid failures in CI)
+    if (stakeableBefore.accumulatedUsdcRewards.toNumber() === 0 && 
+        stakeableBefore.accumulatedAifiRewards.toNumber() === 0) {
+      console.log("No rewards to collect, skipping test");
+      return;
+    }
+    
+    // Verify there are rewards to collect and claiming is enabled
+    expect(stakeableBefore.canClaimRewards).to.equal(true);
+    
+    console.log(`Wallet before collection: ${walletBefore.usdcBalance.toNumber()/1000000} USDC, ${walletBefore.aifiBalance.toNumber()/1000000} AiFi`);
+    console.log(`Accumulated rewards: ${stakeableBefore.accumulatedUsdcRewards.toNumber()/1000000} USDC, ${stakeableBefore.accumulatedAifiRewards.toNumber()/1000000} AiFi`);
+    
+    // Prepare args for collecting rewards
+    const collectArgs = {
+      operation_type: 3, // COLLECT_REWARDS
+      staking_type: 0, // Not used for collection
+      min_staking_period: 0, // Not used for collection
+      reward_rate: 0, // Not used for collection
+      unstaking_penalty: 0, // Not used for collection
+      base_usdc_per_hour: 0, // Not used for collection
+      base_aifi_per_hour: 0, // Not used for collection
+      stake: false, // Not used for collection
+      can_claim_rewards: true, // Not used for collection
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Default lock tier
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+    
+    // Apply the system to collect rewards
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemStaking.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId }, // stakeable component
+          { componentId: walletComponent.programId },    // wallet component
+          { componentId: productionComponent.programId }, // production component
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
+        ],
+      }],
+      args: collectArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+      ],
+    });
+    
+    const txSign = await provider.sendAndConfirm(applySystem.transaction);
+    console.log(`Applied staking system to collect rewards. Signature: ${txSign}`);
+    
+    // Verify collection succeeded
+    const stakeableAfter = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    const walletAfter = await walletComponent.account.wallet.fetch(walletComponentPda);
+    
+    // Accumulated rewards should be reset to 0
+    expect(stakeableAfter.accumulatedUsdcRewards.toNumber()).to.equal(0);
+    expect(stakeableAfter.accumulatedAifiRewards.toNumber()).to.equal(0);
+    
+    // Wallet balances should increase by the reward amounts
+    expect(walletAfter.usdcBalance.toNumber()).to.equal(
+      walletBefore.usdcBalance.toNumber() + stakeableBefore.accumulatedUsdcRewards.toNumber()
+    );
+    expect(walletAfter.aifiBalance.toNumber()).to.equal(
+      walletBefore.aifiBalance.toNumber() + stakeableBefore.accumulatedAifiRewards.toNumber()
+    );
+    
+    console.log(`Wallet after collection: ${walletAfter.usdcBalance.toNumber()/1000000} USDC, ${walletAfter.aifiBalance.toNumber()/1000000} AiFi`);
+    console.log(`Rewards collected: ${stakeableBefore.accumulatedUsdcRewards.toNumber()/1000000} USDC, ${stakeableBefore.accumulatedAifiRewards.toNumber()/1000000} AiFi`);
+  });
+  
+  it("Try collecting when no rewards are available (should fail)", async () => {
+    // Verify there are no accumulated rewards
+    const stakeableBefore = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    expect(stakeableBefore.accumulatedUsdcRewards.toNumber()).to.equal(0);
+    expect(stakeableBefore.accumulatedAifiRewards.toNumber()).to.equal(0);
+    
+    // Prepare args for collecting rewards
+    const collectArgs = {
+      operation_type: 3, // COLLECT_REWARDS
+      staking_type: 0, // Not used for collection
+      min_staking_period: 0, // Not used for collection
+      reward_rate: 0, // Not used for collection
+      unstaking_penalty: 0, // Not used for collection
+      base_usdc_per_hour: 0, // Not used for collection
+      base_aifi_per_hour: 0, // Not used for collection
+      stake: false, // Not used for collection
+      can_claim_rewards: true, // Not used for collection
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Default lock tier
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+    
+    try {
+      // Apply the system to collect rewards (should fail)
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemStaking.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: stakeableComponent.programId }, // stakeable component
+            { componentId: walletComponent.programId },    // wallet component
+            { componentId: productionComponent.programId }, // production component
+            { componentId: ownershipComponent.programId }, // player ownership
+          ],
+        }, {
+          entity: catalogEntityPda,
+          components: [
+            { componentId: priceComponent.programId }, // AiFi price
+            { componentId: stakingPoolComponent.programId }, // staking pool
+            { componentId: lockTiersComponent.programId }, // lock tiers
+            { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+            { componentId: walletComponent.programId }, // treasury wallet
+            { componentId: lotteryPrizeComponent.programId }, // lottery prize
+          ],
+        }],
+        args: collectArgs,
+        extraAccounts: [
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+        ],
+      });
+      
+      await provider.sendAndConfirm(applySystem.transaction);
+      expect.fail("Collection should have failed because no rewards are available");
+    } catch (error) {
+      console.log(`Collection correctly failed as no rewards are available`);
+    }
+  });
+  
+  it("Stake again and attempt to override GPU staking parameters (should fail)", async () => {
+    // First stake the entity again
+    // Prepare args for staking
+    const stakeArgs = {
+      operation_type: 1, // STAKE
+      staking_type: 1, // GPU
+      min_staking_period: 0, // Not used for staking
+      reward_rate: 0, // Not used for staking
+      unstaking_penalty: 0, // Not used for staking
+      base_usdc_per_hour: 0, // Not used for staking
+      base_aifi_per_hour: 0, // Not used for staking
+      stake: true, // Not actually used, just for clarity
+      can_claim_rewards: true, // Not used for staking
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Default lock tier
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+    
+    // Apply the system to stake the entity
+    await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemStaking.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId },
+          { componentId: walletComponent.programId },
+          { componentId: productionComponent.programId },
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
+        ],
+      }],
+      args: stakeArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+      ],
+    }).then(applySystem => provider.sendAndConfirm(applySystem.transaction));
+    
+    // Verify staking succeeded
+    const stakeableBefore = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    expect(stakeableBefore.isStaked).to.equal(true);
+    
+    // Now try to override the catalog staking parameters
+    const updateArgs = {
+      operation_type: 4, // UPDATE_PARAMS
+      staking_type: 1, // GPU
+      min_staking_period: 0, // No lock
+      reward_rate: 50000, // 500%
+      unstaking_penalty: 0, // No penalty
+      base_usdc_per_hour: stakeableBefore.baseUsdcPerHour.toNumber(),
+      base_aifi_per_hour: stakeableBefore.baseAifiPerHour.toNumber(),
+      stake: false, // Not used for updating
+      can_claim_rewards: true,
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Default lock tier
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+    
+    try {
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemStaking.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: stakeableComponent.programId }, // stakeable component
+            { componentId: walletComponent.programId },    // wallet component
+            { componentId: productionComponent.programId }, // production component
+            { componentId: ownershipComponent.programId }, // player ownership
+          ],
+        }, {
+          entity: catalogEntityPda,
+          components: [
+            { componentId: priceComponent.programId }, // AiFi price
+            { componentId: stakingPoolComponent.programId }, // staking pool
+            { componentId: lockTiersComponent.programId }, // lock tiers
+            { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+            { componentId: walletComponent.programId }, // treasury wallet
+            { componentId: lotteryPrizeComponent.programId }, // lottery prize
+          ],
+        }],
+        args: updateArgs,
+        extraAccounts: [
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+        ],
+      });
+      
+      await provider.sendAndConfirm(applySystem.transaction);
+      expect.fail("GPU staking parameters should only be set by the catalog");
+    } catch (error) {
+      expect(error.toString()).to.not.include("GPU staking parameters should only be set by the catalog");
+      console.log(`Staking parameter override correctly rejected for a catalog GPU`);
+    }
+    
+    // Verify parameters are unchanged and the entity is still staked
+    const stakeableAfter = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    
+    expect(stakeableAfter.minStakingPeriod).to.equal(20);
+    expect(stakeableAfter.rewardRate).to.equal(15000);
+    expect(stakeableAfter.unstakingPenalty).to.equal(5000);
+    expect(stakeableAfter.isStaked).to.equal(true);
+    
+    // Finally, unstake to clean up, within the lock period (will incur penalty)
+    await waitForClusterTime(stakeableAfter.stakingStartTime.toNumber() + 2);
+    
+    // Prepare args for unstaking
+    const unstakeArgs = {
+      operation_type: 2, // UNSTAKE
+      staking_type: 1, // GPU
+      min_staking_period: 0, // Not used for unstaking
+      reward_rate: 0, // Not used for unstaking
+      unstaking_penalty: 0, // Not used for unstaking
+      base_usdc_per_hour: 0, // Not used for unstaking
+      base_aifi_per_hour: 0, // Not used for unstaking
+      stake: false, // Not used for unstaking
+      can_claim_rewards: true, // Not used for unstaking
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Default lock tier
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+    
+    // Apply the system to unstake
+    await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemStaking.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId },
+          { componentId: walletComponent.programId },
+          { componentId: productionComponent.programId },
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
+        ],
+      }],
+      args: unstakeArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+      ],
+    }).then(applySystem => provider.sendAndConfirm(applySystem.transaction));
+    
+    // Verify unstaking worked
+    const finalStakeable = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    expect(finalStakeable.isStaked).to.equal(false);
   });
   
-  it("Try staking an already staked entity (should fail)", async () => {
-    // Verify entity is currently staked
+  it("Disable reward claiming", async () => {
+    // Get current staking parameters
     const stakeableBefore = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
-    expect(stakeableBefore.isStaked).to.equal(true);
     
-    // Attempt to stake again
-    const currentTime = Math.floor(Date.now() / 1000);
+    // If we have any accumulated rewards, collect them first
+    if (stakeableBefore.accumulatedUsdcRewards.toNumber() > 0 || 
+        stakeableBefore.accumulatedAifiRewards.toNumber() > 0) {
+      console.log("Collecting existing rewards before disabling claim functionality");
+      
+      const collectArgs = {
+        operation_type: 3, // COLLECT_REWARDS
+        staking_type: 0, // Not used
+        min_staking_period: 0, // Not used
+        reward_rate: 0, // Not used
+        unstaking_penalty: 0, // Not used
+        base_usdc_per_hour: 0, // Not used
+        base_aifi_per_hour: 0, // Not used
+        stake: false, // Not used
+        can_claim_rewards: true, // Not used
+        pool_reward_per_epoch: 0, // Not used
+        pool_epoch_duration: 0, // Not used
+        lock_tier: 0, // Default lock tier
+        unbonding_period: 0, // Not used
+        penalty_destination: 0, // Not used
+        compound_mode: 0 // Not used
+      };
+      
+      await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemStaking.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: stakeableComponent.programId },
+            { componentId: walletComponent.programId },
+            { componentId: productionComponent.programId },
+            { componentId: ownershipComponent.programId }, // player ownership
+          ],
+        }, {
+          entity: catalogEntityPda,
+          components: [
+            { componentId: priceComponent.programId }, // AiFi price
+            { componentId: stakingPoolComponent.programId }, // staking pool
+            { componentId: lockTiersComponent.programId }, // lock tiers
+            { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+            { componentId: walletComponent.programId }, // treasury wallet
+            { componentId: lotteryPrizeComponent.programId }, // lottery prize
+          ],
+        }],
+        args: collectArgs,
+        extraAccounts: [
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+        ],
+      }).then(applySystem => provider.sendAndConfirm(applySystem.transaction))
+      .catch(() => console.log("No rewards to collect"));
+    }
     
-    // Prepare args for staking
+    // Update parameters but disable claiming
+    const updateArgs = {
+      operation_type: 4, // UPDATE_PARAMS
+      staking_type: 1, // GPU
+      min_staking_period: stakeableBefore.minStakingPeriod,
+      reward_rate: stakeableBefore.rewardRate,
+      unstaking_penalty: stakeableBefore.unstakingPenalty,
+      base_usdc_per_hour: stakeableBefore.baseUsdcPerHour.toNumber(),
+      base_aifi_per_hour: stakeableBefore.baseAifiPerHour.toNumber(),
+      stake: false, // Not used
+      can_claim_rewards: false, // Disable claiming
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Default lock tier
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+    
+    // Apply the system to update parameters and disable claiming
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemStaking.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId },
+          { componentId: walletComponent.programId },
+          { componentId: productionComponent.programId },
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
+        ],
+      }],
+      args: updateArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+      ],
+    });
+    
+    const txSign = await provider.sendAndConfirm(applySystem.transaction);
+    console.log(`Applied staking system to disable reward claiming. Signature: ${txSign}`);
+    
+    // Verify claim functionality was disabled
+    const stakeableAfter = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    expect(stakeableAfter.canClaimRewards).to.equal(false);
+    
+    // Stake and generate some rewards
     const stakeArgs = {
-      operation_type: 1, // STAKE
+      operation_type: 1,
+      staking_type: 1,
+      min_staking_period: 0,
+      reward_rate: 0,
+      unstaking_penalty: 0,
+      base_usdc_per_hour: 0,
+      base_aifi_per_hour: 0,
+      stake: true,
+      can_claim_rewards: false,
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Default lock tier
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+    
+    await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemStaking.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId },
+          { componentId: walletComponent.programId },
+          { componentId: productionComponent.programId },
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
+        ],
+      }],
+      args: stakeArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+      ],
+    }).then(applySystem => provider.sendAndConfirm(applySystem.transaction));
+    
+    // Let some rewards accrue
+    const staked = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    await waitForClusterTime(staked.stakingStartTime.toNumber() + 2);
+    
+    // Unstake to generate rewards
+    const unstakeArgs = {
+      operation_type: 2,
+      staking_type: 1,
+      min_staking_period: 0,
+      reward_rate: 0,
+      unstaking_penalty: 0,
+      base_usdc_per_hour: 0,
+      base_aifi_per_hour: 0,
+      stake: false,
+      can_claim_rewards: false,
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Default lock tier
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+    
+    await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemStaking.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId },
+          { componentId: walletComponent.programId },
+          { componentId: productionComponent.programId },
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
+        ],
+      }],
+      args: unstakeArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+      ],
+    }).then(applySystem => provider.sendAndConfirm(applySystem.transaction));
+    
+    // Try to claim rewards with claiming disabled (should fail)
+    const stakeableWithRewards = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    console.log(`Accumulated rewards: ${stakeableWithRewards.accumulatedUsdcRewards.toNumber()/1000000} USDC, ${stakeableWithRewards.accumulatedAifiRewards.toNumber()/1000000} AiFi`);
+    
+    // Skip test if no rewards were generated (to avoid failures in CI)
+    if (stakeableWithRewards.accumulatedUsdcRewards.toNumber() === 0 && 
+        stakeableWithRewards.accumulatedAifiRewards.toNumber() === 0) {
+      console.log("No rewards accumulated, skipping claim test");
+      return;
+    }
+    
+    // Try to claim
+    const claimArgs = {
+      operation_type: 3,
+      staking_type: 0,
+      min_staking_period: 0,
+      reward_rate: 0,
+      unstaking_penalty: 0,
+      base_usdc_per_hour: 0,
+      base_aifi_per_hour: 0,
+      stake: false,
+      can_claim_rewards: false,
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Default lock tier
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+    
+    try {
+      // Apply the system to claim rewards (should fail)
+      const claimSystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemStaking.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: stakeableComponent.programId },
+            { componentId: walletComponent.programId },
+            { componentId: productionComponent.programId },
+            { componentId: ownershipComponent.programId }, // player ownership
+          ],
+        }, {
+          entity: catalogEntityPda,
+          components: [
+            { componentId: priceComponent.programId }, // AiFi price
+            { componentId: stakingPoolComponent.programId }, // staking pool
+            { componentId: lockTiersComponent.programId }, // lock tiers
+            { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+            { componentId: walletComponent.programId }, // treasury wallet
+            { componentId: lotteryPrizeComponent.programId }, // lottery prize
+          ],
+        }],
+        args: claimArgs,
+        extraAccounts: [
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+        ],
+      });
+      
+      await provider.sendAndConfirm(claimSystem.transaction);
+      expect.fail("Claim should have failed because claiming is disabled");
+    } catch (error) {
+      console.log(`Claim correctly failed as reward claiming is disabled`);
+    }
+  });
+
+  it("Configure the staking pool budget", async () => {
+    const configureArgs = {
+      operation_type: 8, // CONFIGURE_POOL
+      staking_type: 1, // GPU
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
+      stake: false, // Not used
+      can_claim_rewards: false, // Not used
+      pool_reward_per_epoch: 3600000, // 3.6 USDC per epoch
+      pool_epoch_duration: 3600, // One hour epochs, 0.001 USDC per second
+      lock_tier: 0, // Default lock tier
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+
+    // The game authority signs and is passed after the components
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemStaking.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId }, // stakeable component
+          { componentId: walletComponent.programId },    // wallet component
+          { componentId: productionComponent.programId }, // production component
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
+        ],
+      }],
+      args: configureArgs,
+      extraAccounts: [
+        { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+      ],
+    });
+
+    const txSign = await provider.sendAndConfirm(applySystem.transaction, [gameAuthority]);
+    console.log(`Configured the staking pool. Signature: ${txSign}`);
+
+    const pool = await stakingPoolComponent.account.stakingPool.fetch(stakingPoolComponentPda);
+    expect(pool.authority.toBase58()).to.equal(gameAuthority.publicKey.toBase58());
+    expect(pool.rewardPerEpoch.toNumber()).to.equal(3600000);
+    expect(pool.epochDuration).to.equal(3600);
+    expect(pool.totalWeight.toNumber()).to.equal(0);
+  });
+
+  it("Attempt to reconfigure the staking pool without the game authority (should fail)", async () => {
+    const configureArgs = {
+      operation_type: 8, // CONFIGURE_POOL
       staking_type: 1, // GPU
-      min_staking_period: 0, // Not used for staking
-      reward_rate: 0, // Not used for staking
-      unstaking_penalty: 0, // Not used for staking
-      base_usdc_per_hour: 0, // Not used for staking
-      base_aifi_per_hour: 0, // Not used for staking
-      current_time: currentTime,
-      stake: true, // Not actually used, just for clarity
-      can_claim_rewards: true // Not used for staking
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
+      stake: false, // Not used
+      can_claim_rewards: false, // Not used
+      pool_reward_per_epoch: 2400000000, // 2,400 USDC per epoch
+      pool_epoch_duration: 60, // One minute epochs
+      lock_tier: 0, // Default lock tier
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
     };
-    
+
     try {
-      // Apply the system to stake the entity (should fail)
       const applySystem = await ApplySystem({
         authority: provider.wallet.publicKey,
         systemId: systemStaking.programId,
@@ -1957,55 +5425,59 @@ describe("yield-wars-program", () => {
             { componentId: stakeableComponent.programId }, // stakeable component
             { componentId: walletComponent.programId },    // wallet component
             { componentId: productionComponent.programId }, // production component
+            { componentId: ownershipComponent.programId }, // player ownership
+          ],
+        }, {
+          entity: catalogEntityPda,
+          components: [
+            { componentId: priceComponent.programId }, // AiFi price
+            { componentId: stakingPoolComponent.programId }, // staking pool
+            { componentId: lockTiersComponent.programId }, // lock tiers
+            { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+            { componentId: walletComponent.programId }, // treasury wallet
+            { componentId: lotteryPrizeComponent.programId }, // lottery prize
           ],
         }],
-        args: stakeArgs,
+        args: configureArgs,
+        extraAccounts: [
+          { pubkey: provider.wallet.publicKey, isSigner: true, isWritable: false }, // not the game authority
+        ],
       });
-      
+
       await provider.sendAndConfirm(applySystem.transaction);
-      expect.fail("Staking should have failed because entity is already staked");
+      expect.fail("Only the game authority should configure the staking pool");
     } catch (error) {
-      console.log(`Staking correctly failed as entity is already staked`);
+      expect(error.toString()).to.not.include("Only the game authority should configure the staking pool");
+      console.log(`Staking pool change correctly rejected without the game authority`);
     }
+
+    const pool = await stakingPoolComponent.account.stakingPool.fetch(stakingPoolComponentPda);
+    expect(pool.rewardPerEpoch.toNumber()).to.equal(3600000);
+    expect(pool.epochDuration).to.equal(3600);
   });
-  
-  it("Unstake entity with rewards", async () => {
-    // We need to wait a bit to simulate time passing for rewards
-    // Sleep for a short period to ensure the test timing works
-    await new Promise(resolve => setTimeout(resolve, 1000));
-    
-    // Get current staking information
+
+  it("Join the staking pool with the GPU", async () => {
     const stakeableBefore = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
-    const walletBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
-    
-    // Verify entity is currently staked
-    expect(stakeableBefore.isStaked).to.equal(true);
-    
-    // Get staking time details
-    const stakingStartTime = stakeableBefore.stakingStartTime.toNumber();
-    
-    // Set timestamp for unstaking - use a time for testing that will produce reasonable rewards
-    // For test stability, we'll use a fixed offset from the staking start time
-    const currentTime = stakingStartTime + 7200; // 2 hours later
-    
-    console.log(`Staking started at: ${stakingStartTime}`);
-    console.log(`Unstaking at: ${currentTime} (${(currentTime - stakingStartTime)/3600} hours of staking)`);
-    
-    // Prepare args for unstaking
-    const unstakeArgs = {
-      operation_type: 2, // UNSTAKE
+    expect(stakeableBefore.isStaked).to.equal(false);
+
+    const joinArgs = {
+      operation_type: 5, // JOIN_POOL
       staking_type: 1, // GPU
-      min_staking_period: 0, // Not used for unstaking
-      reward_rate: 0, // Not used for unstaking
-      unstaking_penalty: 0, // Not used for unstaking
-      base_usdc_per_hour: 0, // Not used for unstaking
-      base_aifi_per_hour: 0, // Not used for unstaking
-      current_time: currentTime,
-      stake: false, // Not used for unstaking
-      can_claim_rewards: true // Not used for unstaking
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
+      stake: false, // Not used
+      can_claim_rewards: false, // Not used
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Default lock tier
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
     };
-    
-    // Apply the system to unstake the entity
+
     const applySystem = await ApplySystem({
       authority: provider.wallet.publicKey,
       systemId: systemStaking.programId,
@@ -2016,69 +5488,197 @@ describe("yield-wars-program", () => {
           { componentId: stakeableComponent.programId }, // stakeable component
           { componentId: walletComponent.programId },    // wallet component
           { componentId: productionComponent.programId }, // production component
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
         ],
       }],
-      args: unstakeArgs,
+      args: joinArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+      ],
     });
-    
+
     const txSign = await provider.sendAndConfirm(applySystem.transaction);
-    console.log(`Applied staking system to unstake entity. Signature: ${txSign}`);
-    
-    // Verify unstaking succeeded
+    console.log(`Joined the staking pool. Signature: ${txSign}`);
+
     const stakeableAfter = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    const pool = await stakingPoolComponent.account.stakingPool.fetch(stakingPoolComponentPda);
     const productionAfter = await productionComponent.account.production.fetch(productionComponentPda);
-    
-    expect(stakeableAfter.isStaked).to.equal(false);
-    expect(stakeableAfter.stakingStartTime.toNumber()).to.equal(0);
-    
-    // Production should be reactivated
-    expect(productionAfter.isActive).to.equal(true);
-    
-    // Check accumulated rewards
-    console.log(`Accumulated rewards after unstaking: ${stakeableAfter.accumulatedUsdcRewards.toNumber()/1000000} USDC, ${stakeableAfter.accumulatedAifiRewards.toNumber()/1000000} AiFi`);
-    
-    // Should have accumulated some rewards (exact value depends on implementation)
-    // We don't verify exact values since they might change, just that we got some rewards
-    expect(stakeableAfter.accumulatedUsdcRewards.toNumber()).to.be.gte(0);
-    expect(stakeableAfter.accumulatedAifiRewards.toNumber()).to.be.gte(0);
+
+    // The GPU is the only staker and weighs its production value
+    expect(stakeableAfter.isStaked).to.equal(true);
+    expect(stakeableAfter.pool.toBase58()).to.equal(stakingPoolComponentPda.toBase58());
+    expect(stakeableAfter.poolWeight.toNumber()).to.be.gt(0);
+    expect(pool.totalWeight.toNumber()).to.equal(stakeableAfter.poolWeight.toNumber());
+    expect(pool.stakerCount).to.equal(1);
+    expect(productionAfter.isActive).to.equal(false);
   });
 
-  it("Collect staking rewards", async () => {
-    // Get current wallet and stakeable info
+  it("Harvest pool rewards within the epoch budget", async () => {
     const stakeableBefore = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
     const walletBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
-    
-    // Skip test if no rewards to collect (to avoid failures in CI)
-    if (stakeableBefore.accumulatedUsdcRewards.toNumber() === 0 && 
-        stakeableBefore.accumulatedAifiRewards.toNumber() === 0) {
-      console.log("No rewards to collect, skipping test");
-      return;
+
+    const poolBefore = await stakingPoolComponent.account.stakingPool.fetch(stakingPoolComponentPda);
+
+    // Let a few seconds of the 0.001 USDC per second budget accrue
+    await waitForClusterTime(stakeableBefore.stakingStartTime.toNumber() + 5);
+    const harvestArgs = {
+      operation_type: 7, // HARVEST
+      staking_type: 1, // GPU
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
+      stake: false, // Not used
+      can_claim_rewards: false, // Not used
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Default lock tier
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemStaking.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId }, // stakeable component
+          { componentId: walletComponent.programId },    // wallet component
+          { componentId: productionComponent.programId }, // production component
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
+        ],
+      }],
+      args: harvestArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+      ],
+    });
+
+    const txSign = await provider.sendAndConfirm(applySystem.transaction);
+    console.log(`Harvested pool rewards. Signature: ${txSign}`);
+
+    const walletAfter = await walletComponent.account.wallet.fetch(walletComponentPda);
+    const pool = await stakingPoolComponent.account.stakingPool.fetch(stakingPoolComponentPda);
+    const harvested = walletAfter.usdcBalance.toNumber() - walletBefore.usdcBalance.toNumber();
+
+    // The only staker receives the whole emission since joining, less rounding
+    const emitted = (pool.lastUpdateTime.toNumber() - poolBefore.lastUpdateTime.toNumber()) * 1000;
+    expect(emitted).to.be.gte(5000);
+    expect(pool.totalEmitted.toNumber() - poolBefore.totalEmitted.toNumber()).to.equal(emitted);
+    expect(harvested).to.be.lte(emitted);
+    expect(harvested).to.be.gte(emitted - 1);
+    console.log(`Harvested ${harvested/1000000} USDC from the pool`);
+  });
+
+  it("Attempt to unstake a pool GPU without leaving the pool (should fail)", async () => {
+    const unstakeArgs = {
+      operation_type: 2, // UNSTAKE
+      staking_type: 1, // GPU
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
+      stake: false, // Not used
+      can_claim_rewards: false, // Not used
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Default lock tier
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+
+    try {
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemStaking.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: stakeableComponent.programId }, // stakeable component
+            { componentId: walletComponent.programId },    // wallet component
+            { componentId: productionComponent.programId }, // production component
+            { componentId: ownershipComponent.programId }, // player ownership
+          ],
+        }, {
+          entity: catalogEntityPda,
+          components: [
+            { componentId: priceComponent.programId }, // AiFi price
+            { componentId: stakingPoolComponent.programId }, // staking pool
+            { componentId: lockTiersComponent.programId }, // lock tiers
+            { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+            { componentId: walletComponent.programId }, // treasury wallet
+            { componentId: lotteryPrizeComponent.programId }, // lottery prize
+          ],
+        }],
+        args: unstakeArgs,
+        extraAccounts: [
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+        ],
+      });
+
+      await provider.sendAndConfirm(applySystem.transaction);
+      expect.fail("Pool stakers should have to leave the pool");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Pool stakers should have to leave the pool");
+      console.log(`Unstake correctly rejected for a pool staker`);
     }
-    
-    // Verify there are rewards to collect and claiming is enabled
-    expect(stakeableBefore.canClaimRewards).to.equal(true);
-    
-    console.log(`Wallet before collection: ${walletBefore.usdcBalance.toNumber()/1000000} USDC, ${walletBefore.aifiBalance.toNumber()/1000000} AiFi`);
-    console.log(`Accumulated rewards: ${stakeableBefore.accumulatedUsdcRewards.toNumber()/1000000} USDC, ${stakeableBefore.accumulatedAifiRewards.toNumber()/1000000} AiFi`);
-    
-    // Set timestamp for collecting rewards
-    const currentTime = Math.floor(Date.now() / 1000);
-    
-    // Prepare args for collecting rewards
-    const collectArgs = {
-      operation_type: 3, // COLLECT_REWARDS
-      staking_type: 0, // Not used for collection
-      min_staking_period: 0, // Not used for collection
-      reward_rate: 0, // Not used for collection
-      unstaking_penalty: 0, // Not used for collection
-      base_usdc_per_hour: 0, // Not used for collection
-      base_aifi_per_hour: 0, // Not used for collection
-      current_time: currentTime,
-      stake: false, // Not used for collection
-      can_claim_rewards: true // Not used for collection
+  });
+
+  it("Leave the staking pool", async () => {
+    const stakeableBefore = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    const walletBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
+    const poolBefore = await stakingPoolComponent.account.stakingPool.fetch(stakingPoolComponentPda);
+
+    // Let a few more seconds accrue
+    await waitForClusterTime(poolBefore.lastUpdateTime.toNumber() + 3);
+    const leaveArgs = {
+      operation_type: 6, // LEAVE_POOL
+      staking_type: 1, // GPU
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
+      stake: false, // Not used
+      can_claim_rewards: false, // Not used
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Default lock tier
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
     };
-    
-    // Apply the system to collect rewards
+
     const applySystem = await ApplySystem({
       authority: provider.wallet.publicKey,
       systemId: systemStaking.programId,
@@ -2089,59 +5689,132 @@ describe("yield-wars-program", () => {
           { componentId: stakeableComponent.programId }, // stakeable component
           { componentId: walletComponent.programId },    // wallet component
           { componentId: productionComponent.programId }, // production component
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
         ],
       }],
-      args: collectArgs,
+      args: leaveArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+      ],
     });
-    
+
     const txSign = await provider.sendAndConfirm(applySystem.transaction);
-    console.log(`Applied staking system to collect rewards. Signature: ${txSign}`);
-    
-    // Verify collection succeeded
+    console.log(`Left the staking pool. Signature: ${txSign}`);
+
     const stakeableAfter = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
     const walletAfter = await walletComponent.account.wallet.fetch(walletComponentPda);
-    
-    // Accumulated rewards should be reset to 0
-    expect(stakeableAfter.accumulatedUsdcRewards.toNumber()).to.equal(0);
-    expect(stakeableAfter.accumulatedAifiRewards.toNumber()).to.equal(0);
-    
-    // Wallet balances should increase by the reward amounts
-    expect(walletAfter.usdcBalance.toNumber()).to.equal(
-      walletBefore.usdcBalance.toNumber() + stakeableBefore.accumulatedUsdcRewards.toNumber()
-    );
-    expect(walletAfter.aifiBalance.toNumber()).to.equal(
-      walletBefore.aifiBalance.toNumber() + stakeableBefore.accumulatedAifiRewards.toNumber()
-    );
-    
-    console.log(`Wallet after collection: ${walletAfter.usdcBalance.toNumber()/1000000} USDC, ${walletAfter.aifiBalance.toNumber()/1000000} AiFi`);
-    console.log(`Rewards collected: ${stakeableBefore.accumulatedUsdcRewards.toNumber()/1000000} USDC, ${stakeableBefore.accumulatedAifiRewards.toNumber()/1000000} AiFi`);
+    const pool = await stakingPoolComponent.account.stakingPool.fetch(stakingPoolComponentPda);
+    const productionAfter = await productionComponent.account.production.fetch(productionComponentPda);
+
+    // The rest of the emission is harvested on the way out
+    expect(stakeableAfter.isStaked).to.equal(false);
+    expect(stakeableAfter.poolWeight.toNumber()).to.equal(0);
+    expect(pool.totalWeight.toNumber()).to.equal(0);
+    expect(pool.stakerCount).to.equal(0);
+    expect(pool.totalEmitted.toNumber() - poolBefore.totalEmitted.toNumber())
+      .to.equal((pool.lastUpdateTime.toNumber() - poolBefore.lastUpdateTime.toNumber()) * 1000);
+    expect(walletAfter.usdcBalance.toNumber()).to.be.gt(walletBefore.usdcBalance.toNumber());
+    expect(productionAfter.isActive).to.equal(true);
   });
-  
-  it("Try collecting when no rewards are available (should fail)", async () => {
-    // Verify there are no accumulated rewards
-    const stakeableBefore = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
-    expect(stakeableBefore.accumulatedUsdcRewards.toNumber()).to.equal(0);
-    expect(stakeableBefore.accumulatedAifiRewards.toNumber()).to.equal(0);
-    
-    // Set timestamp for collecting rewards
-    const currentTime = Math.floor(Date.now() / 1000);
-    
-    // Prepare args for collecting rewards
-    const collectArgs = {
-      operation_type: 3, // COLLECT_REWARDS
-      staking_type: 0, // Not used for collection
-      min_staking_period: 0, // Not used for collection
-      reward_rate: 0, // Not used for collection
-      unstaking_penalty: 0, // Not used for collection
-      base_usdc_per_hour: 0, // Not used for collection
-      base_aifi_per_hour: 0, // Not used for collection
-      current_time: currentTime,
-      stake: false, // Not used for collection
-      can_claim_rewards: true // Not used for collection
+
+  it("Configure the lock tiers", async () => {
+    // 1 day, 7 days and 30 days, with longer locks earning more
+    const tiers = [
+      { duration: 86400, multiplier: 10000, penalty: 5000 },
+      { duration: 7 * 86400, multiplier: 15000, penalty: 5000 },
+      { duration: 30 * 86400, multiplier: 25000, penalty: 7500 },
+    ];
+
+    for (let tier = 0; tier < tiers.length; tier++) {
+      const configureArgs = {
+        operation_type: 9, // CONFIGURE_TIER
+        staking_type: 1, // GPU
+        min_staking_period: tiers[tier].duration,
+        reward_rate: tiers[tier].multiplier, // Reward multiplier of the tier
+        unstaking_penalty: tiers[tier].penalty, // Early unstaking penalty of the tier
+        base_usdc_per_hour: 0, // Not used
+        base_aifi_per_hour: 0, // Not used
+        stake: false, // Not used
+        can_claim_rewards: false, // Not used
+        pool_reward_per_epoch: 0, // Not used
+        pool_epoch_duration: 0, // Not used
+        lock_tier: tier,
+        unbonding_period: 0, // Not used
+        penalty_destination: 0, // Not used
+        compound_mode: 0 // Not used
+      };
+
+      // The game authority signs and is passed after the components
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemStaking.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: stakeableComponent.programId }, // stakeable component
+            { componentId: walletComponent.programId },    // wallet component
+            { componentId: productionComponent.programId }, // production component
+            { componentId: ownershipComponent.programId }, // player ownership
+          ],
+        }, {
+          entity: catalogEntityPda,
+          components: [
+            { componentId: priceComponent.programId }, // AiFi price
+            { componentId: stakingPoolComponent.programId }, // staking pool
+            { componentId: lockTiersComponent.programId }, // lock tiers
+            { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+            { componentId: walletComponent.programId }, // treasury wallet
+            { componentId: lotteryPrizeComponent.programId }, // lottery prize
+          ],
+        }],
+        args: configureArgs,
+        extraAccounts: [
+          { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+        ],
+      });
+
+      await provider.sendAndConfirm(applySystem.transaction, [gameAuthority]);
+    }
+
+    const lockTiers = await lockTiersComponent.account.lockTiers.fetch(lockTiersComponentPda);
+    expect(lockTiers.authority.toBase58()).to.equal(gameAuthority.publicKey.toBase58());
+    expect(lockTiers.durations).to.deep.equal(tiers.map(tier => tier.duration));
+    expect(lockTiers.rewardMultipliers).to.deep.equal(tiers.map(tier => tier.multiplier));
+    expect(lockTiers.penalties).to.deep.equal(tiers.map(tier => tier.penalty));
+  });
+
+  it("Attempt to stake in a lock tier that does not exist (should fail)", async () => {
+    const stakeArgs = {
+      operation_type: 1, // STAKE
+      staking_type: 1, // GPU
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
+      stake: false, // Not used
+      can_claim_rewards: false, // Not used
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 3, // Only tiers 0 to 2 exist
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
     };
-    
+
     try {
-      // Apply the system to collect rewards (should fail)
       const applySystem = await ApplySystem({
         authority: provider.wallet.publicKey,
         systemId: systemStaking.programId,
@@ -2152,79 +5825,175 @@ describe("yield-wars-program", () => {
             { componentId: stakeableComponent.programId }, // stakeable component
             { componentId: walletComponent.programId },    // wallet component
             { componentId: productionComponent.programId }, // production component
+            { componentId: ownershipComponent.programId }, // player ownership
+          ],
+        }, {
+          entity: catalogEntityPda,
+          components: [
+            { componentId: priceComponent.programId }, // AiFi price
+            { componentId: stakingPoolComponent.programId }, // staking pool
+            { componentId: lockTiersComponent.programId }, // lock tiers
+            { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+            { componentId: walletComponent.programId }, // treasury wallet
+            { componentId: lotteryPrizeComponent.programId }, // lottery prize
           ],
         }],
-        args: collectArgs,
+        args: stakeArgs,
+        extraAccounts: [
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+        ],
       });
-      
+
       await provider.sendAndConfirm(applySystem.transaction);
-      expect.fail("Collection should have failed because no rewards are available");
+      expect.fail("Staking should require an existing lock tier");
     } catch (error) {
-      console.log(`Collection correctly failed as no rewards are available`);
+      expect(error.toString()).to.not.include("Staking should require an existing lock tier");
+      console.log(`Staking correctly rejected for a missing lock tier`);
     }
   });
-  
-  it("Stake again and update staking parameters", async () => {
-    // First stake the entity again
-    const stakeTime = Math.floor(Date.now() / 1000);
-    
-    // Prepare args for staking
+
+  it("Route early unstaking penalties to the treasury", async () => {
+    const configureArgs = {
+      operation_type: 12, // CONFIGURE_PENALTIES
+      staking_type: 1, // GPU
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
+      stake: false, // Not used
+      can_claim_rewards: false, // Not used
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Not used
+      unbonding_period: 0, // Not used
+      penalty_destination: 1, // Treasury
+      compound_mode: 0 // Not used
+    };
+
+    // The game authority signs and is passed after the components
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemStaking.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId }, // stakeable component
+          { componentId: walletComponent.programId },    // wallet component
+          { componentId: productionComponent.programId }, // production component
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
+        ],
+      }],
+      args: configureArgs,
+      extraAccounts: [
+        { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+      ],
+    });
+
+    await provider.sendAndConfirm(applySystem.transaction, [gameAuthority]);
+
+    const ledger = await penaltyLedgerComponent.account.penaltyLedger.fetch(penaltyLedgerComponentPda);
+    expect(ledger.authority.toBase58()).to.equal(gameAuthority.publicKey.toBase58());
+    expect(ledger.destination).to.equal(1);
+    expect(ledger.destinationAccount.toBase58()).to.equal(treasuryComponentPda.toBase58());
+  });
+
+  it("Stake in the 7 day lock tier", async () => {
     const stakeArgs = {
       operation_type: 1, // STAKE
       staking_type: 1, // GPU
-      min_staking_period: 0, // Not used for staking
-      reward_rate: 0, // Not used for staking
-      unstaking_penalty: 0, // Not used for staking
-      base_usdc_per_hour: 0, // Not used for staking
-      base_aifi_per_hour: 0, // Not used for staking
-      current_time: stakeTime,
-      stake: true, // Not actually used, just for clarity
-      can_claim_rewards: true // Not used for staking
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
+      stake: false, // Not used
+      can_claim_rewards: false, // Not used
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 1, // 7 day lock
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
     };
-    
-    // Apply the system to stake the entity
-    await ApplySystem({
+
+    const applySystem = await ApplySystem({
       authority: provider.wallet.publicKey,
       systemId: systemStaking.programId,
       world: worldPda,
       entities: [{
         entity: entityPda,
         components: [
-          { componentId: stakeableComponent.programId },
-          { componentId: walletComponent.programId },
-          { componentId: productionComponent.programId },
+          { componentId: stakeableComponent.programId }, // stakeable component
+          { componentId: walletComponent.programId },    // wallet component
+          { componentId: productionComponent.programId }, // production component
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
         ],
       }],
       args: stakeArgs,
-    }).then(applySystem => provider.sendAndConfirm(applySystem.transaction));
-    
-    // Verify staking succeeded
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+      ],
+    });
+
+    const txSign = await provider.sendAndConfirm(applySystem.transaction);
+    console.log(`Staked in the 7 day lock tier. Signature: ${txSign}`);
+
+    const stakeable = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    expect(stakeable.isStaked).to.equal(true);
+    expect(stakeable.lockTier).to.equal(1);
+    expect(stakeable.unlockTime.toNumber()).to.equal(stakeable.stakingStartTime.toNumber() + 7 * 86400);
+    expect(stakeable.lockMultiplier).to.equal(15000);
+    expect(stakeable.lockPenalty).to.equal(5000);
+  });
+
+  it("Unstake early from the lock tier with its penalty", async () => {
     const stakeableBefore = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
-    expect(stakeableBefore.isStaked).to.equal(true);
-    
-    // Now update the staking parameters
-    const newMinStakingPeriod = 43200; // 12 hours (reduced from 24)
-    const newRewardRate = 7500; // 75% (half of previous 150%)
-    const newUnstakingPenalty = 2500; // 25% (half of previous 50%)
-    const newBaseUsdcPerHour = 7500000; // 7.5 USDC/hr
-    const newBaseAifiPerHour = 1500000; // 1.5 AiFi/hr
-    const updateTime = Math.floor(Date.now() / 1000);
-    
-    // Prepare args for updating parameters
-    const updateArgs = {
-      operation_type: 4, // UPDATE_PARAMS
+    const treasuryBefore = await walletComponent.account.wallet.fetch(treasuryComponentPda);
+
+    // A few seconds into a seven day lock
+    await waitForClusterTime(stakeableBefore.stakingStartTime.toNumber() + 3);
+    const unstakeArgs = {
+      operation_type: 2, // UNSTAKE
       staking_type: 1, // GPU
-      min_staking_period: newMinStakingPeriod,
-      reward_rate: newRewardRate,
-      unstaking_penalty: newUnstakingPenalty,
-      base_usdc_per_hour: newBaseUsdcPerHour,
-      base_aifi_per_hour: newBaseAifiPerHour,
-      current_time: updateTime,
-      stake: false, // Not used for updating
-      can_claim_rewards: true
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
+      stake: false, // Not used
+      can_claim_rewards: false, // Not used
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Not used
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
     };
-    
-    // Apply the system to update parameters
+
     const applySystem = await ApplySystem({
       authority: provider.wallet.publicKey,
       systemId: systemStaking.programId,
@@ -2235,124 +6004,359 @@ describe("yield-wars-program", () => {
           { componentId: stakeableComponent.programId }, // stakeable component
           { componentId: walletComponent.programId },    // wallet component
           { componentId: productionComponent.programId }, // production component
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
         ],
       }],
-      args: updateArgs,
+      args: unstakeArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+      ],
     });
-    
+
     const txSign = await provider.sendAndConfirm(applySystem.transaction);
-    console.log(`Applied staking system to update parameters. Signature: ${txSign}`);
-    
-    // Verify parameters were updated
+    console.log(`Unstaked early from the lock tier. Signature: ${txSign}`);
+
     const stakeableAfter = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
-    
-    expect(stakeableAfter.minStakingPeriod).to.equal(newMinStakingPeriod);
-    expect(stakeableAfter.rewardRate).to.equal(newRewardRate);
-    expect(stakeableAfter.unstakingPenalty).to.equal(newUnstakingPenalty);
-    expect(stakeableAfter.baseUsdcPerHour.toNumber()).to.equal(newBaseUsdcPerHour);
-    expect(stakeableAfter.baseAifiPerHour.toNumber()).to.equal(newBaseAifiPerHour);
-    
-    // Entity should still be staked
-    expect(stakeableAfter.isStaked).to.equal(true);
-    
-    console.log(`Staking parameters updated successfully:`);
-    console.log(`Min staking period: ${stakeableAfter.minStakingPeriod/3600} hours`);
-    console.log(`Reward rate: ${stakeableAfter.rewardRate/100}%`);
-    console.log(`Unstaking penalty: ${stakeableAfter.unstakingPenalty/100}%`);
-    console.log(`Base rates: ${stakeableAfter.baseUsdcPerHour.toNumber()/1000000} USDC/hr, ${stakeableAfter.baseAifiPerHour.toNumber()/1000000} AiFi/hr`);
-    
-    // Finally, unstake to clean up
-    const unstakeTime = stakeTime + 3600; // 1 hour later (will incur penalty)
-    
-    // Prepare args for unstaking
+    expect(stakeableAfter.isStaked).to.equal(false);
+    expect(stakeableAfter.lockedUsdcRewards.toNumber()).to.equal(0);
+
+    // Half of the locked rewards are kept
+    console.log(`Rewards kept after the early unstaking penalty: ${(stakeableAfter.accumulatedUsdcRewards.toNumber() - stakeableBefore.accumulatedUsdcRewards.toNumber())/1000000} USDC`);
+
+    // The other half goes to the treasury and is recorded in the ledger
+    const treasuryAfter = await walletComponent.account.wallet.fetch(treasuryComponentPda);
+    const penalty = treasuryAfter.usdcBalance.toNumber() - treasuryBefore.usdcBalance.toNumber();
+    expect(penalty).to.be.greaterThan(0);
+
+    const ledger = await penaltyLedgerComponent.account.penaltyLedger.fetch(penaltyLedgerComponentPda);
+    expect(ledger.totalToTreasury.toNumber()).to.equal(penalty);
+    expect(ledger.redistributionCount.toNumber()).to.equal(1);
+    expect(ledger.recentStakeables[0].toBase58()).to.equal(stakeableComponentPda.toBase58());
+    expect(ledger.recentDestinations).to.deep.equal([1]);
+    expect(ledger.recentAmounts[0].toNumber()).to.equal(penalty);
+    console.log(`Early unstaking penalty sent to the treasury: ${penalty/1000000} USDC`);
+  });
+
+  it("Configure the GPU unbonding period", async () => {
+    const configureArgs = {
+      operation_type: 10, // CONFIGURE_UNBONDING
+      staking_type: 1, // GPU
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
+      stake: false, // Not used
+      can_claim_rewards: false, // Not used
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Not used
+      unbonding_period: 15, // GPUs unbond for 15 seconds
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+
+    // The game authority signs and is passed after the components
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemStaking.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId }, // stakeable component
+          { componentId: walletComponent.programId },    // wallet component
+          { componentId: productionComponent.programId }, // production component
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
+        ],
+      }],
+      args: configureArgs,
+      extraAccounts: [
+        { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+      ],
+    });
+
+    await provider.sendAndConfirm(applySystem.transaction, [gameAuthority]);
+
+    const lockTiers = await lockTiersComponent.account.lockTiers.fetch(lockTiersComponentPda);
+    expect(lockTiers.unbondingPeriods).to.deep.equal([0, 15]);
+  });
+
+  it("Unstake a GPU into its unbonding period", async () => {
+    const stakeArgs = {
+      operation_type: 1, // STAKE
+      staking_type: 1, // GPU
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
+      stake: false, // Not used
+      can_claim_rewards: false, // Not used
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // 1 day lock
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+
+    const stakeSystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemStaking.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: stakeableComponent.programId }, // stakeable component
+          { componentId: walletComponent.programId },    // wallet component
+          { componentId: productionComponent.programId }, // production component
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
+        ],
+      }],
+      args: stakeArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+      ],
+    });
+
+    await provider.sendAndConfirm(stakeSystem.transaction);
+
+    const staked = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    await waitForClusterTime(staked.stakingStartTime.toNumber() + 1);
     const unstakeArgs = {
       operation_type: 2, // UNSTAKE
       staking_type: 1, // GPU
-      min_staking_period: 0, // Not used for unstaking
-      reward_rate: 0, // Not used for unstaking
-      unstaking_penalty: 0, // Not used for unstaking
-      base_usdc_per_hour: 0, // Not used for unstaking
-      base_aifi_per_hour: 0, // Not used for unstaking
-      current_time: unstakeTime,
-      stake: false, // Not used for unstaking
-      can_claim_rewards: true // Not used for unstaking
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
+      stake: false, // Not used
+      can_claim_rewards: false, // Not used
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Not used
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
     };
-    
-    // Apply the system to unstake
-    await ApplySystem({
+
+    const applySystem = await ApplySystem({
       authority: provider.wallet.publicKey,
       systemId: systemStaking.programId,
       world: worldPda,
       entities: [{
         entity: entityPda,
         components: [
-          { componentId: stakeableComponent.programId },
-          { componentId: walletComponent.programId },
-          { componentId: productionComponent.programId },
+          { componentId: stakeableComponent.programId }, // stakeable component
+          { componentId: walletComponent.programId },    // wallet component
+          { componentId: productionComponent.programId }, // production component
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
         ],
       }],
       args: unstakeArgs,
-    }).then(applySystem => provider.sendAndConfirm(applySystem.transaction));
-    
-    // Verify unstaking worked
-    const finalStakeable = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
-    expect(finalStakeable.isStaked).to.equal(false);
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+      ],
+    });
+
+    const txSign = await provider.sendAndConfirm(applySystem.transaction);
+    console.log(`Unstaked into the unbonding period. Signature: ${txSign}`);
+
+    const stakeable = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    expect(stakeable.isStaked).to.equal(false);
+    expect(stakeable.isUnbonding).to.equal(true);
+    expect(stakeable.unbondingEndTime.toNumber()).to.equal(stakeable.lastClaimTime.toNumber() + 15);
+
+    // Unbonding GPUs produce nothing
+    const production = await productionComponent.account.production.fetch(productionComponentPda);
+    expect(production.isActive).to.equal(false);
   });
-  
-  it("Disable reward claiming", async () => {
-    // Get current staking parameters
-    const stakeableBefore = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
-    
-    // If we have any accumulated rewards, collect them first
-    if (stakeableBefore.accumulatedUsdcRewards.toNumber() > 0 || 
-        stakeableBefore.accumulatedAifiRewards.toNumber() > 0) {
-      console.log("Collecting existing rewards before disabling claim functionality");
-      
-      const collectArgs = {
-        operation_type: 3, // COLLECT_REWARDS
-        staking_type: 0, // Not used
-        min_staking_period: 0, // Not used
-        reward_rate: 0, // Not used
-        unstaking_penalty: 0, // Not used
-        base_usdc_per_hour: 0, // Not used
-        base_aifi_per_hour: 0, // Not used
-        current_time: Math.floor(Date.now() / 1000),
-        stake: false, // Not used
-        can_claim_rewards: true // Not used
-      };
-      
-      await ApplySystem({
+
+  it("Attempt to stake an unbonding GPU (should fail)", async () => {
+    const stakeable = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    const stakeArgs = {
+      operation_type: 1, // STAKE
+      staking_type: 1, // GPU
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
+      stake: false, // Not used
+      can_claim_rewards: false, // Not used
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // 1 day lock
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+
+    try {
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemStaking.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: stakeableComponent.programId }, // stakeable component
+            { componentId: walletComponent.programId },    // wallet component
+            { componentId: productionComponent.programId }, // production component
+            { componentId: ownershipComponent.programId }, // player ownership
+          ],
+        }, {
+          entity: catalogEntityPda,
+          components: [
+            { componentId: priceComponent.programId }, // AiFi price
+            { componentId: stakingPoolComponent.programId }, // staking pool
+            { componentId: lockTiersComponent.programId }, // lock tiers
+            { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+            { componentId: walletComponent.programId }, // treasury wallet
+            { componentId: lotteryPrizeComponent.programId }, // lottery prize
+          ],
+        }],
+        args: stakeArgs,
+        extraAccounts: [
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+        ],
+      });
+
+      await provider.sendAndConfirm(applySystem.transaction);
+      expect.fail("Staking should wait for the GPU to be withdrawn");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Staking should wait for the GPU to be withdrawn");
+      console.log(`Staking correctly rejected for an unbonding GPU`);
+    }
+  });
+
+  it("Attempt to withdraw before the unbonding period ends (should fail)", async () => {
+    const stakeable = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    const withdrawArgs = {
+      operation_type: 11, // WITHDRAW
+      staking_type: 1, // GPU
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
+      stake: false, // Not used
+      can_claim_rewards: false, // Not used
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Not used
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
+    };
+
+    try {
+      const applySystem = await ApplySystem({
         authority: provider.wallet.publicKey,
         systemId: systemStaking.programId,
         world: worldPda,
         entities: [{
           entity: entityPda,
           components: [
-            { componentId: stakeableComponent.programId },
-            { componentId: walletComponent.programId },
-            { componentId: productionComponent.programId },
+            { componentId: stakeableComponent.programId }, // stakeable component
+            { componentId: walletComponent.programId },    // wallet component
+            { componentId: productionComponent.programId }, // production component
+            { componentId: ownershipComponent.programId }, // player ownership
+          ],
+        }, {
+          entity: catalogEntityPda,
+          components: [
+            { componentId: priceComponent.programId }, // AiFi price
+            { componentId: stakingPoolComponent.programId }, // staking pool
+            { componentId: lockTiersComponent.programId }, // lock tiers
+            { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+            { componentId: walletComponent.programId }, // treasury wallet
+            { componentId: lotteryPrizeComponent.programId }, // lottery prize
           ],
         }],
-        args: collectArgs,
-      }).then(applySystem => provider.sendAndConfirm(applySystem.transaction))
-      .catch(() => console.log("No rewards to collect"));
+        args: withdrawArgs,
+        extraAccounts: [
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+        ],
+      });
+
+      await provider.sendAndConfirm(applySystem.transaction);
+      expect.fail("Withdraw should wait for the unbonding period");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Withdraw should wait for the unbonding period");
+      console.log(`Withdraw correctly rejected during the unbonding period`);
     }
-    
-    // Update parameters but disable claiming
-    const updateArgs = {
-      operation_type: 4, // UPDATE_PARAMS
+  });
+
+  it("Withdraw the GPU once it has unbonded", async () => {
+    const stakeableBefore = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
+    const withdrawTime = stakeableBefore.unbondingEndTime.toNumber();
+    await waitForClusterTime(withdrawTime);
+    const withdrawArgs = {
+      operation_type: 11, // WITHDRAW
       staking_type: 1, // GPU
-      min_staking_period: stakeableBefore.minStakingPeriod,
-      reward_rate: stakeableBefore.rewardRate,
-      unstaking_penalty: stakeableBefore.unstakingPenalty,
-      base_usdc_per_hour: stakeableBefore.baseUsdcPerHour.toNumber(),
-      base_aifi_per_hour: stakeableBefore.baseAifiPerHour.toNumber(),
-      current_time: Math.floor(Date.now() / 1000),
+      min_staking_period: 0, // Not used
+      reward_rate: 0, // Not used
+      unstaking_penalty: 0, // Not used
+      base_usdc_per_hour: 0, // Not used
+      base_aifi_per_hour: 0, // Not used
       stake: false, // Not used
-      can_claim_rewards: false // Disable claiming
+      can_claim_rewards: false, // Not used
+      pool_reward_per_epoch: 0, // Not used
+      pool_epoch_duration: 0, // Not used
+      lock_tier: 0, // Not used
+      unbonding_period: 0, // Not used
+      penalty_destination: 0, // Not used
+      compound_mode: 0 // Not used
     };
-    
-    // Apply the system to update parameters and disable claiming
+
     const applySystem = await ApplySystem({
       authority: provider.wallet.publicKey,
       systemId: systemStaking.programId,
@@ -2360,126 +6364,357 @@ describe("yield-wars-program", () => {
       entities: [{
         entity: entityPda,
         components: [
-          { componentId: stakeableComponent.programId },
-          { componentId: walletComponent.programId },
-          { componentId: productionComponent.programId },
+          { componentId: stakeableComponent.programId }, // stakeable component
+          { componentId: walletComponent.programId },    // wallet component
+          { componentId: productionComponent.programId }, // production component
+          { componentId: ownershipComponent.programId }, // player ownership
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: priceComponent.programId }, // AiFi price
+          { componentId: stakingPoolComponent.programId }, // staking pool
+          { componentId: lockTiersComponent.programId }, // lock tiers
+          { componentId: penaltyLedgerComponent.programId }, // penalty ledger
+          { componentId: walletComponent.programId }, // treasury wallet
+          { componentId: lotteryPrizeComponent.programId }, // lottery prize
         ],
       }],
-      args: updateArgs,
+      args: withdrawArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // owned entity
+      ],
     });
-    
+
     const txSign = await provider.sendAndConfirm(applySystem.transaction);
-    console.log(`Applied staking system to disable reward claiming. Signature: ${txSign}`);
-    
-    // Verify claim functionality was disabled
+    console.log(`Withdrew the unbonded GPU. Signature: ${txSign}`);
+
     const stakeableAfter = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
-    expect(stakeableAfter.canClaimRewards).to.equal(false);
-    
-    // Stake and generate some rewards
-    const stakeArgs = {
-      operation_type: 1,
-      staking_type: 1,
-      min_staking_period: 0,
-      reward_rate: 0,
-      unstaking_penalty: 0,
-      base_usdc_per_hour: 0,
-      base_aifi_per_hour: 0,
-      current_time: Math.floor(Date.now() / 1000),
-      stake: true,
-      can_claim_rewards: false
+    expect(stakeableAfter.isUnbonding).to.equal(false);
+
+    // Back to normal production
+    const production = await productionComponent.account.production.fetch(productionComponentPda);
+    expect(production.isActive).to.equal(true);
+    expect(production.lastCollectionTime.toNumber()).to.be.gte(withdrawTime);
+  });
+
+  it("Attempt to lock AiFi for less than the minimum duration (should fail)", async () => {
+    const lockArgs = {
+      operation_type: 0, // LOCK
+      amount: 1000000, // 1 AiFi
+      lock_duration: 86400, // Shorter than the 7 day minimum
     };
-    
-    await ApplySystem({
+
+    try {
+      const applySystem = await ApplySystem({
+        authority: provider.wallet.publicKey,
+        systemId: systemAifiLock.programId,
+        world: worldPda,
+        entities: [{
+          entity: entityPda,
+          components: [
+            { componentId: voteEscrowComponent.programId }, // vote-escrow position
+            { componentId: walletComponent.programId },     // player wallet
+            { componentId: playerProfileComponent.programId }, // player profile
+          ],
+        }, {
+          entity: catalogEntityPda,
+          components: [
+            { componentId: feePoolComponent.programId }, // fee pool
+            { componentId: walletComponent.programId },  // treasury wallet
+          ],
+        }],
+        args: lockArgs,
+        extraAccounts: [
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        ],
+      });
+
+      await provider.sendAndConfirm(applySystem.transaction);
+      expect.fail("Locking should require the minimum duration");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Locking should require the minimum duration");
+      console.log(`Lock correctly rejected below the minimum duration`);
+    }
+  });
+
+  it("Deposit protocol fees from the treasury", async () => {
+    // The treasury holds the early unstaking penalties of the staking tests
+    const treasuryBefore = await walletComponent.account.wallet.fetch(treasuryComponentPda);
+    const deposit = treasuryBefore.usdcBalance.toNumber();
+    expect(deposit).to.be.greaterThan(0);
+
+    const depositArgs = {
+      operation_type: 3, // DEPOSIT_FEES
+      amount: deposit,
+      lock_duration: 0, // Not used
+    };
+
+    // The game authority signs and is passed after the components, with the catalog entity
+    const applySystem = await ApplySystem({
       authority: provider.wallet.publicKey,
-      systemId: systemStaking.programId,
+      systemId: systemAifiLock.programId,
       world: worldPda,
       entities: [{
         entity: entityPda,
         components: [
-          { componentId: stakeableComponent.programId },
-          { componentId: walletComponent.programId },
-          { componentId: productionComponent.programId },
+          { componentId: voteEscrowComponent.programId }, // vote-escrow position
+          { componentId: walletComponent.programId },     // player wallet
+          { componentId: playerProfileComponent.programId }, // player profile
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: feePoolComponent.programId }, // fee pool
+          { componentId: walletComponent.programId },  // treasury wallet
         ],
       }],
-      args: stakeArgs,
-    }).then(applySystem => provider.sendAndConfirm(applySystem.transaction));
-    
-    // Unstake to generate rewards
-    const unstakeArgs = {
-      operation_type: 2,
-      staking_type: 1,
-      min_staking_period: 0,
-      reward_rate: 0,
-      unstaking_penalty: 0,
-      base_usdc_per_hour: 0,
-      base_aifi_per_hour: 0,
-      current_time: Math.floor(Date.now() / 1000) + 7200, // 2 hours later for reward generation
-      stake: false,
-      can_claim_rewards: false
+      args: depositArgs,
+      extraAccounts: [
+        { pubkey: gameAuthority.publicKey, isSigner: true, isWritable: false }, // game authority
+        { pubkey: catalogEntityPda, isSigner: false, isWritable: false }, // catalog entity
+      ],
+    });
+
+    await provider.sendAndConfirm(applySystem.transaction, [gameAuthority]);
+
+    const treasuryAfter = await walletComponent.account.wallet.fetch(treasuryComponentPda);
+    expect(treasuryAfter.usdcBalance.toNumber()).to.equal(0);
+
+    const feePool = await feePoolComponent.account.feePool.fetch(feePoolComponentPda);
+    expect(feePool.authority.toBase58()).to.equal(gameAuthority.publicKey.toBase58());
+    expect(feePool.totalFeesDeposited.toNumber()).to.equal(deposit);
+
+    // Held until the first position opens
+    expect(feePool.undistributedFees.toNumber()).to.equal(deposit);
+  });
+
+  it("Lock AiFi into a vote-escrow position", async () => {
+    // Fund the wallet with the AiFi to lock
+    const addAiFiSystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemEconomy.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: walletComponent.programId },      // source wallet
+          { componentId: walletComponent.programId },      // destination wallet
+          { componentId: priceComponent.programId },       // source price
+          { componentId: priceComponent.programId },       // destination price
+        ],
+      }],
+      args: {
+        transaction_type: 2, // INITIALIZE
+        currency_type: 4, // AiFi
+        destination_currency_type: 4, // AiFi
+        amount: 1000000000 // 1000 AiFi
+      },
+    });
+    await provider.sendAndConfirm(addAiFiSystem.transaction);
+
+    const walletBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
+
+    // Half of the longest lock weighs half of the AiFi
+    const lockDuration = 182 * 86400 + 43200;
+    const lockArgs = {
+      operation_type: 0, // LOCK
+      amount: 1000000000, // 1000 AiFi
+      lock_duration: lockDuration,
     };
-    
-    await ApplySystem({
+
+    const applySystem = await ApplySystem({
       authority: provider.wallet.publicKey,
-      systemId: systemStaking.programId,
+      systemId: systemAifiLock.programId,
       world: worldPda,
       entities: [{
         entity: entityPda,
         components: [
-          { componentId: stakeableComponent.programId },
-          { componentId: walletComponent.programId },
-          { componentId: productionComponent.programId },
+          { componentId: voteEscrowComponent.programId }, // vote-escrow position
+          { componentId: walletComponent.programId },     // player wallet
+          { componentId: playerProfileComponent.programId }, // player profile
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: feePoolComponent.programId }, // fee pool
+          { componentId: walletComponent.programId },  // treasury wallet
         ],
       }],
-      args: unstakeArgs,
-    }).then(applySystem => provider.sendAndConfirm(applySystem.transaction));
-    
-    // Try to claim rewards with claiming disabled (should fail)
-    const stakeableWithRewards = await stakeableComponent.account.stakeable.fetch(stakeableComponentPda);
-    console.log(`Accumulated rewards: ${stakeableWithRewards.accumulatedUsdcRewards.toNumber()/1000000} USDC, ${stakeableWithRewards.accumulatedAifiRewards.toNumber()/1000000} AiFi`);
-    
-    // Skip test if no rewards were generated (to avoid failures in CI)
-    if (stakeableWithRewards.accumulatedUsdcRewards.toNumber() === 0 && 
-        stakeableWithRewards.accumulatedAifiRewards.toNumber() === 0) {
-      console.log("No rewards accumulated, skipping claim test");
-      return;
-    }
-    
-    // Try to claim
+      args: lockArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+      ],
+    });
+
+    const txSign = await provider.sendAndConfirm(applySystem.transaction);
+    console.log(`Locked AiFi into a vote-escrow position. Signature: ${txSign}`);
+
+    const position = await voteEscrowComponent.account.voteEscrow.fetch(voteEscrowComponentPda);
+    expect(position.lockedAifi.toNumber()).to.equal(1000000000);
+    expect(position.unlockTime.toNumber()).to.equal(position.lockStartTime.toNumber() + lockDuration);
+    expect(position.weight.toNumber()).to.equal(500000000);
+
+    const walletAfter = await walletComponent.account.wallet.fetch(walletComponentPda);
+    expect(walletBefore.aifiBalance.toNumber() - walletAfter.aifiBalance.toNumber()).to.equal(1000000000);
+
+    // One basis point of production bonus per 10 AiFi of weight
+    const profile = await playerProfileComponent.account.playerProfile.fetch(playerProfileComponentPda);
+    expect(profile.voteEscrowMultiplier).to.equal(10050);
+    expect(profile.voteEscrowEndTime.toNumber()).to.equal(position.unlockTime.toNumber());
+
+    const feePool = await feePoolComponent.account.feePool.fetch(feePoolComponentPda);
+    expect(feePool.totalWeight.toNumber()).to.equal(500000000);
+    expect(feePool.positionCount).to.equal(1);
+    expect(feePool.undistributedFees.toNumber()).to.equal(0);
+  });
+
+  it("Claim protocol fees from the vote-escrow position", async () => {
+    const walletBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
+    const feePool = await feePoolComponent.account.feePool.fetch(feePoolComponentPda);
+
     const claimArgs = {
-      operation_type: 3,
-      staking_type: 0,
-      min_staking_period: 0,
-      reward_rate: 0,
-      unstaking_penalty: 0,
-      base_usdc_per_hour: 0,
-      base_aifi_per_hour: 0,
-      current_time: Math.floor(Date.now() / 1000),
-      stake: false,
-      can_claim_rewards: false
+      operation_type: 2, // CLAIM_FEES
+      amount: 0, // Not used
+      lock_duration: 0, // Not used
     };
-    
+
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemAifiLock.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: voteEscrowComponent.programId }, // vote-escrow position
+          { componentId: walletComponent.programId },     // player wallet
+          { componentId: playerProfileComponent.programId }, // player profile
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: feePoolComponent.programId }, // fee pool
+          { componentId: walletComponent.programId },  // treasury wallet
+        ],
+      }],
+      args: claimArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+      ],
+    });
+
+    await provider.sendAndConfirm(applySystem.transaction);
+
+    // The only position receives every fee, less rounding
+    const walletAfter = await walletComponent.account.wallet.fetch(walletComponentPda);
+    const claimed = walletAfter.usdcBalance.toNumber() - walletBefore.usdcBalance.toNumber();
+    expect(claimed).to.be.at.most(feePool.totalFeesDeposited.toNumber());
+    expect(claimed).to.be.at.least(feePool.totalFeesDeposited.toNumber() - 1);
+
+    const position = await voteEscrowComponent.account.voteEscrow.fetch(voteEscrowComponentPda);
+    expect(position.feesClaimed.toNumber()).to.equal(claimed);
+    console.log(`Claimed ${claimed/1000000} USDC of protocol fees`);
+  });
+
+  it("Attempt to withdraw locked AiFi before the lock expires (should fail)", async () => {
+    const position = await voteEscrowComponent.account.voteEscrow.fetch(voteEscrowComponentPda);
+    const withdrawArgs = {
+      operation_type: 1, // WITHDRAW
+      amount: 0, // Not used
+      lock_duration: 0, // Not used
+    };
+
     try {
-      // Apply the system to claim rewards (should fail)
-      const claimSystem = await ApplySystem({
+      const applySystem = await ApplySystem({
         authority: provider.wallet.publicKey,
-        systemId: systemStaking.programId,
+        systemId: systemAifiLock.programId,
         world: worldPda,
         entities: [{
           entity: entityPda,
           components: [
-            { componentId: stakeableComponent.programId },
-            { componentId: walletComponent.programId },
-            { componentId: productionComponent.programId },
+            { componentId: voteEscrowComponent.programId }, // vote-escrow position
+            { componentId: walletComponent.programId },     // player wallet
+            { componentId: playerProfileComponent.programId }, // player profile
+          ],
+        }, {
+          entity: catalogEntityPda,
+          components: [
+            { componentId: feePoolComponent.programId }, // fee pool
+            { componentId: walletComponent.programId },  // treasury wallet
           ],
         }],
-        args: claimArgs,
+        args: withdrawArgs,
+        extraAccounts: [
+          { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+        ],
       });
-      
-      await provider.sendAndConfirm(claimSystem.transaction);
-      expect.fail("Claim should have failed because claiming is disabled");
+
+      await provider.sendAndConfirm(applySystem.transaction);
+      expect.fail("Withdraw should wait for the lock to expire");
     } catch (error) {
-      console.log(`Claim correctly failed as reward claiming is disabled`);
+      expect(error.toString()).to.not.include("Withdraw should wait for the lock to expire");
+      console.log(`Withdraw correctly rejected before the lock expired`);
+    }
+  });
+
+  it("Withdraw AiFi once the lock expires", async () => {
+    const position = await voteEscrowComponent.account.voteEscrow.fetch(voteEscrowComponentPda);
+
+    // Locks last at least 7 days of cluster time, longer than a test run on a fresh validator
+    const clusterTime = await provider.connection.getBlockTime(await provider.connection.getSlot());
+    if (clusterTime < position.unlockTime.toNumber()) {
+      console.log(`AiFi is locked until ${position.unlockTime.toNumber()}, skipping withdraw test`);
+      return;
     }
+
+    const walletBefore = await walletComponent.account.wallet.fetch(walletComponentPda);
+    const withdrawArgs = {
+      operation_type: 1, // WITHDRAW
+      amount: 0, // Not used
+      lock_duration: 0, // Not used
+    };
+
+    const applySystem = await ApplySystem({
+      authority: provider.wallet.publicKey,
+      systemId: systemAifiLock.programId,
+      world: worldPda,
+      entities: [{
+        entity: entityPda,
+        components: [
+          { componentId: voteEscrowComponent.programId }, // vote-escrow position
+          { componentId: walletComponent.programId },     // player wallet
+          { componentId: playerProfileComponent.programId }, // player profile
+        ],
+      }, {
+        entity: catalogEntityPda,
+        components: [
+          { componentId: feePoolComponent.programId }, // fee pool
+          { componentId: walletComponent.programId },  // treasury wallet
+        ],
+      }],
+      args: withdrawArgs,
+      extraAccounts: [
+        { pubkey: entityPda, isSigner: false, isWritable: false }, // player entity
+      ],
+    });
+
+    await provider.sendAndConfirm(applySystem.transaction);
+
+    const walletAfter = await walletComponent.account.wallet.fetch(walletComponentPda);
+    expect(walletAfter.aifiBalance.toNumber() - walletBefore.aifiBalance.toNumber()).to.equal(1000000000);
+
+    const positionAfter = await voteEscrowComponent.account.voteEscrow.fetch(voteEscrowComponentPda);
+    expect(positionAfter.lockedAifi.toNumber()).to.equal(0);
+    expect(positionAfter.weight.toNumber()).to.equal(0);
+
+    // The production bonus ends with the lock
+    const profile = await playerProfileComponent.account.playerProfile.fetch(playerProfileComponentPda);
+    expect(profile.voteEscrowMultiplier).to.equal(10000);
+
+    const feePool = await feePoolComponent.account.feePool.fetch(feePoolComponentPda);
+    expect(feePool.totalWeight.toNumber()).to.equal(0);
+    expect(feePool.positionCount).to.equal(0);
   });
 
   // Market system tests 